@@ -3,9 +3,11 @@ mod integration {
     use std::{
         io::{self, Read, Write},
         net::{SocketAddr, TcpStream},
+        thread,
+        time::{Duration, Instant},
     };
 
-    use volatix_core::{RequestType, bulkstring, parse_request};
+    use volatix_core::{RequestType, parse_request};
     const BUFFER_SIZE: usize = 1024;
 
     macro_rules! bstring {
@@ -159,18 +161,137 @@ mod integration {
         let addr: SocketAddr = "127.0.0.1:7878".parse().unwrap();
         let mut stream = TcpStream::connect(addr).unwrap();
 
-        let handshake_message = bulkstring!(Some("HELLO"));
+        let handshake_message = array!("HELLO", "3");
 
         stream.write_all(&handshake_message).unwrap();
 
-        let mut buffer = [0u8; 64];
+        let mut buffer = [0u8; 256];
         let n = stream.read(&mut buffer).unwrap();
 
         assert!(n > 0);
         let resp = parse_request(&buffer[..n]).unwrap();
 
-        assert_eq!(resp, RequestType::BulkString { data: b"HELLO" });
-        drop(resp);
+        match resp {
+            RequestType::Map { children } => {
+                assert_eq!(
+                    children.get("server"),
+                    Some(&RequestType::BulkString { data: b"volatix" })
+                );
+                assert_eq!(
+                    children.get("proto"),
+                    Some(&RequestType::Integer { data: b"3" })
+                );
+            }
+            other => panic!("Expected a map, got {other:?}"),
+        }
+
+        let unsupported_req = array!("HELLO", "4");
+        let resp = send_request(&stream, &unsupported_req).unwrap();
+        assert!(matches!(resp, RequestType::SimpleError { .. }));
+    }
+
+    #[test]
+    fn test_hello_resp2() {
+        let addr: SocketAddr = "127.0.0.1:7878".parse().unwrap();
+        let stream = TcpStream::connect(addr).unwrap();
+
+        // New connections speak RESP2, booleans become integers
+        let exists_req = array!("EXISTS", "resp2_missing");
+        let resp = send_request(&stream, &exists_req).unwrap();
+        assert_eq!(resp, RequestType::Integer { data: b"0" });
+
+        // The HELLO reply itself is sent as RESP2, a flat array
+        let hello_req = array!("HELLO", "2", "SETNAME", "resp2-test");
+        let resp = send_request(&stream, &hello_req).unwrap();
+        match resp {
+            RequestType::Array { children } => {
+                assert_eq!(children.len(), 14);
+                assert_eq!(children[0], RequestType::BulkString { data: b"server" });
+            }
+            other => panic!("Expected an array, got {other:?}"),
+        }
+
+        // Booleans become integers
+        let set_req = array!("SET", "resp2_key", "value");
+        send_request(&stream, &set_req).unwrap();
+        let exists_req = array!("EXISTS", "resp2_key");
+        let resp = send_request(&stream, &exists_req).unwrap();
+        assert_eq!(resp, RequestType::Integer { data: b"1" });
+
+        let unsupported_req = array!("HELLO", "4");
+        let resp = send_request(&stream, &unsupported_req).unwrap();
+        assert!(matches!(resp, RequestType::SimpleError { .. }));
+
+        let delete_req = array!("DELETE", "resp2_key");
+        send_request(&stream, &delete_req).unwrap();
+    }
+
+    #[test]
+    fn test_redis_command_names() {
+        let addr: SocketAddr = "127.0.0.1:7878".parse().unwrap();
+        let stream = TcpStream::connect(addr).unwrap();
+
+        let mset_req = array!("MSET", "redis_a", "1", "redis_b", "two");
+        let resp = send_request(&stream, &mset_req).unwrap();
+        assert_eq!(resp, RequestType::SimpleString { data: b"OK" });
+
+        let mget_req = array!("MGET", "redis_a", "redis_missing", "redis_b");
+        let resp = send_request(&stream, &mget_req).unwrap();
+        assert_eq!(
+            resp,
+            RequestType::Array {
+                children: vec![
+                    RequestType::BulkString { data: b"1" },
+                    RequestType::Null,
+                    RequestType::BulkString { data: b"two" },
+                ]
+            }
+        );
+
+        // A TTL past the end of time is an error, the server keeps running
+        let huge_ttl = u64::MAX.to_string();
+        let setex_req = array!("SETEX", "redis_setex", &huge_ttl, "value");
+        let resp = send_request(&stream, &setex_req).unwrap();
+        assert!(matches!(resp, RequestType::SimpleError { .. }));
+
+        let ttl_req = array!("TTL", "redis_missing");
+        let resp = send_request(&stream, &ttl_req).unwrap();
+        assert_eq!(resp, RequestType::Integer { data: b"-2" });
+
+        let del_req = array!("DEL", "redis_a", "redis_b", "redis_missing");
+        let resp = send_request(&stream, &del_req).unwrap();
+        assert_eq!(resp, RequestType::Integer { data: b"2" });
+
+        let ping_req = array!("PING");
+        let resp = send_request(&stream, &ping_req).unwrap();
+        assert_eq!(resp, RequestType::SimpleString { data: b"PONG" });
+    }
+
+    #[test]
+    fn test_pipelined_inline_commands() {
+        let addr: SocketAddr = "127.0.0.1:7878".parse().unwrap();
+        let mut stream = TcpStream::connect(addr).unwrap();
+
+        // Both lines arrive in one read, the second one split across two
+        stream.write_all(b"SET pipe_a 1\r\nGET pi").unwrap();
+        thread::sleep(Duration::from_millis(50));
+        stream.write_all(b"pe_a\r\n").unwrap();
+
+        let expected = b"$7\r\nSUCCESS\r\n:1\r\n";
+        let mut replies = Vec::new();
+        let mut buffer = [0u8; BUFFER_SIZE];
+        stream
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+        while replies.len() < expected.len() {
+            let n = stream.read(&mut buffer).unwrap();
+            assert!(n > 0);
+            replies.extend_from_slice(&buffer[..n]);
+        }
+        assert_eq!(replies, expected);
+
+        let delete_req = array!("DELETE", "pipe_a");
+        send_request(&stream, &delete_req).unwrap();
     }
 
     #[test]
@@ -293,4 +414,77 @@ mod integration {
         assert_eq!(resp, success_resp);
         drop(resp);
     }
+
+    #[test]
+    fn test_blocking_pop() {
+        let addr: SocketAddr = "127.0.0.1:7878".parse().unwrap();
+        let worker = TcpStream::connect(addr).unwrap();
+
+        // Nothing is pushed, the worker gets null once the timeout expires
+        let started = Instant::now();
+        let blpop_req = array!("BLPOP", "queue:empty", "0.2");
+        let resp = send_request(&worker, &blpop_req).unwrap();
+        assert_eq!(resp, RequestType::Null);
+        assert!(started.elapsed() >= Duration::from_millis(200));
+
+        // The worker is woken by a push from another connection
+        let producer = thread::spawn(move || {
+            let stream = TcpStream::connect(addr).unwrap();
+            thread::sleep(Duration::from_millis(100));
+            let rpush_req = array!("RPUSH", "queue:jobs", "job1");
+            let resp = send_request(&stream, &rpush_req).unwrap();
+            assert_eq!(resp, RequestType::Integer { data: b"1" });
+        });
+
+        let brpop_req = array!("BRPOP", "queue:jobs", "5");
+        let resp = send_request(&worker, &brpop_req).unwrap();
+        assert_eq!(
+            resp,
+            RequestType::Array {
+                children: vec![
+                    RequestType::BulkString {
+                        data: b"queue:jobs"
+                    },
+                    RequestType::BulkString { data: b"job1" },
+                ]
+            }
+        );
+        producer.join().unwrap();
+
+        let llen_req = array!("LLEN", "queue:jobs");
+        let resp = send_request(&worker, &llen_req).unwrap();
+        assert_eq!(resp, RequestType::Integer { data: b"0" });
+    }
+
+    #[test]
+    fn test_select_creates_database_on_write() {
+        let addr: SocketAddr = "127.0.0.1:7878".parse().unwrap();
+        let stream = TcpStream::connect(addr).unwrap();
+        let db = RequestType::BulkString {
+            data: b"select_new",
+        };
+        let dblist_req = bstring!("DBLIST");
+
+        let select_req = array!("SELECT", "select_new");
+        send_request(&stream, &select_req).unwrap();
+
+        // Reads see an empty database without creating it
+        let get_req = array!("GET", "select_key");
+        let resp = send_request(&stream, &get_req).unwrap();
+        assert_eq!(resp, RequestType::Null);
+        match send_request(&stream, dblist_req.as_bytes()).unwrap() {
+            RequestType::Array { children } => assert!(!children.contains(&db)),
+            other => panic!("Expected an array, got {other:?}"),
+        }
+
+        let set_req = array!("SET", "select_key", "value");
+        send_request(&stream, &set_req).unwrap();
+        match send_request(&stream, dblist_req.as_bytes()).unwrap() {
+            RequestType::Array { children } => assert!(children.contains(&db)),
+            other => panic!("Expected an array, got {other:?}"),
+        }
+
+        let del_req = array!("DEL", "select_key");
+        send_request(&stream, &del_req).unwrap();
+    }
 }
@@ -14,6 +14,10 @@ const DEFAULT_WORKER_COUNT: usize = 4;
 const DEFAULT_BENCH_DURATION: u64 = 30;
 const DEFAULT_RATIO: f64 = 0.7; // 70% read, 30% writes
 const DEFAULT_VALUESIZE: usize = 2 * 1024;
+const DEFAULT_CAPACITY: usize = 1_000;
+const DEFAULT_SKEW: f64 = 3.0;
+const HIT_RATIO_KEYS: usize = 20_000;
+const HIT_RATIO_OPERATIONS: usize = 100_000;
 
 // $<length>\r\n<data>\r\n
 struct Bstring(String);
@@ -57,6 +61,7 @@ enum Command<'cmd> {
     Get { key: &'cmd str },
     Set { key: &'cmd str, value: &'cmd str },
     ConfSet { key: &'cmd str, value: &'cmd str },
+    ConfReset,
     Flush,
 }
 
@@ -100,6 +105,10 @@ fn serialize_request(command: &Command) -> Vec<u8> {
             arr.0.as_bytes().to_vec()
         }
 
+        Command::ConfReset => {
+            let cmd = Bstring::new("CONFRESET");
+            cmd.0.as_bytes().to_vec()
+        }
         Command::Flush => {
             let cmd = Bstring::new("FLUSH");
             cmd.0.as_bytes().to_vec()
@@ -132,9 +141,15 @@ fn get_key() -> String {
     key
 }
 
+// GET replies with a null bulk string (`$-1\r\n`) on a cache miss
+fn is_hit(response: &[u8]) -> bool {
+    !response.starts_with(b"$-1\r\n") && !response.starts_with(b"_")
+}
+
 struct ThreadResult {
     read_latencies: Vec<Duration>,
     write_latencies: Vec<Duration>,
+    read_hits: usize,
     operations: usize,
     errors: usize,
 }
@@ -157,6 +172,7 @@ fn worker_thread(id: usize, config: &Config) -> ThreadResult {
             return ThreadResult {
                 read_latencies: vec![],
                 write_latencies: vec![],
+                read_hits: 0,
                 operations: 0,
                 errors: 1,
             };
@@ -183,6 +199,7 @@ fn worker_thread(id: usize, config: &Config) -> ThreadResult {
     let mut local_write_lats = Vec::with_capacity(20_000);
     let mut ops = 0;
     let mut errs = 0;
+    let mut hits = 0;
 
     let mut i = 0;
     while start_time.elapsed() < config.duration {
@@ -213,7 +230,12 @@ fn worker_thread(id: usize, config: &Config) -> ThreadResult {
             Ok(n) if n > 0 => {
                 let latency = op_start.elapsed();
                 match op_type {
-                    Command::Get { .. } => local_read_lats.push(latency),
+                    Command::Get { .. } => {
+                        local_read_lats.push(latency);
+                        if is_hit(&buffer[..n]) {
+                            hits += 1;
+                        }
+                    }
                     Command::Set { .. } => local_write_lats.push(latency),
                     _ => {}
                 }
@@ -234,11 +256,117 @@ fn worker_thread(id: usize, config: &Config) -> ThreadResult {
     ThreadResult {
         read_latencies: local_read_lats,
         write_latencies: local_write_lats,
+        read_hits: hits,
         operations: ops,
         errors: errs,
     }
 }
 
+// Sends a request and waits for its reply
+fn request(stream: &mut TcpStream, command: &Command, buffer: &mut [u8]) -> Option<usize> {
+    stream.write_all(&serialize_request(command)).ok()?;
+    match stream.read(buffer) {
+        Ok(n) if n > 0 => Some(n),
+        _ => None,
+    }
+}
+
+// Picks a key index where low indexes are requested far more often than high
+// ones. The larger the skew, the smaller the set of hot keys.
+fn skewed_index(key_count: usize, skew: f64) -> usize {
+    let u = rand::random::<f64>();
+    ((key_count as f64 * u.powf(skew)) as usize).min(key_count - 1)
+}
+
+struct HitRatio {
+    policy: String,
+    hits: usize,
+    reads: usize,
+}
+
+// Runs a read-through workload (GET, then SET on a miss) against a cache of
+// `capacity` entries using the given eviction policy.
+fn hit_ratio_run(policy: &str, capacity: usize, skew: f64, keys: &[String]) -> Option<HitRatio> {
+    let mut stream = TcpStream::connect(ADDRESS).ok()?;
+    let mut buffer = [0u8; 64 * 1024];
+    let capacity = capacity.to_string();
+
+    request(&mut stream, &Command::Flush, &mut buffer)?;
+    request(
+        &mut stream,
+        &Command::ConfSet {
+            key: "MAXCAP",
+            value: &capacity,
+        },
+        &mut buffer,
+    )?;
+    request(
+        &mut stream,
+        &Command::ConfSet {
+            key: "EVICTPOLICY",
+            value: policy,
+        },
+        &mut buffer,
+    )?;
+
+    let mut hits = 0;
+    for _ in 0..HIT_RATIO_OPERATIONS {
+        let key = &keys[skewed_index(keys.len(), skew)];
+        let n = request(&mut stream, &Command::Get { key }, &mut buffer)?;
+        if is_hit(&buffer[..n]) {
+            hits += 1;
+        } else {
+            request(&mut stream, &Command::Set { key, value: key }, &mut buffer)?;
+        }
+    }
+
+    Some(HitRatio {
+        policy: policy.to_uppercase(),
+        hits,
+        reads: HIT_RATIO_OPERATIONS,
+    })
+}
+
+fn compare_policies(policies: &str, capacity: usize, skew: f64) {
+    println!("Comparing eviction policies with:");
+    println!("  - {capacity} entries capacity");
+    println!("  - {HIT_RATIO_KEYS} keys, skew {skew}");
+    println!("  - {HIT_RATIO_OPERATIONS} reads per policy");
+    println!();
+
+    let keys: Vec<_> = (0..HIT_RATIO_KEYS).map(|i| format!("key:{i}")).collect();
+    let mut results = Vec::new();
+    for policy in policies.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        println!("Running {policy} ...");
+        match hit_ratio_run(policy, capacity, skew, &keys) {
+            Some(result) => results.push(result),
+            None => eprintln!("Policy {policy} failed to run"),
+        }
+    }
+
+    println!("\n=== HIT RATIO RESULTS ===");
+    println!(
+        "{:<12} {:>10} {:>10} {:>10}",
+        "Policy", "Hits", "Misses", "Hit ratio"
+    );
+    for r in &results {
+        println!(
+            "{:<12} {:>10} {:>10} {:>9.2}%",
+            r.policy,
+            r.hits,
+            r.reads - r.hits,
+            r.hits as f64 * 100.0 / r.reads as f64
+        );
+    }
+
+    // Cleanup
+    if let Ok(mut stream) = TcpStream::connect(ADDRESS) {
+        let mut buffer = [0u8; 1024];
+        let _ = request(&mut stream, &Command::Flush, &mut buffer);
+        let _ = request(&mut stream, &Command::ConfReset, &mut buffer);
+    }
+}
+
 #[derive(Debug, Parser)]
 struct Cli {
     #[arg(
@@ -267,11 +395,39 @@ struct Cli {
         help = "Maximum size of value entries in bytes"
     )]
     vsize: Option<usize>,
+
+    #[arg(
+        short = 'p',
+        long = "policies",
+        help = "Compare the hit ratio of comma separated eviction policies e.g LRU,LFU,TINYLFU"
+    )]
+    policies: Option<String>,
+
+    #[arg(
+        long = "capacity",
+        help = "Cache capacity used when comparing eviction policies"
+    )]
+    capacity: Option<usize>,
+
+    #[arg(
+        long = "skew",
+        help = "Key popularity skew used when comparing eviction policies"
+    )]
+    skew: Option<f64>,
 }
 
 fn main() {
     let args = Cli::parse();
 
+    if let Some(policies) = &args.policies {
+        compare_policies(
+            policies,
+            args.capacity.unwrap_or(DEFAULT_CAPACITY),
+            args.skew.unwrap_or(DEFAULT_SKEW),
+        );
+        return;
+    }
+
     let thread_count = args.workers.unwrap_or(DEFAULT_WORKER_COUNT);
     let duration_secs = args.duration.unwrap_or(DEFAULT_BENCH_DURATION);
     let mixed_ratio = args.ratio.unwrap_or(DEFAULT_RATIO);
@@ -341,12 +497,14 @@ fn main() {
     let mut all_write_lats = Vec::new();
     let mut total_ops = 0;
     let mut total_errors = 0;
+    let mut total_read_hits = 0;
 
     for handle in handles {
         match handle.join() {
             Ok(result) => {
                 all_read_lats.extend(result.read_latencies);
                 all_write_lats.extend(result.write_latencies);
+                total_read_hits += result.read_hits;
                 total_ops += result.operations;
                 total_errors += result.errors;
             }
@@ -386,6 +544,10 @@ fn main() {
         all_read_lats.sort_unstable();
         let p50 = all_read_lats[all_read_lats.len() * 50 / 100].as_micros();
         let p99 = all_read_lats[all_read_lats.len() * 99 / 100].as_micros();
+        println!(
+            "\nRead hit ratio: {:.2}%",
+            total_read_hits as f64 * 100.0 / all_read_lats.len() as f64
+        );
         println!("\nREAD Latency (µs):");
         println!("  Average: {avg:.2}");
         println!("  P50:     {p50}");
@@ -19,10 +19,14 @@ pub enum Response {
     Integer { data: i64 },
     /// Floating-point number (RESP3 BigNumber).
     BigNumber { data: f64 },
+    /// Floating-point number (RESP3 Double).
+    Double { data: f64 },
     /// Null (no data).
     Null,
     /// Nested array of responses.
     Array { data: Vec<Response> },
+    /// Key-value pairs, sorted by key.
+    Map { data: Vec<(String, Response)> },
 }
 
 impl Display for Response {
@@ -32,11 +36,15 @@ impl Display for Response {
             Self::SimpleString { data } => write!(f, "{data}"),
             Self::Integer { data } => write!(f, "{data}"),
             Self::Null => write!(f, "NULL"),
-            Self::BigNumber { data } => write!(f, "{data}"),
+            Self::BigNumber { data } | Self::Double { data } => write!(f, "{data}"),
             Self::Array { data } => {
                 let arr: Vec<String> = data.iter().map(|c| c.to_string()).collect();
                 write!(f, "{arr:?}")
             }
+            Self::Map { data } => {
+                let entries: Vec<String> = data.iter().map(|(k, v)| format!("{k}: {v}")).collect();
+                write!(f, "{{{}}}", entries.join(", "))
+            }
             Self::SimpleError { data } => write!(f, "{data}"),
             Self::Boolean { data } => write!(f, "{data}"),
         }
@@ -45,8 +53,13 @@ impl Display for Response {
 
 fn de_inner_response(resp: &RequestType) -> Result<Response, String> {
     match resp {
-        RequestType::BulkString { data } => Ok(Response::SimpleString {
-            data: String::from_utf8_lossy(data).to_string(),
+        RequestType::BulkString { data } | RequestType::SimpleString { data } => {
+            Ok(Response::SimpleString {
+                data: String::from_utf8_lossy(data).to_string(),
+            })
+        }
+        RequestType::StreamedString { chunks } => Ok(Response::SimpleString {
+            data: String::from_utf8_lossy(&chunks.concat()).to_string(),
         }),
         RequestType::Null => Ok(Response::Null),
 
@@ -65,13 +78,20 @@ fn de_inner_response(resp: &RequestType) -> Result<Response, String> {
                 .map_err(|e| e.to_string())
         }
 
+        RequestType::Double { data } => {
+            let s = String::from_utf8_lossy(data).to_string();
+            s.parse::<f64>()
+                .map(|f| Response::Double { data: f })
+                .map_err(|e| e.to_string())
+        }
+
         RequestType::BulkError { data } => Ok(Response::SimpleError {
             data: String::from_utf8_lossy(data).to_string(),
         }),
 
         RequestType::Boolean { data } => Ok(Response::Boolean { data: *data }),
 
-        RequestType::Array { children } => {
+        RequestType::Array { children } | RequestType::Push { children } => {
             let mut outer_vec = Vec::new();
             for child in children {
                 outer_vec.push(de_inner_response(child)?);
@@ -79,22 +99,35 @@ fn de_inner_response(resp: &RequestType) -> Result<Response, String> {
             Ok(Response::Array { data: outer_vec })
         }
 
-        RequestType::Map { children: _ } => {
-            unimplemented!("will think about this!")
+        RequestType::Map { children } => {
+            let mut entries = Vec::with_capacity(children.len());
+            for (key, child) in children {
+                entries.push((key.clone(), de_inner_response(child)?));
+            }
+            // The parser doesn't keep the order of the entries
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Ok(Response::Map { data: entries })
         }
 
+        // Attributes are extra information the CLI doesn't show
+        RequestType::Attribute { value, .. } => de_inner_response(value),
+
         _ => Err("Unexpected response type".into()),
     }
 }
 
 /// Parse a raw server response into a `Response` enum.
 ///
-/// - Bulk strings → `Response::SimpleString`
+/// - Bulk and simple strings → `Response::SimpleString`
 /// - Integers → `Response::Integer`
 /// - Big numbers → `Response::BigNumber`
+/// - Doubles → `Response::Double`
 /// - Nulls → `Response::Null`
 /// - Errors → `Response::SimpleError`
-/// - Arrays (nested) → `Response::Array`
+/// - Arrays and pushes (nested) → `Response::Array`
+/// - Streamed strings → `Response::SimpleString`
+/// - Attributes → the response of the value they describe
+/// - Maps (nested) → `Response::Map`
 ///
 /// # Errors
 /// Returns an `Err(String)` if the type is unsupported or the content cannot be parsed.
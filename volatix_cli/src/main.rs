@@ -16,7 +16,7 @@ use parse::{Command, parse_line};
 use serde::{Deserialize, Serialize};
 use serialize::serialize_request;
 use usage::help;
-use volatix_core::volatix_ascii_art;
+use volatix_core::{DEFAULT_DATABASE, volatix_ascii_art};
 
 const HISTORY_CAPACITY: usize = 100;
 
@@ -194,7 +194,8 @@ impl History {
 }
 
 /// Performs initial handshake with the Volatix server
-/// Sends a HELLO command and expects a HELLO response to establish connection
+/// Sends `HELLO 3` to switch the connection to RESP3 and expects the HELLO
+/// map in response to establish connection
 ///
 /// # Arguments
 /// * `stream` - Reference to the TCP stream connected to the server
@@ -222,13 +223,14 @@ fn handshake(mut stream: &TcpStream) -> Result<(), String> {
         Err(e) => return Err(e.to_string()),
     };
 
-    // Verify that server responded with expected HELLO confirmation
+    // Verify that server responded with its HELLO map
     match res {
-        Response::SimpleString { data } => {
-            if data == "HELLO" {
-                return Ok(());
+        Response::Map { data } => {
+            let server = data.iter().find(|(key, _)| key == "server");
+            match server {
+                Some((_, Response::SimpleString { data })) if data == "volatix" => Ok(()),
+                _ => Err("Invalid data for handshake".to_string()),
             }
-            Err("Invalid data for handshake".to_string())
         }
         _ => Err("Invalid response type for handshake".to_string()),
     }
@@ -246,7 +248,7 @@ fn format_response(resp: &Response) -> String {
     match resp {
         Response::SimpleString { data } => data.to_string(),
         Response::SimpleError { data } => data.to_string(),
-        Response::BigNumber { data } => data.to_string(),
+        Response::BigNumber { data } | Response::Double { data } => data.to_string(),
         Response::Integer { data } => data.to_string(),
         Response::Boolean { data } => data.to_string(),
         Response::Null => "NULL".into(),
@@ -255,6 +257,13 @@ fn format_response(resp: &Response) -> String {
             let elements: Vec<String> = data.iter().map(format_response).collect();
             format!("[{}]", elements.join(", "))
         }
+        Response::Map { data } => {
+            let entries: Vec<String> = data
+                .iter()
+                .map(|(key, value)| format!("{key}: {}", format_response(value)))
+                .collect();
+            format!("{{{}}}", entries.join(", "))
+        }
     }
 }
 
@@ -296,25 +305,23 @@ fn read_line(prompt: &str, history: &mut History) -> Result<String, String> {
 
         if let event::Event::Key(key_event) = event {
             match key_event.code {
-                event::KeyCode::Backspace => {
-                    if cursor_pos > 0 {
-                        line.remove(cursor_pos - 1);
-                        cursor_pos -= 1;
+                event::KeyCode::Backspace if cursor_pos > 0 => {
+                    line.remove(cursor_pos - 1);
+                    cursor_pos -= 1;
 
-                        // redraw line from cursor position
-                        stdout
-                            .execute(cursor::MoveLeft(1))
-                            .map_err(|err| err.to_string())?;
-                        stdout
-                            .queue(terminal::Clear(terminal::ClearType::UntilNewLine))
-                            .map_err(|err| err.to_string())?;
+                    // redraw line from cursor position
+                    stdout
+                        .execute(cursor::MoveLeft(1))
+                        .map_err(|err| err.to_string())?;
+                    stdout
+                        .queue(terminal::Clear(terminal::ClearType::UntilNewLine))
+                        .map_err(|err| err.to_string())?;
 
-                        print!("{}", &line[cursor_pos..]);
-                        stdout
-                            .execute(cursor::MoveToColumn((prompt.len() + cursor_pos) as u16))
-                            .map_err(|err| err.to_string())?;
-                        stdout.flush().map_err(|err| err.to_string())?;
-                    }
+                    print!("{}", &line[cursor_pos..]);
+                    stdout
+                        .execute(cursor::MoveToColumn((prompt.len() + cursor_pos) as u16))
+                        .map_err(|err| err.to_string())?;
+                    stdout.flush().map_err(|err| err.to_string())?;
                 }
                 event::KeyCode::Enter => {
                     println!();
@@ -324,21 +331,17 @@ fn read_line(prompt: &str, history: &mut History) -> Result<String, String> {
                     history.push(line.to_string());
                     return Ok(line);
                 }
-                event::KeyCode::Left => {
-                    if cursor_pos > 0 {
-                        cursor_pos -= 1;
-                        stdout
-                            .execute(cursor::MoveLeft(1))
-                            .map_err(|err| err.to_string())?;
-                    }
+                event::KeyCode::Left if cursor_pos > 0 => {
+                    cursor_pos -= 1;
+                    stdout
+                        .execute(cursor::MoveLeft(1))
+                        .map_err(|err| err.to_string())?;
                 }
-                event::KeyCode::Right => {
-                    if cursor_pos < line.len() {
-                        cursor_pos += 1;
-                        stdout
-                            .execute(cursor::MoveRight(1))
-                            .map_err(|err| err.to_string())?;
-                    }
+                event::KeyCode::Right if cursor_pos < line.len() => {
+                    cursor_pos += 1;
+                    stdout
+                        .execute(cursor::MoveRight(1))
+                        .map_err(|err| err.to_string())?;
                 }
                 event::KeyCode::Up => {
                     if let Some(cmd) = history.previous_command() {
@@ -374,21 +377,19 @@ fn read_line(prompt: &str, history: &mut History) -> Result<String, String> {
                     print!("{line}");
                     stdout.flush().map_err(|err| err.to_string())?;
                 }
-                event::KeyCode::Delete => {
-                    if cursor_pos < line.len() {
-                        line.remove(cursor_pos);
+                event::KeyCode::Delete if cursor_pos < line.len() => {
+                    line.remove(cursor_pos);
 
-                        // redraw line from cursor position
-                        stdout
-                            .queue(terminal::Clear(terminal::ClearType::UntilNewLine))
-                            .map_err(|err| err.to_string())?;
+                    // redraw line from cursor position
+                    stdout
+                        .queue(terminal::Clear(terminal::ClearType::UntilNewLine))
+                        .map_err(|err| err.to_string())?;
 
-                        print!("{}", &line[cursor_pos..]);
-                        stdout
-                            .execute(cursor::MoveToColumn((prompt.len() + cursor_pos) as u16))
-                            .map_err(|err| err.to_string())?;
-                        stdout.flush().map_err(|err| err.to_string())?;
-                    }
+                    print!("{}", &line[cursor_pos..]);
+                    stdout
+                        .execute(cursor::MoveToColumn((prompt.len() + cursor_pos) as u16))
+                        .map_err(|err| err.to_string())?;
+                    stdout.flush().map_err(|err| err.to_string())?;
                 }
 
                 event::KeyCode::Char(c) => {
@@ -480,10 +481,16 @@ fn main() -> anyhow::Result<()> {
 
     let _guard = TerminalGuard::new().map_err(|err| err.to_string());
 
+    // Database selected with SELECT, shown in the prompt
+    let mut selected_db: Option<String> = None;
+
     // Main REPL (Read-Eval-Print Loop)
     loop {
-        let prompt = "volatix> ";
-        let line = match read_line(prompt, &mut hist) {
+        let prompt = match &selected_db {
+            Some(db) => format!("volatix[{db}]> "),
+            None => "volatix> ".to_string(),
+        };
+        let line = match read_line(&prompt, &mut hist) {
             Ok(line) => line,
             Err(err) => {
                 if err.as_str() == "EXIT" {
@@ -511,6 +518,10 @@ fn main() -> anyhow::Result<()> {
 
         // Parse user input into a Command object
         let command = parse_line(line);
+        let selecting = match &command {
+            Ok(Command::Select { db }) => Some(db.clone()),
+            _ => None,
+        };
         match command {
             Ok(Command::Help) => {
                 help();
@@ -532,6 +543,8 @@ fn main() -> anyhow::Result<()> {
                     match connect_server(addr) {
                         Ok(new_stream) => {
                             stream = new_stream;
+                            // A new connection starts in the default database
+                            selected_db = None;
                             println!("Successfully reconnected\r");
                             break;
                         }
@@ -585,16 +598,24 @@ fn main() -> anyhow::Result<()> {
         // and possibly by the client's protocol version
         let resp = deserialize_response(&buffer[..read]).map_err(|err| anyhow::anyhow!(err))?;
 
+        if let Some(db) = selecting
+            && matches!(&resp, Response::SimpleString { data } if data == "SUCCESS")
+        {
+            selected_db = Some(db).filter(|db| db != DEFAULT_DATABASE);
+        }
+
         // Display formatted response to user
         match resp {
             Response::SimpleString { data } => println!("{data}\r"),
             Response::SimpleError { data } => println!("{data}\r"),
             Response::Integer { data } => println!("{data}\r"),
-            Response::BigNumber { data } => println!("{data}\r"),
+            Response::BigNumber { data } | Response::Double { data } => println!("{data}\r"),
             Response::Null => println!("NULL\r"),
             Response::Boolean { data } => println!("{data}\r"),
             // Use the formatting function for complex arrays
-            Response::Array { data: _ } => println!("{}\r", format_response(&resp)),
+            Response::Array { data: _ } | Response::Map { data: _ } => {
+                println!("{}\r", format_response(&resp))
+            }
         }
     }
 
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use volatix_core::{Error, parser_error};
+use volatix_core::{BitOp, Error, SetOptions, parser_error};
 
 /// Enum representing all supported Volatix database commands
 /// Each variant contains the necessary parameters for the command
@@ -18,7 +18,8 @@ pub enum Command {
     Set {
         key: String,
         value: String,
-    }, // Store key-value pair
+        options: SetOptions,
+    }, // Store key-value pair, e.g SET key value NX EX 60
     Exists {
         key: String,
     }, // Check if key exists
@@ -33,13 +34,261 @@ pub enum Command {
     Decr {
         key: String,
     }, // Decrement numeric value by 1
+    IncrBy {
+        key: String,
+        amount: i64,
+    }, // Increment numeric value by amount
+    DecrBy {
+        key: String,
+        amount: i64,
+    }, // Decrement numeric value by amount
+    IncrByFloat {
+        key: String,
+        amount: String,
+    }, // Increment numeric value by a floating point amount
+
+    // String operations
+    Append {
+        key: String,
+        value: String,
+    }, // Append to a string value
+    StrLen {
+        key: String,
+    }, // Get the length of a string value
+    GetRange {
+        key: String,
+        start: i64,
+        end: i64,
+    }, // Get a substring of a string value
+    SetRange {
+        key: String,
+        offset: usize,
+        value: String,
+    }, // Overwrite part of a string value
+    GetDel {
+        key: String,
+    }, // Get a value and delete its key
+    GetEx {
+        key: String,
+        options: Vec<String>,
+    }, // Get a value and change its expiry
+
+    // Bitmaps
+    SetBit {
+        key: String,
+        offset: usize,
+        bit: bool,
+    }, // Set or clear a bit
+    GetBit {
+        key: String,
+        offset: usize,
+    }, // Get a bit
+    BitCount {
+        key: String,
+        range: Option<(i64, i64)>,
+    }, // Count the set bits, optionally in a byte range
+    BitOp {
+        op: BitOp,
+        destination: String,
+        keys: Vec<String>,
+    }, // Combine bitmaps into destination
+    BitPos {
+        key: String,
+        bit: bool,
+        start: Option<i64>,
+        end: Option<i64>,
+    }, // Find the first set or clear bit
+
+    // HyperLogLog
+    PfAdd {
+        key: String,
+        elements: Vec<String>,
+    }, // Add elements to a cardinality sketch
+    PfCount {
+        keys: Vec<String>,
+    }, // Estimate the number of distinct elements
+    PfMerge {
+        destination: String,
+        sources: Vec<String>,
+    }, // Merge sketches into destination
+
+    // Bloom and cuckoo filters
+    BfReserve {
+        key: String,
+        error_rate: String,
+        capacity: usize,
+    }, // Create a Bloom filter
+    BfAdd {
+        key: String,
+        item: String,
+    }, // Add an item to a Bloom filter
+    BfMAdd {
+        key: String,
+        items: Vec<String>,
+    }, // Add several items to a Bloom filter
+    BfExists {
+        key: String,
+        item: String,
+    }, // Check whether an item was possibly added
+    BfMExists {
+        key: String,
+        items: Vec<String>,
+    }, // Check several items
+    CfReserve {
+        key: String,
+        capacity: usize,
+    }, // Create a cuckoo filter
+    CfAdd {
+        key: String,
+        item: String,
+    }, // Add an item to a cuckoo filter
+    CfAddNx {
+        key: String,
+        item: String,
+    }, // Add an item unless it is possibly present
+    CfExists {
+        key: String,
+        item: String,
+    }, // Check whether an item was possibly added
+    CfDel {
+        key: String,
+        item: String,
+    }, // Delete an item from a cuckoo filter
+
+    // Lists
+    LPush {
+        key: String,
+        values: Vec<String>,
+    }, // Push values onto the head of a list
+    RPush {
+        key: String,
+        values: Vec<String>,
+    }, // Push values onto the tail of a list
+    LPop {
+        key: String,
+    }, // Pop the first value of a list
+    RPop {
+        key: String,
+    }, // Pop the last value of a list
+    LLen {
+        key: String,
+    }, // Get the length of a list
+    BLPop {
+        keys: Vec<String>,
+        timeout: String,
+    }, // Pop the first value, waiting up to timeout seconds for a push
+    BRPop {
+        keys: Vec<String>,
+        timeout: String,
+    }, // Pop the last value, waiting up to timeout seconds for a push
+
+    // Streams
+    XAdd {
+        key: String,
+        args: Vec<String>,
+    }, // Append an entry: [MAXLEN n] <*|id> field value ...
+    XRange {
+        key: String,
+        start: String,
+        end: String,
+        count: Option<usize>,
+    }, // Get entries in an ID range
+    XRevRange {
+        key: String,
+        end: String,
+        start: String,
+        count: Option<usize>,
+    }, // Get entries in an ID range, newest first
+    XLen {
+        key: String,
+    }, // Get the number of entries
+    XTrim {
+        key: String,
+        max_len: usize,
+    }, // Trim to a maximum length
+    XGroup {
+        args: Vec<String>,
+    }, // CREATE key group <id|$> [MKSTREAM] or DESTROY key group
+    XReadGroup {
+        args: Vec<String>,
+    }, // GROUP group consumer [COUNT n] STREAMS key ... id ...
+    XAck {
+        key: String,
+        group: String,
+        ids: Vec<String>,
+    }, // Acknowledge delivered entries
+    XPending {
+        key: String,
+        group: String,
+        args: Vec<String>,
+    }, // Summary, or entries with start end count [consumer]
+
+    // Geospatial
+    GeoAdd {
+        key: String,
+        args: Vec<String>,
+    }, // Add members: [NX|XX] [CH] longitude latitude member ...
+    GeoDist {
+        key: String,
+        member1: String,
+        member2: String,
+        unit: Option<String>,
+    }, // Get the distance between two members
+    GeoSearch {
+        key: String,
+        args: Vec<String>,
+    }, // Find members within a radius or a box
+
+    // JSON documents
+    JsonSet {
+        key: String,
+        path: String,
+        value: String,
+        condition: Option<String>,
+    }, // Write a JSON value at a path, [NX|XX]
+    JsonGet {
+        key: String,
+        paths: Vec<String>,
+    }, // Read the values at paths, the whole document without paths
+    JsonDel {
+        key: String,
+        path: Option<String>,
+    }, // Remove the values at a path, the whole document by default
+    JsonNumIncrBy {
+        key: String,
+        path: String,
+        value: String,
+    }, // Increment the numbers at a path
+    JsonArrAppend {
+        key: String,
+        path: String,
+        values: Vec<String>,
+    }, // Append JSON values to the arrays at a path
+
+    // Optimistic concurrency
+    GetV {
+        key: String,
+    }, // Get a value with its version
+    Cas {
+        key: String,
+        version: u64,
+        value: String,
+    }, // Set a value if its version matches
 
     // Key management
     Rename {
         old_key: String,
         new_key: String,
     }, // Rename a key
-    Keys,  // List all keys in database
+    Keys {
+        pattern: Option<String>,
+    }, // List all keys or the keys matching a pattern
+    Scan {
+        cursor: u64,
+        pattern: Option<String>,
+        count: Option<usize>,
+        type_name: Option<String>,
+    }, // Iterate over keys page by page
     Flush, // Clear entire database
 
     // Batch operations for efficiency
@@ -57,12 +306,33 @@ pub enum Command {
         list: Vec<String>,
     }, // Delete multiple keys at once
 
+    // Pattern operations
+    GetMatch {
+        pattern: String,
+    }, // Get all keys matching a glob pattern
+    DeleteMatch {
+        pattern: String,
+    }, // Delete all keys matching a glob pattern
+
     // Statistics and monitoring
     GetStats,   // Get global database statistics
     ResetStats, // Reset statistics counters
     Dump {
         key: String,
     }, // Get detailed stats for specific key
+    Type {
+        key: String,
+    }, // Get the type of a value
+    Object {
+        subcommand: String,
+        key: String,
+    }, // Get the access frequency, idle time or encoding of an entry
+    Info {
+        sections: Vec<String>,
+    }, // Report server, memory, persistence and keyspace information
+    MemoryUsage {
+        key: String,
+    }, // Estimate the memory used by an entry
 
     // TTL (Time-to-Live) management
     SetwTtl {
@@ -74,9 +344,27 @@ pub enum Command {
         key: String,
         addition: i64,
     }, // Modify TTL (positive=extend, negative=reduce)
+    ExpireAt {
+        key: String,
+        timestamp: i64,
+    }, // Expire key at a unix time in seconds
+    PExpire {
+        key: String,
+        millis: i64,
+    }, // Set TTL in milliseconds
+    PExpireAt {
+        key: String,
+        timestamp: i64,
+    }, // Expire key at a unix time in milliseconds
+    Persist {
+        key: String,
+    }, // Remove the expiry of key
     GetTtl {
         key: String,
     }, // Get remaining TTL for key
+    PTtl {
+        key: String,
+    }, // Get remaining TTL for key in milliseconds
     EvictNow {
         count: usize,
     }, // Trigger immediate eviction using policy
@@ -91,6 +379,16 @@ pub enum Command {
     }, // Get configuration parameter
     ConfOptions, // List all configurable options
     ConfReset,   // Reset configurable options
+
+    // Logical databases
+    Select {
+        db: String,
+    }, // Switch to another database
+    Move {
+        key: String,
+        db: String,
+    }, // Move a key to another database
+    DbList, // List all databases
 }
 
 /// Parses a single argument from the character stream
@@ -185,6 +483,28 @@ pub fn parse_arg(chars: &[char], pointer: &mut usize, arg_name: &str) -> Result<
     Ok(String::from_iter(arg_chars))
 }
 
+/// Parses all remaining arguments of the line
+///
+/// # Arguments
+/// * `chars` - Input character array
+/// * `pointer` - Current position (will be advanced to the end)
+/// * `arg_name` - Name of the arguments for error messages
+///
+/// # Returns
+/// * `Ok(Vec<String>)` - The arguments, empty if there are none left
+/// * `Err(String)` - Parse error with descriptive message
+pub fn parse_rest(
+    chars: &[char],
+    pointer: &mut usize,
+    arg_name: &str,
+) -> Result<Vec<String>, Error> {
+    let mut args = Vec::new();
+    while *pointer < chars.len() {
+        args.push(parse_arg(chars, pointer, arg_name)?);
+    }
+    Ok(args)
+}
+
 /// Parses a list/array structure from input
 /// Supports both square brackets [item1, item2] and curly braces {item1, item2}
 ///
@@ -340,6 +660,349 @@ pub fn parse_map(data: &[char], pointer: &mut usize) -> Result<HashMap<String, S
     Ok(map)
 }
 
+/// Builds a list push or pop command from its already split arguments
+///
+/// # Arguments
+/// * `cmd` - Uppercase command name
+/// * `args` - Arguments following the command name
+/// * `pointer` - Position in the line, for error messages
+fn parse_list_command(cmd: &str, args: Vec<String>, pointer: usize) -> Result<Command, Error> {
+    let command = match (cmd, args.as_slice()) {
+        ("LPUSH" | "RPUSH", [key, values @ ..]) if !values.is_empty() => {
+            let (key, values) = (key.clone(), values.to_vec());
+            Ok(match cmd {
+                "LPUSH" => Command::LPush { key, values },
+                _ => Command::RPush { key, values },
+            })
+        }
+        ("LPOP", [key]) => Ok(Command::LPop { key: key.clone() }),
+        ("RPOP", [key]) => Ok(Command::RPop { key: key.clone() }),
+        ("LLEN", [key]) => Ok(Command::LLen { key: key.clone() }),
+        ("BLPOP" | "BRPOP", [keys @ .., timeout]) if !keys.is_empty() => {
+            match timeout.parse::<f64>() {
+                Ok(t) if t >= 0.0 && t.is_finite() => {
+                    let (keys, timeout) = (keys.to_vec(), timeout.clone());
+                    Ok(match cmd {
+                        "BLPOP" => Command::BLPop { keys, timeout },
+                        _ => Command::BRPop { keys, timeout },
+                    })
+                }
+                _ => Err("Timeout is not a valid number of seconds".to_string()),
+            }
+        }
+        _ => Err("Wrong number of arguments".to_string()),
+    };
+
+    match command {
+        Ok(command) => Ok(command),
+        Err(e) => parser_error!(format!("{cmd}: {e}"), pointer),
+    }
+}
+
+/// Builds a stream (X*) command from its already split arguments
+///
+/// # Arguments
+/// * `cmd` - Uppercase command name
+/// * `args` - Arguments following the command name
+/// * `pointer` - Position in the line, for error messages
+fn parse_stream_command(cmd: &str, args: Vec<String>, pointer: usize) -> Result<Command, Error> {
+    let number = |arg: &str| {
+        arg.parse::<usize>()
+            .map_err(|e| format!("Invalid integer type: {e}"))
+    };
+    let count = |option: &[String]| match option {
+        [] => Ok(None),
+        [keyword, n] if keyword.eq_ignore_ascii_case("COUNT") => number(n).map(Some),
+        _ => Err("Expected COUNT n".to_string()),
+    };
+
+    let command = match (cmd, args.as_slice()) {
+        ("XADD", [key, rest @ ..]) if rest.len() >= 3 => Ok(Command::XAdd {
+            key: key.clone(),
+            args: rest.to_vec(),
+        }),
+        ("XRANGE", [key, start, end, option @ ..]) => count(option).map(|count| Command::XRange {
+            key: key.clone(),
+            start: start.clone(),
+            end: end.clone(),
+            count,
+        }),
+        ("XREVRANGE", [key, end, start, option @ ..]) => {
+            count(option).map(|count| Command::XRevRange {
+                key: key.clone(),
+                end: end.clone(),
+                start: start.clone(),
+                count,
+            })
+        }
+        ("XLEN", [key]) => Ok(Command::XLen { key: key.clone() }),
+        ("XTRIM", [key, keyword, rest @ ..]) if keyword.eq_ignore_ascii_case("MAXLEN") => {
+            match rest {
+                [max_len] | [_, max_len] => number(max_len).map(|max_len| Command::XTrim {
+                    key: key.clone(),
+                    max_len,
+                }),
+                _ => Err("Expected MAXLEN [=|~] n".to_string()),
+            }
+        }
+        ("XGROUP", [_, _, _, ..]) => Ok(Command::XGroup { args }),
+        ("XREADGROUP", [_, _, _, _, ..]) => Ok(Command::XReadGroup { args }),
+        ("XACK", [key, group, ids @ ..]) if !ids.is_empty() => Ok(Command::XAck {
+            key: key.clone(),
+            group: group.clone(),
+            ids: ids.to_vec(),
+        }),
+        ("XPENDING", [key, group, rest @ ..]) => Ok(Command::XPending {
+            key: key.clone(),
+            group: group.clone(),
+            args: rest.to_vec(),
+        }),
+        _ => Err("Wrong number of arguments".to_string()),
+    };
+
+    match command {
+        Ok(command) => Ok(command),
+        Err(e) => parser_error!(format!("{cmd}: {e}"), pointer),
+    }
+}
+
+/// Builds an introspection command (TYPE, OBJECT, MEMORY) from its already
+/// split arguments
+///
+/// # Arguments
+/// * `cmd` - Uppercase command name
+/// * `args` - Arguments following the command name
+/// * `pointer` - Position in the line, for error messages
+fn parse_introspection_command(
+    cmd: &str,
+    args: Vec<String>,
+    pointer: usize,
+) -> Result<Command, Error> {
+    let command = match (cmd, args.as_slice()) {
+        ("TYPE", [key]) => Ok(Command::Type { key: key.clone() }),
+        ("OBJECT", [subcommand, key]) => match subcommand.to_uppercase().as_str() {
+            sub @ ("FREQ" | "IDLETIME" | "ENCODING") => Ok(Command::Object {
+                subcommand: sub.to_string(),
+                key: key.clone(),
+            }),
+            _ => Err(format!("Unsupported subcommand {subcommand}")),
+        },
+        ("MEMORY", [subcommand, key]) if subcommand.eq_ignore_ascii_case("USAGE") => {
+            Ok(Command::MemoryUsage { key: key.clone() })
+        }
+        ("MEMORY", [subcommand, ..]) if !subcommand.eq_ignore_ascii_case("USAGE") => {
+            Err(format!("Unsupported subcommand {subcommand}"))
+        }
+        _ => Err("Wrong number of arguments".to_string()),
+    };
+
+    match command {
+        Ok(command) => Ok(command),
+        Err(e) => parser_error!(format!("{cmd}: {e}"), pointer),
+    }
+}
+
+/// Builds a geospatial (GEO*) command from its already split arguments
+///
+/// # Arguments
+/// * `cmd` - Uppercase command name
+/// * `args` - Arguments following the command name
+/// * `pointer` - Position in the line, for error messages
+fn parse_geo_command(cmd: &str, args: Vec<String>, pointer: usize) -> Result<Command, Error> {
+    let command = match (cmd, args.as_slice()) {
+        ("GEOADD", [key, rest @ ..]) if rest.len() >= 3 => Ok(Command::GeoAdd {
+            key: key.clone(),
+            args: rest.to_vec(),
+        }),
+        ("GEODIST", [key, member1, member2, unit @ ..]) if unit.len() <= 1 => {
+            Ok(Command::GeoDist {
+                key: key.clone(),
+                member1: member1.clone(),
+                member2: member2.clone(),
+                unit: unit.first().cloned(),
+            })
+        }
+        ("GEOSEARCH", [key, rest @ ..]) if rest.len() >= 4 => Ok(Command::GeoSearch {
+            key: key.clone(),
+            args: rest.to_vec(),
+        }),
+        _ => Err("Wrong number of arguments".to_string()),
+    };
+
+    match command {
+        Ok(command) => Ok(command),
+        Err(e) => parser_error!(format!("{cmd}: {e}"), pointer),
+    }
+}
+
+/// Builds a JSON document (JSON.*) command from its already split arguments
+///
+/// # Arguments
+/// * `cmd` - Uppercase command name
+/// * `args` - Arguments following the command name
+/// * `pointer` - Position in the line, for error messages
+fn parse_json_command(cmd: &str, args: Vec<String>, pointer: usize) -> Result<Command, Error> {
+    let command = match (cmd, args.as_slice()) {
+        ("JSON.SET", [key, path, value, condition @ ..]) if condition.len() <= 1 => {
+            Ok(Command::JsonSet {
+                key: key.clone(),
+                path: path.clone(),
+                value: value.clone(),
+                condition: condition.first().cloned(),
+            })
+        }
+        ("JSON.GET", [key, paths @ ..]) => Ok(Command::JsonGet {
+            key: key.clone(),
+            paths: paths.to_vec(),
+        }),
+        ("JSON.DEL", [key, path @ ..]) if path.len() <= 1 => Ok(Command::JsonDel {
+            key: key.clone(),
+            path: path.first().cloned(),
+        }),
+        ("JSON.NUMINCRBY", [key, path, value]) => Ok(Command::JsonNumIncrBy {
+            key: key.clone(),
+            path: path.clone(),
+            value: value.clone(),
+        }),
+        ("JSON.ARRAPPEND", [key, path, values @ ..]) if !values.is_empty() => {
+            Ok(Command::JsonArrAppend {
+                key: key.clone(),
+                path: path.clone(),
+                values: values.to_vec(),
+            })
+        }
+        _ => Err("Wrong number of arguments".to_string()),
+    };
+
+    match command {
+        Ok(command) => Ok(command),
+        Err(e) => parser_error!(format!("{cmd}: {e}"), pointer),
+    }
+}
+
+/// Builds a Bloom (BF.*) or cuckoo (CF.*) filter command from its already
+/// split arguments
+///
+/// # Arguments
+/// * `cmd` - Uppercase command name
+/// * `args` - Arguments following the command name
+/// * `pointer` - Position in the line, for error messages
+fn parse_filter_command(cmd: &str, args: Vec<String>, pointer: usize) -> Result<Command, Error> {
+    let capacity = |arg: &str| {
+        arg.parse::<usize>()
+            .map_err(|e| format!("Invalid capacity: {e}"))
+    };
+
+    let command = match (cmd, args.as_slice()) {
+        ("BF.RESERVE", [key, error_rate, cap]) => match error_rate.parse::<f64>() {
+            Ok(_) => capacity(cap).map(|capacity| Command::BfReserve {
+                key: key.clone(),
+                error_rate: error_rate.clone(),
+                capacity,
+            }),
+            Err(e) => Err(format!("Invalid error rate: {e}")),
+        },
+        ("CF.RESERVE", [key, cap]) => capacity(cap).map(|capacity| Command::CfReserve {
+            key: key.clone(),
+            capacity,
+        }),
+        ("BF.MADD", [key, items @ ..]) if !items.is_empty() => Ok(Command::BfMAdd {
+            key: key.clone(),
+            items: items.to_vec(),
+        }),
+        ("BF.MEXISTS", [key, items @ ..]) if !items.is_empty() => Ok(Command::BfMExists {
+            key: key.clone(),
+            items: items.to_vec(),
+        }),
+        (_, [key, item]) => {
+            let (key, item) = (key.clone(), item.clone());
+            match cmd {
+                "BF.ADD" => Ok(Command::BfAdd { key, item }),
+                "BF.EXISTS" => Ok(Command::BfExists { key, item }),
+                "CF.ADD" => Ok(Command::CfAdd { key, item }),
+                "CF.ADDNX" => Ok(Command::CfAddNx { key, item }),
+                "CF.EXISTS" => Ok(Command::CfExists { key, item }),
+                "CF.DEL" => Ok(Command::CfDel { key, item }),
+                _ => Err("Wrong number of arguments".to_string()),
+            }
+        }
+        _ => Err("Wrong number of arguments".to_string()),
+    };
+
+    match command {
+        Ok(command) => Ok(command),
+        Err(e) => parser_error!(format!("{cmd}: {e}"), pointer),
+    }
+}
+
+/// Builds a bitmap command (SETBIT, GETBIT, BITCOUNT, BITPOS, BITOP) from
+/// its already split arguments
+///
+/// # Arguments
+/// * `cmd` - Uppercase command name
+/// * `args` - Arguments following the command name
+/// * `pointer` - Position in the line, for error messages
+fn parse_bitmap_command(cmd: &str, args: Vec<String>, pointer: usize) -> Result<Command, Error> {
+    let bit = |arg: &str| match arg {
+        "0" => Ok(false),
+        "1" => Ok(true),
+        _ => Err(format!("Invalid bit {arg}, expected 0 or 1")),
+    };
+    let offset = |arg: &str| {
+        arg.parse::<i64>()
+            .map_err(|e| format!("Invalid integer type: {e}"))
+    };
+    let bit_offset = |arg: &str| {
+        arg.parse::<usize>()
+            .map_err(|e| format!("Invalid offset type: {e}"))
+    };
+
+    let command = match (cmd, args.as_slice()) {
+        ("SETBIT", [key, offset, value]) => bit_offset(offset).and_then(|offset| {
+            Ok(Command::SetBit {
+                key: key.clone(),
+                offset,
+                bit: bit(value)?,
+            })
+        }),
+        ("GETBIT", [key, offset]) => bit_offset(offset).map(|offset| Command::GetBit {
+            key: key.clone(),
+            offset,
+        }),
+        ("BITCOUNT", [key]) => Ok(Command::BitCount {
+            key: key.clone(),
+            range: None,
+        }),
+        ("BITCOUNT", [key, start, end]) => offset(start).and_then(|start| {
+            Ok(Command::BitCount {
+                key: key.clone(),
+                range: Some((start, offset(end)?)),
+            })
+        }),
+        ("BITPOS", [key, value, range @ ..]) if range.len() <= 2 => bit(value).and_then(|bit| {
+            Ok(Command::BitPos {
+                key: key.clone(),
+                bit,
+                start: range.first().map(|s| offset(s)).transpose()?,
+                end: range.get(1).map(|e| offset(e)).transpose()?,
+            })
+        }),
+        ("BITOP", [op, destination, keys @ ..]) if !keys.is_empty() => {
+            BitOp::parse(op).map(|op| Command::BitOp {
+                op,
+                destination: destination.clone(),
+                keys: keys.to_vec(),
+            })
+        }
+        _ => Err("Wrong number of arguments".to_string()),
+    };
+
+    match command {
+        Ok(command) => Ok(command),
+        Err(e) => parser_error!(format!("{cmd}: {e}"), pointer),
+    }
+}
+
 /// Main parsing function - converts a line of text into a Command
 /// Handles command identification, argument parsing, and error cases
 ///
@@ -381,7 +1044,25 @@ pub fn parse_line(line: &str) -> Result<Command, Error> {
 
         "SET" => match parse_arg(&chars, &mut pointer, "key") {
             Ok(key) => match parse_arg(&chars, &mut pointer, "value") {
-                Ok(value) => Ok(Command::Set { key, value }),
+                Ok(value) => {
+                    // Optional flags: NX, XX, GET, KEEPTTL, EX/PX/EXAT/PXAT <n>
+                    let mut args = Vec::new();
+                    while pointer < l {
+                        match parse_arg(&chars, &mut pointer, "option") {
+                            Ok(arg) => args.push(arg),
+                            Err(e) => return parser_error!(format!("SET: {e}"), pointer),
+                        }
+                    }
+
+                    match SetOptions::parse(&args) {
+                        Ok(options) => Ok(Command::Set {
+                            key,
+                            value,
+                            options,
+                        }),
+                        Err(e) => parser_error!(format!("SET: {e}"), pointer),
+                    }
+                }
                 Err(e) => parser_error!(format!("SET: {e}"), pointer),
             },
             Err(e) => parser_error!(format!("SET: {e}"), pointer),
@@ -465,11 +1146,47 @@ pub fn parse_line(line: &str) -> Result<Command, Error> {
             Err(e) => parser_error!(e, pointer),
         },
 
+        "EXPIREAT" | "PEXPIRE" | "PEXPIREAT" => match parse_arg(&chars, &mut pointer, "key") {
+            Ok(key) => match parse_arg(&chars, &mut pointer, "time") {
+                Ok(v) => {
+                    let time = match v.parse::<i64>() {
+                        Ok(v) => v,
+                        Err(e) => {
+                            return parser_error!(format!("Invalid integer type: {e}"), pointer);
+                        }
+                    };
+                    match cmd_str.to_uppercase().as_str() {
+                        "EXPIREAT" => Ok(Command::ExpireAt {
+                            key,
+                            timestamp: time,
+                        }),
+                        "PEXPIRE" => Ok(Command::PExpire { key, millis: time }),
+                        _ => Ok(Command::PExpireAt {
+                            key,
+                            timestamp: time,
+                        }),
+                    }
+                }
+                Err(e) => parser_error!(e, pointer),
+            },
+            Err(e) => parser_error!(e, pointer),
+        },
+
+        "PERSIST" => match parse_arg(&chars, &mut pointer, "key") {
+            Ok(key) => Ok(Command::Persist { key }),
+            Err(e) => parser_error!(e, pointer),
+        },
+
         "GETTTL" => match parse_arg(&chars, &mut pointer, "key") {
             Ok(key) => Ok(Command::GetTtl { key }),
             Err(e) => parser_error!(e, pointer),
         },
 
+        "PTTL" => match parse_arg(&chars, &mut pointer, "key") {
+            Ok(key) => Ok(Command::PTtl { key }),
+            Err(e) => parser_error!(e, pointer),
+        },
+
         // set a config value
         "CONFSET" => match parse_arg(&chars, &mut pointer, "key") {
             Ok(key) => match parse_arg(&chars, &mut pointer, "value") {
@@ -493,6 +1210,14 @@ pub fn parse_line(line: &str) -> Result<Command, Error> {
             Err(e) => parser_error!(e, pointer),
         },
 
+        "TYPE" | "OBJECT" | "MEMORY" => {
+            let args = match parse_rest(&chars, &mut pointer, "argument") {
+                Ok(args) => args,
+                Err(e) => return parser_error!(e, pointer),
+            };
+            parse_introspection_command(&cmd_str.to_uppercase(), args, pointer)
+        }
+
         "CONFOPTIONS" => Ok(Command::ConfOptions),
 
         "EVICTNOW" => {
@@ -517,57 +1242,322 @@ pub fn parse_line(line: &str) -> Result<Command, Error> {
             Err(e) => parser_error!(e, pointer),
         },
 
-        "RENAME" => match parse_arg(&chars, &mut pointer, "old_key") {
-            Ok(old_key) => match parse_arg(&chars, &mut pointer, "new_key") {
-                Ok(new_key) => Ok(Command::Rename { old_key, new_key }),
+        "INCRBY" | "DECRBY" => match parse_arg(&chars, &mut pointer, "key") {
+            Ok(key) => match parse_arg(&chars, &mut pointer, "amount") {
+                Ok(v) => {
+                    let amount = match v.parse::<i64>() {
+                        Ok(v) => v,
+                        Err(e) => {
+                            return parser_error!(format!("Invalid integer type: {e}"), pointer);
+                        }
+                    };
+                    if cmd_str.eq_ignore_ascii_case("INCRBY") {
+                        Ok(Command::IncrBy { key, amount })
+                    } else {
+                        Ok(Command::DecrBy { key, amount })
+                    }
+                }
                 Err(e) => parser_error!(e, pointer),
             },
             Err(e) => parser_error!(e, pointer),
         },
 
-        "KEYS" => Ok(Command::Keys),
+        "INCRBYFLOAT" => match parse_arg(&chars, &mut pointer, "key") {
+            Ok(key) => match parse_arg(&chars, &mut pointer, "amount") {
+                Ok(amount) => match amount.parse::<f64>() {
+                    Ok(f) if f.is_finite() => Ok(Command::IncrByFloat { key, amount }),
+                    _ => parser_error!(format!("Invalid float type: {amount}"), pointer),
+                },
+                Err(e) => parser_error!(e, pointer),
+            },
+            Err(e) => parser_error!(e, pointer),
+        },
 
-        other => parser_error!(format!("Unknown command: {other}"), pointer),
-    }
-}
+        "APPEND" => match parse_arg(&chars, &mut pointer, "key") {
+            Ok(key) => match parse_arg(&chars, &mut pointer, "value") {
+                Ok(value) => Ok(Command::Append { key, value }),
+                Err(e) => parser_error!(e, pointer),
+            },
+            Err(e) => parser_error!(e, pointer),
+        },
 
-#[cfg(test)]
-mod cli_parsing {
-    use super::*;
+        "STRLEN" => match parse_arg(&chars, &mut pointer, "key") {
+            Ok(key) => Ok(Command::StrLen { key }),
+            Err(e) => parser_error!(e, pointer),
+        },
 
-    #[test]
-    fn test_parse_get_ok() {
-        assert_eq!(
-            parse_line("GET mykey"),
-            Ok(Command::Get {
-                key: "mykey".to_string()
-            })
-        );
-        assert_eq!(
-            parse_line("get MyKey"),
-            Ok(Command::Get {
-                key: "MyKey".to_string()
-            })
-        );
-        assert_eq!(
-            parse_line("GET \"my key\""),
-            Ok(Command::Get {
-                key: "my key".to_string()
-            })
-        );
-        assert_eq!(
-            parse_line("GET 'another key'"),
-            Ok(Command::Get {
-                key: "another key".to_string()
-            })
-        );
-        assert_eq!(
-            parse_line("  GET  spaced_key  "),
-            Ok(Command::Get {
-                key: "spaced_key".to_string()
-            })
-        );
-    }
+        "GETRANGE" => match parse_arg(&chars, &mut pointer, "key") {
+            Ok(key) => match parse_arg(&chars, &mut pointer, "start") {
+                Ok(start) => match parse_arg(&chars, &mut pointer, "end") {
+                    Ok(end) => match (start.parse::<i64>(), end.parse::<i64>()) {
+                        (Ok(start), Ok(end)) => Ok(Command::GetRange { key, start, end }),
+                        (Err(e), _) | (_, Err(e)) => {
+                            parser_error!(format!("Invalid integer type: {e}"), pointer)
+                        }
+                    },
+                    Err(e) => parser_error!(e, pointer),
+                },
+                Err(e) => parser_error!(e, pointer),
+            },
+            Err(e) => parser_error!(e, pointer),
+        },
+
+        "SETRANGE" => match parse_arg(&chars, &mut pointer, "key") {
+            Ok(key) => match parse_arg(&chars, &mut pointer, "offset") {
+                Ok(offset) => match offset.parse::<usize>() {
+                    Ok(offset) => match parse_arg(&chars, &mut pointer, "value") {
+                        Ok(value) => Ok(Command::SetRange { key, offset, value }),
+                        Err(e) => parser_error!(e, pointer),
+                    },
+                    Err(e) => parser_error!(format!("Invalid integer type: {e}"), pointer),
+                },
+                Err(e) => parser_error!(e, pointer),
+            },
+            Err(e) => parser_error!(e, pointer),
+        },
+
+        "GETDEL" => match parse_arg(&chars, &mut pointer, "key") {
+            Ok(key) => Ok(Command::GetDel { key }),
+            Err(e) => parser_error!(e, pointer),
+        },
+
+        // Options are validated by the server: EX/PX/EXAT/PXAT <n> or PERSIST
+        "GETEX" => match parse_arg(&chars, &mut pointer, "key") {
+            Ok(key) => match parse_rest(&chars, &mut pointer, "option") {
+                Ok(options) => Ok(Command::GetEx { key, options }),
+                Err(e) => parser_error!(format!("GETEX: {e}"), pointer),
+            },
+            Err(e) => parser_error!(e, pointer),
+        },
+
+        "SETBIT" | "GETBIT" | "BITCOUNT" | "BITPOS" | "BITOP" => {
+            let args = match parse_rest(&chars, &mut pointer, "argument") {
+                Ok(args) => args,
+                Err(e) => return parser_error!(e, pointer),
+            };
+            parse_bitmap_command(&cmd_str.to_uppercase(), args, pointer)
+        }
+
+        "PFADD" => match parse_arg(&chars, &mut pointer, "key") {
+            Ok(key) => match parse_rest(&chars, &mut pointer, "element") {
+                Ok(elements) => Ok(Command::PfAdd { key, elements }),
+                Err(e) => parser_error!(format!("PFADD: {e}"), pointer),
+            },
+            Err(e) => parser_error!(e, pointer),
+        },
+
+        "PFCOUNT" => match parse_rest(&chars, &mut pointer, "key") {
+            Ok(keys) if keys.is_empty() => parser_error!("PFCOUNT: Missing key", pointer),
+            Ok(keys) => Ok(Command::PfCount { keys }),
+            Err(e) => parser_error!(format!("PFCOUNT: {e}"), pointer),
+        },
+
+        "PFMERGE" => match parse_arg(&chars, &mut pointer, "destination") {
+            Ok(destination) => match parse_rest(&chars, &mut pointer, "source key") {
+                Ok(sources) => Ok(Command::PfMerge {
+                    destination,
+                    sources,
+                }),
+                Err(e) => parser_error!(format!("PFMERGE: {e}"), pointer),
+            },
+            Err(e) => parser_error!(e, pointer),
+        },
+
+        "BF.RESERVE" | "BF.ADD" | "BF.MADD" | "BF.EXISTS" | "BF.MEXISTS" | "CF.RESERVE"
+        | "CF.ADD" | "CF.ADDNX" | "CF.EXISTS" | "CF.DEL" => {
+            let args = match parse_rest(&chars, &mut pointer, "argument") {
+                Ok(args) => args,
+                Err(e) => return parser_error!(e, pointer),
+            };
+            parse_filter_command(&cmd_str.to_uppercase(), args, pointer)
+        }
+
+        "LPUSH" | "RPUSH" | "LPOP" | "RPOP" | "LLEN" | "BLPOP" | "BRPOP" => {
+            let args = match parse_rest(&chars, &mut pointer, "argument") {
+                Ok(args) => args,
+                Err(e) => return parser_error!(e, pointer),
+            };
+            parse_list_command(&cmd_str.to_uppercase(), args, pointer)
+        }
+
+        "XADD" | "XRANGE" | "XREVRANGE" | "XLEN" | "XTRIM" | "XGROUP" | "XREADGROUP" | "XACK"
+        | "XPENDING" => {
+            let args = match parse_rest(&chars, &mut pointer, "argument") {
+                Ok(args) => args,
+                Err(e) => return parser_error!(e, pointer),
+            };
+            parse_stream_command(&cmd_str.to_uppercase(), args, pointer)
+        }
+
+        "GEOADD" | "GEODIST" | "GEOSEARCH" => {
+            let args = match parse_rest(&chars, &mut pointer, "argument") {
+                Ok(args) => args,
+                Err(e) => return parser_error!(e, pointer),
+            };
+            parse_geo_command(&cmd_str.to_uppercase(), args, pointer)
+        }
+
+        "JSON.SET" | "JSON.GET" | "JSON.DEL" | "JSON.NUMINCRBY" | "JSON.ARRAPPEND" => {
+            let args = match parse_rest(&chars, &mut pointer, "argument") {
+                Ok(args) => args,
+                Err(e) => return parser_error!(e, pointer),
+            };
+            parse_json_command(&cmd_str.to_uppercase(), args, pointer)
+        }
+
+        "GETV" => match parse_arg(&chars, &mut pointer, "key") {
+            Ok(key) => Ok(Command::GetV { key }),
+            Err(e) => parser_error!(e, pointer),
+        },
+
+        "CAS" => match parse_arg(&chars, &mut pointer, "key") {
+            Ok(key) => match parse_arg(&chars, &mut pointer, "version") {
+                Ok(version) => match version.parse::<u64>() {
+                    Ok(version) => match parse_arg(&chars, &mut pointer, "value") {
+                        Ok(value) => Ok(Command::Cas {
+                            key,
+                            version,
+                            value,
+                        }),
+                        Err(e) => parser_error!(e, pointer),
+                    },
+                    Err(e) => parser_error!(format!("Invalid integer type: {e}"), pointer),
+                },
+                Err(e) => parser_error!(e, pointer),
+            },
+            Err(e) => parser_error!(e, pointer),
+        },
+
+        "RENAME" => match parse_arg(&chars, &mut pointer, "old_key") {
+            Ok(old_key) => match parse_arg(&chars, &mut pointer, "new_key") {
+                Ok(new_key) => Ok(Command::Rename { old_key, new_key }),
+                Err(e) => parser_error!(e, pointer),
+            },
+            Err(e) => parser_error!(e, pointer),
+        },
+
+        // KEYS or KEYS user:*
+        "KEYS" => {
+            let pattern = parse_arg(&chars, &mut pointer, "pattern").ok();
+            Ok(Command::Keys { pattern })
+        }
+
+        "GETMATCH" => match parse_arg(&chars, &mut pointer, "pattern") {
+            Ok(pattern) => Ok(Command::GetMatch { pattern }),
+            Err(e) => parser_error!(format!("GETMATCH: {e}"), pointer),
+        },
+
+        "DELETEMATCH" => match parse_arg(&chars, &mut pointer, "pattern") {
+            Ok(pattern) => Ok(Command::DeleteMatch { pattern }),
+            Err(e) => parser_error!(format!("DELETEMATCH: {e}"), pointer),
+        },
+
+        "SELECT" => match parse_arg(&chars, &mut pointer, "db") {
+            Ok(db) => Ok(Command::Select { db }),
+            Err(e) => parser_error!(format!("SELECT: {e}"), pointer),
+        },
+
+        "MOVE" => match parse_arg(&chars, &mut pointer, "key") {
+            Ok(key) => match parse_arg(&chars, &mut pointer, "db") {
+                Ok(db) => Ok(Command::Move { key, db }),
+                Err(e) => parser_error!(format!("MOVE: {e}"), pointer),
+            },
+            Err(e) => parser_error!(format!("MOVE: {e}"), pointer),
+        },
+
+        "DBLIST" => Ok(Command::DbList),
+
+        // INFO or INFO memory stats
+        "INFO" => match parse_rest(&chars, &mut pointer, "section") {
+            Ok(sections) => Ok(Command::Info { sections }),
+            Err(e) => parser_error!(format!("INFO: {e}"), pointer),
+        },
+
+        // SCAN 0 MATCH user:* COUNT 100 TYPE int
+        "SCAN" => {
+            let cursor = match parse_arg(&chars, &mut pointer, "cursor") {
+                Ok(c) => match c.parse::<u64>() {
+                    Ok(c) => c,
+                    Err(e) => return parser_error!(format!("Invalid cursor: {e}"), pointer),
+                },
+                Err(e) => return parser_error!(format!("SCAN: {e}"), pointer),
+            };
+
+            let mut pattern = None;
+            let mut count = None;
+            let mut type_name = None;
+            while pointer < l {
+                let option = match parse_arg(&chars, &mut pointer, "option") {
+                    Ok(o) => o,
+                    Err(e) => return parser_error!(format!("SCAN: {e}"), pointer),
+                };
+                let value = match parse_arg(&chars, &mut pointer, "option value") {
+                    Ok(v) => v,
+                    Err(e) => return parser_error!(format!("SCAN: {e}"), pointer),
+                };
+
+                match option.to_uppercase().as_str() {
+                    "MATCH" => pattern = Some(value),
+                    "COUNT" => match value.parse::<usize>() {
+                        Ok(c) => count = Some(c),
+                        Err(e) => return parser_error!(format!("Invalid count: {e}"), pointer),
+                    },
+                    "TYPE" => type_name = Some(value),
+                    other => {
+                        return parser_error!(format!("Unknown SCAN option: {other}"), pointer);
+                    }
+                }
+            }
+
+            Ok(Command::Scan {
+                cursor,
+                pattern,
+                count,
+                type_name,
+            })
+        }
+
+        other => parser_error!(format!("Unknown command: {other}"), pointer),
+    }
+}
+
+#[cfg(test)]
+mod cli_parsing {
+    use super::*;
+
+    #[test]
+    fn test_parse_get_ok() {
+        assert_eq!(
+            parse_line("GET mykey"),
+            Ok(Command::Get {
+                key: "mykey".to_string()
+            })
+        );
+        assert_eq!(
+            parse_line("get MyKey"),
+            Ok(Command::Get {
+                key: "MyKey".to_string()
+            })
+        );
+        assert_eq!(
+            parse_line("GET \"my key\""),
+            Ok(Command::Get {
+                key: "my key".to_string()
+            })
+        );
+        assert_eq!(
+            parse_line("GET 'another key'"),
+            Ok(Command::Get {
+                key: "another key".to_string()
+            })
+        );
+        assert_eq!(
+            parse_line("  GET  spaced_key  "),
+            Ok(Command::Get {
+                key: "spaced_key".to_string()
+            })
+        );
+    }
 
     #[test]
     fn test_parse_get_error() {
@@ -583,49 +1573,56 @@ mod cli_parsing {
             parse_line("SET mykey myvalue"),
             Ok(Command::Set {
                 key: "mykey".to_string(),
-                value: "myvalue".to_string()
+                value: "myvalue".to_string(),
+                options: SetOptions::default()
             })
         );
         assert_eq!(
             parse_line("set MyKey YourValue"),
             Ok(Command::Set {
                 key: "MyKey".to_string(),
-                value: "YourValue".to_string()
+                value: "YourValue".to_string(),
+                options: SetOptions::default()
             })
         );
         assert_eq!(
             parse_line("SET \"my key\" \"my value\""),
             Ok(Command::Set {
                 key: "my key".to_string(),
-                value: "my value".to_string()
+                value: "my value".to_string(),
+                options: SetOptions::default()
             })
         );
         assert_eq!(
             parse_line("SET 'key name' 'value content'"),
             Ok(Command::Set {
                 key: "key name".to_string(),
-                value: "value content".to_string()
+                value: "value content".to_string(),
+                options: SetOptions::default()
             })
         );
         assert_eq!(
             parse_line("SET key1 \"value with spaces\""),
             Ok(Command::Set {
                 key: "key1".to_string(),
-                value: "value with spaces".to_string()
+                value: "value with spaces".to_string(),
+                options: SetOptions::default()
             })
         );
         assert_eq!(
             parse_line("SET \"quoted key\" unquoted_value"),
             Ok(Command::Set {
                 key: "quoted key".to_string(),
-                value: "unquoted_value".to_string()
+                value: "unquoted_value".to_string(),
+                options: SetOptions::default()
             })
         );
         assert_eq!(
             parse_line("  SET  key1   value1  "),
             Ok(Command::Set {
                 key: "key1".to_string(),
-                value: "value1".to_string()
+                value: "value1".to_string(),
+                options: SetOptions::default()
             })
         );
     }
@@ -640,6 +1637,42 @@ mod cli_parsing {
         assert!(parse_line("SET \'key\' ").is_err());
     }
 
+    #[test]
+    fn test_parse_set_options() {
+        use std::time::Duration;
+        use volatix_core::{SetCondition, SetExpiry};
+
+        assert_eq!(
+            parse_line("SET lock owner NX px 3000 GET"),
+            Ok(Command::Set {
+                key: "lock".to_string(),
+                value: "owner".to_string(),
+                options: SetOptions {
+                    condition: SetCondition::IfAbsent,
+                    expiry: SetExpiry::In(Duration::from_millis(3000)),
+                    get: true,
+                }
+            })
+        );
+        assert_eq!(
+            parse_line("SET key 'new value' XX KEEPTTL"),
+            Ok(Command::Set {
+                key: "key".to_string(),
+                value: "new value".to_string(),
+                options: SetOptions {
+                    condition: SetCondition::IfPresent,
+                    expiry: SetExpiry::Keep,
+                    get: false,
+                }
+            })
+        );
+        assert!(parse_line("SET key value NX XX").is_err());
+        assert!(parse_line("SET key value EX").is_err());
+        assert!(parse_line("SET key value EX ten").is_err());
+        assert!(parse_line("SET key value EX 10 PX 100").is_err());
+        assert!(parse_line("SET key value FOREVER").is_err());
+    }
+
     #[test]
     fn test_parse_delete_ok() {
         assert_eq!(
@@ -724,6 +1757,579 @@ mod cli_parsing {
         assert_eq!(map, expected);
     }
 
+    #[test]
+    fn test_parse_pattern_commands() {
+        assert_eq!(parse_line("KEYS"), Ok(Command::Keys { pattern: None }));
+        assert_eq!(
+            parse_line("KEYS user:42:*"),
+            Ok(Command::Keys {
+                pattern: Some("user:42:*".to_string())
+            })
+        );
+        assert_eq!(
+            parse_line("GETMATCH 'user:[0-9]'"),
+            Ok(Command::GetMatch {
+                pattern: "user:[0-9]".to_string()
+            })
+        );
+        assert_eq!(
+            parse_line("DELETEMATCH session:*"),
+            Ok(Command::DeleteMatch {
+                pattern: "session:*".to_string()
+            })
+        );
+        assert!(parse_line("GETMATCH").is_err());
+        assert!(parse_line("DELETEMATCH").is_err());
+    }
+
+    #[test]
+    fn test_parse_database_commands() {
+        assert_eq!(
+            parse_line("SELECT 1"),
+            Ok(Command::Select {
+                db: "1".to_string()
+            })
+        );
+        assert_eq!(
+            parse_line("move session:1 'sessions db'"),
+            Ok(Command::Move {
+                key: "session:1".to_string(),
+                db: "sessions db".to_string()
+            })
+        );
+        assert_eq!(parse_line("DBLIST"), Ok(Command::DbList));
+        assert!(parse_line("SELECT").is_err());
+        assert!(parse_line("MOVE key").is_err());
+    }
+
+    #[test]
+    fn test_parse_string_commands() {
+        assert_eq!(
+            parse_line("APPEND log 'line 2;'"),
+            Ok(Command::Append {
+                key: "log".to_string(),
+                value: "line 2;".to_string()
+            })
+        );
+        assert_eq!(
+            parse_line("strlen log"),
+            Ok(Command::StrLen {
+                key: "log".to_string()
+            })
+        );
+        assert_eq!(
+            parse_line("GETRANGE log 0 -1"),
+            Ok(Command::GetRange {
+                key: "log".to_string(),
+                start: 0,
+                end: -1
+            })
+        );
+        assert_eq!(
+            parse_line("SETRANGE log 6 Redis"),
+            Ok(Command::SetRange {
+                key: "log".to_string(),
+                offset: 6,
+                value: "Redis".to_string()
+            })
+        );
+        assert_eq!(
+            parse_line("GETDEL log"),
+            Ok(Command::GetDel {
+                key: "log".to_string()
+            })
+        );
+        assert_eq!(
+            parse_line("GETEX log EX 60"),
+            Ok(Command::GetEx {
+                key: "log".to_string(),
+                options: vec!["EX".to_string(), "60".to_string()]
+            })
+        );
+        assert_eq!(
+            parse_line("GETEX log"),
+            Ok(Command::GetEx {
+                key: "log".to_string(),
+                options: vec![]
+            })
+        );
+        assert!(parse_line("APPEND log").is_err());
+        assert!(parse_line("GETRANGE log 0").is_err());
+        assert!(parse_line("SETRANGE log -1 x").is_err());
+    }
+
+    #[test]
+    fn test_parse_bitmap_commands() {
+        assert_eq!(
+            parse_line("SETBIT active 42 1"),
+            Ok(Command::SetBit {
+                key: "active".to_string(),
+                offset: 42,
+                bit: true
+            })
+        );
+        assert_eq!(
+            parse_line("getbit active 42"),
+            Ok(Command::GetBit {
+                key: "active".to_string(),
+                offset: 42
+            })
+        );
+        assert_eq!(
+            parse_line("BITCOUNT active"),
+            Ok(Command::BitCount {
+                key: "active".to_string(),
+                range: None
+            })
+        );
+        assert_eq!(
+            parse_line("BITCOUNT active 0 -1"),
+            Ok(Command::BitCount {
+                key: "active".to_string(),
+                range: Some((0, -1))
+            })
+        );
+        assert_eq!(
+            parse_line("BITPOS active 0 2"),
+            Ok(Command::BitPos {
+                key: "active".to_string(),
+                bit: false,
+                start: Some(2),
+                end: None
+            })
+        );
+        assert_eq!(
+            parse_line("BITOP and both mon tue"),
+            Ok(Command::BitOp {
+                op: BitOp::And,
+                destination: "both".to_string(),
+                keys: vec!["mon".to_string(), "tue".to_string()]
+            })
+        );
+        assert!(parse_line("SETBIT active 42 2").is_err());
+        assert!(parse_line("SETBIT active -1 1").is_err());
+        assert!(parse_line("BITCOUNT active 0").is_err());
+        assert!(parse_line("BITPOS active 1 0 1 2").is_err());
+        assert!(parse_line("BITOP NAND both mon").is_err());
+        assert!(parse_line("BITOP OR both").is_err());
+    }
+
+    #[test]
+    fn test_parse_hyperloglog_commands() {
+        assert_eq!(
+            parse_line("PFADD visitors alice bob"),
+            Ok(Command::PfAdd {
+                key: "visitors".to_string(),
+                elements: vec!["alice".to_string(), "bob".to_string()]
+            })
+        );
+        assert_eq!(
+            parse_line("pfadd visitors"),
+            Ok(Command::PfAdd {
+                key: "visitors".to_string(),
+                elements: vec![]
+            })
+        );
+        assert_eq!(
+            parse_line("PFCOUNT mon tue"),
+            Ok(Command::PfCount {
+                keys: vec!["mon".to_string(), "tue".to_string()]
+            })
+        );
+        assert_eq!(
+            parse_line("PFMERGE week mon tue"),
+            Ok(Command::PfMerge {
+                destination: "week".to_string(),
+                sources: vec!["mon".to_string(), "tue".to_string()]
+            })
+        );
+        assert!(parse_line("PFADD").is_err());
+        assert!(parse_line("PFCOUNT").is_err());
+        assert!(parse_line("PFMERGE").is_err());
+    }
+
+    #[test]
+    fn test_parse_filter_commands() {
+        assert_eq!(
+            parse_line("BF.RESERVE seen 0.001 10000"),
+            Ok(Command::BfReserve {
+                key: "seen".to_string(),
+                error_rate: "0.001".to_string(),
+                capacity: 10000
+            })
+        );
+        assert_eq!(
+            parse_line("bf.add seen event:1"),
+            Ok(Command::BfAdd {
+                key: "seen".to_string(),
+                item: "event:1".to_string()
+            })
+        );
+        assert_eq!(
+            parse_line("BF.MEXISTS seen a b"),
+            Ok(Command::BfMExists {
+                key: "seen".to_string(),
+                items: vec!["a".to_string(), "b".to_string()]
+            })
+        );
+        assert_eq!(
+            parse_line("CF.RESERVE jobs 1000"),
+            Ok(Command::CfReserve {
+                key: "jobs".to_string(),
+                capacity: 1000
+            })
+        );
+        assert_eq!(
+            parse_line("CF.DEL jobs job:1"),
+            Ok(Command::CfDel {
+                key: "jobs".to_string(),
+                item: "job:1".to_string()
+            })
+        );
+        assert!(parse_line("BF.RESERVE seen often 100").is_err());
+        assert!(parse_line("CF.RESERVE jobs -1").is_err());
+        assert!(parse_line("BF.ADD seen a b").is_err());
+        assert!(parse_line("BF.MADD seen").is_err());
+        assert!(parse_line("CF.EXISTS jobs").is_err());
+    }
+
+    #[test]
+    fn test_parse_list_commands() {
+        assert_eq!(
+            parse_line("LPUSH jobs a b"),
+            Ok(Command::LPush {
+                key: "jobs".to_string(),
+                values: vec!["a".to_string(), "b".to_string()]
+            })
+        );
+        assert_eq!(
+            parse_line("rpop jobs"),
+            Ok(Command::RPop {
+                key: "jobs".to_string()
+            })
+        );
+        assert_eq!(
+            parse_line("BLPOP high low 0.5"),
+            Ok(Command::BLPop {
+                keys: vec!["high".to_string(), "low".to_string()],
+                timeout: "0.5".to_string()
+            })
+        );
+        assert_eq!(
+            parse_line("BRPOP jobs 0"),
+            Ok(Command::BRPop {
+                keys: vec!["jobs".to_string()],
+                timeout: "0".to_string()
+            })
+        );
+        assert!(parse_line("RPUSH jobs").is_err());
+        assert!(parse_line("LLEN a b").is_err());
+        assert!(parse_line("BLPOP jobs").is_err());
+        assert!(parse_line("BLPOP jobs -1").is_err());
+        assert!(parse_line("BRPOP jobs soon").is_err());
+    }
+
+    #[test]
+    fn test_parse_stream_commands() {
+        assert_eq!(
+            parse_line("XADD jobs MAXLEN 100 * job resize"),
+            Ok(Command::XAdd {
+                key: "jobs".to_string(),
+                args: ["MAXLEN", "100", "*", "job", "resize"]
+                    .map(String::from)
+                    .to_vec()
+            })
+        );
+        assert_eq!(
+            parse_line("XREVRANGE jobs + - COUNT 2"),
+            Ok(Command::XRevRange {
+                key: "jobs".to_string(),
+                end: "+".to_string(),
+                start: "-".to_string(),
+                count: Some(2)
+            })
+        );
+        assert_eq!(
+            parse_line("xtrim jobs MAXLEN ~ 10"),
+            Ok(Command::XTrim {
+                key: "jobs".to_string(),
+                max_len: 10
+            })
+        );
+        assert_eq!(
+            parse_line("XACK jobs workers 1-0 2-0"),
+            Ok(Command::XAck {
+                key: "jobs".to_string(),
+                group: "workers".to_string(),
+                ids: vec!["1-0".to_string(), "2-0".to_string()]
+            })
+        );
+        assert_eq!(
+            parse_line("XREADGROUP GROUP workers w1 STREAMS jobs >"),
+            Ok(Command::XReadGroup {
+                args: ["GROUP", "workers", "w1", "STREAMS", "jobs", ">"]
+                    .map(String::from)
+                    .to_vec()
+            })
+        );
+        assert!(parse_line("XADD jobs * job").is_err());
+        assert!(parse_line("XRANGE jobs - + LIMIT 2").is_err());
+        assert!(parse_line("XTRIM jobs 10").is_err());
+        assert!(parse_line("XACK jobs workers").is_err());
+        assert!(parse_line("XLEN").is_err());
+    }
+
+    #[test]
+    fn test_parse_introspection_commands() {
+        assert_eq!(
+            parse_line("type jobs"),
+            Ok(Command::Type {
+                key: "jobs".to_string()
+            })
+        );
+        assert_eq!(
+            parse_line("OBJECT idletime jobs"),
+            Ok(Command::Object {
+                subcommand: "IDLETIME".to_string(),
+                key: "jobs".to_string()
+            })
+        );
+        assert_eq!(
+            parse_line("MEMORY USAGE jobs"),
+            Ok(Command::MemoryUsage {
+                key: "jobs".to_string()
+            })
+        );
+        assert!(parse_line("TYPE").is_err());
+        assert!(parse_line("TYPE a b").is_err());
+        assert!(parse_line("OBJECT REFCOUNT jobs").is_err());
+        assert!(parse_line("OBJECT FREQ").is_err());
+        assert!(parse_line("MEMORY STATS").is_err());
+        assert!(parse_line("MEMORY USAGE").is_err());
+    }
+
+    #[test]
+    fn test_parse_info_command() {
+        assert_eq!(
+            parse_line("INFO"),
+            Ok(Command::Info {
+                sections: Vec::new()
+            })
+        );
+        assert_eq!(
+            parse_line("info memory keyspace"),
+            Ok(Command::Info {
+                sections: vec!["memory".to_string(), "keyspace".to_string()]
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_geo_commands() {
+        assert_eq!(
+            parse_line("GEOADD Sicily 13.361389 38.115556 Palermo"),
+            Ok(Command::GeoAdd {
+                key: "Sicily".to_string(),
+                args: ["13.361389", "38.115556", "Palermo"]
+                    .map(String::from)
+                    .to_vec()
+            })
+        );
+        assert_eq!(
+            parse_line("geodist Sicily Palermo Catania km"),
+            Ok(Command::GeoDist {
+                key: "Sicily".to_string(),
+                member1: "Palermo".to_string(),
+                member2: "Catania".to_string(),
+                unit: Some("km".to_string())
+            })
+        );
+        assert_eq!(
+            parse_line("GEOSEARCH Sicily FROMLONLAT 15 37 BYRADIUS 200 km WITHDIST"),
+            Ok(Command::GeoSearch {
+                key: "Sicily".to_string(),
+                args: [
+                    "FROMLONLAT",
+                    "15",
+                    "37",
+                    "BYRADIUS",
+                    "200",
+                    "km",
+                    "WITHDIST"
+                ]
+                .map(String::from)
+                .to_vec()
+            })
+        );
+        assert!(parse_line("GEOADD Sicily 13.361389 38.115556").is_err());
+        assert!(parse_line("GEODIST Sicily Palermo").is_err());
+        assert!(parse_line("GEODIST Sicily Palermo Catania km m").is_err());
+        assert!(parse_line("GEOSEARCH Sicily FROMMEMBER Palermo").is_err());
+    }
+
+    #[test]
+    fn test_parse_json_commands() {
+        assert_eq!(
+            parse_line(r#"JSON.SET user $ '{"name":"Ada","tags":[]}' NX"#),
+            Ok(Command::JsonSet {
+                key: "user".to_string(),
+                path: "$".to_string(),
+                value: r#"{"name":"Ada","tags":[]}"#.to_string(),
+                condition: Some("NX".to_string())
+            })
+        );
+        assert_eq!(
+            parse_line("json.get user $.name .tags"),
+            Ok(Command::JsonGet {
+                key: "user".to_string(),
+                paths: vec!["$.name".to_string(), ".tags".to_string()]
+            })
+        );
+        assert_eq!(
+            parse_line("JSON.DEL user"),
+            Ok(Command::JsonDel {
+                key: "user".to_string(),
+                path: None
+            })
+        );
+        assert_eq!(
+            parse_line("JSON.NUMINCRBY user $.visits 1.5"),
+            Ok(Command::JsonNumIncrBy {
+                key: "user".to_string(),
+                path: "$.visits".to_string(),
+                value: "1.5".to_string()
+            })
+        );
+        assert_eq!(
+            parse_line(r#"JSON.ARRAPPEND user $.tags '"admin"' 3"#),
+            Ok(Command::JsonArrAppend {
+                key: "user".to_string(),
+                path: "$.tags".to_string(),
+                values: vec![r#""admin""#.to_string(), "3".to_string()]
+            })
+        );
+        assert!(parse_line("JSON.SET user $").is_err());
+        assert!(parse_line("JSON.SET user $ 1 NX XX").is_err());
+        assert!(parse_line("JSON.GET").is_err());
+        assert!(parse_line("JSON.DEL user $.a $.b").is_err());
+        assert!(parse_line("JSON.NUMINCRBY user $.visits").is_err());
+        assert!(parse_line("JSON.ARRAPPEND user $.tags").is_err());
+    }
+
+    #[test]
+    fn test_parse_versioned_commands() {
+        assert_eq!(
+            parse_line("GETV account"),
+            Ok(Command::GetV {
+                key: "account".to_string()
+            })
+        );
+        assert_eq!(
+            parse_line("cas account 7 '{\"balance\": 10}'"),
+            Ok(Command::Cas {
+                key: "account".to_string(),
+                version: 7,
+                value: "{\"balance\": 10}".to_string()
+            })
+        );
+        assert!(parse_line("CAS account -1 x").is_err());
+        assert!(parse_line("CAS account 7").is_err());
+    }
+
+    #[test]
+    fn test_parse_numeric_commands() {
+        assert_eq!(
+            parse_line("INCRBY visits 5"),
+            Ok(Command::IncrBy {
+                key: "visits".to_string(),
+                amount: 5
+            })
+        );
+        assert_eq!(
+            parse_line("decrby visits -3"),
+            Ok(Command::DecrBy {
+                key: "visits".to_string(),
+                amount: -3
+            })
+        );
+        assert_eq!(
+            parse_line("INCRBYFLOAT price 0.25"),
+            Ok(Command::IncrByFloat {
+                key: "price".to_string(),
+                amount: "0.25".to_string()
+            })
+        );
+        assert!(parse_line("INCRBY visits 1.5").is_err());
+        assert!(parse_line("INCRBYFLOAT price nan").is_err());
+        assert!(parse_line("DECRBY visits").is_err());
+    }
+
+    #[test]
+    fn test_parse_expiry_commands() {
+        assert_eq!(
+            parse_line("EXPIREAT session 1700000000"),
+            Ok(Command::ExpireAt {
+                key: "session".to_string(),
+                timestamp: 1700000000
+            })
+        );
+        assert_eq!(
+            parse_line("pexpire session 1500"),
+            Ok(Command::PExpire {
+                key: "session".to_string(),
+                millis: 1500
+            })
+        );
+        assert_eq!(
+            parse_line("PEXPIREAT session 1700000000000"),
+            Ok(Command::PExpireAt {
+                key: "session".to_string(),
+                timestamp: 1700000000000
+            })
+        );
+        assert_eq!(
+            parse_line("PERSIST session"),
+            Ok(Command::Persist {
+                key: "session".to_string()
+            })
+        );
+        assert_eq!(
+            parse_line("PTTL session"),
+            Ok(Command::PTtl {
+                key: "session".to_string()
+            })
+        );
+        assert!(parse_line("PEXPIRE session soon").is_err());
+        assert!(parse_line("EXPIREAT session").is_err());
+        assert!(parse_line("PERSIST").is_err());
+    }
+
+    #[test]
+    fn test_parse_scan() {
+        assert_eq!(
+            parse_line("SCAN 0"),
+            Ok(Command::Scan {
+                cursor: 0,
+                pattern: None,
+                count: None,
+                type_name: None
+            })
+        );
+        assert_eq!(
+            parse_line("scan 42 MATCH 'user:*' count 100 TYPE int"),
+            Ok(Command::Scan {
+                cursor: 42,
+                pattern: Some("user:*".to_string()),
+                count: Some(100),
+                type_name: Some("int".to_string())
+            })
+        );
+        assert!(parse_line("SCAN").is_err());
+        assert!(parse_line("SCAN abc").is_err());
+        assert!(parse_line("SCAN 0 COUNT").is_err());
+        assert!(parse_line("SCAN 0 LIMIT 10").is_err());
+    }
+
     #[test]
     fn test_parse_setlist() {
         let line = "SETLIST names ['foo', 'bar']";
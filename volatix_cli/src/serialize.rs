@@ -88,7 +88,10 @@ pub fn serialize_request(command: &Command) -> Vec<u8> {
             array(&v).as_bytes().to_vec()
         }
 
-        Command::Hello => bstring("HELLO").as_bytes().to_vec(),
+        Command::Hello => {
+            let v = [bstring("HELLO"), bstring("3")];
+            array(&v).as_bytes().to_vec()
+        }
 
         Command::Get { key } => {
             let v = [bstring("GET"), bstring(key)];
@@ -100,8 +103,13 @@ pub fn serialize_request(command: &Command) -> Vec<u8> {
             array(&v).as_bytes().to_vec()
         }
 
-        Command::Set { key, value } => {
-            let v = [bstring("SET"), bstring(key), bstring(value)];
+        Command::Set {
+            key,
+            value,
+            options,
+        } => {
+            let mut v = vec![bstring("SET"), bstring(key), bstring(value)];
+            v.extend(options.to_args().iter().map(|a| bstring(a)));
             array(&v).as_bytes().to_vec()
         }
 
@@ -180,16 +188,56 @@ pub fn serialize_request(command: &Command) -> Vec<u8> {
             array(&v).as_bytes().to_vec()
         }
 
+        Command::ExpireAt { key, timestamp } => {
+            let v = [bstring("EXPIREAT"), bstring(key), integer(*timestamp)];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::PExpire { key, millis } => {
+            let v = [bstring("PEXPIRE"), bstring(key), integer(*millis)];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::PExpireAt { key, timestamp } => {
+            let v = [bstring("PEXPIREAT"), bstring(key), integer(*timestamp)];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::Persist { key } => {
+            let v = [bstring("PERSIST"), bstring(key)];
+            array(&v).as_bytes().to_vec()
+        }
+
         Command::GetTtl { key } => {
             let v = [bstring("GETTTL"), bstring(key)];
             array(&v).as_bytes().to_vec()
         }
 
+        Command::PTtl { key } => {
+            let v = [bstring("PTTL"), bstring(key)];
+            array(&v).as_bytes().to_vec()
+        }
+
         Command::Dump { key } => {
             let v = [bstring("DUMP"), bstring(key)];
             array(&v).as_bytes().to_vec()
         }
 
+        Command::Type { key } => {
+            let v = [bstring("TYPE"), bstring(key)];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::Object { subcommand, key } => {
+            let v = [bstring("OBJECT"), bstring(subcommand), bstring(key)];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::MemoryUsage { key } => {
+            let v = [bstring("MEMORY"), bstring("USAGE"), bstring(key)];
+            array(&v).as_bytes().to_vec()
+        }
+
         Command::ConfOptions => bstring("CONFOPTIONS").as_bytes().to_vec(),
 
         Command::Incr { key } => {
@@ -202,15 +250,432 @@ pub fn serialize_request(command: &Command) -> Vec<u8> {
             array(&v).as_bytes().to_vec()
         }
 
+        Command::IncrBy { key, amount } => {
+            let v = [bstring("INCRBY"), bstring(key), integer(*amount)];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::DecrBy { key, amount } => {
+            let v = [bstring("DECRBY"), bstring(key), integer(*amount)];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::IncrByFloat { key, amount } => {
+            let v = [bstring("INCRBYFLOAT"), bstring(key), bstring(amount)];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::Append { key, value } => {
+            let v = [bstring("APPEND"), bstring(key), bstring(value)];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::StrLen { key } => {
+            let v = [bstring("STRLEN"), bstring(key)];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::GetRange { key, start, end } => {
+            let v = [
+                bstring("GETRANGE"),
+                bstring(key),
+                integer(*start),
+                integer(*end),
+            ];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::SetRange { key, offset, value } => {
+            let v = [
+                bstring("SETRANGE"),
+                bstring(key),
+                integer(*offset as i64),
+                bstring(value),
+            ];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::GetDel { key } => {
+            let v = [bstring("GETDEL"), bstring(key)];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::GetEx { key, options } => {
+            let mut v = vec![bstring("GETEX"), bstring(key)];
+            v.extend(options.iter().map(|o| bstring(o)));
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::SetBit { key, offset, bit } => {
+            let v = [
+                bstring("SETBIT"),
+                bstring(key),
+                integer(*offset as i64),
+                integer(*bit as i64),
+            ];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::GetBit { key, offset } => {
+            let v = [bstring("GETBIT"), bstring(key), integer(*offset as i64)];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::BitCount { key, range } => {
+            let mut v = vec![bstring("BITCOUNT"), bstring(key)];
+            if let Some((start, end)) = range {
+                v.extend([integer(*start), integer(*end)]);
+            }
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::BitOp {
+            op,
+            destination,
+            keys,
+        } => {
+            let op = format!("{op:?}").to_uppercase();
+            let mut v = vec![bstring("BITOP"), bstring(&op), bstring(destination)];
+            v.extend(keys.iter().map(|k| bstring(k)));
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::BitPos {
+            key,
+            bit,
+            start,
+            end,
+        } => {
+            let mut v = vec![bstring("BITPOS"), bstring(key), integer(*bit as i64)];
+            v.extend(start.iter().chain(end).map(|n| integer(*n)));
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::PfAdd { key, elements } => {
+            let mut v = vec![bstring("PFADD"), bstring(key)];
+            v.extend(elements.iter().map(|e| bstring(e)));
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::PfCount { keys } => {
+            let mut v = vec![bstring("PFCOUNT")];
+            v.extend(keys.iter().map(|k| bstring(k)));
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::PfMerge {
+            destination,
+            sources,
+        } => {
+            let mut v = vec![bstring("PFMERGE"), bstring(destination)];
+            v.extend(sources.iter().map(|k| bstring(k)));
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::BfReserve {
+            key,
+            error_rate,
+            capacity,
+        } => {
+            let v = [
+                bstring("BF.RESERVE"),
+                bstring(key),
+                bstring(error_rate),
+                integer(*capacity as i64),
+            ];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::CfReserve { key, capacity } => {
+            let v = [
+                bstring("CF.RESERVE"),
+                bstring(key),
+                integer(*capacity as i64),
+            ];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::BfMAdd { key, items } | Command::BfMExists { key, items } => {
+            let name = match command {
+                Command::BfMAdd { .. } => "BF.MADD",
+                _ => "BF.MEXISTS",
+            };
+            let mut v = vec![bstring(name), bstring(key)];
+            v.extend(items.iter().map(|i| bstring(i)));
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::BfAdd { key, item }
+        | Command::BfExists { key, item }
+        | Command::CfAdd { key, item }
+        | Command::CfAddNx { key, item }
+        | Command::CfExists { key, item }
+        | Command::CfDel { key, item } => {
+            let name = match command {
+                Command::BfAdd { .. } => "BF.ADD",
+                Command::BfExists { .. } => "BF.EXISTS",
+                Command::CfAdd { .. } => "CF.ADD",
+                Command::CfAddNx { .. } => "CF.ADDNX",
+                Command::CfExists { .. } => "CF.EXISTS",
+                _ => "CF.DEL",
+            };
+            let v = [bstring(name), bstring(key), bstring(item)];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::LPush { key, values } | Command::RPush { key, values } => {
+            let name = match command {
+                Command::LPush { .. } => "LPUSH",
+                _ => "RPUSH",
+            };
+            let mut v = vec![bstring(name), bstring(key)];
+            v.extend(values.iter().map(|value| bstring(value)));
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::LPop { key } | Command::RPop { key } | Command::LLen { key } => {
+            let name = match command {
+                Command::LPop { .. } => "LPOP",
+                Command::RPop { .. } => "RPOP",
+                _ => "LLEN",
+            };
+            let v = [bstring(name), bstring(key)];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::BLPop { keys, timeout } | Command::BRPop { keys, timeout } => {
+            let name = match command {
+                Command::BLPop { .. } => "BLPOP",
+                _ => "BRPOP",
+            };
+            let mut v = vec![bstring(name)];
+            v.extend(keys.iter().map(|k| bstring(k)));
+            v.push(bstring(timeout));
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::XAdd { key, args } => {
+            let mut v = vec![bstring("XADD"), bstring(key)];
+            v.extend(args.iter().map(|a| bstring(a)));
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::XRange {
+            key,
+            start: first,
+            end: second,
+            count,
+        }
+        | Command::XRevRange {
+            key,
+            end: first,
+            start: second,
+            count,
+        } => {
+            let name = match command {
+                Command::XRange { .. } => "XRANGE",
+                _ => "XREVRANGE",
+            };
+            let mut v = vec![bstring(name), bstring(key), bstring(first), bstring(second)];
+            if let Some(count) = count {
+                v.extend([bstring("COUNT"), integer(*count as i64)]);
+            }
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::XLen { key } => {
+            let v = [bstring("XLEN"), bstring(key)];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::XTrim { key, max_len } => {
+            let v = [
+                bstring("XTRIM"),
+                bstring(key),
+                bstring("MAXLEN"),
+                integer(*max_len as i64),
+            ];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::XGroup { args } | Command::XReadGroup { args } => {
+            let name = match command {
+                Command::XGroup { .. } => "XGROUP",
+                _ => "XREADGROUP",
+            };
+            let mut v = vec![bstring(name)];
+            v.extend(args.iter().map(|a| bstring(a)));
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::XAck {
+            key,
+            group,
+            ids: args,
+        }
+        | Command::XPending { key, group, args } => {
+            let name = match command {
+                Command::XAck { .. } => "XACK",
+                _ => "XPENDING",
+            };
+            let mut v = vec![bstring(name), bstring(key), bstring(group)];
+            v.extend(args.iter().map(|a| bstring(a)));
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::GeoAdd { key, args } | Command::GeoSearch { key, args } => {
+            let name = match command {
+                Command::GeoAdd { .. } => "GEOADD",
+                _ => "GEOSEARCH",
+            };
+            let mut v = vec![bstring(name), bstring(key)];
+            v.extend(args.iter().map(|a| bstring(a)));
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::JsonSet {
+            key,
+            path,
+            value,
+            condition,
+        } => {
+            let mut v = vec![
+                bstring("JSON.SET"),
+                bstring(key),
+                bstring(path),
+                bstring(value),
+            ];
+            v.extend(condition.iter().map(|c| bstring(c)));
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::JsonGet { key, paths } => {
+            let mut v = vec![bstring("JSON.GET"), bstring(key)];
+            v.extend(paths.iter().map(|p| bstring(p)));
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::JsonDel { key, path } => {
+            let mut v = vec![bstring("JSON.DEL"), bstring(key)];
+            v.extend(path.iter().map(|p| bstring(p)));
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::JsonNumIncrBy { key, path, value } => array(&[
+            bstring("JSON.NUMINCRBY"),
+            bstring(key),
+            bstring(path),
+            bstring(value),
+        ])
+        .as_bytes()
+        .to_vec(),
+
+        Command::JsonArrAppend { key, path, values } => {
+            let mut v = vec![bstring("JSON.ARRAPPEND"), bstring(key), bstring(path)];
+            v.extend(values.iter().map(|value| bstring(value)));
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::GeoDist {
+            key,
+            member1,
+            member2,
+            unit,
+        } => {
+            let mut v = vec![
+                bstring("GEODIST"),
+                bstring(key),
+                bstring(member1),
+                bstring(member2),
+            ];
+            v.extend(unit.iter().map(|u| bstring(u)));
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::GetV { key } => {
+            let v = [bstring("GETV"), bstring(key)];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::Cas {
+            key,
+            version,
+            value,
+        } => {
+            let v = [
+                bstring("CAS"),
+                bstring(key),
+                bstring(&version.to_string()),
+                bstring(value),
+            ];
+            array(&v).as_bytes().to_vec()
+        }
+
         Command::Rename { old_key, new_key } => {
             let v = [bstring("RENAME"), bstring(old_key), bstring(new_key)];
             array(&v).as_bytes().to_vec()
         }
 
-        Command::Keys => bstring("KEYS").as_bytes().to_vec(),
+        Command::Keys { pattern: None } => bstring("KEYS").as_bytes().to_vec(),
+
+        Command::Info { sections } if sections.is_empty() => bstring("INFO").as_bytes().to_vec(),
+
+        Command::Info { sections } => {
+            let mut v = vec![bstring("INFO")];
+            v.extend(sections.iter().map(|s| bstring(s)));
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::Keys {
+            pattern: Some(pattern),
+        } => {
+            let v = [bstring("KEYS"), bstring(pattern)];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::GetMatch { pattern } => {
+            let v = [bstring("GETMATCH"), bstring(pattern)];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::DeleteMatch { pattern } => {
+            let v = [bstring("DELETEMATCH"), bstring(pattern)];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::Scan {
+            cursor,
+            pattern,
+            count,
+            type_name,
+        } => {
+            let mut v = vec![bstring("SCAN"), bstring(&cursor.to_string())];
+            if let Some(pattern) = pattern {
+                v.extend([bstring("MATCH"), bstring(pattern)]);
+            }
+            if let Some(count) = count {
+                v.extend([bstring("COUNT"), bstring(&count.to_string())]);
+            }
+            if let Some(type_name) = type_name {
+                v.extend([bstring("TYPE"), bstring(type_name)]);
+            }
+            array(&v).as_bytes().to_vec()
+        }
 
         Command::ConfReset => bstring("CONFRESET").as_bytes().to_vec(),
 
+        Command::Select { db } => {
+            let v = [bstring("SELECT"), bstring(db)];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::Move { key, db } => {
+            let v = [bstring("MOVE"), bstring(key), bstring(db)];
+            array(&v).as_bytes().to_vec()
+        }
+
+        Command::DbList => bstring("DBLIST").as_bytes().to_vec(),
+
         _ => Vec::new(),
     }
 }
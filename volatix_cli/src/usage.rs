@@ -14,19 +14,238 @@ pub fn help() {
                     "SET <key: string> <value: any>",
                     "Set a single key-value pair",
                 ),
+                ("  [NX | XX]", "Only set if the key is absent | present"),
+                ("  [GET]", "Return the previous value"),
+                (
+                    "  [EX s | PX ms | EXAT unix-s | PXAT unix-ms]",
+                    "Set the expiry",
+                ),
+                ("  [KEEPTTL]", "Retain the existing TTL"),
                 ("GET <key>", "Get the value for a key"),
                 ("DELETE <key>", "Delete a key"),
                 ("EXISTS <key>", "Check if key exists"),
                 ("FLUSH", "Clear the database"),
-                ("KEYS", "Get a list of all key entries"),
+                (
+                    "KEYS [pattern]",
+                    "Get all keys or the keys matching a pattern",
+                ),
+                (
+                    "SCAN <cursor> [MATCH pattern] [COUNT n] [TYPE type]",
+                    "Iterate over keys page by page",
+                ),
                 ("INCR <key>", "Increment an Int value by 1"),
                 ("DECR <key>", "Decrement an Int value by 1"),
+                ("INCRBY <key> <amount: i64>", "Increment an Int value"),
+                ("DECRBY <key> <amount: i64>", "Decrement an Int value"),
+                (
+                    "INCRBYFLOAT <key> <amount: f64>",
+                    "Increment an Int or Float value",
+                ),
+                ("GETV <key>", "Get a value and its version"),
+                (
+                    "CAS <key> <version: u64> <value>",
+                    "Set a value if its version matches (0: key must not exist)",
+                ),
                 (
                     "RENAME <old_key> <new_key>",
                     "Rename key retaining the entry",
                 ),
             ],
         ),
+        (
+            "String Operations",
+            vec![
+                ("APPEND <key> <value>", "Append to a string value"),
+                ("STRLEN <key>", "Get the length of a string value"),
+                (
+                    "GETRANGE <key> <start: i64> <end: i64>",
+                    "Get a substring, negative offsets count from the end",
+                ),
+                (
+                    "SETRANGE <key> <offset: usize> <value>",
+                    "Overwrite part of a string value",
+                ),
+                ("GETDEL <key>", "Get a value and delete the key"),
+                (
+                    "GETEX <key> [EX s | PX ms | EXAT unix-s | PXAT unix-ms | PERSIST]",
+                    "Get a value and change its expiry",
+                ),
+            ],
+        ),
+        (
+            "Bitmaps",
+            vec![
+                ("SETBIT <key> <offset: usize> <0|1>", "Set or clear a bit"),
+                ("GETBIT <key> <offset: usize>", "Get a bit"),
+                (
+                    "BITCOUNT <key> [start end]",
+                    "Count the set bits, optionally in a byte range",
+                ),
+                (
+                    "BITPOS <key> <0|1> [start [end]]",
+                    "Find the first clear or set bit",
+                ),
+                (
+                    "BITOP <AND|OR|XOR|NOT> <destkey> <key> [key ...]",
+                    "Combine bitmaps into destkey",
+                ),
+            ],
+        ),
+        (
+            "HyperLogLog",
+            vec![
+                (
+                    "PFADD <key> [element ...]",
+                    "Add elements to a cardinality sketch",
+                ),
+                (
+                    "PFCOUNT <key> [key ...]",
+                    "Estimate the number of distinct elements (of the union)",
+                ),
+                (
+                    "PFMERGE <destkey> [sourcekey ...]",
+                    "Merge sketches into destkey",
+                ),
+            ],
+        ),
+        (
+            "Bloom and Cuckoo Filters",
+            vec![
+                (
+                    "BF.RESERVE <key> <error_rate> <capacity: usize>",
+                    "Create a Bloom filter, growing past its capacity",
+                ),
+                (
+                    "BF.ADD <key> <item>",
+                    "Add an item to a Bloom filter (created if missing)",
+                ),
+                ("BF.MADD <key> <item> [item ...]", "Add several items"),
+                (
+                    "BF.EXISTS <key> <item>",
+                    "Check whether an item was possibly added",
+                ),
+                ("BF.MEXISTS <key> <item> [item ...]", "Check several items"),
+                (
+                    "CF.RESERVE <key> <capacity: usize>",
+                    "Create a cuckoo filter with a fixed capacity",
+                ),
+                (
+                    "CF.ADD <key> <item>",
+                    "Add an item to a cuckoo filter (created if missing)",
+                ),
+                (
+                    "CF.ADDNX <key> <item>",
+                    "Add an item unless it is possibly present",
+                ),
+                (
+                    "CF.EXISTS <key> <item>",
+                    "Check whether an item was possibly added",
+                ),
+                ("CF.DEL <key> <item>", "Delete one copy of an item"),
+            ],
+        ),
+        (
+            "Lists",
+            vec![
+                (
+                    "LPUSH <key> <value> [value ...]",
+                    "Push values onto the head of a list",
+                ),
+                (
+                    "RPUSH <key> <value> [value ...]",
+                    "Push values onto the tail of a list",
+                ),
+                ("LPOP <key>", "Remove and get the first value"),
+                ("RPOP <key>", "Remove and get the last value"),
+                ("LLEN <key>", "Get the length of a list"),
+                (
+                    "BLPOP <key> [key ...] <timeout>",
+                    "Pop the first value, waiting up to timeout seconds (0: forever)",
+                ),
+                (
+                    "BRPOP <key> [key ...] <timeout>",
+                    "Pop the last value, waiting up to timeout seconds (0: forever)",
+                ),
+            ],
+        ),
+        (
+            "Streams",
+            vec![
+                (
+                    "XADD <key> [MAXLEN n] <*|id> <field> <value> [field value ...]",
+                    "Append an entry, * generates a time-ordered ID",
+                ),
+                (
+                    "XRANGE <key> <start|-> <end|+> [COUNT n]",
+                    "Get entries in an ID range",
+                ),
+                (
+                    "XREVRANGE <key> <end|+> <start|-> [COUNT n]",
+                    "Get entries in an ID range, newest first",
+                ),
+                ("XLEN <key>", "Get the number of entries"),
+                ("XTRIM <key> MAXLEN <n>", "Remove the oldest entries"),
+                (
+                    "XGROUP CREATE <key> <group> <id|$> [MKSTREAM]",
+                    "Create a consumer group",
+                ),
+                ("XGROUP DESTROY <key> <group>", "Delete a consumer group"),
+                (
+                    "XREADGROUP GROUP <group> <consumer> [COUNT n] STREAMS <key ...> <id ...>",
+                    "Read as a consumer, > for new entries",
+                ),
+                (
+                    "XACK <key> <group> <id> [id ...]",
+                    "Acknowledge delivered entries",
+                ),
+                (
+                    "XPENDING <key> <group> [start end count [consumer]]",
+                    "Inspect entries not acknowledged yet",
+                ),
+            ],
+        ),
+        (
+            "Geospatial",
+            vec![
+                (
+                    "GEOADD <key> [NX|XX] [CH] <longitude> <latitude> <member> [...]",
+                    "Add members with positions",
+                ),
+                (
+                    "GEODIST <key> <member1> <member2> [m|km|mi|ft]",
+                    "Get the distance between two members",
+                ),
+                (
+                    "GEOSEARCH <key> FROMMEMBER <member>|FROMLONLAT <lon> <lat> BYRADIUS <r> <unit>|BYBOX <w> <h> <unit> [ASC|DESC] [COUNT n] [WITHDIST] [WITHCOORD]",
+                    "Find members within a radius or a box",
+                ),
+            ],
+        ),
+        (
+            "JSON Documents",
+            vec![
+                (
+                    "JSON.SET <key> <path> <json> [NX|XX]",
+                    "Write a value, new documents at the root $",
+                ),
+                (
+                    "JSON.GET <key> [path ...]",
+                    "Read the values at paths, or the whole document",
+                ),
+                (
+                    "JSON.DEL <key> [path]",
+                    "Remove the values at a path, or the whole document",
+                ),
+                (
+                    "JSON.NUMINCRBY <key> <path> <number>",
+                    "Increment the numbers at a path",
+                ),
+                (
+                    "JSON.ARRAPPEND <key> <path> <json> [...]",
+                    "Append values to the arrays at a path",
+                ),
+            ],
+        ),
         (
             "Batch Operations",
             vec![
@@ -37,6 +256,11 @@ pub fn help() {
                 ),
                 ("GETLIST [key, key, ...]", "Get values for multiple keys"),
                 ("DELETELIST [key, key, ...]", "Delete multiple keys"),
+                (
+                    "GETMATCH <pattern>",
+                    "Get values for keys matching a pattern",
+                ),
+                ("DELETEMATCH <pattern>", "Delete keys matching a pattern"),
             ],
         ),
         (
@@ -55,17 +279,42 @@ pub fn help() {
                 ("    LFA", "Least frequently accessed"),
                 ("    OLDEST", "Oldest entry first"),
                 ("    SIZEAWARE", "Evict largest first"),
+                (
+                    "    TINYLFU",
+                    "Admit by estimated frequency (scan resistant)",
+                ),
                 ("CONFSET <key> <value>", "Set a config value"),
                 ("CONFGET <key>", "Get a config value"),
                 ("CONFRESET", "Reset configurable options to the defaults"),
             ],
         ),
+        (
+            "Databases",
+            vec![
+                (
+                    "SELECT <db>",
+                    "Switch to a database, created on first write",
+                ),
+                ("MOVE <key> <db>", "Move a key to another database"),
+                ("DBLIST", "List all databases"),
+            ],
+        ),
         (
             "Stats",
             vec![
-                ("GETSTATS", "Get global stats"),
-                ("RESETSTATS", "Reset global stats"),
+                ("GETSTATS", "Get the selected database's stats"),
+                ("RESETSTATS", "Reset the selected database's stats"),
                 ("DUMP <key>", "Get stats for a specific entry"),
+                ("TYPE <key>", "Get the type of a value"),
+                (
+                    "OBJECT FREQ|IDLETIME|ENCODING <key>",
+                    "Get an entry's access count, idle seconds or encoding",
+                ),
+                ("MEMORY USAGE <key>", "Estimate an entry's size in bytes"),
+                (
+                    "INFO [section ...]",
+                    "Server info: server, clients, memory, persistence, stats, keyspace",
+                ),
             ],
         ),
         (
@@ -73,7 +322,21 @@ pub fn help() {
             vec![
                 ("SETWTTL <key> <ttl: u64>", "Set key with TTL (in seconds)"),
                 ("EXPIRE <key> <delta: i64>", "Extend or reduce TTL"),
-                ("GETTTL <key>", "Get remaining TTL for key"),
+                (
+                    "EXPIREAT <key> <unix-seconds: i64>",
+                    "Expire key at a unix time",
+                ),
+                ("PEXPIRE <key> <ms: i64>", "Set TTL in milliseconds"),
+                (
+                    "PEXPIREAT <key> <unix-ms: i64>",
+                    "Expire key at a unix time in milliseconds",
+                ),
+                ("PERSIST <key>", "Remove the expiry of key"),
+                (
+                    "GETTTL <key>",
+                    "Get remaining TTL for key (-1 if it never expires)",
+                ),
+                ("PTTL <key>", "Get remaining TTL for key in milliseconds"),
                 (
                     "EVICTNOW [count]",
                     "Trigger count or default(10%) evictions using current policy",
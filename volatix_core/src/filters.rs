@@ -0,0 +1,426 @@
+//! Probabilistic membership filters backing the `BF.*` and `CF.*` commands.
+//!
+//! - `BloomFilter` answers "possibly added" or "definitely not added" with a
+//!   configurable false positive rate. It grows by stacking larger layers
+//!   once its capacity is reached, so adding never fails.
+//! - `CuckooFilter` has a fixed capacity and a false positive rate of about
+//!   0.01%, but also supports deleting items.
+
+use serde::{Deserialize, Serialize};
+
+use crate::hyperloglog::hash;
+
+/// Each Bloom layer holds twice as many items as the previous one.
+const BLOOM_EXPANSION: usize = 2;
+/// Each Bloom layer has half the false positive rate of the previous one,
+/// keeping the compound rate below the configured one.
+const BLOOM_TIGHTENING: f64 = 0.5;
+
+/// Fingerprints per cuckoo bucket.
+const BUCKET_SIZE: usize = 4;
+/// Relocations tried before a cuckoo filter is considered full.
+const MAX_KICKS: usize = 500;
+
+/// A scalable Bloom filter.
+///
+/// # Example
+/// ```rust
+/// use volatix_core::BloomFilter;
+///
+/// let mut filter = BloomFilter::new(0.01, 1000).unwrap();
+/// assert!(filter.add(b"event:1"));
+/// assert!(!filter.add(b"event:1")); // Possibly added already
+///
+/// assert!(filter.contains(b"event:1"));
+/// assert!(!filter.contains(b"event:2"));
+/// ```
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct BloomFilter {
+    error_rate: f64,
+    layers: Vec<BloomLayer>,
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+struct BloomLayer {
+    bits: Vec<u64>,
+    hashes: u32,
+    capacity: usize,
+    items: usize,
+}
+
+impl Default for BloomFilter {
+    /// A filter for 100 items with a 1% false positive rate, used when
+    /// BF.ADD creates a key.
+    fn default() -> Self {
+        Self::new(0.01, 100).expect("default parameters are valid")
+    }
+}
+
+impl BloomFilter {
+    /// Creates an empty filter.
+    ///
+    /// # Arguments
+    /// * `error_rate` - Wanted false positive rate, between 0 and 1 exclusive
+    /// * `capacity` - Number of items expected before the filter grows
+    ///
+    /// # Returns
+    /// The filter, or an error if a parameter is out of range
+    pub fn new(error_rate: f64, capacity: usize) -> Result<Self, String> {
+        if !(error_rate > 0.0 && error_rate < 1.0) {
+            return Err("Error rate must be between 0 and 1 exclusive".to_string());
+        }
+        if capacity == 0 {
+            return Err("Capacity must be greater than 0".to_string());
+        }
+
+        Ok(Self {
+            error_rate,
+            layers: vec![BloomLayer::new(capacity, error_rate * BLOOM_TIGHTENING)],
+        })
+    }
+
+    /// Adds an item to the filter, growing it if the current layer is full.
+    ///
+    /// # Returns
+    /// `true` if the item was added, `false` if it was possibly added before
+    pub fn add(&mut self, item: &[u8]) -> bool {
+        let hash = hash(item);
+        if self.layers.iter().any(|layer| layer.contains(hash)) {
+            return false;
+        }
+
+        let last = self.layers.last().expect("a filter has at least one layer");
+        if last.items >= last.capacity {
+            let error_rate = self.error_rate * BLOOM_TIGHTENING.powi(self.layers.len() as i32 + 1);
+            let layer = BloomLayer::new(last.capacity * BLOOM_EXPANSION, error_rate);
+            self.layers.push(layer);
+        }
+
+        let last = self
+            .layers
+            .last_mut()
+            .expect("a filter has at least one layer");
+        last.insert(hash);
+        true
+    }
+
+    /// Checks whether an item was possibly added to the filter.
+    pub fn contains(&self, item: &[u8]) -> bool {
+        let hash = hash(item);
+        self.layers.iter().any(|layer| layer.contains(hash))
+    }
+
+    /// Number of items added to the filter.
+    pub fn len(&self) -> usize {
+        self.layers.iter().map(|layer| layer.items).sum()
+    }
+
+    /// Whether no item was added to the filter.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Approximate memory used by the filter, in bytes.
+    pub(crate) fn size_in_bytes(&self) -> usize {
+        self.layers
+            .iter()
+            .map(|layer| layer.bits.len() * size_of::<u64>())
+            .sum()
+    }
+}
+
+impl BloomLayer {
+    fn new(capacity: usize, error_rate: f64) -> Self {
+        let ln2 = std::f64::consts::LN_2;
+        let bits = (capacity as f64 * -error_rate.ln() / (ln2 * ln2)).ceil() as usize;
+        let hashes = (-error_rate.log2()).ceil().max(1.0) as u32;
+
+        Self {
+            bits: vec![0; bits.div_ceil(64).max(1)],
+            hashes,
+            capacity,
+            items: 0,
+        }
+    }
+
+    /// Bit positions of a hash, using double hashing to derive the
+    /// `hashes` positions from a single 64-bit hash.
+    fn positions(&self, hash: u64) -> impl Iterator<Item = usize> + use<> {
+        let bits = (self.bits.len() * 64) as u64;
+        let step =
+            (hash.rotate_left(32) ^ 0x9e37_79b9_7f4a_7c15).wrapping_mul(0xbf58_476d_1ce4_e5b9) | 1;
+        (0..self.hashes as u64)
+            .map(move |i| (hash.wrapping_add(i.wrapping_mul(step)) % bits) as usize)
+    }
+
+    fn contains(&self, hash: u64) -> bool {
+        self.positions(hash)
+            .all(|bit| self.bits[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+
+    fn insert(&mut self, hash: u64) {
+        for bit in self.positions(hash) {
+            self.bits[bit / 64] |= 1 << (bit % 64);
+        }
+        self.items += 1;
+    }
+}
+
+/// A cuckoo filter storing 16-bit fingerprints in buckets of four.
+///
+/// # Example
+/// ```rust
+/// use volatix_core::CuckooFilter;
+///
+/// let mut filter = CuckooFilter::new(1000).unwrap();
+/// filter.add(b"session:1").unwrap();
+/// assert!(filter.contains(b"session:1"));
+///
+/// assert!(filter.delete(b"session:1"));
+/// assert!(!filter.contains(b"session:1"));
+/// ```
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct CuckooFilter {
+    /// Fingerprints, 0 marks an empty slot
+    buckets: Vec<[u16; BUCKET_SIZE]>,
+    items: usize,
+    /// A fingerprint evicted by the last failed relocation, as
+    /// (bucket, fingerprint). While set the filter is full.
+    victim: Option<(usize, u16)>,
+}
+
+impl Default for CuckooFilter {
+    /// A filter for 1024 items, used when CF.ADD creates a key.
+    fn default() -> Self {
+        Self::new(1024).expect("default parameters are valid")
+    }
+}
+
+impl CuckooFilter {
+    /// Creates an empty filter.
+    ///
+    /// # Arguments
+    /// * `capacity` - Number of items the filter can hold, rounded up so the
+    ///   number of buckets is a power of two
+    ///
+    /// # Returns
+    /// The filter, or an error if the capacity is 0
+    pub fn new(capacity: usize) -> Result<Self, String> {
+        if capacity == 0 {
+            return Err("Capacity must be greater than 0".to_string());
+        }
+
+        let buckets = capacity.div_ceil(BUCKET_SIZE).next_power_of_two();
+        Ok(Self {
+            buckets: vec![[0; BUCKET_SIZE]; buckets],
+            items: 0,
+            victim: None,
+        })
+    }
+
+    /// Adds an item to the filter. Adding an item twice stores it twice,
+    /// so it has to be deleted twice.
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or an error if the filter is full
+    pub fn add(&mut self, item: &[u8]) -> Result<(), String> {
+        if self.victim.is_some() {
+            return Err("Cuckoo filter is full".to_string());
+        }
+
+        let (index, fingerprint) = self.locate(item);
+        let alternate = self.alternate(index, fingerprint);
+        self.items += 1;
+        if !self.place(index, fingerprint) && !self.place(alternate, fingerprint) {
+            self.relocate(index, fingerprint, hash(item));
+        }
+        Ok(())
+    }
+
+    /// Checks whether an item was possibly added to the filter.
+    pub fn contains(&self, item: &[u8]) -> bool {
+        let (index, fingerprint) = self.locate(item);
+        let alternate = self.alternate(index, fingerprint);
+
+        self.buckets[index].contains(&fingerprint)
+            || self.buckets[alternate].contains(&fingerprint)
+            || self
+                .victim
+                .is_some_and(|(i, fp)| fp == fingerprint && (i == index || i == alternate))
+    }
+
+    /// Deletes one copy of an item. Deleting an item that was never added
+    /// may remove another item sharing its fingerprint.
+    ///
+    /// # Returns
+    /// `true` if a matching fingerprint was removed
+    pub fn delete(&mut self, item: &[u8]) -> bool {
+        let (index, fingerprint) = self.locate(item);
+        let alternate = self.alternate(index, fingerprint);
+
+        if let Some((i, fp)) = self.victim
+            && fp == fingerprint
+            && (i == index || i == alternate)
+        {
+            self.victim = None;
+            self.items -= 1;
+            return true;
+        }
+
+        for i in [index, alternate] {
+            if let Some(slot) = self.buckets[i].iter_mut().find(|fp| **fp == fingerprint) {
+                *slot = 0;
+                self.items -= 1;
+                // The freed slot may make room for the evicted fingerprint
+                if let Some((i, fp)) = self.victim.take() {
+                    self.relocate(i, fp, fp as u64);
+                }
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Number of items stored in the filter.
+    pub fn len(&self) -> usize {
+        self.items
+    }
+
+    /// Whether the filter is empty.
+    pub fn is_empty(&self) -> bool {
+        self.items == 0
+    }
+
+    /// Approximate memory used by the filter, in bytes.
+    pub(crate) fn size_in_bytes(&self) -> usize {
+        self.buckets.len() * size_of::<[u16; BUCKET_SIZE]>()
+    }
+
+    /// Primary bucket and non-zero fingerprint of an item.
+    fn locate(&self, item: &[u8]) -> (usize, u16) {
+        let hash = hash(item);
+        let fingerprint = ((hash >> 48) as u16).max(1);
+        (hash as usize & (self.buckets.len() - 1), fingerprint)
+    }
+
+    /// The other bucket a fingerprint may live in. Applying it twice gives
+    /// back the original bucket.
+    fn alternate(&self, index: usize, fingerprint: u16) -> usize {
+        (index ^ hash(&fingerprint.to_le_bytes()) as usize) & (self.buckets.len() - 1)
+    }
+
+    /// Stores a fingerprint in a free slot of a bucket.
+    fn place(&mut self, index: usize, fingerprint: u16) -> bool {
+        match self.buckets[index].iter_mut().find(|fp| **fp == 0) {
+            Some(slot) => {
+                *slot = fingerprint;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Makes room for a fingerprint by moving existing ones to their
+    /// alternate bucket. The fingerprint left over when giving up is kept
+    /// as the victim.
+    fn relocate(&mut self, mut index: usize, mut fingerprint: u16, seed: u64) {
+        // xorshift, so the evicted slots are spread without needing an RNG
+        let mut state = seed | 1;
+        for _ in 0..MAX_KICKS {
+            if self.place(index, fingerprint) {
+                return;
+            }
+
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let slot = state as usize % BUCKET_SIZE;
+            std::mem::swap(&mut fingerprint, &mut self.buckets[index][slot]);
+            index = self.alternate(index, fingerprint);
+        }
+        self.victim = Some((index, fingerprint));
+    }
+}
+
+#[cfg(test)]
+mod filter_tests {
+    use super::*;
+
+    fn false_positive_rate(contains: impl Fn(&[u8]) -> bool) -> f64 {
+        let hits = (0..100_000)
+            .filter(|i| contains(format!("absent:{i}").as_bytes()))
+            .count();
+        hits as f64 / 100_000.0
+    }
+
+    #[test]
+    fn test_bloom_filter() {
+        let mut filter = BloomFilter::new(0.01, 10_000).unwrap();
+        for i in 0..10_000 {
+            filter.add(format!("item:{i}").as_bytes());
+        }
+        assert_eq!(filter.layers.len(), 1);
+        assert!((0..10_000).all(|i| filter.contains(format!("item:{i}").as_bytes())));
+        assert!(false_positive_rate(|item| filter.contains(item)) < 0.01);
+
+        assert!(BloomFilter::new(0.0, 10).is_err());
+        assert!(BloomFilter::new(1.0, 10).is_err());
+        assert!(BloomFilter::new(0.01, 0).is_err());
+    }
+
+    #[test]
+    fn test_bloom_filter_grows() {
+        let mut filter = BloomFilter::new(0.01, 100).unwrap();
+        let added = (0..5000)
+            .filter(|i| filter.add(format!("item:{i}").as_bytes()))
+            .count();
+        assert_eq!(filter.len(), added);
+        assert!(filter.layers.len() > 1);
+        assert!((0..5000).all(|i| filter.contains(format!("item:{i}").as_bytes())));
+        assert!(false_positive_rate(|item| filter.contains(item)) < 0.01);
+    }
+
+    #[test]
+    fn test_cuckoo_filter() {
+        let mut filter = CuckooFilter::new(1000).unwrap();
+        for i in 0..900 {
+            filter.add(format!("item:{i}").as_bytes()).unwrap();
+        }
+        assert_eq!(filter.len(), 900);
+        assert!((0..900).all(|i| filter.contains(format!("item:{i}").as_bytes())));
+        assert!(false_positive_rate(|item| filter.contains(item)) < 0.001);
+
+        for i in 0..450 {
+            assert!(filter.delete(format!("item:{i}").as_bytes()));
+        }
+        assert_eq!(filter.len(), 450);
+        assert!((450..900).all(|i| filter.contains(format!("item:{i}").as_bytes())));
+        assert!(!filter.contains(b"item:0"));
+        assert!(!filter.delete(b"item:0"));
+
+        // Duplicates are stored, and deleted, one copy at a time
+        filter.add(b"dup").unwrap();
+        filter.add(b"dup").unwrap();
+        assert!(filter.delete(b"dup"));
+        assert!(filter.contains(b"dup"));
+        assert!(filter.delete(b"dup"));
+        assert!(!filter.contains(b"dup"));
+
+        assert!(CuckooFilter::new(0).is_err());
+    }
+
+    #[test]
+    fn test_cuckoo_filter_full() {
+        let mut filter = CuckooFilter::new(64).unwrap();
+        let mut added = 0;
+        while filter.add(format!("item:{added}").as_bytes()).is_ok() {
+            added += 1;
+        }
+        assert!(added > 48, "only {added} items fit");
+        assert!((0..added).all(|i| filter.contains(format!("item:{i}").as_bytes())));
+
+        // Deleting makes room again
+        assert!(filter.delete(b"item:0"));
+        assert!(filter.add(b"item:0").is_ok());
+    }
+}
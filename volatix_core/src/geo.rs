@@ -0,0 +1,465 @@
+//! Geospatial indexes backing `GEOADD`, `GEODIST` and `GEOSEARCH`.
+//!
+//! Members are stored with the 52-bit geohash of their position, which
+//! interleaves 26 bits of longitude and 26 bits of latitude so that nearby
+//! positions mostly get nearby hashes. Members are kept sorted by hash, and
+//! a search only scans the hash ranges of the cells around its center before
+//! checking the exact distances. Like Redis, latitudes are limited to the
+//! range of the Web Mercator projection and distances assume a spherical
+//! Earth, which can be off by up to 0.5%.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+/// Radius of the Earth used by the haversine formula, in meters.
+const EARTH_RADIUS: f64 = 6372797.560856;
+/// Meters per degree of latitude, or of longitude at the equator.
+const METERS_PER_DEGREE: f64 = EARTH_RADIUS * std::f64::consts::PI / 180.0;
+/// Bits of geohash per coordinate.
+const STEP: u32 = 26;
+
+const LONGITUDE_MIN: f64 = -180.0;
+const LONGITUDE_MAX: f64 = 180.0;
+/// Latitude limits of the Web Mercator projection.
+const LATITUDE_MIN: f64 = -85.05112878;
+const LATITUDE_MAX: f64 = 85.05112878;
+
+/// Unit of the distances taken and returned by the GEO commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeoUnit {
+    Meters,
+    Kilometers,
+    Miles,
+    Feet,
+}
+
+impl GeoUnit {
+    /// Parses `m`, `km`, `mi` or `ft`, ignoring case.
+    pub fn parse(unit: &str) -> Result<Self, String> {
+        match unit.to_lowercase().as_str() {
+            "m" => Ok(GeoUnit::Meters),
+            "km" => Ok(GeoUnit::Kilometers),
+            "mi" => Ok(GeoUnit::Miles),
+            "ft" => Ok(GeoUnit::Feet),
+            _ => Err("Unsupported unit, use m, km, mi or ft".to_string()),
+        }
+    }
+
+    /// Length of one unit in meters.
+    pub fn meters(&self) -> f64 {
+        match self {
+            GeoUnit::Meters => 1.0,
+            GeoUnit::Kilometers => 1000.0,
+            GeoUnit::Miles => 1609.34,
+            GeoUnit::Feet => 0.3048,
+        }
+    }
+}
+
+/// Area searched by `GEOSEARCH`, in meters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GeoShape {
+    /// Circle around the center (`BYRADIUS`)
+    Radius(f64),
+    /// Rectangle centered on the center (`BYBOX`)
+    Box { width: f64, height: f64 },
+}
+
+impl GeoShape {
+    /// Distance from the center to the furthest edge of the shape.
+    fn reach(&self) -> f64 {
+        match *self {
+            GeoShape::Radius(radius) => radius,
+            GeoShape::Box { width, height } => width.max(height) / 2.0,
+        }
+    }
+
+    /// Whether `point` lies in the shape drawn around `center`.
+    fn contains(&self, center: (f64, f64), point: (f64, f64), distance: f64) -> bool {
+        match *self {
+            GeoShape::Radius(radius) => distance <= radius,
+            GeoShape::Box { width, height } => {
+                // The east-west distance is measured along the point's latitude
+                let dx = haversine((center.0, point.1), point);
+                let dy = haversine(center, (center.0, point.1));
+                dx <= width / 2.0 && dy <= height / 2.0
+            }
+        }
+    }
+}
+
+/// Center of a `GEOSEARCH`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeoOrigin {
+    /// Position of a member of the set (`FROMMEMBER`)
+    Member(String),
+    /// A (longitude, latitude) position (`FROMLONLAT`)
+    Position(f64, f64),
+}
+
+/// A member found by a search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoMatch {
+    pub member: String,
+    /// Distance from the center of the search, in meters
+    pub distance: f64,
+    pub longitude: f64,
+    pub latitude: f64,
+}
+
+/// A set of members with positions, sorted by geohash.
+///
+/// # Example
+/// ```rust
+/// use volatix_core::{GeoSet, GeoShape};
+///
+/// let mut places = GeoSet::default();
+/// places.add("Palermo", 13.361389, 38.115556).unwrap();
+/// places.add("Catania", 15.087269, 37.502669).unwrap();
+///
+/// let distance = places.distance("Palermo", "Catania").unwrap();
+/// assert_eq!((distance / 1000.0).round(), 166.0);
+///
+/// let found = places.search((15.0, 37.0), GeoShape::Radius(100_000.0));
+/// assert_eq!(found.len(), 1);
+/// assert_eq!(found[0].member, "Catania");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct GeoSet {
+    /// Geohash of every member
+    members: BTreeMap<String, u64>,
+    /// Members ordered by geohash
+    index: BTreeSet<(u64, String)>,
+}
+
+impl GeoSet {
+    /// Adds a member or moves an existing one.
+    ///
+    /// # Returns
+    /// The previous geohash of the member if it existed, or an error if the
+    /// coordinates are out of range
+    pub fn add(
+        &mut self,
+        member: &str,
+        longitude: f64,
+        latitude: f64,
+    ) -> Result<Option<u64>, String> {
+        let hash = encode(longitude, latitude)?;
+        let previous = self.members.insert(member.to_string(), hash);
+        if let Some(previous) = previous {
+            self.index.remove(&(previous, member.to_string()));
+        }
+        self.index.insert((hash, member.to_string()));
+        Ok(previous)
+    }
+
+    /// Position of a member as (longitude, latitude). Positions are rounded
+    /// to the center of their geohash cell, within about 0.6 meters.
+    pub fn position(&self, member: &str) -> Option<(f64, f64)> {
+        self.members.get(member).map(|&hash| decode(hash))
+    }
+
+    /// Geohash of a member.
+    pub fn hash(&self, member: &str) -> Option<u64> {
+        self.members.get(member).copied()
+    }
+
+    /// Distance in meters between two members, if both exist.
+    pub fn distance(&self, a: &str, b: &str) -> Option<f64> {
+        Some(haversine(self.position(a)?, self.position(b)?))
+    }
+
+    /// Finds the members inside `shape` around `center` (longitude,
+    /// latitude), sorted from the nearest to the furthest.
+    pub fn search(&self, center: (f64, f64), shape: GeoShape) -> Vec<GeoMatch> {
+        let mut found: Vec<GeoMatch> = search_ranges(center, shape.reach())
+            .into_iter()
+            .flat_map(|(start, end)| {
+                self.index
+                    .range((start, String::new())..(end, String::new()))
+            })
+            .filter_map(|(hash, member)| {
+                let (longitude, latitude) = decode(*hash);
+                let distance = haversine(center, (longitude, latitude));
+                shape
+                    .contains(center, (longitude, latitude), distance)
+                    .then(|| GeoMatch {
+                        member: member.clone(),
+                        distance,
+                        longitude,
+                        latitude,
+                    })
+            })
+            .collect();
+
+        found.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        found
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Approximate memory used by the members, in bytes.
+    pub(crate) fn size_in_bytes(&self) -> usize {
+        self.members
+            .keys()
+            .map(|member| 2 * (member.len() + size_of::<(u64, String)>()))
+            .sum()
+    }
+}
+
+/// Great-circle distance in meters between two (longitude, latitude)
+/// positions given in degrees.
+pub fn haversine(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (lon1, lat1) = (a.0.to_radians(), a.1.to_radians());
+    let (lon2, lat2) = (b.0.to_radians(), b.1.to_radians());
+    let u = ((lat2 - lat1) / 2.0).sin();
+    let v = ((lon2 - lon1) / 2.0).sin();
+    let h = u * u + lat1.cos() * lat2.cos() * v * v;
+    2.0 * EARTH_RADIUS * h.sqrt().asin()
+}
+
+/// Checks that a position can be indexed.
+pub fn validate_position(longitude: f64, latitude: f64) -> Result<(), String> {
+    if (LONGITUDE_MIN..=LONGITUDE_MAX).contains(&longitude)
+        && (LATITUDE_MIN..=LATITUDE_MAX).contains(&latitude)
+    {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid longitude,latitude pair {longitude},{latitude}"
+        ))
+    }
+}
+
+/// Computes the 52-bit geohash of a position.
+fn encode(longitude: f64, latitude: f64) -> Result<u64, String> {
+    validate_position(longitude, latitude)?;
+
+    let cells = (1u64 << STEP) as f64;
+    let offset = |value: f64, min: f64, max: f64| {
+        (((value - min) / (max - min)) * cells).min(cells - 1.0) as u64
+    };
+    let x = offset(longitude, LONGITUDE_MIN, LONGITUDE_MAX);
+    let y = offset(latitude, LATITUDE_MIN, LATITUDE_MAX);
+    Ok(interleave(y, x))
+}
+
+/// Returns the center of the cell of a 52-bit geohash.
+fn decode(hash: u64) -> (f64, f64) {
+    let (y, x) = deinterleave(hash);
+    let cells = (1u64 << STEP) as f64;
+    let center =
+        |offset: u64, min: f64, max: f64| min + (offset as f64 + 0.5) * (max - min) / cells;
+    (
+        center(x, LONGITUDE_MIN, LONGITUDE_MAX).clamp(LONGITUDE_MIN, LONGITUDE_MAX),
+        center(y, LATITUDE_MIN, LATITUDE_MAX).clamp(LATITUDE_MIN, LATITUDE_MAX),
+    )
+}
+
+/// Spreads the bits of `even` and `odd` over the even and odd bits of the
+/// result.
+fn interleave(even: u64, odd: u64) -> u64 {
+    (0..STEP).fold(0, |hash, bit| {
+        hash | ((even >> bit) & 1) << (2 * bit) | ((odd >> bit) & 1) << (2 * bit + 1)
+    })
+}
+
+/// Splits the even and odd bits of a hash.
+fn deinterleave(hash: u64) -> (u64, u64) {
+    (0..STEP).fold((0, 0), |(even, odd), bit| {
+        (
+            even | ((hash >> (2 * bit)) & 1) << bit,
+            odd | ((hash >> (2 * bit + 1)) & 1) << bit,
+        )
+    })
+}
+
+/// Ranges of geohashes holding every position within `reach` meters of
+/// `center`: the cell containing the center and its eight neighbours, with
+/// cells chosen at least `reach` wide.
+fn search_ranges(center: (f64, f64), reach: f64) -> Vec<(u64, u64)> {
+    let (longitude, latitude) = (
+        center.0.clamp(LONGITUDE_MIN, LONGITUDE_MAX),
+        center.1.clamp(LATITUDE_MIN, LATITUDE_MAX),
+    );
+
+    // Longitude degrees shrink towards the poles, size the cells for the
+    // latitude of the search area closest to a pole
+    let reach_latitude = reach / METERS_PER_DEGREE;
+    let furthest = (latitude.abs() + reach_latitude).min(90.0);
+    let reach_longitude = reach / (METERS_PER_DEGREE * furthest.to_radians().cos());
+
+    let step = (1..=STEP)
+        .rev()
+        .find(|&step| {
+            let cells = (1u64 << step) as f64;
+            (LONGITUDE_MAX - LONGITUDE_MIN) / cells >= reach_longitude
+                && (LATITUDE_MAX - LATITUDE_MIN) / cells >= reach_latitude
+        })
+        .unwrap_or(0);
+    if step == 0 {
+        return vec![(0, u64::MAX)];
+    }
+
+    let shift = 2 * (STEP - step);
+    let (y, x) = deinterleave(encode(longitude, latitude).unwrap_or_default() >> shift);
+    let cells = 1i64 << step;
+
+    let mut ranges: Vec<(u64, u64)> = Vec::new();
+    for dy in -1..=1 {
+        let y = y as i64 + dy;
+        if !(0..cells).contains(&y) {
+            continue;
+        }
+        for dx in -1..=1 {
+            // Longitudes wrap around the antimeridian
+            let x = (x as i64 + dx).rem_euclid(cells);
+            let cell = interleave(y as u64, x as u64);
+            let range = (cell << shift, (cell + 1) << shift);
+            if !ranges.contains(&range) {
+                ranges.push(range);
+            }
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod geo_tests {
+    use super::*;
+
+    fn sicily() -> GeoSet {
+        let mut places = GeoSet::default();
+        places.add("Palermo", 13.361389, 38.115556).unwrap();
+        places.add("Catania", 15.087269, 37.502669).unwrap();
+        places.add("edge1", 12.758489, 38.788135).unwrap();
+        places.add("edge2", 17.241510, 38.788135).unwrap();
+        places
+    }
+
+    fn names(found: &[GeoMatch]) -> Vec<&str> {
+        found.iter().map(|m| m.member.as_str()).collect()
+    }
+
+    fn km(meters: f64) -> String {
+        format!("{:.4}", meters / 1000.0)
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        for (longitude, latitude) in [
+            (13.361389, 38.115556),
+            (-122.4194, 37.7749),
+            (179.9999, -85.0),
+            (-180.0, 85.05112878),
+            (0.0, 0.0),
+        ] {
+            let (lon, lat) = decode(encode(longitude, latitude).unwrap());
+            assert!(haversine((longitude, latitude), (lon, lat)) < 1.0);
+        }
+
+        assert!(encode(180.5, 0.0).is_err());
+        assert!(encode(0.0, 86.0).is_err());
+        assert!(encode(f64::NAN, 0.0).is_err());
+
+        let hash = interleave(0b1011, 0b0110);
+        assert_eq!(deinterleave(hash), (0b1011, 0b0110));
+    }
+
+    #[test]
+    fn test_haversine_known_distances() {
+        // Palermo to Catania
+        let d = haversine((13.361389, 38.115556), (15.087269, 37.502669));
+        assert!((d - 166274.15).abs() < 1.0);
+
+        // London to Paris, about 343.5 km
+        let d = haversine((-0.1278, 51.5074), (2.3522, 48.8566));
+        assert!((d / 1000.0 - 343.5).abs() < 1.0);
+
+        // A quarter of the equator
+        let d = haversine((0.0, 0.0), (90.0, 0.0));
+        assert!((d - EARTH_RADIUS * std::f64::consts::FRAC_PI_2).abs() < 1e-6);
+        assert_eq!(haversine((5.0, 5.0), (5.0, 5.0)), 0.0);
+    }
+
+    #[test]
+    fn test_distance_between_members() {
+        let places = sicily();
+        assert_eq!(
+            km(places.distance("Palermo", "Catania").unwrap()),
+            "166.2742"
+        );
+        assert_eq!(places.distance("Palermo", "Rome"), None);
+    }
+
+    #[test]
+    fn test_search_by_radius() {
+        let places = sicily();
+
+        let found = places.search((15.0, 37.0), GeoShape::Radius(200_000.0));
+        assert_eq!(names(&found), ["Catania", "Palermo"]);
+        assert_eq!(km(found[0].distance), "56.4413");
+        assert_eq!(km(found[1].distance), "190.4424");
+
+        let found = places.search((15.0, 37.0), GeoShape::Radius(100_000.0));
+        assert_eq!(names(&found), ["Catania"]);
+
+        let position = places.position("Catania").unwrap();
+        let found = places.search(position, GeoShape::Radius(1.0));
+        assert_eq!(names(&found), ["Catania"]);
+        assert_eq!(found[0].distance, 0.0);
+    }
+
+    #[test]
+    fn test_search_by_box() {
+        let places = sicily();
+        let shape = GeoShape::Box {
+            width: 400_000.0,
+            height: 400_000.0,
+        };
+
+        let found = places.search((15.0, 37.0), shape);
+        assert_eq!(names(&found), ["Catania", "Palermo", "edge2", "edge1"]);
+        assert_eq!(km(found[2].distance), "279.7403");
+        assert_eq!(km(found[3].distance), "279.7405");
+
+        // Too narrow for Palermo and the edges
+        let shape = GeoShape::Box {
+            width: 100_000.0,
+            height: 400_000.0,
+        };
+        assert_eq!(names(&places.search((15.0, 37.0), shape)), ["Catania"]);
+    }
+
+    #[test]
+    fn test_search_across_antimeridian_and_large_areas() {
+        let mut places = GeoSet::default();
+        places.add("east", 179.9, 0.0).unwrap();
+        places.add("west", -179.9, 0.0).unwrap();
+        places.add("far", 0.0, 0.0).unwrap();
+
+        let found = places.search((180.0, 0.0), GeoShape::Radius(50_000.0));
+        assert_eq!(found.len(), 2);
+
+        // A radius larger than the Earth finds everything
+        let found = places.search((0.0, 0.0), GeoShape::Radius(30_000_000.0));
+        assert_eq!(found.len(), 3);
+        assert_eq!(found[0].member, "far");
+    }
+
+    #[test]
+    fn test_move_member() {
+        let mut places = sicily();
+        assert!(places.add("Catania", 13.36, 38.11).unwrap().is_some());
+        assert_eq!(places.len(), 4);
+        let found = places.search((15.0, 37.0), GeoShape::Radius(100_000.0));
+        assert!(found.is_empty());
+    }
+}
@@ -0,0 +1,203 @@
+//! Glob-style pattern matching used to select keys.
+//!
+//! Supported syntax:
+//! - `*` matches any sequence of characters, including an empty one
+//! - `?` matches exactly one character
+//! - `[abc]` matches one of the listed characters, `[a-z]` a range of them
+//!   and `[^abc]` (or `[!abc]`) any character that is not listed
+//! - `\` escapes the following character, e.g `\*` matches a literal `*`
+
+/// Checks whether the whole `text` matches the glob `pattern`.
+///
+/// # Arguments
+/// * `pattern` - The glob pattern
+/// * `text` - The text to match, usually a key
+///
+/// # Returns
+/// `true` if the pattern matches the entire text, `false` otherwise
+///
+/// # Example
+/// ```rust
+/// use volatix_core::glob_match;
+///
+/// assert!(glob_match("user:42:*", "user:42:name"));
+/// assert!(glob_match("user:?", "user:1"));
+/// assert!(glob_match("h[ae]llo", "hallo"));
+/// assert!(!glob_match("user:42:*", "user:7:name"));
+/// ```
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let mut p = 0;
+    let mut t = 0;
+    // Where to resume after a mismatch: (pattern index after the last `*`,
+    // text index the `*` currently extends to)
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        let next = match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, t));
+                p += 1;
+                continue;
+            }
+            Some('?') => Some(p + 1),
+            Some('[') => match match_class(&pattern, p, text[t]) {
+                Some((true, end)) => Some(end),
+                Some((false, _)) => None,
+                // An unclosed class is matched literally
+                None => (text[t] == '[').then_some(p + 1),
+            },
+            Some('\\') if p + 1 < pattern.len() => (pattern[p + 1] == text[t]).then_some(p + 2),
+            Some(&c) => (c == text[t]).then_some(p + 1),
+            None => None,
+        };
+
+        match (next, backtrack) {
+            (Some(next), _) => {
+                p = next;
+                t += 1;
+            }
+            // Let the last `*` swallow one more character and retry
+            (None, Some((star, star_t))) => {
+                backtrack = Some((star, star_t + 1));
+                p = star;
+                t = star_t + 1;
+            }
+            (None, None) => return false,
+        }
+    }
+
+    // Only stars can match the empty rest of the text
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Matches a character against the class starting at `start` (the index of `[`).
+///
+/// # Returns
+/// Whether the character is matched by the class and the pattern index
+/// right after the closing `]`, or `None` if the class is never closed.
+fn match_class(pattern: &[char], start: usize, c: char) -> Option<(bool, usize)> {
+    let mut i = start + 1;
+    let negated = matches!(pattern.get(i), Some('^' | '!'));
+    if negated {
+        i += 1;
+    }
+
+    let mut matched = false;
+    let mut first = true;
+    loop {
+        let mut low = *pattern.get(i)?;
+        // A `]` right after the opening bracket is a member, not the end
+        if low == ']' && !first {
+            break;
+        }
+        first = false;
+
+        if low == '\\' {
+            i += 1;
+            low = *pattern.get(i)?;
+        }
+
+        // Ranges such as `a-z`. A trailing `-` is a member itself
+        if pattern.get(i + 1) == Some(&'-') && pattern.get(i + 2).is_some_and(|&h| h != ']') {
+            let mut j = i + 2;
+            let mut high = pattern[j];
+            if high == '\\' {
+                j += 1;
+                high = *pattern.get(j)?;
+            }
+
+            let (low, high) = if low <= high {
+                (low, high)
+            } else {
+                (high, low)
+            };
+            matched |= low <= c && c <= high;
+            i = j + 1;
+        } else {
+            matched |= low == c;
+            i += 1;
+        }
+    }
+
+    Some((matched != negated, i + 1))
+}
+
+#[cfg(test)]
+mod glob_tests {
+    use super::*;
+
+    #[test]
+    fn test_literal() {
+        assert!(glob_match("hello", "hello"));
+        assert!(!glob_match("hello", "hell"));
+        assert!(!glob_match("hell", "hello"));
+        assert!(glob_match("", ""));
+        assert!(!glob_match("", "a"));
+    }
+
+    #[test]
+    fn test_star() {
+        assert!(glob_match("*", ""));
+        assert!(glob_match("*", "anything"));
+        assert!(glob_match("user:42:*", "user:42:"));
+        assert!(glob_match("user:42:*", "user:42:name"));
+        assert!(!glob_match("user:42:*", "user:420:name"));
+        assert!(glob_match("*:name", "user:42:name"));
+        assert!(glob_match("a*b*c", "aXXbYYbZZc"));
+        assert!(!glob_match("a*b*c", "aXXbYYbZZ"));
+        assert!(glob_match("**a**", "bab"));
+    }
+
+    #[test]
+    fn test_question_mark() {
+        assert!(glob_match("h?llo", "hello"));
+        assert!(glob_match("h?llo", "hallo"));
+        assert!(!glob_match("h?llo", "hllo"));
+        assert!(glob_match("???", "abc"));
+        assert!(!glob_match("???", "abcd"));
+    }
+
+    #[test]
+    fn test_classes() {
+        assert!(glob_match("h[ae]llo", "hello"));
+        assert!(glob_match("h[ae]llo", "hallo"));
+        assert!(!glob_match("h[ae]llo", "hillo"));
+
+        assert!(glob_match("key[0-9]", "key7"));
+        assert!(!glob_match("key[0-9]", "keyx"));
+        assert!(glob_match("key[9-0]", "key3"));
+
+        assert!(glob_match("h[^e]llo", "hallo"));
+        assert!(!glob_match("h[^e]llo", "hello"));
+        assert!(glob_match("h[!e]llo", "hallo"));
+
+        assert!(glob_match("[]]", "]"));
+        assert!(glob_match("[a-]", "-"));
+        assert!(glob_match("[\\]]", "]"));
+    }
+
+    #[test]
+    fn test_unclosed_class_is_literal() {
+        assert!(glob_match("a[b", "a[b"));
+        assert!(!glob_match("a[b", "ab"));
+    }
+
+    #[test]
+    fn test_escaping() {
+        assert!(glob_match("what\\?", "what?"));
+        assert!(!glob_match("what\\?", "whats"));
+        assert!(glob_match("\\*", "*"));
+        assert!(!glob_match("\\*", "a"));
+        assert!(glob_match("\\[a]", "[a]"));
+        assert!(glob_match("end\\", "end\\"));
+    }
+
+    #[test]
+    fn test_unicode() {
+        assert!(glob_match("caf?", "café"));
+        assert!(glob_match("[é]*", "école"));
+    }
+}
@@ -0,0 +1,773 @@
+//! JSON documents backing the `JSON.*` commands.
+//!
+//! Documents are stored as regular `StorageValue` trees: objects become
+//! `Map`, arrays `List`, and scalars `Text`, `Int`, `Float`, `Bool` or
+//! `Null`. This module converts between that tree and JSON text, and
+//! evaluates the supported subset of JSONPath against it:
+//!
+//! * `$` the root, `.name` or `['name']` an object member
+//! * `[n]` an array element, negative indices counting from the end
+//! * `.*` or `[*]` every member or element
+//!
+//! Paths that don't start with `$` use the legacy syntax (`.a.b` or `a.b`),
+//! which returns the first match instead of an array of matches.
+
+use std::fmt;
+
+use crate::StorageValue;
+
+/// Maximum nesting of arrays and objects accepted by the parser.
+const MAX_DEPTH: usize = 128;
+
+/// Parses JSON text into a storage value.
+///
+/// # Example
+/// ```rust
+/// use volatix_core::{StorageValue, parse_json, to_json};
+///
+/// let value = parse_json(r#"{"name": "Ada", "langs": ["en", "fr"], "age": 36}"#).unwrap();
+/// assert_eq!(
+///     value,
+///     StorageValue::Map(vec![
+///         ("name".to_string(), StorageValue::Text("Ada".to_string())),
+///         ("langs".to_string(), StorageValue::List(vec![
+///             StorageValue::Text("en".to_string()),
+///             StorageValue::Text("fr".to_string()),
+///         ])),
+///         ("age".to_string(), StorageValue::Int(36)),
+///     ])
+/// );
+/// assert_eq!(to_json(&value), r#"{"name":"Ada","langs":["en","fr"],"age":36}"#);
+/// ```
+pub fn parse_json(text: &str) -> Result<StorageValue, String> {
+    let mut parser = Parser {
+        bytes: text.as_bytes(),
+        pos: 0,
+    };
+    let value = parser.value(0)?;
+    parser.skip_whitespace();
+    match parser.pos == parser.bytes.len() {
+        true => Ok(value),
+        false => Err(parser.error("Unexpected trailing characters")),
+    }
+}
+
+/// Serializes a storage value to compact JSON text. Values with no JSON
+/// equivalent, such as bytes or sketches, are written as strings.
+pub fn to_json(value: &StorageValue) -> String {
+    let mut out = String::new();
+    write_json(value, &mut out);
+    out
+}
+
+fn write_json(value: &StorageValue, out: &mut String) {
+    match value {
+        StorageValue::Null => out.push_str("null"),
+        StorageValue::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        StorageValue::Int(i) => out.push_str(&i.to_string()),
+        // Debug keeps the fractional part of round floats, e.g 3.0
+        StorageValue::Float(f) if f.is_finite() => out.push_str(&format!("{f:?}")),
+        StorageValue::Float(_) => out.push_str("null"),
+        StorageValue::Text(text) => write_string(text, out),
+        StorageValue::Bytes(bytes) => write_string(&String::from_utf8_lossy(bytes), out),
+        StorageValue::List(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_json(item, out);
+            }
+            out.push(']');
+        }
+        StorageValue::Map(items) => {
+            out.push('{');
+            for (i, (key, item)) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(key, out);
+                out.push(':');
+                write_json(item, out);
+            }
+            out.push('}');
+        }
+        other => write_string(&other.to_string(), out),
+    }
+}
+
+fn write_string(text: &str, out: &mut String) {
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Recursive descent parser over the bytes of a JSON text.
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> String {
+        format!("Invalid JSON at position {}: {message}", self.pos)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.bytes.get(self.pos) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, literal: &str) -> Result<(), String> {
+        match self.bytes[self.pos..].starts_with(literal.as_bytes()) {
+            true => {
+                self.pos += literal.len();
+                Ok(())
+            }
+            false => Err(self.error(&format!("Expected {literal}"))),
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<StorageValue, String> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("Nesting is too deep"));
+        }
+
+        self.skip_whitespace();
+        match self.bytes.get(self.pos) {
+            Some(b'{') => self.object(depth),
+            Some(b'[') => self.array(depth),
+            Some(b'"') => self.string().map(StorageValue::Text),
+            Some(b't') => self.expect("true").map(|_| StorageValue::Bool(true)),
+            Some(b'f') => self.expect("false").map(|_| StorageValue::Bool(false)),
+            Some(b'n') => self.expect("null").map(|_| StorageValue::Null),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("Unexpected character")),
+            None => Err(self.error("Unexpected end of input")),
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<StorageValue, String> {
+        self.pos += 1;
+        let mut items: Vec<(String, StorageValue)> = Vec::new();
+        self.skip_whitespace();
+        if self.bytes.get(self.pos) == Some(&b'}') {
+            self.pos += 1;
+            return Ok(StorageValue::Map(items));
+        }
+
+        loop {
+            self.skip_whitespace();
+            if self.bytes.get(self.pos) != Some(&b'"') {
+                return Err(self.error("Expected an object key"));
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(":")?;
+            let value = self.value(depth + 1)?;
+
+            // The last duplicate key wins, as in most JSON parsers
+            match items.iter_mut().find(|(k, _)| *k == key) {
+                Some(item) => item.1 = value,
+                None => items.push((key, value)),
+            }
+
+            self.skip_whitespace();
+            match self.bytes.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(StorageValue::Map(items));
+                }
+                _ => return Err(self.error("Expected , or }")),
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<StorageValue, String> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.bytes.get(self.pos) == Some(&b']') {
+            self.pos += 1;
+            return Ok(StorageValue::List(items));
+        }
+
+        loop {
+            items.push(self.value(depth + 1)?);
+            self.skip_whitespace();
+            match self.bytes.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(StorageValue::List(items));
+                }
+                _ => return Err(self.error("Expected , or ]")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.pos += 1;
+        let mut out = Vec::new();
+        loop {
+            let Some(&byte) = self.bytes.get(self.pos) else {
+                return Err(self.error("Unterminated string"));
+            };
+            self.pos += 1;
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let Some(&escape) = self.bytes.get(self.pos) else {
+                        return Err(self.error("Unterminated string"));
+                    };
+                    self.pos += 1;
+                    let c = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.unicode_escape()?,
+                        _ => return Err(self.error("Invalid escape")),
+                    };
+                    out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                0..0x20 => return Err(self.error("Control character in string")),
+                _ => out.push(byte),
+            }
+        }
+        // The input is a &str, so unescaped bytes are valid UTF-8
+        String::from_utf8(out).map_err(|_| self.error("Invalid UTF-8"))
+    }
+
+    /// Decodes the `XXXX` of a `\uXXXX` escape, joining surrogate pairs.
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex4()?;
+        let code = match high {
+            0xD800..0xDC00 => {
+                self.expect("\\u")?;
+                let low = self.hex4()?;
+                if !(0xDC00..0xE000).contains(&low) {
+                    return Err(self.error("Invalid surrogate pair"));
+                }
+                0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+            }
+            _ => high,
+        };
+        char::from_u32(code).ok_or_else(|| self.error("Invalid unicode escape"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let digits = self
+            .bytes
+            .get(self.pos..self.pos + 4)
+            .and_then(|d| std::str::from_utf8(d).ok())
+            .and_then(|d| u32::from_str_radix(d, 16).ok())
+            .ok_or_else(|| self.error("Invalid unicode escape"))?;
+        self.pos += 4;
+        Ok(digits)
+    }
+
+    fn number(&mut self) -> Result<StorageValue, String> {
+        let start = self.pos;
+        let digits = |parser: &mut Self| {
+            let from = parser.pos;
+            while let Some(b'0'..=b'9') = parser.bytes.get(parser.pos) {
+                parser.pos += 1;
+            }
+            parser.pos > from
+        };
+
+        if self.bytes.get(self.pos) == Some(&b'-') {
+            self.pos += 1;
+        }
+        if !digits(self) {
+            return Err(self.error("Invalid number"));
+        }
+        let mut float = false;
+        if self.bytes.get(self.pos) == Some(&b'.') {
+            self.pos += 1;
+            float = true;
+            if !digits(self) {
+                return Err(self.error("Invalid number"));
+            }
+        }
+        if let Some(b'e' | b'E') = self.bytes.get(self.pos) {
+            self.pos += 1;
+            float = true;
+            if let Some(b'+' | b'-') = self.bytes.get(self.pos) {
+                self.pos += 1;
+            }
+            if !digits(self) {
+                return Err(self.error("Invalid number"));
+            }
+        }
+
+        let text = std::str::from_utf8(&self.bytes[start..self.pos]).unwrap_or_default();
+        match text.parse::<i64>() {
+            Ok(int) if !float => Ok(StorageValue::Int(int)),
+            // Integers out of the i64 range are kept as floats
+            _ => text
+                .parse::<f64>()
+                .map(StorageValue::Float)
+                .map_err(|_| self.error("Invalid number")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    /// Object member
+    Key(String),
+    /// Array element, negative from the end
+    Index(i64),
+    /// Every member or element
+    Wildcard,
+}
+
+/// A parsed JSONPath, see the module documentation for the syntax.
+///
+/// # Example
+/// ```rust
+/// use volatix_core::{JsonPath, StorageValue, parse_json};
+///
+/// let doc = parse_json(r#"{"users": [{"name": "Ada"}, {"name": "Alan"}]}"#).unwrap();
+/// let path = JsonPath::parse("$.users[*].name").unwrap();
+/// let names = path.get(&doc);
+/// assert_eq!(names, [&StorageValue::Text("Ada".to_string()), &StorageValue::Text("Alan".to_string())]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+    legacy: bool,
+    /// The path as written, used to label multi-path replies
+    raw: String,
+}
+
+impl fmt::Display for JsonPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+impl JsonPath {
+    /// Parses a path such as `$.users[0].name` or the legacy `.users[0].name`.
+    pub fn parse(path: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid JSON path {path}");
+        let (legacy, full) = match path.strip_prefix('$') {
+            Some(rest) => (false, rest.to_string()),
+            None if path.starts_with(['.', '[']) => (true, path.to_string()),
+            // Legacy paths may omit the leading dot, e.g `a.b`
+            None => (true, format!(".{path}")),
+        };
+
+        let mut rest = full.as_str();
+        let mut segments = Vec::new();
+        while !rest.is_empty() {
+            if let Some(tail) = rest.strip_prefix(".*") {
+                segments.push(Segment::Wildcard);
+                rest = tail;
+            } else if let Some(tail) = rest.strip_prefix('.') {
+                let end = tail.find(['.', '[']).unwrap_or(tail.len());
+                if end == 0 {
+                    // A lone `.` is the legacy root
+                    if legacy && tail.is_empty() && segments.is_empty() {
+                        break;
+                    }
+                    return Err(invalid());
+                }
+                segments.push(Segment::Key(tail[..end].to_string()));
+                rest = &tail[end..];
+            } else if let Some(tail) = rest.strip_prefix('[') {
+                let end = tail.find(']').ok_or_else(invalid)?;
+                let inner = tail[..end].trim();
+                let segment = match inner {
+                    "*" => Segment::Wildcard,
+                    _ if inner.len() >= 2
+                        && (inner.starts_with('\'') && inner.ends_with('\'')
+                            || inner.starts_with('"') && inner.ends_with('"')) =>
+                    {
+                        Segment::Key(inner[1..inner.len() - 1].to_string())
+                    }
+                    _ => Segment::Index(inner.parse().map_err(|_| invalid())?),
+                };
+                segments.push(segment);
+                rest = &tail[end + 1..];
+            } else {
+                return Err(invalid());
+            }
+        }
+        Ok(JsonPath {
+            segments,
+            legacy,
+            raw: path.to_string(),
+        })
+    }
+
+    /// Whether the path designates the whole document.
+    pub fn is_root(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Whether the path uses the legacy syntax, which returns single values.
+    pub fn is_legacy(&self) -> bool {
+        self.legacy
+    }
+
+    /// Returns the values matched by the path.
+    pub fn get<'a>(&self, doc: &'a StorageValue) -> Vec<&'a StorageValue> {
+        let mut found = Vec::new();
+        visit(doc, &self.segments, &mut found);
+        found
+    }
+
+    /// Applies `f` to every value matched by the path.
+    pub fn update<T>(
+        &self,
+        doc: &mut StorageValue,
+        mut f: impl FnMut(&mut StorageValue) -> T,
+    ) -> Vec<T> {
+        let mut results = Vec::new();
+        visit_mut(doc, &self.segments, &mut |value| results.push(f(value)));
+        results
+    }
+
+    /// Writes `value` at every match of the path. A missing object member
+    /// is created if its parent exists.
+    ///
+    /// # Returns
+    /// The number of values written
+    pub fn set(&self, doc: &mut StorageValue, value: &StorageValue) -> usize {
+        let Some((last, parents)) = self.segments.split_last() else {
+            *doc = value.clone();
+            return 1;
+        };
+
+        let mut written = 0;
+        visit_mut(doc, parents, &mut |parent| match (last, parent) {
+            (Segment::Key(key), StorageValue::Map(items)) => {
+                match items.iter_mut().find(|(k, _)| k == key) {
+                    Some(item) => item.1 = value.clone(),
+                    None => items.push((key.clone(), value.clone())),
+                }
+                written += 1;
+            }
+            (Segment::Index(index), StorageValue::List(items)) => {
+                if let Some(i) = resolve_index(*index, items.len()) {
+                    items[i] = value.clone();
+                    written += 1;
+                }
+            }
+            (Segment::Wildcard, StorageValue::List(items)) => {
+                written += items.len();
+                items.iter_mut().for_each(|item| *item = value.clone());
+            }
+            (Segment::Wildcard, StorageValue::Map(items)) => {
+                written += items.len();
+                items.iter_mut().for_each(|item| item.1 = value.clone());
+            }
+            _ => {}
+        });
+        written
+    }
+
+    /// Removes the values matched by the path. The root can't be removed
+    /// from a document, callers delete the whole key instead.
+    ///
+    /// # Returns
+    /// The number of values removed
+    pub fn delete(&self, doc: &mut StorageValue) -> usize {
+        let Some((last, parents)) = self.segments.split_last() else {
+            return 0;
+        };
+
+        let mut removed = 0;
+        visit_mut(doc, parents, &mut |parent| match (last, parent) {
+            (Segment::Key(key), StorageValue::Map(items)) => {
+                let len = items.len();
+                items.retain(|(k, _)| k != key);
+                removed += len - items.len();
+            }
+            (Segment::Index(index), StorageValue::List(items)) => {
+                if let Some(i) = resolve_index(*index, items.len()) {
+                    items.remove(i);
+                    removed += 1;
+                }
+            }
+            (Segment::Wildcard, StorageValue::List(items)) => removed += items.drain(..).count(),
+            (Segment::Wildcard, StorageValue::Map(items)) => removed += items.drain(..).count(),
+            _ => {}
+        });
+        removed
+    }
+
+    /// Shapes per-match results as the commands reply them: an array for
+    /// JSONPath, or the first result for legacy paths. `None` marks a match
+    /// of the wrong type, replied as null in arrays.
+    ///
+    /// # Returns
+    /// The reply, or an error if a legacy path matched nothing (or a value
+    /// of the wrong type, reported as `mismatch`)
+    pub fn reply(
+        &self,
+        results: Vec<Option<StorageValue>>,
+        mismatch: &str,
+    ) -> Result<StorageValue, String> {
+        if !self.legacy {
+            let results = results.into_iter().map(|r| r.unwrap_or(StorageValue::Null));
+            return Ok(StorageValue::List(results.collect()));
+        }
+        match results.into_iter().next() {
+            Some(Some(result)) => Ok(result),
+            Some(None) => Err(mismatch.to_string()),
+            None => Err("Path does not exist".to_string()),
+        }
+    }
+}
+
+/// Converts a possibly negative index into a position in `0..len`.
+fn resolve_index(index: i64, len: usize) -> Option<usize> {
+    let index = match index < 0 {
+        true => len as i64 + index,
+        false => index,
+    };
+    (0..len as i64).contains(&index).then_some(index as usize)
+}
+
+fn visit<'a>(value: &'a StorageValue, segments: &[Segment], found: &mut Vec<&'a StorageValue>) {
+    let Some((segment, rest)) = segments.split_first() else {
+        found.push(value);
+        return;
+    };
+
+    match (segment, value) {
+        (Segment::Key(key), StorageValue::Map(items)) => items
+            .iter()
+            .filter(|(k, _)| k == key)
+            .for_each(|(_, item)| visit(item, rest, found)),
+        (Segment::Index(index), StorageValue::List(items)) => {
+            if let Some(i) = resolve_index(*index, items.len()) {
+                visit(&items[i], rest, found);
+            }
+        }
+        (Segment::Wildcard, StorageValue::List(items)) => {
+            items.iter().for_each(|item| visit(item, rest, found))
+        }
+        (Segment::Wildcard, StorageValue::Map(items)) => {
+            items.iter().for_each(|(_, item)| visit(item, rest, found))
+        }
+        _ => {}
+    }
+}
+
+fn visit_mut(value: &mut StorageValue, segments: &[Segment], f: &mut dyn FnMut(&mut StorageValue)) {
+    let Some((segment, rest)) = segments.split_first() else {
+        f(value);
+        return;
+    };
+
+    match (segment, value) {
+        (Segment::Key(key), StorageValue::Map(items)) => items
+            .iter_mut()
+            .filter(|(k, _)| k == key)
+            .for_each(|(_, item)| visit_mut(item, rest, f)),
+        (Segment::Index(index), StorageValue::List(items)) => {
+            if let Some(i) = resolve_index(*index, items.len()) {
+                visit_mut(&mut items[i], rest, f);
+            }
+        }
+        (Segment::Wildcard, StorageValue::List(items)) => {
+            items.iter_mut().for_each(|item| visit_mut(item, rest, f))
+        }
+        (Segment::Wildcard, StorageValue::Map(items)) => items
+            .iter_mut()
+            .for_each(|(_, item)| visit_mut(item, rest, f)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod json_tests {
+    use super::*;
+
+    fn doc() -> StorageValue {
+        parse_json(r#"{"name":"Ada","age":36,"langs":["en","fr"],"address":{"city":"London"}}"#)
+            .unwrap()
+    }
+
+    fn text(v: &str) -> StorageValue {
+        StorageValue::Text(v.to_string())
+    }
+
+    #[test]
+    fn test_parse_and_serialize() {
+        let json =
+            r#"{"a":[1,-2.5,1e3,true,false,null],"b":{"c":"x\"y\\z\n\u00e9\ud83d\ude00"},"d":[]}"#;
+        let value = parse_json(json).unwrap();
+        assert_eq!(
+            to_json(&value),
+            r#"{"a":[1,-2.5,1000.0,true,false,null],"b":{"c":"x\"y\\z\né😀"},"d":[]}"#
+        );
+        assert_eq!(parse_json(&to_json(&value)).unwrap(), value);
+
+        assert_eq!(parse_json(" 42 ").unwrap(), StorageValue::Int(42));
+        assert_eq!(parse_json("\"\"").unwrap(), text(""));
+        assert_eq!(
+            parse_json("99999999999999999999").unwrap(),
+            StorageValue::Float(1e20)
+        );
+        assert_eq!(to_json(&StorageValue::Float(f64::NAN)), "null");
+        assert_eq!(to_json(&text("\u{1}")), "\"\\u0001\"");
+
+        // Duplicate keys keep the last value
+        assert_eq!(
+            to_json(&parse_json(r#"{"a":1,"a":2}"#).unwrap()),
+            r#"{"a":2}"#
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for invalid in [
+            "",
+            "{",
+            "[1,]",
+            "{\"a\" 1}",
+            "{a:1}",
+            "01x",
+            "1.",
+            "-",
+            "tru",
+            "\"abc",
+            "[1] 2",
+            "\"\\x\"",
+            "\"\\ud800\"",
+            "\"\t\"",
+        ] {
+            assert!(parse_json(invalid).is_err(), "{invalid:?} should not parse");
+        }
+
+        let deep = "[".repeat(MAX_DEPTH + 2) + &"]".repeat(MAX_DEPTH + 2);
+        assert!(parse_json(&deep).is_err());
+    }
+
+    #[test]
+    fn test_parse_paths() {
+        let path = JsonPath::parse("$.a['b c'][-1][*].*").unwrap();
+        assert_eq!(
+            path.segments,
+            [
+                Segment::Key("a".to_string()),
+                Segment::Key("b c".to_string()),
+                Segment::Index(-1),
+                Segment::Wildcard,
+                Segment::Wildcard,
+            ]
+        );
+        assert!(!path.is_legacy());
+
+        assert!(JsonPath::parse("$").unwrap().is_root());
+        assert!(JsonPath::parse(".").unwrap().is_root());
+        assert!(JsonPath::parse(".").unwrap().is_legacy());
+        assert_eq!(
+            JsonPath::parse("a.b").unwrap().segments,
+            JsonPath::parse(".a.b").unwrap().segments
+        );
+        assert_eq!(JsonPath::parse("a.b").unwrap().to_string(), "a.b");
+
+        for invalid in ["$a", "$.", "$..a", "$[x]", "$[0", "$.a."] {
+            assert!(
+                JsonPath::parse(invalid).is_err(),
+                "{invalid:?} should not parse"
+            );
+        }
+    }
+
+    #[test]
+    fn test_get() {
+        let doc = doc();
+        let get = |path: &str| JsonPath::parse(path).unwrap().get(&doc);
+
+        assert_eq!(get("$.name"), [&text("Ada")]);
+        assert_eq!(get("$.langs[-1]"), [&text("fr")]);
+        assert_eq!(get("$.langs[*]"), [&text("en"), &text("fr")]);
+        assert_eq!(get("$.address.city"), [&text("London")]);
+        assert_eq!(get("$.*").len(), 4);
+        assert!(get("$.missing").is_empty());
+        assert!(get("$.langs[2]").is_empty());
+        assert!(get("$.name.first").is_empty());
+        assert_eq!(get("$"), [&doc]);
+    }
+
+    #[test]
+    fn test_set_and_delete() {
+        let mut doc = doc();
+        let path = |path: &str| JsonPath::parse(path).unwrap();
+
+        assert_eq!(path("$.age").set(&mut doc, &StorageValue::Int(37)), 1);
+        assert_eq!(path("$.address.zip").set(&mut doc, &text("NW1")), 1);
+        assert_eq!(path("$.langs[0]").set(&mut doc, &text("de")), 1);
+        // Only the last member of a path is created
+        assert_eq!(path("$.job.title").set(&mut doc, &text("x")), 0);
+        assert_eq!(path("$.langs[5]").set(&mut doc, &text("x")), 0);
+        assert_eq!(
+            to_json(&doc),
+            r#"{"name":"Ada","age":37,"langs":["de","fr"],"address":{"city":"London","zip":"NW1"}}"#
+        );
+
+        assert_eq!(path("$.langs[-1]").delete(&mut doc), 1);
+        assert_eq!(path("$.address.*").delete(&mut doc), 2);
+        assert_eq!(path("$.missing").delete(&mut doc), 0);
+        assert_eq!(path("$").delete(&mut doc), 0);
+        assert_eq!(
+            to_json(&doc),
+            r#"{"name":"Ada","age":37,"langs":["de"],"address":{}}"#
+        );
+
+        assert_eq!(path("$").set(&mut doc, &StorageValue::Int(1)), 1);
+        assert_eq!(doc, StorageValue::Int(1));
+    }
+
+    #[test]
+    fn test_update_and_reply() {
+        let mut doc = parse_json(r#"{"a":[1,2],"b":[3]}"#).unwrap();
+        let path = JsonPath::parse("$.*").unwrap();
+        let lengths = path.update(&mut doc, |value| match value {
+            StorageValue::List(items) => {
+                items.push(StorageValue::Int(0));
+                items.len()
+            }
+            _ => 0,
+        });
+        assert_eq!(lengths, [3, 2]);
+
+        let results = vec![Some(StorageValue::Int(3)), None];
+        let reply = path.reply(results.clone(), "Not an array");
+        assert_eq!(to_json(&reply.unwrap()), "[3,null]");
+
+        let legacy = JsonPath::parse(".a").unwrap();
+        assert_eq!(
+            legacy.reply(results, "Not an array"),
+            Ok(StorageValue::Int(3))
+        );
+        let mismatch = legacy.reply(vec![None], "Not an array");
+        assert_eq!(mismatch, Err("Not an array".to_string()));
+        let missing = legacy.reply(Vec::new(), "Not an array");
+        assert_eq!(missing, Err("Path does not exist".to_string()));
+    }
+}
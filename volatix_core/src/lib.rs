@@ -3,9 +3,10 @@
  *
  * Volatix is a Redis-compatible cache server built in Rust that provides:
  * - Thread-safe concurrent access
- * - Multiple data types (Int, Float, Bool, Text, Bytes, List, Map)
+ * - Multiple data types (Int, Float, Bool, Text, Bytes, List, Map, HyperLogLog,
+ *   Bloom and cuckoo filters, Stream, Geo)
  * - TTL support with automatic expiration
- * - Configurable eviction policies (LRU, LFU, Oldest, Size-aware)
+ * - Configurable eviction policies (LRU, LFU, Oldest, Size-aware, W-TinyLFU)
  * - Disk persistence with background snapshots
  * - Redis Serialization (RESP3) protocol compatibility
  * - Optional compression for large values
@@ -24,19 +25,57 @@
  * RESP3 protocol implementation:
  * - `RequestType`: All supported RESP3 data types
  * - `parse_request()`: Converts bytes to structured requests
- * - Response functions: Convert internal data to RESP3 format
+ * - `split_inline()`, `parse_inline()`: Split inline commands typed over
+ *   telnet or netcat into lines and arguments
+ * - `RespValue`: Typed replies, encoded binary-safe into a `Vec<u8>` or any
+ *   `io::Write`
+ * - Response macros: Build `RespValue`s from internal data
+ *
+ * ### Glob Patterns (`glob`)
+ * - `glob_match()`: Matches keys against `*`, `?` and `[abc]` patterns
+ *
+ * ### Cardinality Estimation (`hyperloglog`)
+ * - `HyperLogLog`: Sketch backing `PFADD`, `PFCOUNT` and `PFMERGE`
+ *
+ * ### Membership Filters (`filters`)
+ * - `BloomFilter`: Scalable Bloom filter backing the `BF.*` commands
+ * - `CuckooFilter`: Filter supporting deletes, backing the `CF.*` commands
+ *
+ * ### Streams (`stream`)
+ * - `Stream`: Append-only log with consumer groups, backing the `X*` commands
+ * - `StreamId`: Time-ordered identifier of a stream entry
+ *
+ * ### Geospatial Indexes (`geo`)
+ * - `GeoSet`: Members sorted by geohash, backing the `GEO*` commands
+ * - `haversine()`: Great-circle distance between two positions
+ *
+ * ### JSON Documents (`json`)
+ * - `parse_json()` / `to_json()`: Convert between JSON text and `StorageValue`
+ * - `JsonPath`: JSONPath subset used by the `JSON.*` commands
  *
  */
 
 // Re-export all public APIs from the modules
 pub mod error;
+pub mod filters;
+pub mod geo;
+pub mod glob;
+pub mod hyperloglog;
+pub mod json;
 pub mod resp3;
 pub mod storage;
+pub mod stream;
 
 // Make common types available at the crate root
 pub use error::*;
+pub use filters::*;
+pub use geo::*;
+pub use glob::*;
+pub use hyperloglog::*;
+pub use json::*;
 pub use resp3::*;
 pub use storage::*;
+pub use stream::*;
 
 /// Ascii representation of the word `Volatix`.
 pub fn volatix_ascii_art() -> &'static str {
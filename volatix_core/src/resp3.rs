@@ -1,11 +1,249 @@
 // Redis Serialization Protocol v3.0
 
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    io::{self, Write},
+};
 
 use crate::{StorageValue, parser_error};
 
-/// Produces a Vec of byte representation of the `RequestType::Array` from
-/// a Vec of String expressions.
+/// A typed RESP3 value, written to the wire with [`RespValue::encode`] or
+/// [`RespValue::write_to`]. Aggregates nest any other value and bulk strings
+/// carry raw bytes, so binary values go out unchanged.
+///
+/// # Example
+/// ```rust
+/// use volatix_core::RespValue;
+///
+/// let reply = RespValue::Map(vec![(
+///     RespValue::bulk("tags"),
+///     RespValue::Set(vec![RespValue::bulk("a"), RespValue::Double(0.5)]),
+/// )]);
+/// assert_eq!(
+///     reply.encode(),
+///     b"%1\r\n$4\r\ntags\r\n~2\r\n$1\r\na\r\n,0.5\r\n".to_vec()
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum RespValue {
+    /// `+<string>\r\n`, must not contain line breaks
+    SimpleString(String),
+    /// `-<error>\r\n`, must not contain line breaks
+    SimpleError(String),
+    /// `:<integer>\r\n`
+    Integer(i64),
+    /// `$<length>\r\n<bytes>\r\n`
+    BulkString(Vec<u8>),
+    /// `$-1\r\n`, the null bulk string replied for missing keys
+    NullBulkString,
+    /// `_\r\n`
+    Null,
+    /// `#t\r\n` or `#f\r\n`
+    Boolean(bool),
+    /// `,<floating-point-number>\r\n`
+    Double(f64),
+    /// `(<big-number>\r\n`
+    BigNumber(String),
+    /// `!<length>\r\n<error>\r\n`
+    BulkError(String),
+    /// `=<length>\r\n<encoding>:<data>\r\n`, the encoding is three bytes
+    /// long, e.g `txt`
+    VerbatimString { encoding: String, data: Vec<u8> },
+    /// `*<number-of-elements>\r\n<element-1>...<element-n>`
+    Array(Vec<RespValue>),
+    /// `%<number-of-entries>\r\n<key-1><value-1>...<key-n><value-n>`
+    Map(Vec<(RespValue, RespValue)>),
+    /// `~<number-of-elements>\r\n<element-1>...<element-n>`
+    Set(Vec<RespValue>),
+    /// `|<number-of-entries>\r\n<key-1><value-1>...` followed by the value
+    /// the attributes describe
+    Attribute {
+        attributes: Vec<(RespValue, RespValue)>,
+        value: Box<RespValue>,
+    },
+    /// `><number-of-elements>\r\n<element-1>...<element-n>`, an out-of-band
+    /// message
+    Push(Vec<RespValue>),
+}
+
+impl RespValue {
+    /// Builds a bulk string from text or raw bytes.
+    pub fn bulk(data: impl AsRef<[u8]>) -> Self {
+        RespValue::BulkString(data.as_ref().to_vec())
+    }
+
+    /// Builds a bulk error from a message.
+    pub fn error(message: impl Into<String>) -> Self {
+        RespValue::BulkError(message.into())
+    }
+
+    /// Encodes the value into a new buffer.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.encode_into(&mut out);
+        out
+    }
+
+    /// Writes the encoded value to a writer, e.g a `TcpStream`.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.encode())
+    }
+
+    /// Appends the encoded value to a buffer.
+    pub fn encode_into(&self, out: &mut Vec<u8>) {
+        // <type><length or data>\r\n
+        let mut header = |kind: u8, head: &dyn std::fmt::Display| {
+            out.push(kind);
+            out.extend(head.to_string().as_bytes());
+            out.extend(b"\r\n");
+        };
+
+        match self {
+            RespValue::SimpleString(s) => header(b'+', s),
+            RespValue::SimpleError(e) => header(b'-', e),
+            RespValue::Integer(i) => header(b':', i),
+            RespValue::BulkString(data) => {
+                header(b'$', &data.len());
+                out.extend(data);
+                out.extend(b"\r\n");
+            }
+            RespValue::NullBulkString => header(b'$', &-1),
+            RespValue::Null => out.extend(b"_\r\n"),
+            RespValue::Boolean(b) => header(b'#', &if *b { 't' } else { 'f' }),
+            RespValue::Double(d) => header(b',', &format_double(*d)),
+            RespValue::BigNumber(n) => header(b'(', n),
+            RespValue::BulkError(e) => {
+                header(b'!', &e.len());
+                out.extend(e.as_bytes());
+                out.extend(b"\r\n");
+            }
+            RespValue::VerbatimString { encoding, data } => {
+                header(b'=', &(encoding.len() + 1 + data.len()));
+                out.extend(encoding.as_bytes());
+                out.push(b':');
+                out.extend(data);
+                out.extend(b"\r\n");
+            }
+            RespValue::Array(items) | RespValue::Set(items) | RespValue::Push(items) => {
+                let kind = match self {
+                    RespValue::Array(_) => b'*',
+                    RespValue::Set(_) => b'~',
+                    _ => b'>',
+                };
+                header(kind, &items.len());
+                for item in items {
+                    item.encode_into(out);
+                }
+            }
+            RespValue::Map(entries) => {
+                header(b'%', &entries.len());
+                encode_entries(entries, out);
+            }
+            RespValue::Attribute { attributes, value } => {
+                header(b'|', &attributes.len());
+                encode_entries(attributes, out);
+                value.encode_into(out);
+            }
+        }
+    }
+
+    /// Downgrades the value to the types RESP2 clients understand: maps are
+    /// flattened to arrays of keys and values, sets and pushes become arrays,
+    /// booleans integers, nulls `$-1`, and doubles, big numbers and verbatim
+    /// strings bulk strings. Attributes are dropped, keeping the value only.
+    ///
+    /// # Example
+    /// ```rust
+    /// use volatix_core::RespValue;
+    ///
+    /// let reply = RespValue::Map(vec![(RespValue::bulk("ok"), RespValue::Boolean(true))]);
+    /// assert_eq!(
+    ///     reply.into_resp2().encode(),
+    ///     b"*2\r\n$2\r\nok\r\n:1\r\n".to_vec()
+    /// );
+    /// ```
+    pub fn into_resp2(self) -> RespValue {
+        let all = |items: Vec<RespValue>| items.into_iter().map(RespValue::into_resp2).collect();
+
+        match self {
+            RespValue::Null => RespValue::NullBulkString,
+            RespValue::Boolean(b) => RespValue::Integer(b as i64),
+            RespValue::Double(d) => RespValue::bulk(format_double(d)),
+            RespValue::BigNumber(n) => RespValue::bulk(n),
+            // Simple errors can't span several lines
+            RespValue::BulkError(e) => RespValue::SimpleError(e.replace(['\r', '\n'], " ")),
+            RespValue::VerbatimString { data, .. } => RespValue::BulkString(data),
+            RespValue::Array(items) | RespValue::Set(items) | RespValue::Push(items) => {
+                RespValue::Array(all(items))
+            }
+            RespValue::Map(entries) => RespValue::Array(
+                entries
+                    .into_iter()
+                    .flat_map(|(key, value)| [key.into_resp2(), value.into_resp2()])
+                    .collect(),
+            ),
+            RespValue::Attribute { value, .. } => value.into_resp2(),
+            other => other,
+        }
+    }
+}
+
+fn encode_entries(entries: &[(RespValue, RespValue)], out: &mut Vec<u8>) {
+    for (key, value) in entries {
+        key.encode_into(out);
+        value.encode_into(out);
+    }
+}
+
+/// Formats a double the way RESP3 spells it, with `inf`, `-inf` and `nan`.
+fn format_double(d: f64) -> String {
+    if d.is_nan() {
+        "nan".to_string()
+    } else if d.is_infinite() {
+        if d > 0.0 { "inf" } else { "-inf" }.to_string()
+    } else {
+        d.to_string()
+    }
+}
+
+/// Maps a stored value to its RESP3 type: integers stay integers, floats
+/// are doubles, lists arrays and maps maps. Binary values are sent as is,
+/// the sketch, stream and geospatial types as their description.
+impl From<&StorageValue> for RespValue {
+    fn from(value: &StorageValue) -> Self {
+        match value {
+            StorageValue::Int(i) => RespValue::Integer(*i),
+            StorageValue::Bool(b) => RespValue::Boolean(*b),
+            StorageValue::Float(d) => RespValue::Double(*d),
+            StorageValue::Null => RespValue::Null,
+            StorageValue::Text(t) => RespValue::bulk(t),
+            StorageValue::Bytes(b) => RespValue::bulk(b),
+            StorageValue::HyperLogLog(_)
+            | StorageValue::BloomFilter(_)
+            | StorageValue::CuckooFilter(_)
+            | StorageValue::Stream(_)
+            | StorageValue::Geo(_) => RespValue::bulk(value.to_string()),
+            StorageValue::List(values) => {
+                RespValue::Array(values.iter().map(RespValue::from).collect())
+            }
+            StorageValue::Map(items) => RespValue::Map(
+                items
+                    .iter()
+                    .map(|(k, v)| (RespValue::bulk(k), RespValue::from(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<StorageValue> for RespValue {
+    fn from(value: StorageValue) -> Self {
+        RespValue::from(&value)
+    }
+}
+
+/// Produces a `RespValue::Array` of bulk strings from a collection of
+/// strings.
 ///
 /// # Examples
 /// ```rust
@@ -13,38 +251,22 @@ use crate::{StorageValue, parser_error};
 ///
 /// let names = vec!["Mike".to_string()];
 /// let arr = array!(names);
-/// assert_eq!(arr, b"*1\r\n$4\r\nMike\r\n".to_vec());
+/// assert_eq!(arr.encode(), b"*1\r\n$4\r\nMike\r\n".to_vec());
 /// ```
 #[macro_export]
 macro_rules! array {
     ($data:expr) => {
-        {
-            let mut arr = String::new();
-            let delimeter = "\r\n";
-
-            arr.push('*');
-            arr.push_str(&$data.len().to_string());
-            arr.push_str(delimeter);
-
-            for entry in $data {
-                let mut s = String::new();
-                s.push('$');
-                s.push_str(&entry.len().to_string());
-                s.push_str(delimeter);
-                s.push_str(&entry);
-                s.push_str(delimeter);
-
-                arr.push_str(&s);
-            }
-
-            arr.as_bytes().to_vec()
-        }
-    }
+        $crate::RespValue::Array(
+            $data
+                .into_iter()
+                .map(|entry| $crate::RespValue::bulk(entry))
+                .collect(),
+        )
+    };
 }
 
-/// Converts a String expression if one is provided, to a Vec of byte
-/// representation of the `RequestType::BulkString` and a `RequestType::Null`
-/// if none is provided.
+/// Converts a String expression if one is provided, to a
+/// `RespValue::BulkString`, and to a null bulk string if none is provided.
 ///
 /// # Examples
 /// ```rust
@@ -52,49 +274,38 @@ macro_rules! array {
 ///
 /// let name = "Mike";
 /// let bulk_string = bulkstring!(Some(name));
-/// assert_eq!(bulk_string, b"$4\r\nMike\r\n".to_vec());
+/// assert_eq!(bulk_string.encode(), b"$4\r\nMike\r\n".to_vec());
 ///
 /// let non_string = bulkstring!(None::<&str>);
-/// assert_eq!(non_string, b"$-1\r\n".to_vec());
+/// assert_eq!(non_string.encode(), b"$-1\r\n".to_vec());
 /// ```
 #[macro_export]
 macro_rules! bulkstring {
     ($data:expr) => {
         match $data {
-            Some(rsp) => {
-                let mut s = String::new();
-                s.push('$');
-                let len = rsp.len();
-                s.push_str(&len.to_string());
-                s.push_str("\r\n");
-                s.push_str(&rsp);
-                s.push_str("\r\n");
-
-                s.as_bytes().to_vec()
-            }
+            Some(rsp) => $crate::RespValue::bulk(rsp),
             None => $crate::null!(),
         }
     };
 }
 
-/// Produces a Vec of byte representation of the `RequestType::Null`
+/// Produces the null bulk string, replied for missing values.
 ///
 /// # Examples
 /// ```rust
 /// use volatix_core::null;
 ///
 /// let n = null!();
-/// assert_eq!(n, b"$-1\r\n".to_vec());
+/// assert_eq!(n.encode(), b"$-1\r\n".to_vec());
 /// ```
 #[macro_export]
 macro_rules! null {
     () => {
-        b"$-1\r\n".to_vec()
+        $crate::RespValue::NullBulkString
     };
 }
 
-/// Converts a String expression to a Vec of byte representation of the
-/// `RequestType::BulkError`
+/// Converts a String expression to a `RespValue::BulkError`
 ///
 /// # Examples
 /// ```rust
@@ -102,25 +313,60 @@ macro_rules! null {
 ///
 /// let err = "error";
 /// let bulk_error = bulkerror!(err);
-/// assert_eq!(bulk_error, b"!5\r\nerror\r\n".to_vec());
+/// assert_eq!(bulk_error.encode(), b"!5\r\nerror\r\n".to_vec());
 /// ```
 #[macro_export]
 macro_rules! bulkerror {
-    ($err:expr) => {{
-        let mut s = String::new();
-        s.push('!');
-        let len = $err.len();
-        s.push_str(&len.to_string());
-        s.push_str("\r\n");
-        s.push_str($err);
-        s.push_str("\r\n");
-
-        s.as_bytes().to_vec()
-    }};
+    ($err:expr) => {
+        $crate::RespValue::error($err)
+    };
+}
+
+/// Converts key-value pairs to a `RespValue::Map`. Keys are bulk strings
+/// and values keep their RESP3 type, so integers stay integers and floats
+/// doubles.
+///
+/// # Examples
+/// ```rust
+/// use volatix_core::{StorageValue, map};
+///
+/// let stats = [
+///     ("hits", StorageValue::Int(3)),
+///     ("compressed", StorageValue::Bool(false)),
+/// ];
+/// let reply = map!(&stats);
+/// assert_eq!(reply.encode(), b"%2\r\n$4\r\nhits\r\n:3\r\n$10\r\ncompressed\r\n#f\r\n".to_vec());
+/// ```
+#[macro_export]
+macro_rules! map {
+    ($items:expr) => {
+        $crate::RespValue::Map(
+            $items
+                .into_iter()
+                .map(|(key, value)| ($crate::RespValue::bulk(key), $crate::RespValue::from(value)))
+                .collect(),
+        )
+    };
+}
+
+/// Converts a String expression without line breaks to a
+/// `RespValue::SimpleString`
+///
+/// # Examples
+/// ```rust
+/// use volatix_core::simplestring;
+///
+/// let kind = simplestring!("list");
+/// assert_eq!(kind.encode(), b"+list\r\n".to_vec());
+/// ```
+#[macro_export]
+macro_rules! simplestring {
+    ($data:expr) => {
+        $crate::RespValue::SimpleString($data.to_string())
+    };
 }
 
-/// Converts a Bool expression to a Vec of byte representation of the
-/// `RequestType::Boolean`
+/// Converts a Bool expression to a `RespValue::Boolean`
 ///
 /// # Examples
 /// ```rust
@@ -128,25 +374,20 @@ macro_rules! bulkerror {
 ///
 /// let is_valid = true;
 /// let t = boolean!(is_valid);
-/// assert_eq!(t, b"#t\r\n".to_vec());
+/// assert_eq!(t.encode(), b"#t\r\n".to_vec());
 ///
 /// let invalid = false;
 /// let f = boolean!(invalid);
-/// assert_eq!(f, b"#f\r\n".to_vec());
+/// assert_eq!(f.encode(), b"#f\r\n".to_vec());
 /// ```
 #[macro_export]
 macro_rules! boolean {
     ($b:expr) => {
-        if $b {
-            b"#t\r\n".to_vec()
-        } else {
-            b"#f\r\n".to_vec()
-        }
+        $crate::RespValue::Boolean($b)
     };
 }
 
-/// Converts a String expression to a Vec of byte representation of the
-/// `RequestType::BulkError`
+/// Converts an integer expression to a `RespValue::Integer`
 ///
 /// # Examples
 /// ```rust
@@ -154,146 +395,53 @@ macro_rules! boolean {
 ///
 /// let n = 10;
 /// let n_bytes = integer!(n);
-/// assert_eq!(n_bytes, b":10\r\n".to_vec());
+/// assert_eq!(n_bytes.encode(), b":10\r\n".to_vec());
 ///
 /// let n = -10;
 /// let n_bytes = integer!(n);
-/// assert_eq!(n_bytes, b":-10\r\n".to_vec());
+/// assert_eq!(n_bytes.encode(), b":-10\r\n".to_vec());
 #[macro_export]
 macro_rules! integer {
     ($i:expr) => {
-        format!(":{}\r\n", $i).as_bytes().to_vec()
+        $crate::RespValue::Integer(($i) as i64)
     };
 }
 
 /// Convert a StorageValue to the appropriate RequestType String representation.
+/// Binary values are converted lossily, see [`RespValue`] for a binary-safe
+/// encoding.
+///
 /// Example
 /// StorageValue::Int gets mapped to RequestType::Integer
 pub fn storagevalue_to_string(value: &StorageValue) -> String {
-    let delim = "\r\n";
-    match value {
-        StorageValue::Int(i) => format!(":{}\r\n", i),
-
-        StorageValue::Bool(b) => {
-            if *b {
-                "#t\r\n".to_string()
-            } else {
-                "#f\r\n".to_string()
-            }
-        }
-
-        StorageValue::Text(_) | StorageValue::Bytes(_) => {
-            let mut v = String::new();
-            v.push('$');
-            v.push_str(&value.to_string().len().to_string());
-            v.push_str(delim);
-            v.push_str(&value.to_string());
-            v.push_str(delim);
-
-            v
-        }
-
-        // ,[<+|->]<integral>[.<fractional>][<E|e>[sign]<exponent>]\r\n
-        StorageValue::Float(d) => {
-            let mut v = String::new();
-            v.push(',');
-            v.push_str(&d.to_string());
-            v.push_str(delim);
-
-            v
-        }
-
-        StorageValue::Null => "_\r\n".to_string(),
-
-        StorageValue::List(storage_values) => {
-            let mut outer = String::new();
-            outer.push('*');
-            outer.push_str(&storage_values.len().to_string());
-            outer.push_str(delim);
-
-            for inner in storage_values {
-                let val = storagevalue_to_string(inner);
-                outer.push_str(&val);
-            }
-
-            outer
-        }
-        StorageValue::Map(items) => {
-            let mut outer = String::new();
-            outer.push('%');
-            outer.push_str(&items.len().to_string());
-            outer.push_str(delim);
-
-            for (key, value) in items {
-                let mut k = String::new();
-                k.push('$');
-                k.push_str(&key.to_string().len().to_string());
-                k.push_str(delim);
-                k.push_str(&key.to_string());
-                k.push_str(delim);
-
-                let val = storagevalue_to_string(value);
-
-                outer.push_str(key);
-                outer.push_str(&val);
-            }
-
-            outer
-        }
-    }
+    String::from_utf8_lossy(&RespValue::from(value).encode()).to_string()
 }
 
 /// Handles the transformation of the result from `Storage::get_entries()` to
-/// a nested array `RequestType` structure.
+/// a nested array `RespValue` structure, or a null bulk string if there are
+/// no entries.
 /// Format:
 ///     [[key, value|null], [key, value|null]]
-/// The result is a vec of bytes of the string representation.
 #[macro_export]
 macro_rules! batch_getlist_entries {
-    ($data:expr) => {
-        {
-            if $data.is_empty() {
-                return null!();
-            }
-
-            // *<number-of-elements>\r\n<element-1>...<element-n>
-            let mut outer_vec = String::new();
-            outer_vec.push('*');
-            outer_vec.push_str(&$data.len().to_string());
-            let delim = "\r\n";
-            outer_vec.push_str(delim);
-
-            for (key, value) in $data {
-                let key = {
-                    let mut k = String::new();
-                    k.push('$');
-                    k.push_str(&key.len().to_string());
-                    k.push_str(delim);
-                    k.push_str(key);
-                    k.push_str(delim);
-
-                    k
-                };
-
-                let value = {
-                    match value {
-                        None => "$-1\r\n".to_string(),
-                        Some(v) => $crate::storagevalue_to_string(&v.value),
-                    }
-                };
-
-                let mut inner_vec = String::new();
-                inner_vec.push('*');
-                inner_vec.push_str(&2.to_string());
-                inner_vec.push_str(delim);
-                inner_vec.push_str(&key);
-                inner_vec.push_str(&value);
-
-                outer_vec.push_str(&inner_vec);
-            }
-            outer_vec.as_bytes().to_vec()
+    ($data:expr) => {{
+        if $data.is_empty() {
+            return $crate::null!();
         }
-    };
+
+        $crate::RespValue::Array(
+            $data
+                .iter()
+                .map(|(key, value)| {
+                    let value = match value {
+                        None => $crate::null!(),
+                        Some(v) => $crate::RespValue::from(&v.value),
+                    };
+                    $crate::RespValue::Array(vec![$crate::RespValue::bulk(key), value])
+                })
+                .collect(),
+        )
+    }};
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -351,6 +499,19 @@ pub enum RequestType<'re> {
     Set {
         children: HashSet<&'re [u8]>,
     },
+    /// Out-of-band message
+    Push {
+        children: Vec<RequestType<'re>>,
+    },
+    /// Attributes describing the value that follows them
+    Attribute {
+        attributes: HashMap<String, RequestType<'re>>,
+        value: Box<RequestType<'re>>,
+    },
+    /// Bulk string sent in chunks, in order
+    StreamedString {
+        chunks: Vec<&'re [u8]>,
+    },
 }
 
 /// Identifies different RequestTypes based on their first byte.
@@ -369,6 +530,8 @@ pub enum DataType {
     VerbatimString,
     Maps,
     Sets,
+    Push,
+    Attribute,
     Unknown,
 }
 
@@ -388,6 +551,8 @@ impl std::fmt::Display for DataType {
             DataType::VerbatimString => write!(f, "VerbatimString"),
             DataType::Maps => write!(f, "Maps"),
             DataType::Sets => write!(f, "Sets"),
+            DataType::Push => write!(f, "Push"),
+            DataType::Attribute => write!(f, "Attribute"),
             DataType::Unknown => write!(f, "Unknown"),
         }
     }
@@ -410,10 +575,17 @@ fn get_data_type(byte: u8) -> DataType {
         b'=' => DataType::VerbatimString,
         b'%' => DataType::Maps,
         b'~' => DataType::Sets,
+        b'>' => DataType::Push,
+        b'|' => DataType::Attribute,
         _ => DataType::Unknown,
     }
 }
 
+/// Checks for the CRLF terminator at `i`, false when the data ends before.
+fn crlf_at(data: &[u8], i: usize) -> bool {
+    data.get(i..i + 2) == Some(b"\r\n".as_slice())
+}
+
 /// Parse a series of bytes into `RequestType::SimpleString`
 /// The format of simplestring bytes representation is:
 ///     +<data>\r\n
@@ -581,6 +753,11 @@ fn parse_bulk_strings<'re>(
     }
     i += 1;
 
+    // $?\r\n starts a string sent in chunks
+    if data.get(i) == Some(&b'?') {
+        return parse_streamed_strings(data, byte_offset);
+    }
+
     let mut length = Vec::new();
     while i < data.len() && data[i] != b'\r' {
         length.push(data[i]);
@@ -657,6 +834,10 @@ fn parse_arrays<'re>(
     }
     i += 1;
 
+    if data.get(i) == Some(&b'?') {
+        return parse_streamed_aggregates(data, byte_offset);
+    }
+
     let mut length = Vec::new();
     while i < data.len() && data[i] != b'\r' {
         length.push(data[i]);
@@ -695,7 +876,8 @@ fn parse_arrays<'re>(
     // Parse each element in the array
     // Make a helper function to do the handler assignments
     for _ in 0..length {
-        let (content, consumed) = match_parser_against_datatype(&data[i..], byte_offset)?;
+        let rest = remaining(data, i, byte_offset)?;
+        let (content, consumed) = match_parser_against_datatype(rest, byte_offset)?;
         elements.push(content);
         i += consumed;
     }
@@ -723,7 +905,7 @@ fn parse_null<'re>(
     }
     i += 1;
 
-    if data[i] == b'\r' && data[i + 1] == b'\n' {
+    if crlf_at(data, i) {
         *byte_offset += 2;
         return Ok((RequestType::Null, 2));
     }
@@ -752,14 +934,18 @@ fn parse_booleans<'re>(
     }
     i += 1;
 
+    if data.len() < 4 {
+        return parser_error!("Invalid boolean format", *byte_offset);
+    }
+
     if (data[i] == b't') && (data[i + 1] == b'\r' && data[i + 2] == b'\n') {
-        *byte_offset += 3;
-        return Ok((RequestType::Boolean { data: true }, 3));
+        *byte_offset += 4;
+        return Ok((RequestType::Boolean { data: true }, 4));
     }
 
     if (data[i] == b'f') && (data[i + 1] == b'\r' && data[i + 2] == b'\n') {
-        *byte_offset += 3;
-        return Ok((RequestType::Boolean { data: false }, 3));
+        *byte_offset += 4;
+        return Ok((RequestType::Boolean { data: false }, 4));
     }
 
     parser_error!("Invalid boolean format", *byte_offset)
@@ -803,20 +989,17 @@ fn parse_doubles<'re>(
     *byte_offset += 1;
 
     let start = i;
-    match data[i] {
-        b'+' | b'-' => {
-            i += 1;
-            *byte_offset += 1;
-        }
-        _ => {}
+    if let Some(b'+' | b'-') = data.get(i) {
+        i += 1;
+        *byte_offset += 1;
     }
 
     // ,inf\r\n
-    if data[i] == b'i' && data[i + 1] == b'n' && data[i + 2] == b'f' {
+    if data[i..].starts_with(b"inf") {
         *byte_offset += 3;
         i += 3;
         let end = i;
-        if data[i] == b'\r' && data[i + 1] == b'\n' {
+        if crlf_at(data, i) {
             *byte_offset += 2;
             i += 2;
             return Ok((
@@ -831,11 +1014,11 @@ fn parse_doubles<'re>(
     }
 
     // ,nan\r\n
-    if data[i] == b'n' && data[i + 1] == b'a' && data[i + 2] == b'n' {
+    if data[i..].starts_with(b"nan") {
         *byte_offset += 3;
         i += 3;
         let end = i;
-        if data[i] == b'\r' && data[i + 1] == b'\n' {
+        if crlf_at(data, i) {
             *byte_offset += 2;
             i += 2;
             return Ok((
@@ -854,8 +1037,8 @@ fn parse_doubles<'re>(
         *byte_offset += 1;
     }
 
-    let dot = match data[i] {
-        b'.' => {
+    let dot = match data.get(i) {
+        Some(b'.') => {
             i += 1;
             *byte_offset += 1;
             Some(b'.')
@@ -864,7 +1047,7 @@ fn parse_doubles<'re>(
     };
 
     if dot.is_none() {
-        if data[i] == b'\r' && data[i + 1] == b'\n' {
+        if crlf_at(data, i) {
             let end = i;
             i += 2;
             *byte_offset += 2;
@@ -885,7 +1068,7 @@ fn parse_doubles<'re>(
     }
 
     let end = i;
-    if data[i] == b'\r' && data[i + 1] == b'\n' {
+    if crlf_at(data, i) {
         *byte_offset += 2;
         i += 2;
     } else {
@@ -937,7 +1120,7 @@ fn parse_big_numbers<'re>(
     }
 
     let end = i;
-    if data[i] != b'\r' && data[i + 1] != b'\n' {
+    if !crlf_at(data, i) {
         return parser_error!("Unterminated big number", *byte_offset);
     }
     i += 2;
@@ -1120,13 +1303,15 @@ fn parse_verbatim_strings<'re>(
     *byte_offset += 1;
 
     // why minus 4? Encoding + colon
-    let v_string = &data[i..i + s_len - 4];
+    let Some(v_string) = s_len.checked_sub(4).and_then(|len| data.get(i..i + len)) else {
+        return parser_error!("Data length mismatch actual length", *byte_offset);
+    };
 
-    let diff = s_len - 4;
+    let diff = v_string.len();
     i += diff;
     *byte_offset += diff;
 
-    if data[i] != b'\r' || data[i + 1] != b'\n' {
+    if !crlf_at(data, i) {
         return parser_error!("No proper termination", *byte_offset);
     }
     i += 2;
@@ -1165,6 +1350,10 @@ fn parse_maps<'re>(
     }
     i += 1;
 
+    if data.get(i) == Some(&b'?') {
+        return parse_streamed_aggregates(data, byte_offset);
+    }
+
     let mut len_bytes = Vec::new();
     while i < data.len() && data[i].is_ascii_alphanumeric() {
         len_bytes.push(data[i]);
@@ -1207,13 +1396,7 @@ fn parse_maps<'re>(
         let (value, consumed) = match_parser_against_datatype(&data[i..], byte_offset)?;
         i += consumed;
 
-        let key = match key {
-            RequestType::BulkString { data } => String::from_utf8_lossy(data).to_string(),
-            RequestType::SimpleString { data } => String::from_utf8_lossy(data).to_string(),
-            _ => return parser_error!("Invalid key type", *byte_offset),
-        };
-
-        entries.insert(key, value);
+        entries.insert(map_key(key, byte_offset)?, value);
         n += 1;
     }
 
@@ -1244,6 +1427,10 @@ fn parse_sets<'re>(
     }
     i += 1;
 
+    if data.get(i) == Some(&b'?') {
+        return parse_streamed_aggregates(data, byte_offset);
+    }
+
     let mut len_bytes = Vec::new();
 
     while i < data.len() && data[i].is_ascii_alphanumeric() {
@@ -1283,15 +1470,8 @@ fn parse_sets<'re>(
 
     let mut n = 0;
     while i < data.len() && n < length {
-        let (entry, consumed) = match_parser_against_datatype(data, byte_offset)?;
-        let entry = match entry {
-            RequestType::BulkString { data } => data,
-            _ => {
-                return parser_error!("Invalid set entry data type", *byte_offset);
-            }
-        };
-
-        entries.insert(entry);
+        let (entry, consumed) = match_parser_against_datatype(&data[i..], byte_offset)?;
+        entries.insert(set_entry(entry, byte_offset)?);
         i += consumed;
         n += 1;
     }
@@ -1299,6 +1479,244 @@ fn parse_sets<'re>(
     Ok((RequestType::Set { children: entries }, i))
 }
 
+/// Converts a parsed map key to a String, keys must be strings.
+fn map_key(key: RequestType, byte_offset: &usize) -> Result<String, crate::Error> {
+    match key {
+        RequestType::BulkString { data } | RequestType::SimpleString { data } => {
+            Ok(String::from_utf8_lossy(data).to_string())
+        }
+        _ => parser_error!("Invalid key type", *byte_offset),
+    }
+}
+
+/// Unwraps a parsed set entry, entries must be bulk strings.
+fn set_entry<'re>(entry: RequestType<'re>, byte_offset: &usize) -> Result<&'re [u8], crate::Error> {
+    match entry {
+        RequestType::BulkString { data } => Ok(data),
+        _ => parser_error!("Invalid set entry data type", *byte_offset),
+    }
+}
+
+/// Parses the `<number>\r\n` header that follows the type byte of an
+/// aggregate.
+///
+/// # Returns
+/// The number and the bytes consumed including the type byte
+fn parse_aggregate_length(
+    data: &[u8],
+    byte_offset: &mut usize,
+) -> Result<(usize, usize), crate::Error> {
+    let mut i = 1;
+    while i < data.len() && data[i].is_ascii_digit() {
+        i += 1;
+    }
+    let length = String::from_utf8_lossy(&data[1..i]).parse::<usize>();
+    *byte_offset += i;
+
+    if i + 1 >= data.len() || data[i] != b'\r' || data[i + 1] != b'\n' {
+        return parser_error!("No proper termination", *byte_offset);
+    }
+    let Ok(length) = length else {
+        return parser_error!(
+            "Failed to parse the `length` field from ascii",
+            *byte_offset
+        );
+    };
+
+    *byte_offset += 2;
+    Ok((length, i + 2))
+}
+
+/// Returns the bytes of a frame from `i` on, an error if the frame was
+/// truncated before.
+fn remaining<'re>(
+    data: &'re [u8],
+    i: usize,
+    byte_offset: &usize,
+) -> Result<&'re [u8], crate::Error> {
+    match data.get(i..) {
+        Some(rest) => Ok(rest),
+        None => parser_error!("Unexpected end of data!", *byte_offset),
+    }
+}
+
+/// Parse a series of bytes into `RequestType::Push`
+/// The format of push bytes representation is:
+///     ><number-of-elements>\r\n<element-1>...<element-n>
+///
+/// A greater-than sign (>) as the first byte.
+/// One or more decimal digits (0..9) as the number of elements.
+/// The CRLF terminator.
+/// An additional RESP type for every element of the push.
+fn parse_pushes<'re>(
+    data: &'re [u8],
+    byte_offset: &mut usize,
+) -> Result<(RequestType<'re>, usize), crate::Error> {
+    let (length, mut i) = parse_aggregate_length(data, byte_offset)?;
+
+    // Every element takes at least one byte, whatever count the client sent
+    let mut children = Vec::with_capacity(length.min(data.len()));
+    for _ in 0..length {
+        let rest = remaining(data, i, byte_offset)?;
+        let (child, consumed) = match_parser_against_datatype(rest, byte_offset)?;
+        children.push(child);
+        i += consumed;
+    }
+
+    Ok((RequestType::Push { children }, i))
+}
+
+/// Parse a series of bytes into `RequestType::Attribute`
+/// The format of attribute bytes representation is:
+///     |<number-of-entries>\r\n<key-1><value-1>...<key-n><value-n><value>
+///
+/// A pipe (|) as the first byte.
+/// One or more decimal digits (0..9) as the number of attributes.
+/// The CRLF terminator.
+/// The attributes, in the same form as map entries.
+/// The value the attributes describe, as an additional RESP type.
+fn parse_attributes<'re>(
+    data: &'re [u8],
+    byte_offset: &mut usize,
+) -> Result<(RequestType<'re>, usize), crate::Error> {
+    let (length, mut i) = parse_aggregate_length(data, byte_offset)?;
+
+    let mut attributes = HashMap::with_capacity(length.min(data.len()));
+    for _ in 0..length {
+        let rest = remaining(data, i, byte_offset)?;
+        let (key, consumed) = match_parser_against_datatype(rest, byte_offset)?;
+        i += consumed;
+        let rest = remaining(data, i, byte_offset)?;
+        let (value, consumed) = match_parser_against_datatype(rest, byte_offset)?;
+        i += consumed;
+        attributes.insert(map_key(key, byte_offset)?, value);
+    }
+
+    let rest = remaining(data, i, byte_offset)?;
+    let (value, consumed) = match_parser_against_datatype(rest, byte_offset)?;
+    i += consumed;
+
+    Ok((
+        RequestType::Attribute {
+            attributes,
+            value: Box::new(value),
+        },
+        i,
+    ))
+}
+
+/// Parse a series of bytes into `RequestType::StreamedString`
+/// The format of streamed string bytes representation is:
+///     $?\r\n;<length>\r\n<data>\r\n...;0\r\n
+///
+/// A dollar sign ($) followed by a question mark (?) and the CRLF terminator.
+/// Chunks made of a semicolon (;), their length, the CRLF terminator, the
+/// data and a final CRLF.
+/// A last chunk of length zero, without data.
+fn parse_streamed_strings<'re>(
+    data: &'re [u8],
+    byte_offset: &mut usize,
+) -> Result<(RequestType<'re>, usize), crate::Error> {
+    if !data.starts_with(b"$?\r\n") {
+        return parser_error!("No proper termination", *byte_offset);
+    }
+    let mut i = 4;
+    *byte_offset += 4;
+
+    let mut chunks = Vec::new();
+    loop {
+        if data.get(i) != Some(&b';') {
+            return parser_error!("Expected a string chunk", *byte_offset);
+        }
+        let (length, consumed) = parse_aggregate_length(&data[i..], byte_offset)?;
+        i += consumed;
+        if length == 0 {
+            break;
+        }
+
+        // The length comes from the client and may be anything
+        let Some(end) = i
+            .checked_add(length)
+            .and_then(|end| end.checked_add(2))
+            .filter(|end| *end <= data.len())
+        else {
+            return parser_error!(
+                "Data `length` field and actual data length mismatch",
+                *byte_offset
+            );
+        };
+        if &data[end - 2..end] != b"\r\n" {
+            *byte_offset += length;
+            return parser_error!("No proper termination", *byte_offset);
+        }
+        chunks.push(&data[i..end - 2]);
+        i = end;
+        *byte_offset += length + 2;
+    }
+
+    Ok((RequestType::StreamedString { chunks }, i))
+}
+
+/// Parse a series of bytes of a streamed array, set or map into a
+/// `RequestType::Array`, `RequestType::Set` or `RequestType::Map`
+/// The format of streamed aggregates bytes representation is:
+///     <type>?\r\n<element-1>...<element-n>.\r\n
+///
+/// The type byte of the aggregate (*, ~ or %) followed by a question mark (?)
+/// and the CRLF terminator.
+/// An additional RESP type for every element, or every key and value of a
+/// map.
+/// A final dot (.) and CRLF terminator.
+fn parse_streamed_aggregates<'re>(
+    data: &'re [u8],
+    byte_offset: &mut usize,
+) -> Result<(RequestType<'re>, usize), crate::Error> {
+    if data.len() < 4 || &data[1..4] != b"?\r\n" {
+        return parser_error!("No proper termination", *byte_offset);
+    }
+    let mut i = 4;
+    *byte_offset += 4;
+
+    let mut elements = Vec::new();
+    loop {
+        match data.get(i) {
+            Some(b'.') if data[i..].starts_with(b".\r\n") => break,
+            Some(_) => {
+                let (element, consumed) = match_parser_against_datatype(&data[i..], byte_offset)?;
+                elements.push(element);
+                i += consumed;
+            }
+            None => return parser_error!("Unterminated streamed aggregate", *byte_offset),
+        }
+    }
+    i += 3;
+    *byte_offset += 3;
+
+    let aggregate = match data[0] {
+        b'%' => {
+            if elements.len() % 2 != 0 {
+                return parser_error!("Map entry without a value", *byte_offset);
+            }
+            let mut children = HashMap::with_capacity(elements.len() / 2);
+            let mut elements = elements.into_iter();
+            while let (Some(key), Some(value)) = (elements.next(), elements.next()) {
+                children.insert(map_key(key, byte_offset)?, value);
+            }
+            RequestType::Map { children }
+        }
+        b'~' => {
+            let children = elements
+                .into_iter()
+                .map(|e| set_entry(e, byte_offset))
+                .collect::<Result<_, _>>()?;
+            RequestType::Set { children }
+        }
+        _ => RequestType::Array { children: elements },
+    };
+
+    Ok((aggregate, i))
+}
+
 /// Pass data to the correct parser according the data's first byte which
 /// represents the data type.
 /// On success, Returns a RequestType represented by the data and the total
@@ -1326,6 +1744,8 @@ fn match_parser_against_datatype<'re>(
         DataType::VerbatimString => parse_verbatim_strings(data, current_offset),
         DataType::Maps => parse_maps(data, current_offset),
         DataType::Sets => parse_sets(data, current_offset),
+        DataType::Push => parse_pushes(data, current_offset),
+        DataType::Attribute => parse_attributes(data, current_offset),
         DataType::Unknown => {
             parser_error!("Unknown data type", *current_offset)
         }
@@ -1349,27 +1769,154 @@ pub fn parse_request<'re>(data: &'re [u8]) -> Result<RequestType<'re>, crate::Er
     match_parser_against_datatype(data, &mut current_offset).map(|(content, _consumed)| content)
 }
 
+/// Checks whether a request is an inline command, typed by hand over
+/// telnet or netcat, rather than a RESP frame.
+/// Inline commands are the ones that don't start with a RESP type byte.
+pub fn is_inline(data: &[u8]) -> bool {
+    data.first()
+        .is_some_and(|&b| get_data_type(b) == DataType::Unknown)
+}
+
+/// Splits the inline commands at the start of a buffer into lines, so that
+/// commands pipelined in a single read are run one by one.
+///
+/// # Returns
+/// The complete lines with their line endings, and the rest of the buffer:
+/// a line still being typed, or RESP frames sent after the inline commands
+///
+/// # Example
+/// ```rust
+/// use volatix_core::split_inline;
+///
+/// let (lines, rest) = split_inline(b"SET a 1\r\nGET a\r\nGET");
+/// assert_eq!(lines, vec![&b"SET a 1\r\n"[..], &b"GET a\r\n"[..]]);
+/// assert_eq!(rest, b"GET");
+/// ```
+pub fn split_inline(mut data: &[u8]) -> (Vec<&[u8]>, &[u8]) {
+    let mut lines = Vec::new();
+    while is_inline(data) {
+        let Some(end) = data.iter().position(|&b| b == b'\n') else {
+            break;
+        };
+        let (line, rest) = data.split_at(end + 1);
+        lines.push(line);
+        data = rest;
+    }
+    (lines, data)
+}
+
+/// Splits an inline command into its arguments, e.g `SET key "a b"` into
+/// `SET`, `key` and `a b`. Arguments are separated by whitespace and may be
+/// quoted: double quotes understand the `\n`, `\r`, `\t`, `\b`, `\a`, `\\`,
+/// `\"` and `\xHH` escapes, single quotes only `\'`.
+/// A blank line gives no arguments.
+///
+/// # Example
+/// ```rust
+/// use volatix_core::parse_inline;
+///
+/// let args = parse_inline(b"SET greeting \"hello\\tworld\"\r\n").unwrap();
+/// assert_eq!(args, vec![b"SET".to_vec(), b"greeting".to_vec(), b"hello\tworld".to_vec()]);
+/// ```
+pub fn parse_inline(line: &[u8]) -> Result<Vec<Vec<u8>>, crate::Error> {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+
+    let mut args = Vec::new();
+    let mut i = 0;
+    loop {
+        while i < line.len() && line[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if i == line.len() {
+            return Ok(args);
+        }
+
+        let mut arg = Vec::new();
+        let mut quote: Option<u8> = None;
+        while i < line.len() {
+            let c = line[i];
+            match quote {
+                Some(b'"') if c == b'\\' && i + 1 < line.len() => {
+                    let hex = line
+                        .get(i + 2..i + 4)
+                        .and_then(|h| std::str::from_utf8(h).ok())
+                        .and_then(|h| u8::from_str_radix(h, 16).ok());
+                    match (line[i + 1], hex) {
+                        (b'x', Some(byte)) => {
+                            arg.push(byte);
+                            i += 4;
+                            continue;
+                        }
+                        (b'n', _) => arg.push(b'\n'),
+                        (b'r', _) => arg.push(b'\r'),
+                        (b't', _) => arg.push(b'\t'),
+                        (b'b', _) => arg.push(0x08),
+                        (b'a', _) => arg.push(0x07),
+                        (other, _) => arg.push(other),
+                    }
+                    i += 2;
+                }
+                Some(b'\'') if c == b'\\' && line.get(i + 1) == Some(&b'\'') => {
+                    arg.push(b'\'');
+                    i += 2;
+                }
+                Some(q) if c == q => {
+                    // "a"b would be ambiguous
+                    if line.get(i + 1).is_some_and(|c| !c.is_ascii_whitespace()) {
+                        return parser_error!("Closing quote must be followed by a space", i);
+                    }
+                    quote = None;
+                    i += 1;
+                    break;
+                }
+                Some(_) => {
+                    arg.push(c);
+                    i += 1;
+                }
+                None if c.is_ascii_whitespace() => break,
+                None if c == b'"' || c == b'\'' => {
+                    quote = Some(c);
+                    i += 1;
+                }
+                None => {
+                    arg.push(c);
+                    i += 1;
+                }
+            }
+        }
+        if quote.is_some() {
+            return parser_error!("Unbalanced quotes in inline command", i);
+        }
+        args.push(arg);
+    }
+}
+
 #[cfg(test)]
 mod resp3_tests {
-    use std::collections::HashMap;
+    use std::collections::{HashMap, HashSet};
 
     use crate::{
-        DataEncoding,
-        resp3::{RequestType, parse_request},
+        DataEncoding, StorageValue,
+        resp3::{
+            RequestType, RespValue, is_inline, parse_inline, parse_request, split_inline,
+            storagevalue_to_string,
+        },
     };
 
     use super::{
         parse_big_numbers, parse_booleans, parse_bulk_errors, parse_doubles, parse_maps,
-        parse_verbatim_strings,
+        parse_streamed_strings, parse_verbatim_strings,
     };
 
     #[test]
     fn test_booleans() {
         let n = b"#t\r\n";
         let mut offset = 0;
-        let (data, _) = parse_booleans(n, &mut offset).unwrap();
+        let (data, consumed) = parse_booleans(n, &mut offset).unwrap();
 
         assert_eq!(data, RequestType::Boolean { data: true });
+        assert_eq!(consumed, n.len());
 
         let n = b"#f\r\n";
         offset = 0;
@@ -1384,36 +1931,21 @@ mod resp3_tests {
         let (data, consumed) = parse_doubles(n, &mut offset).unwrap();
 
         assert!(consumed > 0);
-        assert_eq!(
-            data,
-            RequestType::Double {
-                data: &[b'1', b'.', b'2', b'3']
-            }
-        );
+        assert_eq!(data, RequestType::Double { data: b"1.23" });
 
         let n = b",+1.23\r\n";
         offset = 0;
         let (data, consumed) = parse_doubles(n, &mut offset).unwrap();
 
         assert!(consumed > 0);
-        assert_eq!(
-            data,
-            RequestType::Double {
-                data: &[b'+', b'1', b'.', b'2', b'3']
-            }
-        );
+        assert_eq!(data, RequestType::Double { data: b"+1.23" });
 
         let n = b",-1.23\r\n";
         offset = 0;
         let (data, consumed) = parse_doubles(n, &mut offset).unwrap();
 
         assert!(consumed > 0);
-        assert_eq!(
-            data,
-            RequestType::Double {
-                data: &[b'-', b'1', b'.', b'2', b'3']
-            }
-        );
+        assert_eq!(data, RequestType::Double { data: b"-1.23" });
     }
 
     #[test]
@@ -1422,12 +1954,7 @@ mod resp3_tests {
         let mut offset = 0;
         let (data, _) = parse_doubles(n, &mut offset).unwrap();
 
-        assert_eq!(
-            data,
-            RequestType::Double {
-                data: &[b'1', b'0']
-            }
-        );
+        assert_eq!(data, RequestType::Double { data: b"10" });
     }
 
     #[test]
@@ -1436,12 +1963,7 @@ mod resp3_tests {
         let mut offset = 0;
         let (data, _) = parse_doubles(n, &mut offset).unwrap();
 
-        assert_eq!(
-            data,
-            RequestType::Double {
-                data: &[b'-', b'i', b'n', b'f']
-            }
-        );
+        assert_eq!(data, RequestType::Double { data: b"-inf" });
     }
 
     #[test]
@@ -1451,12 +1973,7 @@ mod resp3_tests {
         let mut offset = 0;
         let (data, _) = parse_doubles(n, &mut offset).unwrap();
 
-        assert_eq!(
-            data,
-            RequestType::Double {
-                data: &[b'n', b'a', b'n']
-            }
-        );
+        assert_eq!(data, RequestType::Double { data: b"nan" });
     }
 
     #[test]
@@ -1522,47 +2039,349 @@ mod resp3_tests {
         assert_eq!(consumed, l);
     }
 
+    #[test]
+    fn test_encoded_map_round_trip() {
+        let items = vec![
+            ("name".to_string(), StorageValue::Text("Ada".to_string())),
+            ("visits".to_string(), StorageValue::Int(3)),
+            ("ratio".to_string(), StorageValue::Float(0.5)),
+        ];
+        let encoded = storagevalue_to_string(&StorageValue::Map(items.clone()));
+        assert_eq!(encoded.as_bytes(), crate::map!(&items).encode());
+
+        let expected = HashMap::from([
+            ("name".to_string(), RequestType::BulkString { data: b"Ada" }),
+            ("visits".to_string(), RequestType::Integer { data: b"3" }),
+            ("ratio".to_string(), RequestType::Double { data: b"0.5" }),
+        ]);
+        let parsed = parse_request(encoded.as_bytes()).unwrap();
+        assert_eq!(parsed, RequestType::Map { children: expected });
+    }
+
+    #[test]
+    fn test_encode_binary_bulk_string() {
+        let bytes = vec![0xff, 0x00, b'\r', b'\n', 0x80];
+        let encoded = RespValue::from(StorageValue::Bytes(bytes.clone())).encode();
+        assert_eq!(encoded, b"$5\r\n\xff\x00\r\n\x80\r\n".to_vec());
+
+        let parsed = parse_request(&encoded).unwrap();
+        assert_eq!(parsed, RequestType::BulkString { data: &bytes });
+    }
+
+    #[test]
+    fn test_encode_nested_aggregates() {
+        let value = RespValue::Array(vec![
+            RespValue::Map(vec![(
+                RespValue::bulk("scores"),
+                RespValue::Array(vec![RespValue::Double(1.5), RespValue::Integer(-2)]),
+            )]),
+            RespValue::Set(vec![RespValue::bulk("a")]),
+            RespValue::Null,
+            RespValue::NullBulkString,
+            RespValue::Boolean(true),
+        ]);
+        assert_eq!(
+            value.encode(),
+            b"*5\r\n%1\r\n$6\r\nscores\r\n*2\r\n,1.5\r\n:-2\r\n~1\r\n$1\r\na\r\n_\r\n$-1\r\n#t\r\n"
+                .to_vec()
+        );
+
+        let mut written = Vec::new();
+        value.write_to(&mut written).unwrap();
+        assert_eq!(written, value.encode());
+    }
+
+    #[test]
+    fn test_encode_other_types() {
+        let cases = [
+            (RespValue::SimpleString("OK".to_string()), &b"+OK\r\n"[..]),
+            (RespValue::SimpleError("ERR".to_string()), b"-ERR\r\n"),
+            (RespValue::error("no such key"), b"!11\r\nno such key\r\n"),
+            (
+                RespValue::BigNumber("12345678901234567890".to_string()),
+                b"(12345678901234567890\r\n",
+            ),
+            (RespValue::Double(f64::INFINITY), b",inf\r\n"),
+            (RespValue::Double(f64::NEG_INFINITY), b",-inf\r\n"),
+            (RespValue::Double(f64::NAN), b",nan\r\n"),
+            (
+                RespValue::VerbatimString {
+                    encoding: "txt".to_string(),
+                    data: b"Some string".to_vec(),
+                },
+                b"=15\r\ntxt:Some string\r\n",
+            ),
+            (
+                RespValue::Push(vec![RespValue::bulk("message"), RespValue::Integer(1)]),
+                b">2\r\n$7\r\nmessage\r\n:1\r\n",
+            ),
+            (
+                RespValue::Attribute {
+                    attributes: vec![(RespValue::bulk("ttl"), RespValue::Integer(3))],
+                    value: Box::new(RespValue::bulk("v")),
+                },
+                b"|1\r\n$3\r\nttl\r\n:3\r\n$1\r\nv\r\n",
+            ),
+        ];
+
+        for (value, expected) in cases {
+            assert_eq!(value.encode(), expected.to_vec(), "{value:?}");
+        }
+    }
+
+    #[test]
+    fn test_inline_commands() {
+        assert!(is_inline(b"GET foo\r\n"));
+        assert!(!is_inline(b"*1\r\n$4\r\nPING\r\n"));
+        assert!(!is_inline(b""));
+
+        let args = parse_inline(b"  SET   key  value\n").unwrap();
+        assert_eq!(
+            args,
+            vec![b"SET".to_vec(), b"key".to_vec(), b"value".to_vec()]
+        );
+
+        let args = parse_inline(b"SET \"a \\\"b\\\"\\x41\\n\" 'it\\'s' \"\"\r\n").unwrap();
+        assert_eq!(
+            args,
+            vec![
+                b"SET".to_vec(),
+                b"a \"b\"A\n".to_vec(),
+                b"it's".to_vec(),
+                Vec::new()
+            ]
+        );
+
+        assert!(parse_inline(b"\r\n").unwrap().is_empty());
+        assert!(parse_inline(b"SET \"unbalanced").is_err());
+        assert!(parse_inline(b"SET \"a\"b").is_err());
+    }
+
+    #[test]
+    fn test_split_inline() {
+        // Two pipelined lines, then a partial one
+        let (lines, rest) = split_inline(b"SET a 1\r\nGET a\nGET");
+        assert_eq!(lines, vec![&b"SET a 1\r\n"[..], &b"GET a\n"[..]]);
+        assert_eq!(rest, b"GET");
+
+        // RESP frames after inline commands are left as they are
+        let (lines, rest) = split_inline(b"PING\r\n*1\r\n$4\r\nPING\r\n");
+        assert_eq!(lines, vec![&b"PING\r\n"[..]]);
+        assert_eq!(rest, b"*1\r\n$4\r\nPING\r\n");
+
+        let (lines, rest) = split_inline(b"*1\r\n$4\r\nPING\r\n");
+        assert!(lines.is_empty());
+        assert_eq!(rest, b"*1\r\n$4\r\nPING\r\n");
+    }
+
+    #[test]
+    fn test_into_resp2() {
+        let cases = [
+            (RespValue::Null, &b"$-1\r\n"[..]),
+            (RespValue::Boolean(false), b":0\r\n"),
+            (RespValue::Double(1.5), b"$3\r\n1.5\r\n"),
+            (RespValue::error("bad\r\nvalue"), b"-bad  value\r\n"),
+            (
+                RespValue::VerbatimString {
+                    encoding: "txt".to_string(),
+                    data: b"text".to_vec(),
+                },
+                b"$4\r\ntext\r\n",
+            ),
+            (
+                RespValue::Map(vec![(
+                    RespValue::bulk("tags"),
+                    RespValue::Set(vec![RespValue::bulk("a"), RespValue::Null]),
+                )]),
+                b"*2\r\n$4\r\ntags\r\n*2\r\n$1\r\na\r\n$-1\r\n",
+            ),
+            (
+                RespValue::Attribute {
+                    attributes: vec![(RespValue::bulk("ttl"), RespValue::Integer(3))],
+                    value: Box::new(RespValue::Boolean(true)),
+                },
+                b":1\r\n",
+            ),
+            (RespValue::bulk("same"), b"$4\r\nsame\r\n"),
+        ];
+
+        for (value, expected) in cases {
+            assert_eq!(
+                value.clone().into_resp2().encode(),
+                expected.to_vec(),
+                "{value:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_push_round_trip() {
+        let push = RespValue::Push(vec![
+            RespValue::bulk("message"),
+            RespValue::bulk("news"),
+            RespValue::Integer(1),
+        ]);
+        let encoded = push.encode();
+        assert_eq!(
+            parse_request(&encoded).unwrap(),
+            RequestType::Push {
+                children: vec![
+                    RequestType::BulkString { data: b"message" },
+                    RequestType::BulkString { data: b"news" },
+                    RequestType::Integer { data: b"1" },
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn test_attribute_round_trip() {
+        let value = RespValue::Attribute {
+            attributes: vec![(RespValue::bulk("ttl"), RespValue::Integer(3))],
+            value: Box::new(RespValue::Array(vec![RespValue::bulk("a")])),
+        };
+        let encoded = value.encode();
+        assert_eq!(
+            parse_request(&encoded).unwrap(),
+            RequestType::Attribute {
+                attributes: HashMap::from([(
+                    "ttl".to_string(),
+                    RequestType::Integer { data: b"3" }
+                )]),
+                value: Box::new(RequestType::Array {
+                    children: vec![RequestType::BulkString { data: b"a" }]
+                }),
+            }
+        );
+
+        // Attributes inside an aggregate apply to the element that follows
+        let nested = RespValue::Array(vec![value, RespValue::Boolean(true)]).encode();
+        let RequestType::Array { children } = parse_request(&nested).unwrap() else {
+            panic!("expected an array");
+        };
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[1], RequestType::Boolean { data: true });
+    }
+
+    #[test]
+    fn test_truncated_push_and_attribute() {
+        assert!(parse_request(b">2\r\n$1\r\na\r\n").is_err());
+        assert!(parse_request(b">2\r\n$1\r\na").is_err());
+        assert!(parse_request(b"|1\r\n+ttl\r\n").is_err());
+        assert!(parse_request(b"|1\r\n+ttl\r\n:3\r\n").is_err());
+        assert!(parse_request(b"|1\r\n+ttl\r\n:3").is_err());
+
+        // Elements cut short inside a push
+        for data in [
+            &b">1\r\n=7\r\ntxt:ab"[..],
+            b">2\r\n*1",
+            b">2\r\n*1\r\n$1\r\na\r\n(12",
+            b">2\r\n%1\r\n+a\r\n,1.5",
+            b">3\r\n#t\r\n_",
+        ] {
+            assert!(parse_request(data).is_err());
+        }
+
+        // Counts far beyond the frame must not be allocated up front
+        assert!(parse_request(b">99999999999999\r\n").is_err());
+        assert!(parse_request(b"|99999999999999\r\n").is_err());
+    }
+
+    #[test]
+    fn test_set_round_trip() {
+        let set = RespValue::Set(vec![RespValue::bulk("a"), RespValue::bulk("b")]);
+        assert_eq!(
+            parse_request(&set.encode()).unwrap(),
+            RequestType::Set {
+                children: HashSet::from([&b"a"[..], &b"b"[..]])
+            }
+        );
+    }
+
+    #[test]
+    fn test_streamed_strings() {
+        let data = b"$?\r\n;4\r\nHell\r\n;5\r\no wor\r\n;1\r\nd\r\n;0\r\n";
+        let (parsed, consumed) = parse_streamed_strings(data, &mut 0).unwrap();
+        assert_eq!(consumed, data.len());
+        assert_eq!(
+            parsed,
+            RequestType::StreamedString {
+                chunks: vec![b"Hell", b"o wor", b"d"]
+            }
+        );
+
+        // Streamed strings nest like any other value
+        let data = b"*2\r\n$?\r\n;2\r\nab\r\n;0\r\n:1\r\n";
+        assert_eq!(
+            parse_request(data).unwrap(),
+            RequestType::Array {
+                children: vec![
+                    RequestType::StreamedString {
+                        chunks: vec![b"ab"]
+                    },
+                    RequestType::Integer { data: b"1" },
+                ]
+            }
+        );
+
+        assert!(parse_request(b"$?\r\n;4\r\nHell\r\n").is_err());
+        assert!(parse_request(b"$?\r\n;9\r\nHell\r\n;0\r\n").is_err());
+        assert!(parse_request(b"$?\r\n4\r\nHell\r\n;0\r\n").is_err());
+        assert!(parse_request(b"$?\r\n;18446744073709551615\r\nab\r\n;0\r\n").is_err());
+    }
+
+    #[test]
+    fn test_streamed_aggregates() {
+        let cases: [(&[u8], &[u8]); 4] = [
+            (b"*?\r\n:1\r\n$1\r\na\r\n.\r\n", b"*2\r\n:1\r\n$1\r\na\r\n"),
+            (
+                b"%?\r\n+a\r\n:1\r\n$1\r\nb\r\n#f\r\n.\r\n",
+                b"%2\r\n+a\r\n:1\r\n$1\r\nb\r\n#f\r\n",
+            ),
+            (
+                b"~?\r\n$1\r\na\r\n$1\r\nb\r\n.\r\n",
+                b"~2\r\n$1\r\na\r\n$1\r\nb\r\n",
+            ),
+            (b"*?\r\n*?\r\n:1\r\n.\r\n.\r\n", b"*1\r\n*1\r\n:1\r\n"),
+        ];
+        for (streamed, sized) in cases {
+            assert_eq!(
+                parse_request(streamed).unwrap(),
+                parse_request(sized).unwrap()
+            );
+        }
+
+        assert!(parse_request(b"*?\r\n:1\r\n").is_err());
+        assert!(parse_request(b"%?\r\n+a\r\n.\r\n").is_err());
+        assert!(parse_request(b"~?\r\n:1\r\n.\r\n").is_err());
+    }
+
     #[test]
     fn t_parse_string() {
         let s = b"+OK\r\n";
         let result = parse_request(s).unwrap();
-        assert_eq!(
-            result,
-            RequestType::SimpleString {
-                data: &[b'O', b'K'],
-            }
-        )
+        assert_eq!(result, RequestType::SimpleString { data: b"OK" })
     }
 
     #[test]
     fn t_parse_errors() {
         let s = b"-Error\r\n";
         let result = parse_request(s).unwrap();
-        assert_eq!(
-            result,
-            RequestType::SimpleError {
-                data: &[b'E', b'r', b'r', b'o', b'r'],
-            }
-        )
+        assert_eq!(result, RequestType::SimpleError { data: b"Error" })
     }
 
     #[test]
     fn t_parse_simple_integer() {
         let s = b":0\r\n";
         let result = parse_request(s).unwrap();
-        assert_eq!(result, RequestType::Integer { data: &[b'0'] })
+        assert_eq!(result, RequestType::Integer { data: b"0" })
     }
 
     #[test]
     fn t_parse_large_integer() {
         let s = b":245670\r\n";
         let result = parse_request(s).unwrap();
-        assert_eq!(
-            result,
-            RequestType::Integer {
-                data: &[b'2', b'4', b'5', b'6', b'7', b'0'],
-            }
-        )
+        assert_eq!(result, RequestType::Integer { data: b"245670" })
     }
 
     #[test]
@@ -1570,12 +2389,7 @@ mod resp3_tests {
         let s = b":-245670\r\n";
         let result = parse_request(s).unwrap();
 
-        assert_eq!(
-            result,
-            RequestType::Integer {
-                data: &[b'-', b'2', b'4', b'5', b'6', b'7', b'0'],
-            }
-        )
+        assert_eq!(result, RequestType::Integer { data: b"-245670" })
     }
 
     #[test]
@@ -1596,12 +2410,7 @@ mod resp3_tests {
     fn t_parse_bstring() {
         let s = b"$5\r\nhello\r\n";
         let result = parse_request(s).unwrap();
-        assert_eq!(
-            result,
-            RequestType::BulkString {
-                data: &[b'h', b'e', b'l', b'l', b'o'],
-            }
-        );
+        assert_eq!(result, RequestType::BulkString { data: b"hello" });
     }
 
     #[test]
@@ -1619,12 +2428,8 @@ mod resp3_tests {
             result,
             RequestType::Array {
                 children: vec![
-                    RequestType::BulkString {
-                        data: &[b'h', b'e', b'l', b'l', b'o'],
-                    },
-                    RequestType::BulkString {
-                        data: &[b'w', b'o', b'r', b'l', b'd'],
-                    }
+                    RequestType::BulkString { data: b"hello" },
+                    RequestType::BulkString { data: b"world" }
                 ]
             }
         );
@@ -1639,9 +2444,9 @@ mod resp3_tests {
             result,
             RequestType::Array {
                 children: vec![
-                    RequestType::Integer { data: &[b'1'] },
-                    RequestType::Integer { data: &[b'2'] },
-                    RequestType::Integer { data: &[b'3'] }
+                    RequestType::Integer { data: b"1" },
+                    RequestType::Integer { data: b"2" },
+                    RequestType::Integer { data: b"3" }
                 ]
             }
         );
@@ -1653,13 +2458,9 @@ mod resp3_tests {
             result,
             RequestType::Array {
                 children: vec![
-                    RequestType::Integer {
-                        data: &[b'-', b'1']
-                    },
-                    RequestType::Integer {
-                        data: &[b'+', b'2']
-                    },
-                    RequestType::Integer { data: &[b'3'] }
+                    RequestType::Integer { data: b"-1" },
+                    RequestType::Integer { data: b"+2" },
+                    RequestType::Integer { data: b"3" }
                 ]
             }
         );
@@ -1674,13 +2475,9 @@ mod resp3_tests {
             result,
             RequestType::Array {
                 children: vec![
-                    RequestType::Integer {
-                        data: &[b'-', b'1'],
-                    },
-                    RequestType::Integer { data: &[b'2'] },
-                    RequestType::Integer {
-                        data: &[b'3', b'0', b'0'],
-                    }
+                    RequestType::Integer { data: b"-1" },
+                    RequestType::Integer { data: b"2" },
+                    RequestType::Integer { data: b"300" }
                 ]
             }
         );
@@ -1694,13 +2491,11 @@ mod resp3_tests {
             result,
             RequestType::Array {
                 children: vec![
-                    RequestType::Integer { data: &[b'1'] },
-                    RequestType::Integer { data: &[b'2'] },
-                    RequestType::Integer { data: &[b'3'] },
-                    RequestType::Integer { data: &[b'4'] },
-                    RequestType::BulkString {
-                        data: &[b'h', b'e', b'l', b'l', b'o'],
-                    }
+                    RequestType::Integer { data: b"1" },
+                    RequestType::Integer { data: b"2" },
+                    RequestType::Integer { data: b"3" },
+                    RequestType::Integer { data: b"4" },
+                    RequestType::BulkString { data: b"hello" }
                 ]
             }
         );
@@ -1714,21 +2509,17 @@ mod resp3_tests {
         // First nested array: [1, 2, 3]
         let nested1 = RequestType::Array {
             children: vec![
-                RequestType::Integer { data: &[b'1'] },
-                RequestType::Integer { data: &[b'2'] },
-                RequestType::Integer { data: &[b'3'] },
+                RequestType::Integer { data: b"1" },
+                RequestType::Integer { data: b"2" },
+                RequestType::Integer { data: b"3" },
             ],
         };
 
         // Second nested array: ["Hello", "World"]
         let nested2 = RequestType::Array {
             children: vec![
-                RequestType::SimpleString {
-                    data: &[b'H', b'e', b'l', b'l', b'o'],
-                },
-                RequestType::SimpleError {
-                    data: &[b'W', b'o', b'r', b'l', b'd'],
-                },
+                RequestType::SimpleString { data: b"Hello" },
+                RequestType::SimpleError { data: b"World" },
             ],
         };
 
@@ -1748,16 +2539,16 @@ mod resp3_tests {
         // First nested array: [1, 2]
         let nested1 = RequestType::Array {
             children: vec![
-                RequestType::Integer { data: &[b'1'] },
-                RequestType::Integer { data: &[b'2'] },
+                RequestType::Integer { data: b"1" },
+                RequestType::Integer { data: b"2" },
             ],
         };
 
         // Second nested array: [3, 4]
         let nested2 = RequestType::Array {
             children: vec![
-                RequestType::Integer { data: &[b'3'] },
-                RequestType::Integer { data: &[b'4'] },
+                RequestType::Integer { data: b"3" },
+                RequestType::Integer { data: b"4" },
             ],
         };
 
@@ -1777,18 +2568,16 @@ mod resp3_tests {
         // First nested array: [1, 2]
         let nested1 = RequestType::Array {
             children: vec![
-                RequestType::Integer { data: &[b'1'] },
-                RequestType::Integer { data: &[b'2'] },
+                RequestType::Integer { data: b"1" },
+                RequestType::Integer { data: b"2" },
             ],
         };
 
         // Third nested array: ["world", 5]
         let nested3 = RequestType::Array {
             children: vec![
-                RequestType::BulkString {
-                    data: &[b'w', b'o', b'r', b'l', b'd'],
-                },
-                RequestType::Integer { data: &[b'5'] },
+                RequestType::BulkString { data: b"world" },
+                RequestType::Integer { data: b"5" },
             ],
         };
 
@@ -1822,9 +2611,7 @@ mod resp3_tests {
 
         // Innermost array: [42]
         let innermost = RequestType::Array {
-            children: vec![RequestType::Integer {
-                data: &[b'4', b'2'],
-            }],
+            children: vec![RequestType::Integer { data: b"42" }],
         };
 
         // Middle array: [[42]]
@@ -1855,13 +2642,9 @@ mod resp3_tests {
             result,
             RequestType::Array {
                 children: vec![
-                    RequestType::BulkString {
-                        data: &[b'h', b'e', b'l', b'l', b'o'],
-                    },
+                    RequestType::BulkString { data: b"hello" },
                     RequestType::Null,
-                    RequestType::BulkString {
-                        data: &[b'w', b'o', b'r', b'l', b'd'],
-                    }
+                    RequestType::BulkString { data: b"world" }
                 ]
             }
         );
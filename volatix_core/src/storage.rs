@@ -1,21 +1,32 @@
 use std::{
-    collections::{BinaryHeap, HashMap},
+    borrow::Cow,
+    collections::{
+        BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, VecDeque, hash_map::DefaultHasher,
+    },
     fmt::Display,
     fs::{File, OpenOptions},
-    io::{self, BufReader, BufWriter, Read, Write},
+    hash::{Hash, Hasher},
+    io::{self, BufWriter, Read, Write},
+    ops::RangeInclusive,
     path::{Path, PathBuf},
     sync::{
         Arc,
-        atomic::{AtomicBool, AtomicUsize, Ordering},
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
     },
     time::{Duration, SystemTime},
 };
 
 use anyhow::Context;
 use flate2::bufread::{ZlibDecoder, ZlibEncoder};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    BloomFilter, CuckooFilter, GeoMatch, GeoOrigin, GeoSet, GeoShape, HyperLogLog, JsonPath,
+    PendingEntry, PendingSummary, Stream, StreamFields, StreamId, glob_match, to_json,
+    validate_position,
+};
+
 /// Represents all possible value types that can be stored in the cache.
 /// Supports Redis-like data structures with automatic size calculation.
 ///
@@ -48,6 +59,16 @@ pub enum StorageValue {
     List(Vec<StorageValue>),
     /// Key-value pairs (similar to JSON object)
     Map(Vec<(String, StorageValue)>),
+    /// Cardinality estimation sketch (PFADD, PFCOUNT, PFMERGE)
+    HyperLogLog(HyperLogLog),
+    /// Probabilistic membership filter (BF.*)
+    BloomFilter(BloomFilter),
+    /// Probabilistic membership filter supporting deletes (CF.*)
+    CuckooFilter(CuckooFilter),
+    /// Append-only log with consumer groups (X*)
+    Stream(Box<Stream>),
+    /// Members with positions, sorted by geohash (GEO*)
+    Geo(GeoSet),
 }
 
 impl Display for StorageValue {
@@ -60,12 +81,37 @@ impl Display for StorageValue {
             StorageValue::Bytes(b) => write!(f, "{b:?}"),
             StorageValue::List(storage_values) => write!(f, "{storage_values:?}"),
             StorageValue::Map(items) => write!(f, "{items:?}"),
+            StorageValue::HyperLogLog(hll) => write!(f, "hyperloglog({})", hll.encoding()),
+            StorageValue::BloomFilter(filter) => write!(f, "bloomfilter({} items)", filter.len()),
+            StorageValue::CuckooFilter(filter) => write!(f, "cuckoofilter({} items)", filter.len()),
+            StorageValue::Stream(stream) => write!(f, "stream({} entries)", stream.len()),
+            StorageValue::Geo(set) => write!(f, "geo({} members)", set.len()),
             StorageValue::Null => write!(f, "null"),
         }
     }
 }
 
 impl StorageValue {
+    /// Returns the name of the value's type, e.g `"int"` or `"list"`.
+    /// Used by type filters such as `SCAN ... TYPE list`.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            StorageValue::Null => "null",
+            StorageValue::Int(_) => "int",
+            StorageValue::Float(_) => "float",
+            StorageValue::Bool(_) => "bool",
+            StorageValue::Text(_) => "text",
+            StorageValue::Bytes(_) => "bytes",
+            StorageValue::List(_) => "list",
+            StorageValue::Map(_) => "map",
+            StorageValue::HyperLogLog(_) => "hyperloglog",
+            StorageValue::BloomFilter(_) => "bloomfilter",
+            StorageValue::CuckooFilter(_) => "cuckoofilter",
+            StorageValue::Stream(_) => "stream",
+            StorageValue::Geo(_) => "geo",
+        }
+    }
+
     /// Calculates the approximate memory usage of this value in bytes.
     /// Used for eviction policies and memory management.
     fn size_in_bytes(&self) -> usize {
@@ -85,6 +131,11 @@ impl StorageValue {
                 .iter()
                 .map(|(k, v)| k.capacity() + v.size_in_bytes())
                 .sum(),
+            StorageValue::HyperLogLog(hll) => size_of_val(self) + hll.size_in_bytes(),
+            StorageValue::BloomFilter(filter) => size_of_val(self) + filter.size_in_bytes(),
+            StorageValue::CuckooFilter(filter) => size_of_val(self) + filter.size_in_bytes(),
+            StorageValue::Stream(stream) => size_of_val(self) + stream.size_in_bytes(),
+            StorageValue::Geo(set) => size_of_val(self) + set.size_in_bytes(),
             StorageValue::Null => 0,
         }
     }
@@ -105,38 +156,109 @@ pub struct StorageEntry {
     pub access_count: usize,
     /// Size of the entry in bytes (for size-aware eviction)
     pub entry_size: usize,
-    /// Time-to-live for this entry
-    pub ttl: Duration,
+    /// When this entry expires, `None` if it never does
+    pub expires_at: Option<SystemTime>,
     /// Whether this entry's value is compressed
     pub compressed: bool,
+    /// Version of the value, increased on every write (for CAS)
+    pub version: u64,
 }
 
 impl Display for StorageEntry {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Value:{}\r\nCreated_at:{:?}\r\nLastaccessed:{:?}\r\nAccessCount:{}\r\nEntrysize:{}\r\nTtl:{}\r\nCompressed:{}",
+            "Value:{}\r\nCreated_at:{:?}\r\nLastaccessed:{:?}\r\nAccessCount:{}\r\nEntrysize:{}\r\nTtl:{}\r\nCompressed:{}\r\nVersion:{}",
             self.value,
             self.created_at,
             self.last_accessed,
             self.access_count,
             self.entry_size,
-            self.ttl.as_secs(),
+            self.remaining_ttl().map_or(-1, |ttl| ttl.as_secs() as i64),
             self.compressed,
+            self.version,
         )
     }
 }
 
 impl StorageEntry {
-    /// Checks if this entry has expired based on its TTL and creation time.
+    /// Describes the entry as typed fields, for the DUMP reply: the value,
+    /// its type, creation and last access times in unix milliseconds, access
+    /// count, size in bytes, remaining TTL in seconds (-1 if it never
+    /// expires), compression and version.
+    pub fn to_map(&self) -> Vec<(String, StorageValue)> {
+        let unix_millis = |time: SystemTime| {
+            let since_epoch = time
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default();
+            StorageValue::Int(since_epoch.as_millis() as i64)
+        };
+        let ttl = self.remaining_ttl().map_or(-1, |ttl| ttl.as_secs() as i64);
+
+        vec![
+            ("value".to_string(), self.value.clone()),
+            (
+                "type".to_string(),
+                StorageValue::Text(self.type_name().to_string()),
+            ),
+            ("created_at".to_string(), unix_millis(self.created_at)),
+            ("last_accessed".to_string(), unix_millis(self.last_accessed)),
+            (
+                "access_count".to_string(),
+                StorageValue::Int(self.access_count as i64),
+            ),
+            (
+                "entry_size".to_string(),
+                StorageValue::Int(self.entry_size as i64),
+            ),
+            ("ttl".to_string(), StorageValue::Int(ttl)),
+            (
+                "compressed".to_string(),
+                StorageValue::Bool(self.compressed),
+            ),
+            (
+                "version".to_string(),
+                StorageValue::Int(self.version as i64),
+            ),
+        ]
+    }
+
+    /// Returns the type name of the stored value.
+    /// Compressed entries report the type of their uncompressed text.
+    pub fn type_name(&self) -> &'static str {
+        if self.compressed {
+            "text"
+        } else {
+            self.value.type_name()
+        }
+    }
+
+    /// Returns how the value is held in memory: `compressed` for text
+    /// compressed with zlib, `raw` otherwise.
+    pub fn encoding(&self) -> &'static str {
+        if self.compressed { "compressed" } else { "raw" }
+    }
+
+    /// Checks if this entry has reached its expiry deadline.
     ///
     /// # Returns
-    /// `true` if the entry has exceeded its TTL, `false` otherwise.
+    /// `true` if the entry has expired, `false` otherwise or if it never expires.
     fn is_expired(&self) -> bool {
-        SystemTime::now()
-            .duration_since(self.created_at)
-            .expect("clock gone backwards")
-            > self.ttl
+        self.expires_at
+            .is_some_and(|deadline| SystemTime::now() >= deadline)
+    }
+
+    /// Gets the time left until this entry expires.
+    ///
+    /// # Returns
+    /// `Some(Duration)` with the remaining time (zero once the deadline has
+    /// passed), or `None` if the entry never expires
+    pub fn remaining_ttl(&self) -> Option<Duration> {
+        self.expires_at.map(|deadline| {
+            deadline
+                .duration_since(SystemTime::now())
+                .unwrap_or_default()
+        })
     }
 
     /// Decompresses the entry value if it was compressed.
@@ -145,19 +267,42 @@ impl StorageEntry {
     /// # Errors
     /// Returns `io::Error` if decompression fails.
     fn decompress(&mut self) -> io::Result<()> {
-        let mut output = String::new();
         let input = match &self.value {
             StorageValue::Bytes(bytes) => bytes,
             _ => return Ok(()), // Not compressed or not bytes
         };
-        let mut z = ZlibDecoder::new(&input[..]);
-        z.read_to_string(&mut output)?;
 
-        self.value = StorageValue::Text(output);
+        self.value = StorageValue::Text(decompress(input)?);
         Ok(())
     }
 }
 
+/// Bitwise operations that combine bitmaps (`BITOP`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitOp {
+    And,
+    Or,
+    Xor,
+    /// Inverts a single bitmap
+    Not,
+}
+
+impl BitOp {
+    /// Parses the operation name, case insensitively.
+    ///
+    /// # Returns
+    /// The operation, or an error naming the unknown operation
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_uppercase().as_str() {
+            "AND" => Ok(BitOp::And),
+            "OR" => Ok(BitOp::Or),
+            "XOR" => Ok(BitOp::Xor),
+            "NOT" => Ok(BitOp::Not),
+            _ => Err(format!("Unknown BITOP operation: {name}")),
+        }
+    }
+}
+
 /// Configuration options for the storage engine.
 /// These settings control behavior like TTL, capacity limits, and compression.
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
@@ -254,6 +399,23 @@ impl Default for StorageOptions {
     }
 }
 
+impl StorageOptions {
+    /// Lists the options as typed fields keyed by their CONFGET name, for the
+    /// CONFOPTIONS reply.
+    pub fn to_map(&self) -> Vec<(String, StorageValue)> {
+        [
+            ConfigEntry::GlobalTtl(self.ttl.as_secs() as usize),
+            ConfigEntry::MaxCapacity(self.max_capacity),
+            ConfigEntry::EvictPolicy(self.eviction_policy),
+            ConfigEntry::Compression(self.compression.into()),
+            ConfigEntry::CompressionThreshold(self.compression_threshold),
+        ]
+        .iter()
+        .map(|entry| (entry.name().to_string(), entry.value()))
+        .collect()
+    }
+}
+
 impl Display for StorageOptions {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -268,6 +430,150 @@ impl Display for StorageOptions {
     }
 }
 
+/// When a SET is allowed to write its value.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SetCondition {
+    /// Always write (the default)
+    #[default]
+    Always,
+    /// Only write if the key doesn't exist (`NX`)
+    IfAbsent,
+    /// Only write if the key already exists (`XX`)
+    IfPresent,
+}
+
+/// Expiry of a value written by SET.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SetExpiry {
+    /// Use the global TTL (the default)
+    #[default]
+    Default,
+    /// Retain the TTL of the existing entry (`KEEPTTL`)
+    Keep,
+    /// Expire after the duration (`EX` seconds or `PX` milliseconds)
+    In(Duration),
+    /// Expire at the point in time (`EXAT` or `PXAT` unix time)
+    At(SystemTime),
+}
+
+/// Options of the SET command.
+/// Shared by the server and clients to parse and build SET arguments.
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+/// use volatix_core::{SetCondition, SetExpiry, SetOptions};
+///
+/// let options = SetOptions::parse(&["NX", "EX", "60", "GET"]).unwrap();
+/// assert_eq!(options.condition, SetCondition::IfAbsent);
+/// assert_eq!(options.expiry, SetExpiry::In(Duration::from_secs(60)));
+/// assert!(options.get);
+/// assert_eq!(options.to_args(), vec!["NX", "EX", "60", "GET"]);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SetOptions {
+    /// When the value is written
+    pub condition: SetCondition,
+    /// When the written value expires
+    pub expiry: SetExpiry,
+    /// Reply with the previous value of the key (`GET`)
+    pub get: bool,
+}
+
+impl SetOptions {
+    /// Parses SET options: `[NX | XX] [GET] [EX seconds | PX milliseconds |
+    /// EXAT unix-seconds | PXAT unix-milliseconds | KEEPTTL]`.
+    /// Option names are case insensitive and may come in any order.
+    ///
+    /// # Arguments
+    /// * `args` - The arguments following the key and value
+    ///
+    /// # Returns
+    /// The parsed options or an error message for invalid or conflicting options
+    pub fn parse<S: AsRef<str>>(args: &[S]) -> Result<Self, String> {
+        let mut options = SetOptions::default();
+        let mut args = args.iter().map(|a| a.as_ref());
+
+        while let Some(arg) = args.next() {
+            let option = arg.to_uppercase();
+            match option.as_str() {
+                "NX" | "XX" => {
+                    if options.condition != SetCondition::Always {
+                        return Err("NX and XX options are mutually exclusive".to_string());
+                    }
+                    options.condition = if option == "NX" {
+                        SetCondition::IfAbsent
+                    } else {
+                        SetCondition::IfPresent
+                    };
+                }
+                "GET" => options.get = true,
+                "KEEPTTL" | "EX" | "PX" | "EXAT" | "PXAT" => {
+                    if options.expiry != SetExpiry::Default {
+                        return Err("Only one expiry option is allowed".to_string());
+                    }
+                    if option == "KEEPTTL" {
+                        options.expiry = SetExpiry::Keep;
+                        continue;
+                    }
+
+                    let n = match args.next().map(|v| v.parse::<u64>()) {
+                        Some(Ok(n)) if n > 0 => n,
+                        _ => return Err(format!("Invalid {option} value")),
+                    };
+                    let at = |offset| {
+                        SystemTime::UNIX_EPOCH
+                            .checked_add(offset)
+                            .map(SetExpiry::At)
+                            .ok_or_else(|| format!("Invalid {option} value"))
+                    };
+                    options.expiry = match option.as_str() {
+                        "EX" => SetExpiry::In(Duration::from_secs(n)),
+                        "PX" => SetExpiry::In(Duration::from_millis(n)),
+                        "EXAT" => at(Duration::from_secs(n))?,
+                        _ => at(Duration::from_millis(n))?,
+                    };
+                }
+                _ => return Err(format!("Unknown SET option: {arg}")),
+            }
+        }
+
+        Ok(options)
+    }
+
+    /// Builds the SET arguments for these options, the inverse of `parse`.
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        match self.condition {
+            SetCondition::Always => {}
+            SetCondition::IfAbsent => args.push("NX".to_string()),
+            SetCondition::IfPresent => args.push("XX".to_string()),
+        }
+
+        match self.expiry {
+            SetExpiry::Default => {}
+            SetExpiry::Keep => args.push("KEEPTTL".to_string()),
+            SetExpiry::In(d) if d.subsec_millis() == 0 => {
+                args.extend(["EX".to_string(), d.as_secs().to_string()])
+            }
+            SetExpiry::In(d) => args.extend(["PX".to_string(), d.as_millis().to_string()]),
+            SetExpiry::At(t) => {
+                let since_epoch = t.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default();
+                if since_epoch.subsec_millis() == 0 {
+                    args.extend(["EXAT".to_string(), since_epoch.as_secs().to_string()]);
+                } else {
+                    args.extend(["PXAT".to_string(), since_epoch.as_millis().to_string()]);
+                }
+            }
+        }
+
+        if self.get {
+            args.push("GET".to_string());
+        }
+        args
+    }
+}
+
 /// Non-atomic version of statistics for serialization.
 /// Used when saving/loading storage state to/from disk.
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -295,6 +601,24 @@ pub struct StorageStats {
     pub expired_removals: AtomicUsize,
 }
 
+impl StorageStats {
+    /// Lists the counters as integer fields, for the GETSTATS reply.
+    pub fn to_map(&self) -> Vec<(String, StorageValue)> {
+        let counter = |name: &str, counter: &AtomicUsize| {
+            let count = counter.load(Ordering::Relaxed) as i64;
+            (name.to_string(), StorageValue::Int(count))
+        };
+
+        vec![
+            counter("total_entries", &self.total_entries),
+            counter("hits", &self.hits),
+            counter("misses", &self.misses),
+            counter("evictions", &self.evictions),
+            counter("expired_removals", &self.expired_removals),
+        ]
+    }
+}
+
 impl Display for StorageStats {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -309,6 +633,17 @@ impl Display for StorageStats {
     }
 }
 
+/// Key counts and memory footprint of a storage, reported by INFO.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct KeyspaceInfo {
+    /// Number of live keys
+    pub keys: usize,
+    /// Number of live keys with a TTL
+    pub expires: usize,
+    /// Bytes used by the keys and their values
+    pub memory: usize,
+}
+
 /// Strategies for removing entries when the cache reaches capacity.
 /// Each policy optimizes for different use cases and access patterns.
 #[derive(Default, Debug, Clone, Copy, Serialize, Deserialize)]
@@ -322,6 +657,10 @@ pub enum EvictionPolicy {
     LFU,
     /// Remove largest entries first (by size)
     SizeAware,
+    /// Admit new entries through a small window and only let them displace
+    /// an existing entry if they are estimated to be accessed more often
+    /// (W-TinyLFU)
+    TinyLFU,
 }
 
 impl Display for EvictionPolicy {
@@ -331,6 +670,7 @@ impl Display for EvictionPolicy {
             EvictionPolicy::LRU => write!(f, "LRU"),
             EvictionPolicy::LFU => write!(f, "LFU"),
             EvictionPolicy::SizeAware => write!(f, "SizeAware"),
+            EvictionPolicy::TinyLFU => write!(f, "TinyLFU"),
         }
     }
 }
@@ -346,6 +686,31 @@ pub enum ConfigEntry {
     CompressionThreshold(usize),
 }
 
+impl ConfigEntry {
+    /// The CONFGET name of the option, e.g `MAXCAP`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConfigEntry::EvictPolicy(_) => "EVICTPOLICY",
+            ConfigEntry::GlobalTtl(_) => "GLOBALTTL",
+            ConfigEntry::MaxCapacity(_) => "MAXCAP",
+            ConfigEntry::Compression(_) => "COMPRESSION",
+            ConfigEntry::CompressionThreshold(_) => "COMPRESSIONTHRESHOLD",
+        }
+    }
+
+    /// The value of the option: the policy name as text, compression as a
+    /// boolean and the other options as integers.
+    pub fn value(&self) -> StorageValue {
+        match self {
+            ConfigEntry::EvictPolicy(e) => StorageValue::Text(e.to_string()),
+            ConfigEntry::GlobalTtl(t) => StorageValue::Int(*t as i64),
+            ConfigEntry::MaxCapacity(c) => StorageValue::Int(*c as i64),
+            ConfigEntry::Compression(b) => StorageValue::Bool((*b).into()),
+            ConfigEntry::CompressionThreshold(s) => StorageValue::Int(*s as i64),
+        }
+    }
+}
+
 impl Display for ConfigEntry {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -358,6 +723,226 @@ impl Display for ConfigEntry {
     }
 }
 
+/// Number of rows (independent hash functions) in the frequency sketch.
+const SKETCH_DEPTH: usize = 4;
+/// Counters saturate at this value, like 4-bit counters.
+const SKETCH_MAX_COUNT: u8 = 15;
+/// Counters per row for every entry of capacity. Workloads touch many more
+/// keys than the cache holds, with one counter per entry most of them collide
+/// and cold keys look as popular as hot ones.
+const SKETCH_WIDTH_FACTOR: usize = 8;
+/// Counters are halved after `width * SKETCH_SAMPLE_FACTOR` recorded accesses.
+const SKETCH_SAMPLE_FACTOR: usize = 10;
+
+/// Count-min sketch estimating how often a key has been accessed.
+/// Used by the TinyLFU admission filter to compare the popularity of a
+/// candidate entry against an eviction victim without tracking every key.
+///
+/// The table is allocated lazily, so storages that never use the TinyLFU
+/// policy pay nothing for it.
+#[derive(Debug, Default)]
+struct FrequencySketch {
+    /// `SKETCH_DEPTH` rows of `width` counters laid out back to back
+    table: Vec<u8>,
+    /// Number of counters per row, always a power of two
+    width: usize,
+    /// Accesses recorded since the last aging
+    additions: usize,
+}
+
+impl FrequencySketch {
+    /// Grows the sketch to fit the given cache capacity.
+    /// Resizing resets all counters.
+    fn ensure_capacity(&mut self, capacity: usize) {
+        let width = capacity
+            .saturating_mul(SKETCH_WIDTH_FACTOR)
+            .clamp(16, 1 << 22)
+            .next_power_of_two();
+        if width > self.width {
+            self.table = vec![0; width * SKETCH_DEPTH];
+            self.width = width;
+            self.additions = 0;
+        }
+    }
+
+    /// Returns the counter index of the key in each row.
+    fn indexes(&self, key: &str) -> [usize; SKETCH_DEPTH] {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        // Double hashing derives the per-row hashes from a single hash
+        let h1 = hash as u32 as usize;
+        let h2 = ((hash >> 32) as usize) | 1;
+        std::array::from_fn(|row| {
+            row * self.width + (h1.wrapping_add(row.wrapping_mul(h2)) & (self.width - 1))
+        })
+    }
+
+    /// Records an access of the key.
+    fn increment(&mut self, key: &str) {
+        if self.width == 0 {
+            return;
+        }
+
+        let indexes = self.indexes(key);
+        let min = indexes.iter().map(|&i| self.table[i]).min().unwrap_or(0);
+        if min < SKETCH_MAX_COUNT {
+            // Conservative update: only the smallest counters are bumped
+            for i in indexes {
+                if self.table[i] == min {
+                    self.table[i] += 1;
+                }
+            }
+        }
+
+        self.additions += 1;
+        if self.additions >= self.width * SKETCH_SAMPLE_FACTOR {
+            self.age();
+        }
+    }
+
+    /// Estimated number of accesses of the key.
+    fn frequency(&self, key: &str) -> u8 {
+        if self.width == 0 {
+            return 0;
+        }
+        self.indexes(key)
+            .iter()
+            .map(|&i| self.table[i])
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// Halves every counter so that old popularity fades out.
+    fn age(&mut self) {
+        for counter in self.table.iter_mut() {
+            *counter >>= 1;
+        }
+        self.additions /= 2;
+    }
+}
+
+/// State of the W-TinyLFU admission policy.
+///
+/// New keys enter a small admission window. When the cache is full, the
+/// oldest key leaving the window competes against the least recently used
+/// entry of the main region and the one with the lower estimated access
+/// frequency is evicted. One-hit wonders (e.g. scans) therefore cannot
+/// flush frequently used entries out of the cache.
+#[derive(Debug, Default)]
+struct AdmissionFilter {
+    /// Access frequency estimates
+    sketch: FrequencySketch,
+    /// Recently inserted keys, oldest first
+    window: VecDeque<String>,
+    /// Keys of `window`, to skip them quickly when looking for victims
+    window_keys: HashSet<String>,
+    /// Eviction candidates of the main region (least recently used first),
+    /// paired with their last access time when they were selected
+    victims: VecDeque<(SystemTime, String)>,
+}
+
+impl AdmissionFilter {
+    /// Adds a new key to the admission window.
+    ///
+    /// # Returns
+    /// The oldest key of the window once it holds more than `capacity` keys
+    fn push_window(&mut self, key: &str, capacity: usize) -> Option<String> {
+        // A key removed and inserted again keeps its place in the window
+        if self.window_keys.insert(key.to_string()) {
+            self.window.push_back(key.to_string());
+        }
+        if self.window.len() <= capacity {
+            return None;
+        }
+        let oldest = self.window.pop_front()?;
+        self.window_keys.remove(&oldest);
+        Some(oldest)
+    }
+}
+
+/// Hashes a key to its position in the `SCAN` keyspace order.
+fn scan_hash(key: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Gets the bytes of a string value: text, bytes, or a number or boolean
+/// as its text. Compressed values must be decompressed before.
+fn string_bytes(value: &StorageValue) -> Result<Cow<'_, [u8]>, String> {
+    match value {
+        StorageValue::Text(text) => Ok(Cow::Borrowed(text.as_bytes())),
+        StorageValue::Bytes(bytes) => Ok(Cow::Borrowed(bytes)),
+        // SET stores numeric looking values as numbers
+        StorageValue::Int(_) | StorageValue::Float(_) | StorageValue::Bool(_) => {
+            Ok(Cow::Owned(value.to_string().into_bytes()))
+        }
+        _ => Err("Value is not a string".to_string()),
+    }
+}
+
+/// Resolves an inclusive offset range over `len` bytes. Negative offsets
+/// count from the end, out of range offsets are clamped.
+///
+/// # Returns
+/// The range of indexes, or `None` if it is empty
+fn resolve_range(len: usize, start: i64, end: i64) -> Option<RangeInclusive<usize>> {
+    let len = len as i64;
+    let resolve = |offset: i64| if offset < 0 { len + offset } else { offset };
+    let start = resolve(start).max(0);
+    let end = resolve(end).min(len - 1);
+
+    (start <= end).then_some(start as usize..=end as usize)
+}
+
+/// Counts the set bits, a word at a time.
+fn popcount(bytes: &[u8]) -> usize {
+    let mut words = bytes.chunks_exact(8);
+    let count: usize = words
+        .by_ref()
+        .map(|word| {
+            u64::from_ne_bytes(word.try_into().expect("8 byte chunk")).count_ones() as usize
+        })
+        .sum();
+
+    count
+        + words
+            .remainder()
+            .iter()
+            .map(|byte| byte.count_ones() as usize)
+            .sum::<usize>()
+}
+
+/// Builds a string value from bytes. Text stays text as long as the bytes
+/// are valid UTF-8, otherwise it becomes a bytes value.
+fn bytes_to_value(bytes: Vec<u8>, is_text: bool) -> StorageValue {
+    if !is_text {
+        return StorageValue::Bytes(bytes);
+    }
+
+    match String::from_utf8(bytes) {
+        Ok(text) => StorageValue::Text(text),
+        Err(err) => StorageValue::Bytes(err.into_bytes()),
+    }
+}
+
+/// Whether a value can be read as a JSON document: a scalar, list or map.
+/// Bytes and the sketch, stream and geospatial types can't.
+fn is_document(value: &StorageValue) -> bool {
+    matches!(
+        value,
+        StorageValue::Null
+            | StorageValue::Int(_)
+            | StorageValue::Float(_)
+            | StorageValue::Bool(_)
+            | StorageValue::Text(_)
+            | StorageValue::List(_)
+            | StorageValue::Map(_)
+    )
+}
+
 /// Compresses a string using zlib compression.
 /// Used automatically for large text values when compression is enabled.
 ///
@@ -378,6 +963,24 @@ fn compress(data: &str) -> Result<Vec<u8>, String> {
     Ok(output)
 }
 
+/// Decompresses zlib compressed bytes back into a string.
+/// The inverse of `compress`.
+///
+/// # Arguments
+/// * `data` - The compressed bytes
+///
+/// # Returns
+/// The original string or the decompression error
+fn decompress(data: &[u8]) -> io::Result<String> {
+    let mut output = String::new();
+    let mut z = ZlibDecoder::new(data);
+    z.read_to_string(&mut output)?;
+    Ok(output)
+}
+
+/// Largest string SETRANGE may create, matching Redis' 512MB limit
+const MAX_STRING_SIZE: usize = 512 * 1024 * 1024;
+
 /// Thread-safe storage wrapper with atomic statistics and configuration options.
 /// This is the main interface for cache operations.
 ///
@@ -403,6 +1006,12 @@ pub struct LockedStorage {
     pub stats: StorageStats,
     /// Current number of entries in the store
     pub entry_count: AtomicUsize,
+    /// Admission state for the TinyLFU eviction policy
+    admission: Arc<Mutex<AdmissionFilter>>,
+    /// Last version handed out to an entry
+    version: AtomicU64,
+    /// Keys sorted by their `SCAN` hash, so a page is a range lookup
+    scan_index: RwLock<BTreeSet<(u64, String)>>,
 }
 
 /// Serializable version of storage for disk persistence.
@@ -412,6 +1021,96 @@ struct SerializableStorage {
     store: HashMap<String, StorageEntry>,
     options: StorageOptions,
     stats: NonAtomicStats,
+    version: u64,
+}
+
+/// Entry layout of snapshots written before expiry deadlines: the TTL is
+/// relative to the creation time and there is no version.
+#[derive(Deserialize)]
+struct EntryWithTtl {
+    value: StorageValue,
+    created_at: SystemTime,
+    last_accessed: SystemTime,
+    access_count: usize,
+    entry_size: usize,
+    ttl: Duration,
+    compressed: bool,
+}
+
+impl From<EntryWithTtl> for StorageEntry {
+    fn from(entry: EntryWithTtl) -> Self {
+        StorageEntry {
+            value: entry.value,
+            created_at: entry.created_at,
+            last_accessed: entry.last_accessed,
+            access_count: entry.access_count,
+            entry_size: entry.entry_size,
+            // A deadline past the end of time never comes
+            expires_at: entry.created_at.checked_add(entry.ttl),
+            compressed: entry.compressed,
+            version: 0,
+        }
+    }
+}
+
+/// Entry layout of snapshots written before per-entry versions.
+#[derive(Deserialize)]
+struct EntryWithoutVersion {
+    value: StorageValue,
+    created_at: SystemTime,
+    last_accessed: SystemTime,
+    access_count: usize,
+    entry_size: usize,
+    expires_at: Option<SystemTime>,
+    compressed: bool,
+}
+
+impl From<EntryWithoutVersion> for StorageEntry {
+    fn from(entry: EntryWithoutVersion) -> Self {
+        StorageEntry {
+            value: entry.value,
+            created_at: entry.created_at,
+            last_accessed: entry.last_accessed,
+            access_count: entry.access_count,
+            entry_size: entry.entry_size,
+            expires_at: entry.expires_at,
+            compressed: entry.compressed,
+            version: 0,
+        }
+    }
+}
+
+/// Storage layout of snapshots written before per-entry versions, holding
+/// entries of an older layout.
+#[derive(Deserialize)]
+struct UnversionedStorage<E> {
+    store: HashMap<String, E>,
+    options: StorageOptions,
+    stats: NonAtomicStats,
+}
+
+impl<E: Into<StorageEntry>> From<UnversionedStorage<E>> for SerializableStorage {
+    fn from(storage: UnversionedStorage<E>) -> Self {
+        // Version 0 stands for a missing key in CAS, so every migrated
+        // entry gets a version of its own
+        let store: HashMap<String, StorageEntry> = storage
+            .store
+            .into_iter()
+            .zip(1..)
+            .map(|((key, entry), version)| {
+                let mut entry: StorageEntry = entry.into();
+                entry.version = version;
+                (key, entry)
+            })
+            .collect();
+
+        SerializableStorage {
+            version: store.len() as u64,
+            store,
+            options: storage.options,
+            stats: storage.stats,
+        }
+    }
 }
 
 impl LockedStorage {
@@ -431,6 +1130,9 @@ impl LockedStorage {
             stats: StorageStats::default(),
             is_dirty: AtomicBool::new(false),
             entry_count: AtomicUsize::new(0),
+            admission: Arc::new(Mutex::new(AdmissionFilter::default())),
+            version: AtomicU64::new(0),
+            scan_index: RwLock::new(BTreeSet::new()),
         }
     }
 
@@ -439,8 +1141,11 @@ impl LockedStorage {
     /// To reset config options try `reset_options`
     pub fn flush(&mut self) {
         let old_options = self.options;
+        // Versions must never repeat, or a stale CAS could succeed
+        let version = self.version.load(Ordering::Relaxed);
         let _old_storage = std::mem::take(self);
         self.options = old_options;
+        self.version.store(version, Ordering::Relaxed);
         self.is_dirty.store(true, Ordering::Relaxed);
     }
 
@@ -464,6 +1169,9 @@ impl LockedStorage {
     /// }
     /// ```
     pub fn get_entry(&self, key: &str) -> Option<StorageEntry> {
+        // TinyLFU learns from every lookup, including misses
+        self.record_access(key);
+
         // First, try to get the entry and update its access metadata
         let mut entry = if let Some(entry) = self.store.write().get_mut(key)
             && !entry.is_expired()
@@ -489,6 +1197,35 @@ impl LockedStorage {
         Some(entry)
     }
 
+    /// Passes the entry at `key` to `inspect` without counting an access:
+    /// access metadata and hit/miss statistics are left untouched and the
+    /// value is not decompressed. Meant for introspection commands.
+    ///
+    /// # Returns
+    /// The result of `inspect`, or `None` if the key is missing or expired
+    ///
+    /// # Example
+    /// ```rust
+    /// use volatix_core::{LockedStorage, StorageValue};
+    ///
+    /// let mut storage = LockedStorage::default();
+    /// storage.insert_entry("jobs".to_string(), StorageValue::List(vec![])).unwrap();
+    ///
+    /// assert_eq!(storage.inspect_entry("jobs", |e| e.type_name()), Some("list"));
+    /// assert_eq!(storage.inspect_entry("jobs", |e| e.access_count), Some(0));
+    /// ```
+    pub fn inspect_entry<T>(
+        &self,
+        key: &str,
+        inspect: impl FnOnce(&StorageEntry) -> T,
+    ) -> Option<T> {
+        let store = self.store.read();
+        store
+            .get(key)
+            .filter(|entry| !entry.is_expired())
+            .map(inspect)
+    }
+
     /// Checks if a key exists in the cache without updating access metadata.
     ///
     /// # Arguments
@@ -509,6 +1246,119 @@ impl LockedStorage {
         self.store.read().keys().cloned().collect()
     }
 
+    /// Returns all keys matching a glob pattern, e.g `user:42:*`.
+    /// Expired entries are skipped.
+    ///
+    /// # Arguments
+    /// * `pattern` - Glob pattern, see [`glob_match`]
+    ///
+    /// # Returns
+    /// Vector of the matching keys
+    pub fn get_keys_matching(&self, pattern: &str) -> Vec<String> {
+        self.store
+            .read()
+            .iter()
+            .filter(|(k, v)| !v.is_expired() && glob_match(pattern, k))
+            .map(|(k, _)| k.clone())
+            .collect()
+    }
+
+    /// Retrieves all entries whose keys match a glob pattern.
+    ///
+    /// # Arguments
+    /// * `pattern` - Glob pattern, see [`glob_match`]
+    ///
+    /// # Returns
+    /// Vector of tuples containing (key, `Option<StorageEntry>`), in the
+    /// same format as [`LockedStorage::get_entries`]
+    pub fn get_entries_matching(&self, pattern: &str) -> Vec<(String, Option<StorageEntry>)> {
+        let keys = self.get_keys_matching(pattern);
+        self.get_entries(&keys)
+    }
+
+    /// Returns one page of keys for cursor based iteration over the keyspace.
+    ///
+    /// Keys are visited in the order of their hash, and the cursor is the
+    /// hash to continue from. A full iteration therefore returns every key
+    /// that exists during the whole iteration exactly once, no matter how
+    /// many keys are inserted or removed between calls. Keys are kept sorted
+    /// by hash, so a page costs `O(count + log n)` rather than a pass over
+    /// the whole keyspace.
+    ///
+    /// The filters are applied after a page has been selected, so a page
+    /// may hold fewer than `count` keys (or none) while the iteration is
+    /// not finished yet.
+    ///
+    /// # Arguments
+    /// * `cursor` - `0` to start a new iteration, otherwise the cursor
+    ///   returned by the previous call
+    /// * `count` - Number of keys to visit
+    /// * `pattern` - Only return keys matching this glob pattern
+    /// * `type_name` - Only return keys whose value has this type
+    ///
+    /// # Returns
+    /// The next cursor (`0` once the iteration is complete) and the matching keys
+    ///
+    /// # Example
+    /// ```rust
+    /// use volatix_core::{LockedStorage, StorageOptions, StorageValue};
+    ///
+    /// let mut storage = LockedStorage::new(StorageOptions::default());
+    /// storage.insert_entry("user:1".to_string(), StorageValue::Int(1)).unwrap();
+    ///
+    /// let mut cursor = 0;
+    /// loop {
+    ///     let (next, keys) = storage.scan(cursor, 10, Some("user:*"), None);
+    ///     for key in keys {
+    ///         println!("{key}");
+    ///     }
+    ///     if next == 0 {
+    ///         break;
+    ///     }
+    ///     cursor = next;
+    /// }
+    /// ```
+    pub fn scan(
+        &self,
+        cursor: u64,
+        count: usize,
+        pattern: Option<&str>,
+        type_name: Option<&str>,
+    ) -> (u64, Vec<String>) {
+        let count = count.max(1);
+        let store = self.store.read();
+        let index = self.scan_index.read();
+
+        let mut entries = index.range((cursor, String::new())..).peekable();
+        let mut page = Vec::with_capacity(count);
+        while let Some((hash, key)) = entries.next() {
+            page.push((*hash, key));
+            // Keys sharing the last hash would be skipped by the next cursor
+            if page.len() >= count && entries.peek().is_none_or(|(next, _)| next != hash) {
+                break;
+            }
+        }
+        // Any key left has a higher hash than the page, so this can't overflow
+        let next_cursor = match entries.peek() {
+            Some(_) => page.last().map_or(0, |(hash, _)| hash + 1),
+            None => 0,
+        };
+
+        let keys = page
+            .into_iter()
+            .filter(|(_, key)| pattern.is_none_or(|p| glob_match(p, key)))
+            .filter(|(_, key)| {
+                store.get(*key).is_some_and(|e| {
+                    !e.is_expired()
+                        && type_name.is_none_or(|t| e.type_name().eq_ignore_ascii_case(t))
+                })
+            })
+            .map(|(_, key)| key.clone())
+            .collect();
+
+        (next_cursor, keys)
+    }
+
     /// Retrieves multiple entries in a single operation (batch get).
     /// More efficient than individual gets for multiple keys.
     ///
@@ -540,16 +1390,28 @@ impl LockedStorage {
     }
 
     /// Inserts multiple entries in a single operation (batch set).
-    /// Uses the default TTL for all entries.
+    /// Uses the default TTL for all entries. Every entry is prepared before
+    /// the first one is written, so either all of them are inserted or none.
     ///
     /// # Arguments
-    /// * `entries` - HashMap of key-value pairs to insert
+    /// * `entries` - Key-value pairs to insert
     ///
     /// # Returns
     /// `Ok(())` on success, `Err(String)` on failure
-    pub fn insert_entries(&mut self, entries: HashMap<String, StorageValue>) -> Result<(), String> {
-        for (key, value) in entries {
-            self.insert_entry(key, value)?;
+    pub fn insert_entries(
+        &mut self,
+        entries: impl IntoIterator<Item = (String, StorageValue)>,
+    ) -> Result<(), String> {
+        let expires_at = SystemTime::now()
+            .checked_add(self.options.ttl)
+            .ok_or_else(|| "Invalid expire time".to_string())?;
+        let entries = entries
+            .into_iter()
+            .map(|(key, value)| Ok((key, self.new_entry(value, Some(expires_at))?)))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        for (key, entry) in entries {
+            self.insert_raw_entry(key, entry);
         }
         Ok(())
     }
@@ -564,6 +1426,19 @@ impl LockedStorage {
         }
     }
 
+    /// Removes all entries whose keys match a glob pattern.
+    ///
+    /// # Arguments
+    /// * `pattern` - Glob pattern, see [`glob_match`]
+    ///
+    /// # Returns
+    /// The number of removed entries
+    pub fn remove_matching(&mut self, pattern: &str) -> usize {
+        let keys = self.get_keys_matching(pattern);
+        self.remove_entries(&keys);
+        keys.len()
+    }
+
     /// Inserts a single entry with the default TTL.
     /// Convenience method that uses the global TTL setting.
     ///
@@ -577,136 +1452,272 @@ impl LockedStorage {
         self.insert_with_ttl(key, value, self.options.ttl)
     }
 
-    /// Increments an integer value by 1.
-    /// Only works if the existing value is an integer.
-    /// Updates access statistics on success.
+    /// Inserts an entry according to SET options: conditionally on the key's
+    /// existence, with a custom or retained expiry.
     ///
     /// # Arguments
-    /// * `key` - The key containing an integer value
-    pub fn increment_entry(&mut self, key: &str) {
-        if let Some(entry) = self.store.write().get_mut(key)
-            && let StorageValue::Int(n) = entry.value
+    /// * `key` - The key to insert
+    /// * `value` - The value to store
+    /// * `options` - Condition and expiry of the write
+    ///
+    /// # Returns
+    /// Whether the value was written, and the previous entry if the key
+    /// existed (returned even when the value was not written)
+    ///
+    /// # Example
+    /// ```rust
+    /// use volatix_core::{LockedStorage, SetOptions, StorageValue};
+    ///
+    /// let mut storage = LockedStorage::default();
+    /// let nx = SetOptions::parse(&["NX"]).unwrap();
+    ///
+    /// let (written, _) = storage.insert_with_options("k".to_string(), StorageValue::Int(1), &nx).unwrap();
+    /// assert!(written);
+    /// let (written, old) = storage.insert_with_options("k".to_string(), StorageValue::Int(2), &nx).unwrap();
+    /// assert!(!written);
+    /// assert_eq!(old.unwrap().value, StorageValue::Int(1));
+    /// ```
+    pub fn insert_with_options(
+        &mut self,
+        key: String,
+        value: StorageValue,
+        options: &SetOptions,
+    ) -> Result<(bool, Option<StorageEntry>), String> {
+        // Only look the key up when the options depend on it
+        let old = if options.get
+            || options.condition != SetCondition::Always
+            || options.expiry == SetExpiry::Keep
         {
-            entry.value = StorageValue::Int(n + 1);
-            entry.last_accessed = SystemTime::now();
-            entry.access_count += 1;
-            self.stats.hits.fetch_add(1, Ordering::Relaxed);
-            return;
+            self.get_entry(&key)
+        } else {
+            None
+        };
+
+        let allowed = match options.condition {
+            SetCondition::Always => true,
+            SetCondition::IfAbsent => old.is_none(),
+            SetCondition::IfPresent => old.is_some(),
+        };
+        if !allowed {
+            return Ok((false, old));
         }
-        self.stats.misses.fetch_add(1, Ordering::Relaxed);
+
+        let now = SystemTime::now();
+        let deadline = |ttl| {
+            now.checked_add(ttl)
+                .map(Some)
+                .ok_or_else(|| "Invalid expire time".to_string())
+        };
+        let expires_at = match options.expiry {
+            SetExpiry::Default => deadline(self.options.ttl)?,
+            SetExpiry::Keep => match &old {
+                Some(entry) => entry.expires_at,
+                None => deadline(self.options.ttl)?,
+            },
+            SetExpiry::In(ttl) => deadline(ttl)?,
+            // A deadline in the past deletes the key right away
+            SetExpiry::At(deadline) if deadline <= now => {
+                self.remove_entry(&key);
+                return Ok((true, old));
+            }
+            SetExpiry::At(deadline) => Some(deadline),
+        };
+
+        self.insert_with_expiry(key, value, expires_at)?;
+        Ok((true, old))
     }
 
-    /// Decrements an integer value by 1.
-    /// Only works if the existing value is an integer.
-    /// Updates access statistics on success.
+    /// Writes a value only if the entry is still at the expected version.
+    /// The entry keeps its expiry, and a new entry gets the default TTL.
     ///
     /// # Arguments
-    /// * `key` - The key containing an integer value
-    pub fn decrement_entry(&mut self, key: &str) {
-        if let Some(entry) = self.store.write().get_mut(key)
-            && let StorageValue::Int(n) = entry.value
-        {
-            entry.value = StorageValue::Int(n - 1);
-            entry.last_accessed = SystemTime::now();
-            entry.access_count += 1;
-            self.stats.hits.fetch_add(1, Ordering::Relaxed);
-            return;
+    /// * `key` - The key to write
+    /// * `expected` - The version the entry must have, 0 if it must not exist
+    /// * `value` - The value to store
+    ///
+    /// # Returns
+    /// `Ok(Some(version))` with the new version when written, `Ok(None)` if
+    /// the version did not match
+    ///
+    /// # Example
+    /// ```rust
+    /// use volatix_core::{LockedStorage, StorageOptions, StorageValue};
+    ///
+    /// let mut storage = LockedStorage::new(StorageOptions::default());
+    ///
+    /// let v1 = storage.compare_and_swap("k", 0, StorageValue::Int(1)).unwrap().unwrap();
+    /// let v2 = storage.compare_and_swap("k", v1, StorageValue::Int(2)).unwrap().unwrap();
+    /// assert!(v2 > v1);
+    ///
+    /// // A writer that read the old version loses
+    /// assert_eq!(storage.compare_and_swap("k", v1, StorageValue::Int(3)), Ok(None));
+    /// ```
+    pub fn compare_and_swap(
+        &mut self,
+        key: &str,
+        expected: u64,
+        value: StorageValue,
+    ) -> Result<Option<u64>, String> {
+        let current = self
+            .store
+            .read()
+            .get(key)
+            .filter(|entry| !entry.is_expired())
+            .map(|entry| (entry.version, entry.expires_at));
+
+        match current {
+            Some((version, expires_at)) if version == expected => {
+                self.insert_with_expiry(key.to_string(), value, expires_at)?
+            }
+            None if expected == 0 => self.insert_entry(key.to_string(), value)?,
+            _ => return Ok(None),
         }
 
-        self.stats.misses.fetch_add(1, Ordering::Relaxed);
+        Ok(self.store.read().get(key).map(|entry| entry.version))
     }
 
-    /// Removes a single entry from the cache.
-    /// Updates the total entry count if an entry was actually removed.
+    /// Increments an integer value by 1.
+    /// A missing key is created at 0 before being incremented.
     ///
     /// # Arguments
-    /// * `key` - The key to remove
-    pub fn remove_entry(&mut self, key: &str) {
-        if self.store.write().remove_entry(key).is_some() {
-            self.stats.total_entries.fetch_sub(1, Ordering::Relaxed);
-            self.is_dirty.store(true, Ordering::Relaxed);
-        }
+    /// * `key` - The key containing an integer value
+    ///
+    /// # Returns
+    /// The new value, or an error if the value is not an integer or overflows
+    pub fn increment_entry(&mut self, key: &str) -> Result<i64, String> {
+        self.increment_by(key, 1)
     }
 
-    /// Inserts an entry with a specific TTL.
-    /// This is the core insertion method that handles compression and eviction.
+    /// Decrements an integer value by 1.
+    /// A missing key is created at 0 before being decremented.
     ///
     /// # Arguments
-    /// * `key` - The key to insert
-    /// * `value` - The value to store
-    /// * `ttl` - Time-to-live for this specific entry
+    /// * `key` - The key containing an integer value
     ///
     /// # Returns
-    /// `Ok(())` on success, `Err(String)` on failure
+    /// The new value, or an error if the value is not an integer or overflows
+    pub fn decrement_entry(&mut self, key: &str) -> Result<i64, String> {
+        self.increment_by(key, -1)
+    }
+
+    /// Adds `delta` to an integer value, creating missing keys at 0.
+    /// The value is read and written under a single lock, so concurrent
+    /// increments are never lost. Updates access statistics.
+    ///
+    /// # Arguments
+    /// * `key` - The key containing an integer value
+    /// * `delta` - Amount to add, negative to subtract
+    ///
+    /// # Returns
+    /// The new value, or an error if the value is not an integer or the
+    /// result does not fit in an `i64`
     ///
     /// # Example
     /// ```rust
-    /// use std::time::Duration;
-    /// use volatix_core::{LockedStorage, StorageValue, StorageOptions};
+    /// use volatix_core::{LockedStorage, StorageOptions, StorageValue};
     ///
     /// let mut storage = LockedStorage::new(StorageOptions::default());
     ///
-    /// storage.insert_with_ttl(
-    ///     "session:abc123".to_string(),
-    ///     StorageValue::Text("user_data".to_string()),
-    ///     Duration::from_secs(3600) // 1 hour TTL
-    /// ).unwrap();
+    /// assert_eq!(storage.increment_by("visits", 5), Ok(5));
+    /// assert_eq!(storage.increment_by("visits", -2), Ok(3));
+    ///
+    /// storage.insert_entry("max".to_string(), StorageValue::Int(i64::MAX)).unwrap();
+    /// assert!(storage.increment_by("max", 1).is_err());
     /// ```
-    pub fn insert_with_ttl(
-        &mut self,
-        key: String,
-        value: StorageValue,
-        ttl: Duration,
-    ) -> Result<(), String> {
-        let entry_size = value.size_in_bytes();
-        let now = SystemTime::now();
+    pub fn increment_by(&mut self, key: &str, delta: i64) -> Result<i64, String> {
+        self.update_number(key, StorageValue::Int, |current| match current {
+            None => Ok(delta),
+            Some(StorageValue::Int(n)) => n
+                .checked_add(delta)
+                .ok_or_else(|| "Increment or decrement would overflow".to_string()),
+            Some(_) => Err("Value is not an integer".to_string()),
+        })
+    }
 
-        // Handle automatic compression for large text values
-        let (value, compressed) = {
-            if self.options.compression
-                && let StorageValue::Text(text) = &value
-                && entry_size > self.options.compression_threshold
-            {
-                let bytes = compress(text).map_err(|err| format!("Compression error: {err}"))?;
-                (StorageValue::Bytes(bytes), true)
+    /// Adds `delta` to a numeric value, creating missing keys at 0.
+    /// Integer values are converted to floats.
+    ///
+    /// # Arguments
+    /// * `key` - The key containing an integer or float value
+    /// * `delta` - Amount to add, negative to subtract
+    ///
+    /// # Returns
+    /// The new value, or an error if the value is not a number or the
+    /// result is NaN or infinite
+    ///
+    /// # Example
+    /// ```rust
+    /// use volatix_core::{LockedStorage, StorageOptions, StorageValue};
+    ///
+    /// let mut storage = LockedStorage::new(StorageOptions::default());
+    /// storage.insert_entry("price".to_string(), StorageValue::Int(10)).unwrap();
+    ///
+    /// assert_eq!(storage.increment_by_float("price", 0.5), Ok(10.5));
+    /// ```
+    pub fn increment_by_float(&mut self, key: &str, delta: f64) -> Result<f64, String> {
+        let number = |current: Option<&StorageValue>| match current {
+            None => Ok(0.0),
+            Some(StorageValue::Int(n)) => Ok(*n as f64),
+            Some(StorageValue::Float(f)) => Ok(*f),
+            Some(_) => Err("Value is not a number".to_string()),
+        };
+
+        self.update_number(key, StorageValue::Float, |current| {
+            let result = number(current)? + delta;
+            if result.is_finite() {
+                Ok(result)
             } else {
-                (value, false)
+                Err("Increment would produce NaN or Infinity".to_string())
             }
-        };
+        })
+    }
 
-        // Create the storage entry with all metadata
-        let entry = StorageEntry {
-            value,
-            created_at: now,
-            last_accessed: now,
-            access_count: 0,
-            entry_size,
-            ttl,
-            compressed,
+    /// Replaces a numeric value with the result of `update`, which receives
+    /// the current value, or `None` if the key is missing or expired.
+    /// Missing keys are inserted with the default TTL.
+    fn update_number<T: Copy>(
+        &mut self,
+        key: &str,
+        wrap: fn(T) -> StorageValue,
+        update: impl FnOnce(Option<&StorageValue>) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let (result, existed) = {
+            let mut store = self.store.write();
+            match store.get_mut(key) {
+                Some(entry) if !entry.is_expired() => {
+                    // Compressed entries hold text, never a number
+                    let current = (!entry.compressed).then_some(&entry.value);
+                    let result = update(Some(current.unwrap_or(&StorageValue::Null)))?;
+                    entry.value = wrap(result);
+                    entry.version = self.next_version();
+                    entry.last_accessed = SystemTime::now();
+                    entry.access_count += 1;
+                    (result, true)
+                }
+                _ => (update(None)?, false),
+            }
         };
 
-        // Insert the entry and update statistics
-        // Check if we need to make room for this entry
-        if self.is_full() {
-            // Evict 10% of the entries
-            self.evict_entries(0);
+        if existed {
+            self.stats.hits.fetch_add(1, Ordering::Relaxed);
+            self.record_access(key);
+            self.is_dirty.store(true, Ordering::Relaxed);
+        } else {
+            self.stats.misses.fetch_add(1, Ordering::Relaxed);
+            self.insert_entry(key.to_string(), wrap(result))?;
         }
-        self.store.write().insert(key, entry);
-        self.stats.total_entries.fetch_add(1, Ordering::Relaxed);
-        self.is_dirty.store(true, Ordering::Relaxed);
-        self.entry_count.fetch_add(1, Ordering::Relaxed);
-        Ok(())
+        Ok(result)
     }
 
-    /// Extends or reduces the TTL of an existing entry.
-    /// Can add or subtract time from the current TTL.
+    /// Appends text to a string value, creating missing keys.
     ///
     /// # Arguments
-    /// * `key` - The key to modify
-    /// * `additional_time` - Seconds to add (positive) or subtract (negative)
+    /// * `key` - The key containing a text or bytes value
+    /// * `value` - The text to append
     ///
     /// # Returns
-    /// `Ok(())` on success, `Err("UNALTERED")` if the operation would make TTL negative
+    /// The length of the string in bytes after the append, or an error if the
+    /// value is not a string
     ///
     /// # Example
     /// ```rust
@@ -714,629 +1725,3589 @@ impl LockedStorage {
     ///
     /// let mut storage = LockedStorage::new(StorageOptions::default());
     ///
-    /// // Add 30 minutes
-    /// storage.extend_ttl("session:123", 1800).unwrap();
-    ///
-    /// // Subtract 10 minutes (but don't go negative)
-    /// storage.extend_ttl("session:123", -600).unwrap();
+    /// assert_eq!(storage.append("log", "started;"), Ok(8));
+    /// assert_eq!(storage.append("log", "stopped;"), Ok(16));
     /// ```
-    pub fn extend_ttl(&mut self, key: &str, additional_time: i64) -> Result<(), String> {
-        if let Some(entry) = self.store.write().get_mut(key) {
-            if additional_time < 0 {
-                // Prevent TTL from going negative
-                if additional_time.unsigned_abs() > entry.ttl.as_secs() {
-                    return Err("UNALTERED".to_string());
-                }
+    pub fn append(&mut self, key: &str, value: &str) -> Result<usize, String> {
+        self.update_string(key, true, |bytes| {
+            bytes.extend_from_slice(value.as_bytes());
+            Ok(bytes.len())
+        })
+    }
 
-                entry.ttl -= Duration::from_secs(additional_time.unsigned_abs());
-            } else {
-                entry.ttl += Duration::from_secs(additional_time as u64);
-            }
-            // Update access metadata
-            entry.access_count += 1;
-            entry.last_accessed = SystemTime::now();
+    /// Overwrites part of a string value starting at `offset`, creating
+    /// missing keys. The string is padded with zero bytes if it is shorter
+    /// than `offset`.
+    ///
+    /// # Arguments
+    /// * `key` - The key containing a text or bytes value
+    /// * `offset` - Byte offset to start writing at
+    /// * `value` - The text to write
+    ///
+    /// # Returns
+    /// The length of the string in bytes after the write, or an error if the
+    /// value is not a string or would grow larger than 512MB
+    ///
+    /// # Example
+    /// ```rust
+    /// use volatix_core::{LockedStorage, StorageOptions, StorageValue};
+    ///
+    /// let mut storage = LockedStorage::new(StorageOptions::default());
+    /// storage.insert_entry("greeting".to_string(), StorageValue::Text("Hello World".to_string())).unwrap();
+    ///
+    /// assert_eq!(storage.set_range("greeting", 6, "Redis"), Ok(11));
+    /// assert_eq!(storage.get_range("greeting", 0, -1).unwrap(), b"Hello Redis");
+    /// ```
+    pub fn set_range(&mut self, key: &str, offset: usize, value: &str) -> Result<usize, String> {
+        let end = offset
+            .checked_add(value.len())
+            .filter(|&end| end <= MAX_STRING_SIZE)
+            .ok_or_else(|| "String exceeds maximum allowed size".to_string())?;
+
+        // Writing nothing leaves the key as is, it is not created either
+        if value.is_empty() {
+            return self.string_length(key);
         }
 
-        Ok(())
+        self.update_string(key, true, |bytes| {
+            if bytes.len() < end {
+                bytes.resize(end, 0);
+            }
+            bytes[offset..end].copy_from_slice(value.as_bytes());
+            Ok(bytes.len())
+        })
     }
 
-    /// Gets the remaining TTL for a key.
+    /// Gets the length of a string value.
     ///
     /// # Arguments
-    /// * `key` - The key to check
+    /// * `key` - The key containing a text or bytes value
     ///
     /// # Returns
-    /// `Some(Duration)` if the key exists, `None` otherwise
-    pub fn time_to_live(&self, key: &str) -> Option<Duration> {
-        self.get_entry(key).map(|e| e.ttl)
+    /// The length of the (uncompressed) string in bytes, 0 for missing keys,
+    /// or an error if the value is not a string
+    pub fn string_length(&self, key: &str) -> Result<usize, String> {
+        match self.get_entry(key) {
+            Some(entry) => string_bytes(&entry.value).map(|bytes| bytes.len()),
+            None => Ok(0),
+        }
     }
 
-    /// Checks if the cache is at capacity.
-    /// Used internally to decide when eviction is needed.
-    pub fn is_full(&self) -> bool {
-        self.entry_count.load(Ordering::Relaxed) >= self.options.max_capacity
+    /// Gets a substring of a string value.
+    /// Negative offsets count from the end of the string, -1 being the last
+    /// byte. Out of range offsets are clamped to the string.
+    ///
+    /// # Arguments
+    /// * `key` - The key containing a text or bytes value
+    /// * `start` - Offset of the first byte, inclusive
+    /// * `end` - Offset of the last byte, inclusive
+    ///
+    /// # Returns
+    /// The bytes in the range (empty for missing keys and empty ranges), or an
+    /// error if the value is not a string
+    pub fn get_range(&self, key: &str, start: i64, end: i64) -> Result<Vec<u8>, String> {
+        let Some(entry) = self.get_entry(key) else {
+            return Ok(Vec::new());
+        };
+        let bytes = string_bytes(&entry.value)?;
+
+        match resolve_range(bytes.len(), start, end) {
+            Some(range) => Ok(bytes[range].to_vec()),
+            None => Ok(Vec::new()),
+        }
     }
 
-    /// Checks whether storage has any unsyched changes to disk
-    pub fn should_flush(&self) -> bool {
-        self.is_dirty.load(Ordering::Relaxed)
+    /// Sets or clears a single bit of a bytes value, creating missing keys.
+    /// The value grows with zero bytes when the offset is past its end.
+    /// Bit 0 is the most significant bit of the first byte.
+    ///
+    /// # Arguments
+    /// * `key` - The key containing a bitmap
+    /// * `offset` - Offset of the bit
+    /// * `bit` - The new value of the bit
+    ///
+    /// # Returns
+    /// The previous value of the bit, or an error if the value is not a
+    /// string or would grow larger than 512MB
+    ///
+    /// # Example
+    /// ```rust
+    /// use volatix_core::{LockedStorage, StorageOptions};
+    ///
+    /// let mut storage = LockedStorage::new(StorageOptions::default());
+    ///
+    /// // User 42 was active today
+    /// assert_eq!(storage.set_bit("active:today", 42, true), Ok(false));
+    /// assert_eq!(storage.get_bit("active:today", 42), Ok(true));
+    /// assert_eq!(storage.bit_count("active:today", None), Ok(1));
+    /// ```
+    pub fn set_bit(&mut self, key: &str, offset: usize, bit: bool) -> Result<bool, String> {
+        let byte = offset / 8;
+        if byte >= MAX_STRING_SIZE {
+            return Err("Bit offset is out of range".to_string());
+        }
+        let mask = 0x80 >> (offset % 8);
+
+        self.update_string(key, false, |bytes| {
+            if bytes.len() <= byte {
+                bytes.resize(byte + 1, 0);
+            }
+
+            let old = bytes[byte] & mask != 0;
+            if bit {
+                bytes[byte] |= mask;
+            } else {
+                bytes[byte] &= !mask;
+            }
+            Ok(old)
+        })
     }
 
-    /// Flip the state of the is_dirty storage flag
-    pub fn toggle_dirty_flag(&mut self) {
-        self.is_dirty.fetch_not(Ordering::Relaxed);
+    /// Gets a single bit of a bytes value.
+    ///
+    /// # Arguments
+    /// * `key` - The key containing a bitmap
+    /// * `offset` - Offset of the bit
+    ///
+    /// # Returns
+    /// The bit (`false` past the end of the value and for missing keys), or
+    /// an error if the value is not a string
+    pub fn get_bit(&self, key: &str, offset: usize) -> Result<bool, String> {
+        let Some(entry) = self.get_entry(key) else {
+            return Ok(false);
+        };
+        let bytes = string_bytes(&entry.value)?;
+
+        Ok(bytes
+            .get(offset / 8)
+            .is_some_and(|byte| byte & (0x80 >> (offset % 8)) != 0))
     }
 
-    /// Checks the current maximum capacity of the storage
-    pub fn max_capacity(&self) -> usize {
-        self.options.max_capacity
+    /// Counts the set bits of a bytes value.
+    ///
+    /// # Arguments
+    /// * `key` - The key containing a bitmap
+    /// * `range` - Inclusive byte range to count in, negative offsets count
+    ///   from the end. `None` counts the whole value
+    ///
+    /// # Returns
+    /// The number of set bits (0 for missing keys), or an error if the value
+    /// is not a string
+    pub fn bit_count(&self, key: &str, range: Option<(i64, i64)>) -> Result<usize, String> {
+        let Some(entry) = self.get_entry(key) else {
+            return Ok(0);
+        };
+        let bytes = string_bytes(&entry.value)?;
+
+        let bytes = match range {
+            Some((start, end)) => match resolve_range(bytes.len(), start, end) {
+                Some(range) => &bytes[range],
+                None => return Ok(0),
+            },
+            None => &bytes[..],
+        };
+        Ok(popcount(bytes))
     }
 
-    /// Removes all expired entries from the cache.
-    /// Called automatically during eviction and can be called manually.
-    /// Updates statistics to track expired removals.
-    pub fn remove_expired(&mut self) {
-        let prev_count = self.store.read().len();
-        let now = SystemTime::now();
-        {
-            // Remove entries that have exceeded their TTL
-            self.store.write().retain(|_, value| {
-                now.duration_since(value.created_at)
-                    .expect("clock gone backwards")
-                    < value.ttl
-            });
+    /// Finds the first bit set to `bit` in a bytes value.
+    ///
+    /// # Arguments
+    /// * `key` - The key containing a bitmap
+    /// * `bit` - The bit value to look for
+    /// * `start` - Byte offset to start looking at, negative counts from the end
+    /// * `end` - Last byte to look at (inclusive), `None` for the end of the value
+    ///
+    /// # Returns
+    /// The bit offset, or -1 if there is no such bit. When looking for a
+    /// clear bit without an `end`, the bytes past the value count as clear,
+    /// so the bit right after the value is returned. Errors if the value is
+    /// not a string
+    pub fn bit_pos(
+        &self,
+        key: &str,
+        bit: bool,
+        start: i64,
+        end: Option<i64>,
+    ) -> Result<i64, String> {
+        let entry = self.get_entry(key);
+        let bytes = match &entry {
+            Some(entry) => string_bytes(&entry.value)?,
+            None => Cow::Borrowed(&[][..]),
+        };
+
+        if let Some(range) = resolve_range(bytes.len(), start, end.unwrap_or(-1)) {
+            let first = *range.start();
+            for (i, &byte) in bytes[range].iter().enumerate() {
+                // Look for a set bit either way
+                let byte = if bit { byte } else { !byte };
+                if byte != 0 {
+                    return Ok(((first + i) * 8 + byte.leading_zeros() as usize) as i64);
+                }
+            }
         }
-        let current_count = self.store.read().len();
-        let removed = prev_count - current_count;
 
-        // Update statistics
-        self.stats
-            .expired_removals
-            .fetch_add(removed, Ordering::Release);
-        self.stats
-            .total_entries
-            .store(current_count, Ordering::Release);
-        self.is_dirty.store(true, Ordering::Relaxed);
+        if !bit && end.is_none() {
+            return Ok(bytes.len() as i64 * 8);
+        }
+        Ok(-1)
     }
 
-    /// Performs eviction based on the configured eviction policy.
-    /// First removes expired entries, then applies the eviction policy.
-    /// If the specified count is greater than or equal to the
-    /// [`Self::entry_count`] the storage is flushed.
-    /// If count is zero, 10% of total entries is evicted.
-    pub fn evict_entries(&mut self, count: usize) {
-        let s = self.entry_count.load(Ordering::Relaxed);
-        if count >= s {
-            self.flush();
-            return;
+    /// Combines bitmaps bit by bit and stores the result in `destination`.
+    /// Shorter and missing values are treated as zero padded to the longest
+    /// one. An empty result deletes `destination`.
+    ///
+    /// # Arguments
+    /// * `op` - The operation, NOT takes exactly one source key
+    /// * `destination` - The key to store the result in
+    /// * `keys` - The source keys
+    ///
+    /// # Returns
+    /// The length of the result in bytes, or an error if a value is not a
+    /// string or the number of keys is wrong
+    ///
+    /// # Example
+    /// ```rust
+    /// use volatix_core::{BitOp, LockedStorage, StorageOptions};
+    ///
+    /// let mut storage = LockedStorage::new(StorageOptions::default());
+    /// storage.set_bit("active:mon", 1, true).unwrap();
+    /// storage.set_bit("active:tue", 1, true).unwrap();
+    /// storage.set_bit("active:tue", 2, true).unwrap();
+    ///
+    /// // Users active on both days
+    /// storage.bit_op(BitOp::And, "active:both", &["active:mon", "active:tue"]).unwrap();
+    /// assert_eq!(storage.bit_count("active:both", None), Ok(1));
+    /// ```
+    pub fn bit_op<S: AsRef<str>>(
+        &mut self,
+        op: BitOp,
+        destination: &str,
+        keys: &[S],
+    ) -> Result<usize, String> {
+        match (op, keys.len()) {
+            (_, 0) => return Err("BITOP requires at least one source key".to_string()),
+            (BitOp::Not, 2..) => return Err("BITOP NOT requires a single source key".to_string()),
+            _ => {}
         }
 
-        self.remove_expired();
+        let sources = keys
+            .iter()
+            .map(|key| match self.get_entry(key.as_ref()) {
+                Some(entry) => string_bytes(&entry.value).map(Cow::into_owned),
+                None => Ok(Vec::new()),
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let len = sources.iter().map(Vec::len).max().unwrap_or(0);
+        let mut result = sources[0].clone();
+        result.resize(len, 0);
+        for source in &sources[1..] {
+            for (i, byte) in result.iter_mut().enumerate() {
+                let other = source.get(i).copied().unwrap_or(0);
+                match op {
+                    BitOp::And => *byte &= other,
+                    BitOp::Or => *byte |= other,
+                    BitOp::Xor => *byte ^= other,
+                    BitOp::Not => unreachable!("NOT has a single source"),
+                }
+            }
+        }
+        if op == BitOp::Not {
+            result.iter_mut().for_each(|byte| *byte = !*byte);
+        }
 
-        let count = if count == 0 {
-            (10.0 / 100.0 * s as f64).ceil() as usize
+        if result.is_empty() {
+            self.remove_entry(destination);
         } else {
-            count
-        };
-
-        let oldest_metric = |_k: &String, v: &StorageEntry| v.created_at;
-        let lru_metric = |_k: &String, v: &StorageEntry| v.last_accessed;
-        let lfu_metric = |_k: &String, v: &StorageEntry| v.access_count;
-        let largest_metric = |_k: &String, v: &StorageEntry| -(v.entry_size as i64); // Invert the metric
-
-        match self.options.eviction_policy {
-            EvictionPolicy::Oldest => self.remove_n_entries(count, oldest_metric),
-            EvictionPolicy::LRU => self.remove_n_entries(count, lru_metric),
-            EvictionPolicy::LFU => self.remove_n_entries(count, lfu_metric),
-            EvictionPolicy::SizeAware => self.remove_n_entries(count, largest_metric),
+            self.insert_entry(destination.to_string(), StorageValue::Bytes(result))?;
         }
+        Ok(len)
     }
 
-    /// Removes the least n entries by a metric.
-    /// Removes the least recently used n entries (LRU eviction policy).
-    /// Removes the least frequently used n entries (LFU eviction policy).
-    /// Removes the oldest n entries by their creation time (Oldest eviction policy).
-    /// Removes the largest n entries by their size (Size-aware eviction policy).
-    pub fn remove_n_entries<F, M>(&mut self, n: usize, mut metric: F)
-    where
-        M: Ord + Copy,
-        F: FnMut(&String, &StorageEntry) -> M,
-    {
-        let mut heap: BinaryHeap<(M, String)> = BinaryHeap::with_capacity(n);
-        {
-            let store = self.store.read();
-            for (k, v) in store.iter() {
-                let m = metric(k, v);
-                if heap.len() < n {
-                    heap.push((m, k.clone()));
-                } else if let Some((top_m, _)) = heap.peek()
-                    && m < *top_m
-                // Keep smallest by metric
-                {
-                    heap.pop();
-                    heap.push((m, k.clone()));
-                }
-            }
-        }
-        let keys = heap.into_iter().map(|(_, k)| k).collect::<Vec<String>>();
-        self.remove_entries(&keys);
-        self.stats.evictions.fetch_add(n, Ordering::Relaxed);
+    /// Adds elements to the HyperLogLog sketch stored at `key`.
+    /// A missing key is created with an empty sketch, even without elements.
+    ///
+    /// # Arguments
+    /// * `key` - The key holding the sketch
+    /// * `elements` - The elements to count
+    ///
+    /// # Returns
+    /// `true` if the key was created or its estimate may have changed,
+    /// or an error if the key holds another type
+    ///
+    /// # Example
+    /// ```rust
+    /// use volatix_core::LockedStorage;
+    ///
+    /// let mut storage = LockedStorage::default();
+    /// assert!(storage.pf_add("visitors", &["alice", "bob"]).unwrap());
+    /// assert!(!storage.pf_add("visitors", &["alice"]).unwrap());
+    /// assert_eq!(storage.pf_count(&["visitors"]).unwrap(), 2);
+    /// ```
+    pub fn pf_add<S: AsRef<str>>(&mut self, key: &str, elements: &[S]) -> Result<bool, String> {
+        let mut created = false;
+        let create = || {
+            created = true;
+            Ok(StorageValue::HyperLogLog(HyperLogLog::new()))
+        };
+
+        let changed = self.update_in_place(key, create, |value| {
+            let StorageValue::HyperLogLog(hll) = value else {
+                return Err("Value is not a HyperLogLog".to_string());
+            };
+            let changed = elements
+                .iter()
+                .fold(false, |changed, e| hll.add(e.as_ref().as_bytes()) | changed);
+            Ok((changed, changed))
+        })?;
+        Ok(changed || created)
     }
 
-    /// Renames an existing key to a new name.
-    /// The old key is removed and a new entry is created with the same value.
-    /// Updates access statistics.
+    /// Estimates the number of distinct elements added to the sketches at
+    /// `keys`. With several keys the cardinality of their union is returned.
+    /// Missing keys count as empty sketches.
     ///
     /// # Arguments
-    /// * `old_key` - The current key name
-    /// * `new_key` - The new key name
+    /// * `keys` - The keys holding the sketches
+    ///
+    /// # Returns
+    /// The estimated cardinality, or an error if a key holds another type
+    pub fn pf_count<S: AsRef<str>>(&self, keys: &[S]) -> Result<u64, String> {
+        Ok(self.merged_sketch(keys)?.count())
+    }
+
+    /// Merges the sketches at `sources` into `destination`, creating it
+    /// if needed. An existing destination sketch is part of the union and
+    /// keeps its expiry.
+    ///
+    /// # Arguments
+    /// * `destination` - The key receiving the union
+    /// * `sources` - The keys holding the sketches to merge
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or an error if a key holds another type
     ///
     /// # Example
     /// ```rust
-    /// use volatix_core::{StorageOptions, LockedStorage};
+    /// use volatix_core::LockedStorage;
     ///
-    /// let mut storage = LockedStorage::new(StorageOptions::default());
-    /// storage.rename_entry("old_name", "new_name");
+    /// let mut storage = LockedStorage::default();
+    /// storage.pf_add("mon", &["alice", "bob"]).unwrap();
+    /// storage.pf_add("tue", &["bob", "carol"]).unwrap();
+    ///
+    /// storage.pf_merge("week", &["mon", "tue"]).unwrap();
+    /// assert_eq!(storage.pf_count(&["week"]).unwrap(), 3);
     /// ```
-    pub fn rename_entry(&mut self, old_key: &str, new_key: &str) {
-        if let Some((_, mut entry)) = self.store.write().remove_entry(old_key) {
-            // Update access metadata
-            entry.access_count += 1;
-            entry.last_accessed = SystemTime::now();
-            self.stats.hits.fetch_add(1, Ordering::Relaxed);
+    pub fn pf_merge<S: AsRef<str>>(
+        &mut self,
+        destination: &str,
+        sources: &[S],
+    ) -> Result<(), String> {
+        let mut hll = self.merged_sketch(sources)?;
+        match self.get_entry(destination) {
+            Some(StorageEntry {
+                value: StorageValue::HyperLogLog(current),
+                expires_at,
+                ..
+            }) => {
+                hll.merge(&current);
+                let value = StorageValue::HyperLogLog(hll);
+                self.insert_with_expiry(destination.to_string(), value, expires_at)
+            }
+            Some(_) => Err("Value is not a HyperLogLog".to_string()),
+            None => self.insert_entry(destination.to_string(), StorageValue::HyperLogLog(hll)),
+        }
+    }
 
-            // Insert with new key
-            self.store.write().insert(new_key.to_string(), entry);
-            return;
+    /// Builds the union of the HyperLogLog sketches stored at `keys`.
+    fn merged_sketch<S: AsRef<str>>(&self, keys: &[S]) -> Result<HyperLogLog, String> {
+        let mut merged = HyperLogLog::new();
+        for key in keys {
+            self.read_in_place(key.as_ref(), |value| match value {
+                Some(StorageValue::HyperLogLog(hll)) => {
+                    merged.merge(hll);
+                    Ok(())
+                }
+                Some(_) => Err("Value is not a HyperLogLog".to_string()),
+                None => Ok(()),
+            })?;
         }
-        self.stats.misses.fetch_add(1, Ordering::Relaxed);
+        Ok(merged)
     }
 
-    /// Gets a snapshot of the current statistics.
-    /// Returns non-atomic copies of the atomic values.
+    /// Creates an empty Bloom filter at `key`.
+    ///
+    /// # Arguments
+    /// * `key` - The key to create
+    /// * `error_rate` - Wanted false positive rate, between 0 and 1 exclusive
+    /// * `capacity` - Number of items expected before the filter grows
     ///
     /// # Returns
-    /// `StorageStats` with current values
-    pub fn get_stats(&self) -> StorageStats {
-        StorageStats {
-            total_entries: self.stats.total_entries.load(Ordering::Relaxed).into(),
-            hits: self.stats.hits.load(Ordering::Relaxed).into(),
-            misses: self.stats.misses.load(Ordering::Relaxed).into(),
-            evictions: self.stats.evictions.load(Ordering::Relaxed).into(),
-            expired_removals: self.stats.expired_removals.load(Ordering::Relaxed).into(),
+    /// `Ok(())` on success, or an error if the key exists or a parameter is
+    /// out of range
+    ///
+    /// # Example
+    /// ```rust
+    /// use volatix_core::LockedStorage;
+    ///
+    /// let mut storage = LockedStorage::default();
+    /// storage.bf_reserve("seen", 0.001, 10_000).unwrap();
+    /// assert_eq!(storage.bf_add("seen", &["a", "b", "a"]), Ok(vec![true, true, false]));
+    /// assert_eq!(storage.bf_exists("seen", &["a", "c"]), Ok(vec![true, false]));
+    /// ```
+    pub fn bf_reserve(
+        &mut self,
+        key: &str,
+        error_rate: f64,
+        capacity: usize,
+    ) -> Result<(), String> {
+        if self.key_exists(key) {
+            return Err("Key already exists".to_string());
         }
+        let filter = BloomFilter::new(error_rate, capacity)?;
+        self.insert_entry(key.to_string(), StorageValue::BloomFilter(filter))
     }
 
-    /// Resets all statistics to zero.
-    /// Used by the RESETSTATS command.
-    pub fn reset_stats(&mut self) {
-        let _old_stats = std::mem::take(&mut self.stats);
+    /// Adds items to the Bloom filter at `key`. A missing key is created
+    /// with a filter for 100 items and a 1% error rate.
+    ///
+    /// # Returns
+    /// For each item, `true` if it was added and `false` if it was possibly
+    /// added before, or an error if the key holds another type
+    pub fn bf_add<S: AsRef<str>>(&mut self, key: &str, items: &[S]) -> Result<Vec<bool>, String> {
+        let create = || Ok(StorageValue::BloomFilter(BloomFilter::default()));
+        self.update_in_place(key, create, |value| {
+            let StorageValue::BloomFilter(filter) = value else {
+                return Err("Value is not a Bloom filter".to_string());
+            };
+            let added: Vec<bool> = items
+                .iter()
+                .map(|item| filter.add(item.as_ref().as_bytes()))
+                .collect();
+            let changed = added.contains(&true);
+            Ok((added, changed))
+        })
     }
 
-    /// Retrieves a configuration entry by key name.
-    /// Used by the CONFGET command.
+    /// Checks whether items were possibly added to the Bloom filter at
+    /// `key`. Nothing was added to a missing key.
+    ///
+    /// # Returns
+    /// For each item, whether it was possibly added, or an error if the key
+    /// holds another type
+    pub fn bf_exists<S: AsRef<str>>(&self, key: &str, items: &[S]) -> Result<Vec<bool>, String> {
+        self.read_in_place(key, |value| match value {
+            Some(StorageValue::BloomFilter(filter)) => Ok(items
+                .iter()
+                .map(|item| filter.contains(item.as_ref().as_bytes()))
+                .collect()),
+            Some(_) => Err("Value is not a Bloom filter".to_string()),
+            None => Ok(vec![false; items.len()]),
+        })
+    }
+
+    /// Creates an empty cuckoo filter at `key`.
     ///
     /// # Arguments
-    /// * `key` - Configuration key name (case insensitive)
+    /// * `key` - The key to create
+    /// * `capacity` - Number of items the filter can hold
     ///
     /// # Returns
-    /// `Some(ConfigEntry)` if the key exists, `None` otherwise
-    pub fn get_config_entry(&self, key: &str) -> Option<ConfigEntry> {
-        match key.to_uppercase().as_str() {
-            "EVICTPOLICY" => Some(ConfigEntry::EvictPolicy(self.options.eviction_policy)),
-            "MAXCAP" => Some(ConfigEntry::MaxCapacity(self.options.max_capacity)),
-            "GLOBALTTL" => Some(ConfigEntry::GlobalTtl(self.options.ttl.as_secs() as usize)),
-            "COMPRESSION" => Some(ConfigEntry::Compression(self.options.compression.into())),
-            "COMPRESSIONTHRESHOLD" => Some(ConfigEntry::CompressionThreshold(
-                self.options.compression_threshold,
-            )),
-            _ => None,
+    /// `Ok(())` on success, or an error if the key exists or the capacity
+    /// is 0
+    ///
+    /// # Example
+    /// ```rust
+    /// use volatix_core::LockedStorage;
+    ///
+    /// let mut storage = LockedStorage::default();
+    /// storage.cf_reserve("jobs", 1000).unwrap();
+    /// assert_eq!(storage.cf_add("jobs", "job:1", true), Ok(true));
+    /// assert_eq!(storage.cf_add("jobs", "job:1", true), Ok(false));
+    /// assert_eq!(storage.cf_delete("jobs", "job:1"), Ok(true));
+    /// assert_eq!(storage.cf_exists("jobs", "job:1"), Ok(false));
+    /// ```
+    pub fn cf_reserve(&mut self, key: &str, capacity: usize) -> Result<(), String> {
+        if self.key_exists(key) {
+            return Err("Key already exists".to_string());
         }
+        let filter = CuckooFilter::new(capacity)?;
+        self.insert_entry(key.to_string(), StorageValue::CuckooFilter(filter))
     }
 
-    /// Updates a configuration setting.
-    /// Used by the CONFSET command.
+    /// Adds an item to the cuckoo filter at `key`. A missing key is created
+    /// with a filter for 1024 items.
     ///
     /// # Arguments
-    /// * `entry` - The configuration entry to update
-    pub fn set_config_entry(&mut self, entry: &ConfigEntry) {
-        match entry {
-            ConfigEntry::EvictPolicy(p) => self.options.eviction_policy = *p,
-            ConfigEntry::GlobalTtl(t) => self.options.ttl = Duration::from_secs(*t as u64),
-            ConfigEntry::MaxCapacity(c) => self.options.max_capacity = *c,
-            ConfigEntry::Compression(b) => {
-                self.options.compression = <Compression as Into<bool>>::into(*b)
+    /// * `key` - The key holding the filter
+    /// * `item` - The item to add
+    /// * `if_absent` - Skip items that are possibly in the filter already,
+    ///   otherwise an item added twice must be deleted twice
+    ///
+    /// # Returns
+    /// Whether the item was added, or an error if the filter is full or the
+    /// key holds another type
+    pub fn cf_add(&mut self, key: &str, item: &str, if_absent: bool) -> Result<bool, String> {
+        let create = || Ok(StorageValue::CuckooFilter(CuckooFilter::default()));
+        self.update_in_place(key, create, |value| {
+            let StorageValue::CuckooFilter(filter) = value else {
+                return Err("Value is not a cuckoo filter".to_string());
+            };
+            if if_absent && filter.contains(item.as_bytes()) {
+                return Ok((false, false));
             }
-            ConfigEntry::CompressionThreshold(s) => self.options.compression_threshold = *s,
-        }
+            filter.add(item.as_bytes())?;
+            Ok((true, true))
+        })
     }
 
-    /// Gets a copy of the current storage options.
+    /// Checks whether an item was possibly added to the cuckoo filter at
+    /// `key`. Nothing was added to a missing key.
     ///
     /// # Returns
-    /// Current `StorageOptions` configuration
-    pub fn get_options(&self) -> StorageOptions {
-        self.options
+    /// Whether the item was possibly added, or an error if the key holds
+    /// another type
+    pub fn cf_exists(&self, key: &str, item: &str) -> Result<bool, String> {
+        self.read_in_place(key, |value| match value {
+            Some(StorageValue::CuckooFilter(filter)) => Ok(filter.contains(item.as_bytes())),
+            Some(_) => Err("Value is not a cuckoo filter".to_string()),
+            None => Ok(false),
+        })
     }
 
-    /// Resets the current storage options to the default value
-    /// provided by `StorageOptions::default()`
-    pub fn reset_options(&mut self) {
-        std::mem::take(&mut self.options);
+    /// Deletes one copy of an item from the cuckoo filter at `key`.
+    ///
+    /// # Returns
+    /// Whether the item was found and deleted, or an error if the key is
+    /// missing or holds another type
+    pub fn cf_delete(&mut self, key: &str, item: &str) -> Result<bool, String> {
+        let create = || Err("Key does not exist".to_string());
+        self.update_in_place(key, create, |value| {
+            let StorageValue::CuckooFilter(filter) = value else {
+                return Err("Value is not a cuckoo filter".to_string());
+            };
+            let deleted = filter.delete(item.as_bytes());
+            Ok((deleted, deleted))
+        })
     }
 
-    /// Loads storage data from disk.
-    /// Used during startup to restore cache state from previous runs.
+    /// Pushes values onto the head (`front`) or the tail of the list at
+    /// `key`, creating missing keys. Values pushed onto the head end up in
+    /// reverse order, as if they were pushed one at a time.
     ///
     /// # Arguments
-    /// * `path` - Path to the storage file
+    /// * `key` - The key holding the list
+    /// * `values` - The values to push
+    /// * `front` - Push onto the head instead of the tail
     ///
     /// # Returns
-    /// `anyhow::Result<()>` - Success or error details
+    /// The length of the list after the push, or an error if the key holds
+    /// another type
     ///
     /// # Example
     /// ```rust
-    /// use volatix_core::{StorageOptions, LockedStorage};
-    /// use std::path::Path;
+    /// use volatix_core::{LockedStorage, StorageValue};
     ///
-    /// let mut storage = LockedStorage::new(StorageOptions::default());
+    /// let mut storage = LockedStorage::default();
+    /// let jobs = vec![StorageValue::Text("a".to_string()), StorageValue::Text("b".to_string())];
     ///
-    /// let db_path = Path::new("cache.bin").to_path_buf();
-    /// storage.load_from_disk(&db_path).expect("Failed to load cache");
+    /// assert_eq!(storage.list_push("jobs", jobs, false), Ok(2));
+    /// assert_eq!(storage.list_pop("jobs", true), Ok(Some(StorageValue::Text("a".to_string()))));
+    /// assert_eq!(storage.list_len("jobs"), Ok(1));
     /// ```
-    pub fn load_from_disk(&mut self, path: &PathBuf) -> anyhow::Result<()> {
-        let path = Path::new(path);
-        if !path.exists() {
-            File::create(path)?;
-            return Ok(()); // No existing data to load
-        }
+    pub fn list_push(
+        &mut self,
+        key: &str,
+        values: Vec<StorageValue>,
+        front: bool,
+    ) -> Result<usize, String> {
+        let create = || Ok(StorageValue::List(Vec::new()));
+        self.update_in_place(key, create, |value| {
+            let StorageValue::List(list) = value else {
+                return Err("Value is not a list".to_string());
+            };
+            if front {
+                list.splice(0..0, values.into_iter().rev());
+            } else {
+                list.extend(values);
+            }
+            Ok((list.len(), true))
+        })
+    }
 
-        let file = File::open(path).context("open db")?;
-        let mut reader = BufReader::new(file);
+    /// Removes and returns the first (`front`) or the last value of the list
+    /// at `key`. The key is deleted once its list is empty.
+    ///
+    /// # Arguments
+    /// * `key` - The key holding the list
+    /// * `front` - Pop from the head instead of the tail
+    ///
+    /// # Returns
+    /// The popped value, `None` for a missing key, or an error if the key
+    /// holds another type
+    pub fn list_pop(&mut self, key: &str, front: bool) -> Result<Option<StorageValue>, String> {
+        self.record_access(key);
 
-        // Deserialize the storage data
-        let loaded_storage: SerializableStorage = bincode2::deserialize_from(&mut reader)
-            .context("deserialize from buffer")
-            .unwrap_or_default();
+        let mut store = self.store.write();
+        let Some(entry) = store.get_mut(key).filter(|entry| !entry.is_expired()) else {
+            self.stats.misses.fetch_add(1, Ordering::Relaxed);
+            return Ok(None);
+        };
+        let StorageValue::List(list) = &mut entry.value else {
+            return Err("Value is not a list".to_string());
+        };
 
-        // Convert non-atomic stats back to atomic
-        let stats = StorageStats {
-            total_entries: AtomicUsize::new(loaded_storage.stats.total_entries),
-            hits: AtomicUsize::new(loaded_storage.stats.hits),
-            misses: AtomicUsize::new(loaded_storage.stats.misses),
-            evictions: AtomicUsize::new(loaded_storage.stats.evictions),
-            expired_removals: AtomicUsize::new(loaded_storage.stats.expired_removals),
+        let popped = match front {
+            true => (!list.is_empty()).then(|| list.remove(0)),
+            false => list.pop(),
         };
+        self.stats.hits.fetch_add(1, Ordering::Relaxed);
 
-        // Replace current storage with loaded data
-        let _ = std::mem::replace(&mut self.store, Arc::new(RwLock::new(loaded_storage.store)));
-        let _ = std::mem::replace(&mut self.stats, stats);
-        let _ = std::mem::replace(&mut self.options, loaded_storage.options);
-        self.is_dirty.store(false, Ordering::Relaxed);
+        if list.is_empty() {
+            drop(store);
+            self.remove_entry(key);
+        } else {
+            entry.entry_size = entry.value.size_in_bytes();
+            entry.version = self.next_version();
+            entry.last_accessed = SystemTime::now();
+            entry.access_count += 1;
+            self.is_dirty.store(true, Ordering::Relaxed);
+        }
+        Ok(popped)
+    }
 
-        Ok(())
+    /// Length of the list at `key`, 0 for a missing key.
+    pub fn list_len(&self, key: &str) -> Result<usize, String> {
+        self.read_in_place(key, |value| match value {
+            Some(StorageValue::List(list)) => Ok(list.len()),
+            Some(_) => Err("Value is not a list".to_string()),
+            None => Ok(0),
+        })
     }
 
-    /// Saves storage data to disk.
-    /// Used for persistence during shutdown and periodic snapshots.
+    /// Adds members with positions to the geospatial index at `key`,
+    /// creating missing keys. Existing members are moved.
     ///
     /// # Arguments
-    /// * `path` - Path where to save the storage file
+    /// * `key` - The key holding the index
+    /// * `members` - (longitude, latitude, member) triples
+    /// * `condition` - Only add new members (`IfAbsent`) or only move
+    ///   existing ones (`IfPresent`)
+    /// * `count_changed` - Count moved members as well as new ones (`CH`)
     ///
     /// # Returns
-    /// `anyhow::Result<()>` - Success or error details
+    /// The number of members added (or changed), or an error if a position
+    /// is out of range or the key holds another type. Nothing is written if
+    /// any position is invalid
     ///
     /// # Example
     /// ```rust
-    /// use volatix_core::{LockedStorage, StorageOptions};
-    /// use std::path::Path;
+    /// use volatix_core::{LockedStorage, SetCondition};
     ///
-    /// let storage = LockedStorage::new(StorageOptions::default());
+    /// let mut storage = LockedStorage::default();
+    /// let places = [(13.361389, 38.115556, "Palermo"), (15.087269, 37.502669, "Catania")];
     ///
-    /// let db_path = Path::new("/tmp/cache.bin").to_path_buf();
-    /// if storage.should_flush() {
-    ///     storage.save_to_disk(&db_path).expect("Failed to save cache");
-    /// }
+    /// assert_eq!(storage.geo_add("sicily", &places, SetCondition::Always, false), Ok(2));
+    /// let distance = storage.geo_dist("sicily", "Palermo", "Catania").unwrap().unwrap();
+    /// assert_eq!((distance / 1000.0).round(), 166.0);
     /// ```
-    pub fn save_to_disk(&self, path: &PathBuf) -> anyhow::Result<()> {
-        let path = Path::new(path);
-        let file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(path)
-            .context("open db for writing")?;
-
-        // Convert atomic stats to non-atomic for serialization
-        let stats = NonAtomicStats {
-            total_entries: self.stats.total_entries.load(Ordering::Relaxed),
-            hits: self.stats.hits.load(Ordering::Relaxed),
-            misses: self.stats.misses.load(Ordering::Relaxed),
-            evictions: self.stats.evictions.load(Ordering::Relaxed),
-            expired_removals: self.stats.expired_removals.load(Ordering::Relaxed),
-        };
-
-        // Create serializable version
-        let unlocked_storage = SerializableStorage {
-            store: self.store.read().clone(),
-            options: self.options,
-            stats,
-        };
+    pub fn geo_add<S: AsRef<str>>(
+        &mut self,
+        key: &str,
+        members: &[(f64, f64, S)],
+        condition: SetCondition,
+        count_changed: bool,
+    ) -> Result<usize, String> {
+        for (longitude, latitude, _) in members {
+            validate_position(*longitude, *latitude)?;
+        }
+        if condition == SetCondition::IfPresent && !self.read_in_place(key, |v| v.is_some()) {
+            return Ok(0);
+        }
 
-        // Serialize to disk
-        let mut writer = BufWriter::new(file);
-        bincode2::serialize_into(&mut writer, &unlocked_storage).context("serialize into db")?;
-        writer.flush().context("flush writer")?;
-        Ok(())
+        let create = || Ok(StorageValue::Geo(GeoSet::default()));
+        self.update_in_place(key, create, |value| {
+            let StorageValue::Geo(set) = value else {
+                return Err("Value is not a geospatial index".to_string());
+            };
+
+            let mut count = 0;
+            for (longitude, latitude, member) in members {
+                let member = member.as_ref();
+                let exists = set.position(member).is_some();
+                match condition {
+                    SetCondition::IfAbsent if exists => continue,
+                    SetCondition::IfPresent if !exists => continue,
+                    _ => {}
+                }
+                match set.add(member, *longitude, *latitude)? {
+                    None => count += 1,
+                    Some(previous) if count_changed && set.hash(member) != Some(previous) => {
+                        count += 1
+                    }
+                    Some(_) => {}
+                }
+            }
+            Ok((count, true))
+        })
     }
-}
-
-#[cfg(test)]
-mod storage_tests {
-    use std::{collections::HashMap, sync::atomic::Ordering, thread, time::Duration};
-
-    use super::*;
 
-    // Test creating storage with custom options
-    #[test]
-    fn test_custom_options() {
-        let ttl = Duration::from_secs(30);
-        let max_capacity = 50;
-        let options = StorageOptions::new(
-            ttl,
-            max_capacity,
-            EvictionPolicy::Oldest,
-            Compression::Disabled,
-            0,
-        );
-        let storage = LockedStorage::new(options);
+    /// Distance in meters between two members of the geospatial index at
+    /// `key`, `None` if the key or a member is missing.
+    pub fn geo_dist(&self, key: &str, a: &str, b: &str) -> Result<Option<f64>, String> {
+        self.read_geo(key, |set| Ok(set.and_then(|set| set.distance(a, b))))
+    }
 
-        assert_eq!(storage.options.ttl, Duration::from_secs(30));
-        assert_eq!(storage.options.max_capacity, 50);
-        assert_eq!(storage.store.read().len(), 0);
+    /// Finds the members of the geospatial index at `key` inside `shape`
+    /// around `origin`, sorted from the nearest to the furthest.
+    ///
+    /// # Returns
+    /// The matching members with their distance and position, nothing for a
+    /// missing key, or an error if the origin member doesn't exist or the
+    /// key holds another type
+    pub fn geo_search(
+        &self,
+        key: &str,
+        origin: &GeoOrigin,
+        shape: GeoShape,
+    ) -> Result<Vec<GeoMatch>, String> {
+        self.read_geo(key, |set| {
+            let Some(set) = set else {
+                return Ok(Vec::new());
+            };
+            let center = match origin {
+                GeoOrigin::Member(member) => set
+                    .position(member)
+                    .ok_or_else(|| "Member not found".to_string())?,
+                GeoOrigin::Position(longitude, latitude) => {
+                    validate_position(*longitude, *latitude)?;
+                    (*longitude, *latitude)
+                }
+            };
+            Ok(set.search(center, shape))
+        })
     }
 
-    // Test inserting and retrieving keys
-    #[test]
-    fn test_insert_and_get_key() {
-        let mut storage = LockedStorage::default();
-        let v = StorageValue::Text("test value".to_string());
-        storage
-            .insert_entry("test_key".to_string(), v.clone())
-            .unwrap();
+    /// Passes the geospatial index at `key` to `read`, `None` for a missing
+    /// key.
+    fn read_geo<T>(
+        &self,
+        key: &str,
+        read: impl FnOnce(Option<&GeoSet>) -> Result<T, String>,
+    ) -> Result<T, String> {
+        self.read_in_place(key, |value| match value {
+            Some(StorageValue::Geo(set)) => read(Some(set)),
+            Some(_) => Err("Value is not a geospatial index".to_string()),
+            None => read(None),
+        })
+    }
 
-        let entry = storage.get_entry("test_key");
-        assert!(entry.is_some());
+    /// Writes `value` at `path` in the JSON document at `key`. A missing key
+    /// is created, which is only possible at the root path.
+    ///
+    /// # Arguments
+    /// * `key` - The key holding the document
+    /// * `path` - Where to write the value
+    /// * `value` - The value to write, usually from `parse_json`
+    /// * `condition` - Only write if the path doesn't exist (`IfAbsent`) or
+    ///   exists (`IfPresent`)
+    ///
+    /// # Returns
+    /// Whether the value was written, or an error if a new key isn't set at
+    /// the root or the key holds another type
+    ///
+    /// # Example
+    /// ```rust
+    /// use volatix_core::{JsonPath, LockedStorage, SetCondition, parse_json};
+    ///
+    /// let mut storage = LockedStorage::default();
+    /// let root = JsonPath::parse("$").unwrap();
+    /// let city = JsonPath::parse("$.address.city").unwrap();
+    ///
+    /// let doc = parse_json(r#"{"name":"Ada","address":{"city":"London"}}"#).unwrap();
+    /// assert_eq!(storage.json_set("user", &root, doc, SetCondition::Always), Ok(true));
+    /// let paris = parse_json(r#""Paris""#).unwrap();
+    /// assert_eq!(storage.json_set("user", &city, paris, SetCondition::Always), Ok(true));
+    ///
+    /// let json = storage.json_get("user", &[city]).unwrap();
+    /// assert_eq!(json.as_deref(), Some(r#"["Paris"]"#));
+    /// ```
+    pub fn json_set(
+        &mut self,
+        key: &str,
+        path: &JsonPath,
+        value: StorageValue,
+        condition: SetCondition,
+    ) -> Result<bool, String> {
+        self.update_document(key, |doc| {
+            let Some(doc) = doc else {
+                if !path.is_root() {
+                    return Err("New documents must be created at the root".to_string());
+                }
+                if condition == SetCondition::IfPresent {
+                    return Ok((false, false));
+                }
+                *doc = Some(value);
+                return Ok((true, true));
+            };
+
+            let exists = !path.get(doc).is_empty();
+            match condition {
+                SetCondition::IfAbsent if exists => return Ok((false, false)),
+                SetCondition::IfPresent if !exists => return Ok((false, false)),
+                _ => {}
+            }
+            let written = path.set(doc, &value) > 0;
+            Ok((written, written))
+        })
+    }
+
+    /// Serializes the values at `paths` of the JSON document at `key`.
+    ///
+    /// # Returns
+    /// The JSON text, `None` for a missing key, or an error if a legacy path
+    /// doesn't exist or the key holds another type.
+    /// No path returns the whole document, a single path its reply (see
+    /// `JsonPath::reply`), and several paths an object of replies keyed by
+    /// path.
+    pub fn json_get(&self, key: &str, paths: &[JsonPath]) -> Result<Option<String>, String> {
+        let Some(entry) = self.get_entry(key) else {
+            return Ok(None);
+        };
+        let doc = &entry.value;
+        if !is_document(doc) {
+            return Err("Value is not a JSON document".to_string());
+        }
+
+        let reply = |path: &JsonPath| {
+            let results = path.get(doc).into_iter().map(|v| Some(v.clone()));
+            path.reply(results.collect(), "")
+        };
+        let json = match paths {
+            [] => to_json(doc),
+            [path] => to_json(&reply(path)?),
+            paths => {
+                let mut replies = Vec::with_capacity(paths.len());
+                for path in paths {
+                    replies.push((path.to_string(), reply(path)?));
+                }
+                to_json(&StorageValue::Map(replies))
+            }
+        };
+        Ok(Some(json))
+    }
+
+    /// Removes the values at `path` from the JSON document at `key`.
+    /// Deleting the root removes the key.
+    ///
+    /// # Returns
+    /// The number of values removed, or an error if the key holds another
+    /// type
+    pub fn json_del(&mut self, key: &str, path: &JsonPath) -> Result<usize, String> {
+        self.update_document(key, |doc| match doc {
+            None => Ok((0, false)),
+            Some(_) if path.is_root() => {
+                *doc = None;
+                Ok((1, true))
+            }
+            Some(value) => {
+                let removed = path.delete(value);
+                Ok((removed, removed > 0))
+            }
+        })
+    }
+
+    /// Adds `by` to the numbers at `path` in the JSON document at `key`.
+    /// Integers that would overflow become floats.
+    ///
+    /// # Returns
+    /// The new values shaped by `JsonPath::reply`, with null for values that
+    /// aren't numbers, or an error if the key is missing or holds another
+    /// type
+    ///
+    /// # Example
+    /// ```rust
+    /// use volatix_core::{JsonPath, LockedStorage, SetCondition, StorageValue, parse_json};
+    ///
+    /// let mut storage = LockedStorage::default();
+    /// let doc = parse_json(r#"{"visits":41,"name":"Ada"}"#).unwrap();
+    /// let root = JsonPath::parse("$").unwrap();
+    /// storage.json_set("user", &root, doc, SetCondition::Always).unwrap();
+    ///
+    /// let visits = JsonPath::parse(".visits").unwrap();
+    /// let incremented = storage.json_num_incr_by("user", &visits, StorageValue::Int(1));
+    /// assert_eq!(incremented, Ok(StorageValue::Int(42)));
+    /// ```
+    pub fn json_num_incr_by(
+        &mut self,
+        key: &str,
+        path: &JsonPath,
+        by: StorageValue,
+    ) -> Result<StorageValue, String> {
+        self.update_document(key, |doc| {
+            let Some(doc) = doc else {
+                return Err("Key does not exist".to_string());
+            };
+            let results = path.update(doc, |value| {
+                let sum = match (&*value, &by) {
+                    (StorageValue::Int(a), StorageValue::Int(b)) => match a.checked_add(*b) {
+                        Some(sum) => StorageValue::Int(sum),
+                        None => StorageValue::Float(*a as f64 + *b as f64),
+                    },
+                    (StorageValue::Int(a), StorageValue::Float(b)) => {
+                        StorageValue::Float(*a as f64 + b)
+                    }
+                    (StorageValue::Float(a), StorageValue::Int(b)) => {
+                        StorageValue::Float(a + *b as f64)
+                    }
+                    (StorageValue::Float(a), StorageValue::Float(b)) => StorageValue::Float(a + b),
+                    _ => return None,
+                };
+                *value = sum.clone();
+                Some(sum)
+            });
+            let changed = results.iter().any(Option::is_some);
+            Ok((path.reply(results, "Value is not a number")?, changed))
+        })
+    }
+
+    /// Appends `values` to the arrays at `path` in the JSON document at
+    /// `key`.
+    ///
+    /// # Returns
+    /// The new lengths of the arrays shaped by `JsonPath::reply`, with null
+    /// for values that aren't arrays, or an error if the key is missing or
+    /// holds another type
+    pub fn json_arr_append(
+        &mut self,
+        key: &str,
+        path: &JsonPath,
+        values: &[StorageValue],
+    ) -> Result<StorageValue, String> {
+        self.update_document(key, |doc| {
+            let Some(doc) = doc else {
+                return Err("Key does not exist".to_string());
+            };
+            let results = path.update(doc, |value| match value {
+                StorageValue::List(items) => {
+                    items.extend_from_slice(values);
+                    Some(StorageValue::Int(items.len() as i64))
+                }
+                _ => None,
+            });
+            let changed = !values.is_empty() && results.iter().any(Option::is_some);
+            Ok((path.reply(results, "Value is not an array")?, changed))
+        })
+    }
+
+    /// Modifies the JSON document at `key`. `update` receives the document,
+    /// `None` for a missing key, and may replace it or set it to `None` to
+    /// remove the key. It returns its result and whether it changed the
+    /// document. Compressed documents are decompressed first and compressed
+    /// again if changed, new keys are inserted with the default TTL.
+    fn update_document<T>(
+        &mut self,
+        key: &str,
+        update: impl FnOnce(&mut Option<StorageValue>) -> Result<(T, bool), String>,
+    ) -> Result<T, String> {
+        self.record_access(key);
+
+        let mut store = self.store.write();
+        let Some(entry) = store.get_mut(key).filter(|entry| !entry.is_expired()) else {
+            drop(store);
+            let mut doc = None;
+            let (result, _) = update(&mut doc)?;
+            self.stats.misses.fetch_add(1, Ordering::Relaxed);
+            if let Some(doc) = doc {
+                self.insert_entry(key.to_string(), doc)?;
+            }
+            return Ok(result);
+        };
+
+        // Compressed text is left in the entry until it actually changes
+        let mut doc = match &entry.value {
+            StorageValue::Bytes(bytes) if entry.compressed => Some(StorageValue::Text(
+                decompress(bytes).map_err(|err| format!("Decompression error: {err}"))?,
+            )),
+            value if is_document(value) => {
+                Some(std::mem::replace(&mut entry.value, StorageValue::Null))
+            }
+            _ => return Err("Value is not a JSON document".to_string()),
+        };
+        self.stats.hits.fetch_add(1, Ordering::Relaxed);
+        entry.last_accessed = SystemTime::now();
+        entry.access_count += 1;
+
+        let outcome = update(&mut doc);
+        let changed = matches!(outcome, Ok((_, true)));
+        match doc {
+            Some(doc) if changed || !entry.compressed => {
+                entry.entry_size = doc.size_in_bytes();
+                (entry.value, entry.compressed) = self.compress_value(doc)?;
+            }
+            Some(_) => {}
+            None => {
+                drop(store);
+                self.remove_entry(key);
+                return outcome.map(|(result, _)| result);
+            }
+        }
+        if changed {
+            entry.version = self.next_version();
+            self.is_dirty.store(true, Ordering::Relaxed);
+        }
+        outcome.map(|(result, _)| result)
+    }
+
+    /// Appends an entry to the stream at `key`, creating the stream if needed.
+    ///
+    /// # Arguments
+    /// * `key` - The key holding the stream
+    /// * `id` - `*` to generate the identifier, `<ms>-*` to generate the
+    ///   sequence number only, or an explicit identifier
+    /// * `fields` - The field-value pairs of the entry
+    /// * `max_len` - Trim the oldest entries to keep at most this many
+    ///
+    /// # Returns
+    /// The identifier of the new entry, or an error if the identifier is
+    /// invalid or the key holds another type
+    ///
+    /// # Example
+    /// ```rust
+    /// use volatix_core::{LockedStorage, StreamId};
+    ///
+    /// let mut storage = LockedStorage::default();
+    /// let fields = vec![("job".to_string(), "resize".to_string())];
+    /// let id = storage.stream_add("jobs", "*", fields.clone(), None).unwrap();
+    ///
+    /// assert_eq!(storage.stream_len("jobs"), Ok(1));
+    /// let entries = storage.stream_range("jobs", StreamId::MIN, StreamId::MAX, None, false);
+    /// assert_eq!(entries, Ok(vec![(id, fields)]));
+    /// ```
+    pub fn stream_add(
+        &mut self,
+        key: &str,
+        id: &str,
+        fields: StreamFields,
+        max_len: Option<usize>,
+    ) -> Result<StreamId, String> {
+        self.update_stream(key, true, |stream| {
+            let id = stream.add(id, fields)?;
+            if let Some(max_len) = max_len {
+                stream.trim(max_len);
+            }
+            Ok((id, true))
+        })
+    }
+
+    /// Number of entries in the stream at `key`, 0 for a missing key.
+    pub fn stream_len(&self, key: &str) -> Result<usize, String> {
+        self.read_stream(key, |stream| Ok(stream.map_or(0, Stream::len)))
+    }
+
+    /// Returns the entries of the stream at `key` with identifiers between
+    /// `start` and `end` inclusive, newest first if `rev` is set.
+    /// A missing key has no entries.
+    pub fn stream_range(
+        &self,
+        key: &str,
+        start: StreamId,
+        end: StreamId,
+        count: Option<usize>,
+        rev: bool,
+    ) -> Result<Vec<(StreamId, StreamFields)>, String> {
+        self.read_stream(key, |stream| {
+            Ok(stream.map_or_else(Vec::new, |s| s.range(start, end, count, rev)))
+        })
+    }
+
+    /// Trims the oldest entries of the stream at `key` to keep at most
+    /// `max_len` of them.
+    ///
+    /// # Returns
+    /// The number of removed entries, 0 for a missing key
+    pub fn stream_trim(&mut self, key: &str, max_len: usize) -> Result<usize, String> {
+        if !self.key_exists(key) {
+            return Ok(0);
+        }
+        self.update_stream(key, false, |stream| {
+            let removed = stream.trim(max_len);
+            Ok((removed, removed > 0))
+        })
+    }
+
+    /// Creates a consumer group on the stream at `key`.
+    ///
+    /// # Arguments
+    /// * `key` - The key holding the stream
+    /// * `group` - The name of the group
+    /// * `id` - The group delivers entries following this identifier,
+    ///   `$` for the last entry of the stream
+    /// * `create` - Create an empty stream if the key is missing
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or an error if the key is missing and `create`
+    /// isn't set, or the group already exists
+    pub fn stream_create_group(
+        &mut self,
+        key: &str,
+        group: &str,
+        id: &str,
+        create: bool,
+    ) -> Result<(), String> {
+        self.update_stream(key, create, |stream| {
+            let id = match id {
+                "$" => stream.last_id(),
+                _ => StreamId::parse(id, 0)?,
+            };
+            stream.create_group(group, id)?;
+            Ok(((), true))
+        })
+    }
+
+    /// Deletes a consumer group of the stream at `key`.
+    ///
+    /// # Returns
+    /// Whether the group existed, or an error if the key is missing
+    pub fn stream_destroy_group(&mut self, key: &str, group: &str) -> Result<bool, String> {
+        self.update_stream(key, false, |stream| {
+            let destroyed = stream.destroy_group(group);
+            Ok((destroyed, destroyed))
+        })
+    }
+
+    /// Delivers entries of the stream at `key` to a consumer of a group.
+    ///
+    /// # Arguments
+    /// * `after` - `None` to deliver new entries, which become pending, or
+    ///   an identifier to read the consumer's pending entries following it
+    /// * `count` - Maximum number of entries to return
+    ///
+    /// # Returns
+    /// The entries, or an error if the key or group doesn't exist
+    ///
+    /// # Example
+    /// ```rust
+    /// use volatix_core::{LockedStorage, StreamId};
+    ///
+    /// let mut storage = LockedStorage::default();
+    /// storage.stream_create_group("jobs", "workers", "$", true).unwrap();
+    /// let fields = vec![("job".to_string(), "resize".to_string())];
+    /// let id = storage.stream_add("jobs", "*", fields, None).unwrap();
+    ///
+    /// let entries = storage.stream_read_group("jobs", "workers", "w1", None, None).unwrap();
+    /// assert_eq!(entries[0].0, id);
+    /// assert_eq!(storage.stream_ack("jobs", "workers", &[id]), Ok(1));
+    /// ```
+    pub fn stream_read_group(
+        &mut self,
+        key: &str,
+        group: &str,
+        consumer: &str,
+        after: Option<StreamId>,
+        count: Option<usize>,
+    ) -> Result<Vec<(StreamId, StreamFields)>, String> {
+        self.update_stream(key, false, |stream| {
+            let entries = stream.read_group(group, consumer, after, count)?;
+            let changed = after.is_none() && !entries.is_empty();
+            Ok((entries, changed))
+        })
+    }
+
+    /// Acknowledges entries of the stream at `key` for a group.
+    ///
+    /// # Returns
+    /// The number of entries that were pending, or an error if the key or
+    /// group doesn't exist
+    pub fn stream_ack(
+        &mut self,
+        key: &str,
+        group: &str,
+        ids: &[StreamId],
+    ) -> Result<usize, String> {
+        self.update_stream(key, false, |stream| {
+            let acked = stream.ack(group, ids)?;
+            Ok((acked, acked > 0))
+        })
+    }
+
+    /// Summarizes the pending entries of a group of the stream at `key`.
+    pub fn stream_pending_summary(&self, key: &str, group: &str) -> Result<PendingSummary, String> {
+        self.read_stream(key, |stream| match stream {
+            Some(stream) => stream.pending_summary(group),
+            None => Err("No such key".to_string()),
+        })
+    }
+
+    /// Lists the pending entries of a group of the stream at `key` between
+    /// `start` and `end`, optionally only those of one consumer.
+    pub fn stream_pending(
+        &self,
+        key: &str,
+        group: &str,
+        start: StreamId,
+        end: StreamId,
+        count: usize,
+        consumer: Option<&str>,
+    ) -> Result<Vec<(StreamId, PendingEntry)>, String> {
+        self.read_stream(key, |stream| match stream {
+            Some(stream) => stream.pending(group, start, end, count, consumer),
+            None => Err("No such key".to_string()),
+        })
+    }
+
+    /// Applies `update` to the stream at `key`, creating an empty stream
+    /// for a missing key if `create` is set.
+    fn update_stream<T>(
+        &mut self,
+        key: &str,
+        create: bool,
+        update: impl FnOnce(&mut Stream) -> Result<(T, bool), String>,
+    ) -> Result<T, String> {
+        let create = || match create {
+            true => Ok(StorageValue::Stream(Box::default())),
+            false => Err("No such key".to_string()),
+        };
+        self.update_in_place(key, create, |value| match value {
+            StorageValue::Stream(stream) => update(stream),
+            _ => Err("Value is not a stream".to_string()),
+        })
+    }
+
+    /// Passes the stream at `key` to `read`, `None` for a missing key.
+    fn read_stream<T>(
+        &self,
+        key: &str,
+        read: impl FnOnce(Option<&Stream>) -> Result<T, String>,
+    ) -> Result<T, String> {
+        self.read_in_place(key, |value| match value {
+            Some(StorageValue::Stream(stream)) => read(Some(stream.as_ref())),
+            Some(_) => Err("Value is not a stream".to_string()),
+            None => read(None),
+        })
+    }
+
+    /// Passes the value at `key` to `read` without cloning it, counting the
+    /// access like `get_entry`. Missing and expired keys are passed as `None`.
+    /// Only meant for values that are never compressed.
+    fn read_in_place<T>(&self, key: &str, read: impl FnOnce(Option<&StorageValue>) -> T) -> T {
+        self.record_access(key);
+
+        let mut store = self.store.write();
+        match store.get_mut(key).filter(|entry| !entry.is_expired()) {
+            Some(entry) => {
+                entry.access_count += 1;
+                entry.last_accessed = SystemTime::now();
+                self.stats.hits.fetch_add(1, Ordering::Relaxed);
+                read(Some(&entry.value))
+            }
+            None => {
+                self.stats.misses.fetch_add(1, Ordering::Relaxed);
+                read(None)
+            }
+        }
+    }
+
+    /// Modifies the value at `key` in place. Missing keys are built with
+    /// `create`, then updated and inserted with the default TTL.
+    /// `update` returns its result and whether it changed the value, which
+    /// decides if the entry gets a new version and size.
+    /// Only meant for values that are never compressed.
+    fn update_in_place<T>(
+        &mut self,
+        key: &str,
+        create: impl FnOnce() -> Result<StorageValue, String>,
+        update: impl FnOnce(&mut StorageValue) -> Result<(T, bool), String>,
+    ) -> Result<T, String> {
+        self.record_access(key);
+
+        if let Some(entry) = self.store.write().get_mut(key)
+            && !entry.is_expired()
+        {
+            let (result, changed) = update(&mut entry.value)?;
+            if changed {
+                entry.entry_size = entry.value.size_in_bytes();
+                entry.version = self.next_version();
+                self.is_dirty.store(true, Ordering::Relaxed);
+            }
+            entry.last_accessed = SystemTime::now();
+            entry.access_count += 1;
+            self.stats.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(result);
+        }
+
+        let mut value = create()?;
+        let (result, _) = update(&mut value)?;
+        self.stats.misses.fetch_add(1, Ordering::Relaxed);
+        self.insert_entry(key.to_string(), value)?;
+        Ok(result)
+    }
+
+    /// Gets an entry and removes it from the cache.
+    ///
+    /// # Arguments
+    /// * `key` - The key to remove
+    ///
+    /// # Returns
+    /// `Some(StorageEntry)` with the decompressed entry if it existed, `None` otherwise
+    pub fn get_and_remove(&mut self, key: &str) -> Option<StorageEntry> {
+        let entry = self.get_entry(key)?;
+        self.remove_entry(key);
+        Some(entry)
+    }
+
+    /// Replaces a string value with the result of `update`, which receives
+    /// the current value as bytes (empty if the key is missing or expired).
+    /// Compressed values are decompressed first and the result is compressed
+    /// again according to `compression_threshold`.
+    /// Missing keys are inserted with the default TTL.
+    ///
+    /// With `text` set, text values stay text as long as they are valid
+    /// UTF-8, otherwise the result is always stored as bytes.
+    fn update_string<T>(
+        &mut self,
+        key: &str,
+        text: bool,
+        update: impl FnOnce(&mut Vec<u8>) -> Result<T, String>,
+    ) -> Result<T, String> {
+        if let Some(entry) = self.store.write().get_mut(key)
+            && !entry.is_expired()
+        {
+            let (mut bytes, is_text) = match &entry.value {
+                StorageValue::Bytes(bytes) if entry.compressed => {
+                    let text =
+                        decompress(bytes).map_err(|err| format!("Decompression error: {err}"))?;
+                    (text.into_bytes(), true)
+                }
+                StorageValue::Bytes(bytes) => (bytes.clone(), false),
+                StorageValue::Text(text) => (text.clone().into_bytes(), true),
+                value @ (StorageValue::Int(_) | StorageValue::Float(_) | StorageValue::Bool(_)) => {
+                    (value.to_string().into_bytes(), true)
+                }
+                _ => return Err("Value is not a string".to_string()),
+            };
+
+            let result = update(&mut bytes)?;
+            let value = bytes_to_value(bytes, text && is_text);
+            entry.entry_size = value.size_in_bytes();
+            (entry.value, entry.compressed) = self.compress_value(value)?;
+            entry.version = self.next_version();
+            entry.last_accessed = SystemTime::now();
+            entry.access_count += 1;
+
+            self.stats.hits.fetch_add(1, Ordering::Relaxed);
+            self.is_dirty.store(true, Ordering::Relaxed);
+            return Ok(result);
+        }
+
+        let mut bytes = Vec::new();
+        let result = update(&mut bytes)?;
+        self.stats.misses.fetch_add(1, Ordering::Relaxed);
+        self.insert_entry(key.to_string(), bytes_to_value(bytes, text))?;
+        Ok(result)
+    }
+
+    /// Removes a single entry from the cache.
+    /// Updates the total entry count if an entry was actually removed.
+    ///
+    /// # Arguments
+    /// * `key` - The key to remove
+    pub fn remove_entry(&mut self, key: &str) {
+        if self.store.write().remove_entry(key).is_some() {
+            self.unindex_key(key);
+            self.stats.total_entries.fetch_sub(1, Ordering::Relaxed);
+            self.entry_count.fetch_sub(1, Ordering::Relaxed);
+            self.is_dirty.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Adds a key that is new to the store to the `SCAN` order.
+    fn index_key(&self, key: &str) {
+        self.scan_index
+            .write()
+            .insert((scan_hash(key), key.to_string()));
+    }
+
+    /// Drops a key removed from the store from the `SCAN` order.
+    fn unindex_key(&self, key: &str) {
+        self.scan_index
+            .write()
+            .remove(&(scan_hash(key), key.to_string()));
+    }
+
+    /// Inserts an entry with a specific TTL.
+    ///
+    /// # Arguments
+    /// * `key` - The key to insert
+    /// * `value` - The value to store
+    /// * `ttl` - Time-to-live for this specific entry
+    ///
+    /// # Returns
+    /// `Ok(())` on success, `Err(String)` on failure, including a TTL too
+    /// large for the clock
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::time::Duration;
+    /// use volatix_core::{LockedStorage, StorageValue, StorageOptions};
+    ///
+    /// let mut storage = LockedStorage::new(StorageOptions::default());
+    ///
+    /// storage.insert_with_ttl(
+    ///     "session:abc123".to_string(),
+    ///     StorageValue::Text("user_data".to_string()),
+    ///     Duration::from_secs(3600) // 1 hour TTL
+    /// ).unwrap();
+    /// ```
+    pub fn insert_with_ttl(
+        &mut self,
+        key: String,
+        value: StorageValue,
+        ttl: Duration,
+    ) -> Result<(), String> {
+        let deadline = SystemTime::now()
+            .checked_add(ttl)
+            .ok_or_else(|| "Invalid expire time".to_string())?;
+        self.insert_with_expiry(key, value, Some(deadline))
+    }
+
+    /// Inserts an entry with an absolute expiry deadline.
+    /// This is the core insertion method that handles compression and eviction.
+    ///
+    /// # Arguments
+    /// * `key` - The key to insert
+    /// * `value` - The value to store
+    /// * `expires_at` - When the entry expires, `None` to never expire it
+    ///
+    /// # Example
+    /// ```rust
+    /// use volatix_core::{LockedStorage, StorageOptions, StorageValue};
+    ///
+    /// let mut storage = LockedStorage::new(StorageOptions::default());
+    ///
+    /// storage.insert_with_expiry(
+    ///     "config:motd".to_string(),
+    ///     StorageValue::Text("hello".to_string()),
+    ///     None // never expires
+    /// ).unwrap();
+    /// assert_eq!(storage.time_to_live("config:motd"), Some(None));
+    /// ```
+    pub fn insert_with_expiry(
+        &mut self,
+        key: String,
+        value: StorageValue,
+        expires_at: Option<SystemTime>,
+    ) -> Result<(), String> {
+        let entry = self.new_entry(value, expires_at)?;
+        self.insert_raw_entry(key, entry);
+        Ok(())
+    }
+
+    /// Creates an entry with fresh metadata, compressing its value.
+    fn new_entry(
+        &self,
+        value: StorageValue,
+        expires_at: Option<SystemTime>,
+    ) -> Result<StorageEntry, String> {
+        let entry_size = value.size_in_bytes();
+        let now = SystemTime::now();
+        let (value, compressed) = self.compress_value(value)?;
+
+        Ok(StorageEntry {
+            value,
+            created_at: now,
+            last_accessed: now,
+            access_count: 0,
+            entry_size,
+            expires_at,
+            compressed,
+            version: self.next_version(),
+        })
+    }
+
+    /// Hands out the next entry version.
+    /// Versions are unique within the storage and only ever increase, so a
+    /// key that is deleted and created again never reuses an old version.
+    fn next_version(&self) -> u64 {
+        self.version.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Compresses large text values when compression is enabled.
+    ///
+    /// # Returns
+    /// The value to store and whether it was compressed
+    fn compress_value(&self, value: StorageValue) -> Result<(StorageValue, bool), String> {
+        if self.options.compression
+            && let StorageValue::Text(text) = &value
+            && value.size_in_bytes() > self.options.compression_threshold
+        {
+            let bytes = compress(text).map_err(|err| format!("Compression error: {err}"))?;
+            Ok((StorageValue::Bytes(bytes), true))
+        } else {
+            Ok((value, false))
+        }
+    }
+
+    /// Inserts an already built entry as is, keeping its metadata (TTL,
+    /// access statistics, compression). Makes room for the entry if the
+    /// cache is full. Used to move entries between databases.
+    ///
+    /// # Arguments
+    /// * `key` - The key to insert
+    /// * `entry` - The entry to store under the key
+    pub fn insert_raw_entry(&mut self, key: String, entry: StorageEntry) {
+        // Entries coming from another database keep their version, later
+        // writes must still produce higher ones
+        self.version.fetch_max(entry.version, Ordering::Relaxed);
+
+        // Check if we need to make room for this entry.
+        // Overwriting an existing key does not grow the store.
+        if !self.store.read().contains_key(&key) {
+            if let EvictionPolicy::TinyLFU = self.options.eviction_policy {
+                self.admit(&key);
+            } else if self.is_full() {
+                // Evict 10% of the entries
+                self.evict_entries(0);
+            }
+        }
+        if self.store.write().insert(key.clone(), entry).is_none() {
+            self.index_key(&key);
+            self.stats.total_entries.fetch_add(1, Ordering::Relaxed);
+            self.entry_count.fetch_add(1, Ordering::Relaxed);
+        }
+        self.is_dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Removes an entry and returns it with its metadata untouched.
+    /// Expired entries are removed but not returned.
+    ///
+    /// # Arguments
+    /// * `key` - The key to remove
+    ///
+    /// # Returns
+    /// The removed entry, or `None` if the key doesn't exist or has expired
+    pub fn take_entry(&mut self, key: &str) -> Option<StorageEntry> {
+        let entry = self.store.write().remove(key)?;
+        self.unindex_key(key);
+        self.stats.total_entries.fetch_sub(1, Ordering::Relaxed);
+        self.entry_count.fetch_sub(1, Ordering::Relaxed);
+        self.is_dirty.store(true, Ordering::Relaxed);
+
+        if entry.is_expired() {
+            self.stats.expired_removals.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+        Some(entry)
+    }
+
+    /// Records a key access in the TinyLFU frequency sketch.
+    /// Does nothing for the other eviction policies.
+    fn record_access(&self, key: &str) {
+        if let EvictionPolicy::TinyLFU = self.options.eviction_policy {
+            let mut filter = self.admission.lock();
+            filter.sketch.ensure_capacity(self.options.max_capacity);
+            filter.sketch.increment(key);
+        }
+    }
+
+    /// Admits a new key into the TinyLFU window, making room for it if the
+    /// cache is full.
+    ///
+    /// The key leaving the window competes against the least recently used
+    /// entry of the main region; whichever has the lower estimated access
+    /// frequency is evicted. If the window has room the main region victim is
+    /// evicted directly.
+    fn admit(&mut self, key: &str) {
+        self.record_access(key);
+
+        let full = self.is_full();
+        let admission = Arc::clone(&self.admission);
+        let mut filter = admission.lock();
+
+        let window_capacity = (self.options.max_capacity / 100).max(1);
+        let candidate = filter.push_window(key, window_capacity);
+
+        // With room to spare the candidate simply graduates to the main region
+        if !full {
+            return;
+        }
+
+        let candidate = candidate.filter(|c| self.store.read().contains_key(c));
+        let victim = self.next_victim(&mut filter);
+        let evicted = match (candidate, victim) {
+            (Some(candidate), Some(victim)) => {
+                if filter.sketch.frequency(&candidate) > filter.sketch.frequency(&victim) {
+                    victim
+                } else {
+                    candidate
+                }
+            }
+            (Some(candidate), None) => candidate,
+            (None, Some(victim)) => victim,
+            (None, None) => return,
+        };
+        drop(filter);
+
+        self.remove_entry(&evicted);
+        self.stats.evictions.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Picks the least recently used entry outside the admission window.
+    ///
+    /// Victims are selected in batches of 10% of the capacity to avoid a full
+    /// scan on every insert. A queued victim that has been accessed or
+    /// replaced since it was selected is skipped.
+    fn next_victim(&self, filter: &mut AdmissionFilter) -> Option<String> {
+        for _ in 0..2 {
+            while let Some((accessed, key)) = filter.victims.pop_front() {
+                if let Some(entry) = self.store.read().get(&key)
+                    && entry.last_accessed == accessed
+                {
+                    return Some(key);
+                }
+            }
+
+            // Refill the victims queue
+            let n = (self.options.max_capacity / 10).max(1);
+            let mut heap: BinaryHeap<(SystemTime, String)> = BinaryHeap::with_capacity(n);
+            {
+                let store = self.store.read();
+                for (k, v) in store.iter() {
+                    if filter.window_keys.contains(k) {
+                        continue;
+                    }
+                    if heap.len() < n {
+                        heap.push((v.last_accessed, k.clone()));
+                    } else if let Some((top, _)) = heap.peek()
+                        && v.last_accessed < *top
+                    {
+                        heap.pop();
+                        heap.push((v.last_accessed, k.clone()));
+                    }
+                }
+            }
+
+            if heap.is_empty() {
+                break;
+            }
+            filter.victims = heap.into_sorted_vec().into();
+        }
+
+        None
+    }
+
+    /// Extends or reduces the TTL of an existing entry.
+    /// Can add or subtract time from the current TTL. An entry without
+    /// expiry gets a deadline `additional_time` seconds from now.
+    ///
+    /// # Arguments
+    /// * `key` - The key to modify
+    /// * `additional_time` - Seconds to add (positive) or subtract (negative)
+    ///
+    /// # Returns
+    /// `Ok(())` on success, `Err("UNALTERED")` if the operation would move the
+    /// deadline into the past
+    ///
+    /// # Example
+    /// ```rust
+    /// use volatix_core::{LockedStorage, StorageOptions};
+    ///
+    /// let mut storage = LockedStorage::new(StorageOptions::default());
+    ///
+    /// // Add 30 minutes
+    /// storage.extend_ttl("session:123", 1800).unwrap();
+    ///
+    /// // Subtract 10 minutes (but don't go negative)
+    /// storage.extend_ttl("session:123", -600).unwrap();
+    /// ```
+    pub fn extend_ttl(&mut self, key: &str, additional_time: i64) -> Result<(), String> {
+        if let Some(entry) = self.store.write().get_mut(key) {
+            let now = SystemTime::now();
+            let deadline = entry.expires_at.unwrap_or(now);
+            let change = Duration::from_secs(additional_time.unsigned_abs());
+            let deadline = if additional_time < 0 {
+                deadline.checked_sub(change)
+            } else {
+                deadline.checked_add(change)
+            };
+
+            // Prevent the deadline from moving into the past
+            match deadline {
+                Some(deadline) if deadline > now => entry.expires_at = Some(deadline),
+                _ => return Err("UNALTERED".to_string()),
+            }
+            // Update access metadata
+            entry.access_count += 1;
+            entry.last_accessed = now;
+            self.is_dirty.store(true, Ordering::Relaxed);
+        }
+
+        Ok(())
+    }
+
+    /// Sets the absolute expiry deadline of an existing entry.
+    /// A deadline that already passed removes the entry right away.
+    ///
+    /// # Arguments
+    /// * `key` - The key to modify
+    /// * `deadline` - When the entry expires
+    ///
+    /// # Returns
+    /// `true` if the key exists, `false` otherwise
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::time::{Duration, SystemTime};
+    /// use volatix_core::{LockedStorage, StorageOptions, StorageValue};
+    ///
+    /// let mut storage = LockedStorage::new(StorageOptions::default());
+    /// storage.insert_entry("session:123".to_string(), StorageValue::Int(1)).unwrap();
+    ///
+    /// // Expire the session in 90 seconds
+    /// let deadline = SystemTime::now() + Duration::from_secs(90);
+    /// assert!(storage.expire_at("session:123", deadline));
+    /// ```
+    pub fn expire_at(&mut self, key: &str, deadline: SystemTime) -> bool {
+        let now = SystemTime::now();
+        if deadline <= now {
+            let exists = self.store.read().get(key).is_some_and(|e| !e.is_expired());
+            self.remove_entry(key);
+            return exists;
+        }
+
+        match self.store.write().get_mut(key) {
+            Some(entry) if !entry.is_expired() => {
+                entry.expires_at = Some(deadline);
+                entry.last_accessed = now;
+            }
+            _ => return false,
+        }
+        self.is_dirty.store(true, Ordering::Relaxed);
+        true
+    }
+
+    /// Removes the expiry of an entry so that it never expires.
+    ///
+    /// # Arguments
+    /// * `key` - The key to modify
+    ///
+    /// # Returns
+    /// `true` if the entry had an expiry that was removed, `false` if the
+    /// key does not exist or already never expires
+    pub fn persist(&mut self, key: &str) -> bool {
+        match self.store.write().get_mut(key) {
+            Some(entry) if entry.expires_at.is_some() && !entry.is_expired() => {
+                entry.expires_at = None;
+                entry.last_accessed = SystemTime::now();
+            }
+            _ => return false,
+        }
+        self.is_dirty.store(true, Ordering::Relaxed);
+        true
+    }
+
+    /// Gets the remaining TTL for a key.
+    ///
+    /// # Arguments
+    /// * `key` - The key to check
+    ///
+    /// # Returns
+    /// `None` if the key does not exist, `Some(None)` if it never expires and
+    /// `Some(Some(Duration))` with the time left until it expires otherwise
+    pub fn time_to_live(&self, key: &str) -> Option<Option<Duration>> {
+        self.get_entry(key).map(|e| e.remaining_ttl())
+    }
+
+    /// Checks if the cache is at capacity.
+    /// Used internally to decide when eviction is needed.
+    pub fn is_full(&self) -> bool {
+        self.entry_count.load(Ordering::Relaxed) >= self.options.max_capacity
+    }
+
+    /// Checks whether storage has any unsyched changes to disk
+    pub fn should_flush(&self) -> bool {
+        self.is_dirty.load(Ordering::Relaxed)
+    }
+
+    /// Flip the state of the is_dirty storage flag
+    pub fn toggle_dirty_flag(&mut self) {
+        self.is_dirty.fetch_not(Ordering::Relaxed);
+    }
+
+    /// Checks the current maximum capacity of the storage
+    pub fn max_capacity(&self) -> usize {
+        self.options.max_capacity
+    }
+
+    /// Removes all expired entries from the cache.
+    /// Called automatically during eviction and can be called manually.
+    /// Updates statistics to track expired removals.
+    pub fn remove_expired(&mut self) {
+        let prev_count = self.store.read().len();
+        let now = SystemTime::now();
+        {
+            // Remove entries that have exceeded their TTL
+            let mut store = self.store.write();
+            let mut index = self.scan_index.write();
+            store.retain(|key, value| {
+                let live = value.expires_at.is_none_or(|deadline| now < deadline);
+                if !live {
+                    index.remove(&(scan_hash(key), key.clone()));
+                }
+                live
+            });
+        }
+        let current_count = self.store.read().len();
+        let removed = prev_count - current_count;
+        self.entry_count.store(current_count, Ordering::Relaxed);
+
+        // Update statistics
+        self.stats
+            .expired_removals
+            .fetch_add(removed, Ordering::Release);
+        self.stats
+            .total_entries
+            .store(current_count, Ordering::Release);
+        self.is_dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Performs eviction based on the configured eviction policy.
+    /// First removes expired entries, then applies the eviction policy.
+    /// If the specified count is greater than or equal to the
+    /// [`Self::entry_count`] the storage is flushed.
+    /// If count is zero, 10% of total entries is evicted.
+    pub fn evict_entries(&mut self, count: usize) {
+        let s = self.entry_count.load(Ordering::Relaxed);
+        if count >= s {
+            self.flush();
+            return;
+        }
+
+        self.remove_expired();
+
+        let count = if count == 0 {
+            (10.0 / 100.0 * s as f64).ceil() as usize
+        } else {
+            count
+        };
+
+        let oldest_metric = |_k: &String, v: &StorageEntry| v.created_at;
+        let lru_metric = |_k: &String, v: &StorageEntry| v.last_accessed;
+        let lfu_metric = |_k: &String, v: &StorageEntry| v.access_count;
+        let largest_metric = |_k: &String, v: &StorageEntry| -(v.entry_size as i64); // Invert the metric
+        let admission = Arc::clone(&self.admission);
+        let tinylfu_metric = |k: &String, _v: &StorageEntry| admission.lock().sketch.frequency(k);
+
+        match self.options.eviction_policy {
+            EvictionPolicy::Oldest => self.remove_n_entries(count, oldest_metric),
+            EvictionPolicy::LRU => self.remove_n_entries(count, lru_metric),
+            EvictionPolicy::LFU => self.remove_n_entries(count, lfu_metric),
+            EvictionPolicy::SizeAware => self.remove_n_entries(count, largest_metric),
+            EvictionPolicy::TinyLFU => self.remove_n_entries(count, tinylfu_metric),
+        }
+    }
+
+    /// Removes the least n entries by a metric.
+    /// Removes the least recently used n entries (LRU eviction policy).
+    /// Removes the least frequently used n entries (LFU eviction policy).
+    /// Removes the oldest n entries by their creation time (Oldest eviction policy).
+    /// Removes the largest n entries by their size (Size-aware eviction policy).
+    pub fn remove_n_entries<F, M>(&mut self, n: usize, mut metric: F)
+    where
+        M: Ord + Copy,
+        F: FnMut(&String, &StorageEntry) -> M,
+    {
+        let mut heap: BinaryHeap<(M, String)> = BinaryHeap::with_capacity(n);
+        {
+            let store = self.store.read();
+            for (k, v) in store.iter() {
+                let m = metric(k, v);
+                if heap.len() < n {
+                    heap.push((m, k.clone()));
+                } else if let Some((top_m, _)) = heap.peek()
+                    && m < *top_m
+                // Keep smallest by metric
+                {
+                    heap.pop();
+                    heap.push((m, k.clone()));
+                }
+            }
+        }
+        let keys = heap.into_iter().map(|(_, k)| k).collect::<Vec<String>>();
+        self.remove_entries(&keys);
+        self.stats.evictions.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Renames an existing key to a new name.
+    /// The old key is removed and a new entry is created with the same value.
+    /// Updates access statistics.
+    ///
+    /// # Arguments
+    /// * `old_key` - The current key name
+    /// * `new_key` - The new key name
+    ///
+    /// # Example
+    /// ```rust
+    /// use volatix_core::{StorageOptions, LockedStorage};
+    ///
+    /// let mut storage = LockedStorage::new(StorageOptions::default());
+    /// storage.rename_entry("old_name", "new_name");
+    /// ```
+    pub fn rename_entry(&mut self, old_key: &str, new_key: &str) {
+        // The write guard has to be gone before the entry is inserted again
+        let removed = self.store.write().remove_entry(old_key);
+        if let Some((_, mut entry)) = removed {
+            self.unindex_key(old_key);
+            // Update access metadata
+            entry.access_count += 1;
+            entry.last_accessed = SystemTime::now();
+            self.stats.hits.fetch_add(1, Ordering::Relaxed);
+
+            // Insert with new key
+            if self
+                .store
+                .write()
+                .insert(new_key.to_string(), entry)
+                .is_some()
+            {
+                // The new key replaced an existing entry
+                self.stats.total_entries.fetch_sub(1, Ordering::Relaxed);
+                self.entry_count.fetch_sub(1, Ordering::Relaxed);
+            } else {
+                self.index_key(new_key);
+            }
+            return;
+        }
+        self.stats.misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Gets a snapshot of the current statistics.
+    /// Returns non-atomic copies of the atomic values.
+    ///
+    /// # Returns
+    /// `StorageStats` with current values
+    pub fn get_stats(&self) -> StorageStats {
+        StorageStats {
+            total_entries: self.stats.total_entries.load(Ordering::Relaxed).into(),
+            hits: self.stats.hits.load(Ordering::Relaxed).into(),
+            misses: self.stats.misses.load(Ordering::Relaxed).into(),
+            evictions: self.stats.evictions.load(Ordering::Relaxed).into(),
+            expired_removals: self.stats.expired_removals.load(Ordering::Relaxed).into(),
+        }
+    }
+
+    /// Counts the live keys and the memory they use.
+    /// Expired entries that haven't been removed yet are skipped.
+    ///
+    /// # Returns
+    /// `KeyspaceInfo` with the key counts and the size in bytes
+    pub fn keyspace_info(&self) -> KeyspaceInfo {
+        self.store
+            .read()
+            .iter()
+            .filter(|(_, e)| !e.is_expired())
+            .fold(KeyspaceInfo::default(), |mut info, (k, e)| {
+                info.keys += 1;
+                info.expires += e.expires_at.is_some() as usize;
+                info.memory += k.len() + e.entry_size;
+                info
+            })
+    }
+
+    /// Resets all statistics to zero.
+    /// Used by the RESETSTATS command.
+    pub fn reset_stats(&mut self) {
+        let _old_stats = std::mem::take(&mut self.stats);
+    }
+
+    /// Retrieves a configuration entry by key name.
+    /// Used by the CONFGET command.
+    ///
+    /// # Arguments
+    /// * `key` - Configuration key name (case insensitive)
+    ///
+    /// # Returns
+    /// `Some(ConfigEntry)` if the key exists, `None` otherwise
+    pub fn get_config_entry(&self, key: &str) -> Option<ConfigEntry> {
+        match key.to_uppercase().as_str() {
+            "EVICTPOLICY" => Some(ConfigEntry::EvictPolicy(self.options.eviction_policy)),
+            "MAXCAP" => Some(ConfigEntry::MaxCapacity(self.options.max_capacity)),
+            "GLOBALTTL" => Some(ConfigEntry::GlobalTtl(self.options.ttl.as_secs() as usize)),
+            "COMPRESSION" => Some(ConfigEntry::Compression(self.options.compression.into())),
+            "COMPRESSIONTHRESHOLD" => Some(ConfigEntry::CompressionThreshold(
+                self.options.compression_threshold,
+            )),
+            _ => None,
+        }
+    }
+
+    /// Updates a configuration setting.
+    /// Used by the CONFSET command.
+    ///
+    /// # Arguments
+    /// * `entry` - The configuration entry to update
+    pub fn set_config_entry(&mut self, entry: &ConfigEntry) {
+        match entry {
+            ConfigEntry::EvictPolicy(p) => self.options.eviction_policy = *p,
+            ConfigEntry::GlobalTtl(t) => self.options.ttl = Duration::from_secs(*t as u64),
+            ConfigEntry::MaxCapacity(c) => self.options.max_capacity = *c,
+            ConfigEntry::Compression(b) => {
+                self.options.compression = <Compression as Into<bool>>::into(*b)
+            }
+            ConfigEntry::CompressionThreshold(s) => self.options.compression_threshold = *s,
+        }
+    }
+
+    /// Gets a copy of the current storage options.
+    ///
+    /// # Returns
+    /// Current `StorageOptions` configuration
+    pub fn get_options(&self) -> StorageOptions {
+        self.options
+    }
+
+    /// Resets the current storage options to the default value
+    /// provided by `StorageOptions::default()`
+    pub fn reset_options(&mut self) {
+        std::mem::take(&mut self.options);
+    }
+
+    /// Loads storage data from disk.
+    /// Used during startup to restore cache state from previous runs.
+    /// Snapshots of several databases load their default database.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the storage file
+    ///
+    /// # Returns
+    /// `anyhow::Result<()>` - Success or error details
+    ///
+    /// # Example
+    /// ```rust
+    /// use volatix_core::{StorageOptions, LockedStorage};
+    /// use std::path::Path;
+    ///
+    /// let mut storage = LockedStorage::new(StorageOptions::default());
+    ///
+    /// let db_path = Path::new("cache.bin").to_path_buf();
+    /// storage.load_from_disk(&db_path).expect("Failed to load cache");
+    /// ```
+    pub fn load_from_disk(&mut self, path: &PathBuf) -> anyhow::Result<()> {
+        let mut loaded = read_snapshot(path)?;
+
+        // Snapshots of several databases load their default one
+        let position = loaded
+            .iter()
+            .position(|(name, _)| name == DEFAULT_DATABASE)
+            .unwrap_or(0);
+        if position < loaded.len() {
+            let (_, storage) = loaded.swap_remove(position);
+            self.restore(storage);
+        }
+        Ok(())
+    }
+
+    /// Replaces the storage contents with a deserialized snapshot.
+    fn restore(&mut self, loaded_storage: SerializableStorage) {
+        // Convert non-atomic stats back to atomic
+        let stats = StorageStats {
+            total_entries: AtomicUsize::new(loaded_storage.stats.total_entries),
+            hits: AtomicUsize::new(loaded_storage.stats.hits),
+            misses: AtomicUsize::new(loaded_storage.stats.misses),
+            evictions: AtomicUsize::new(loaded_storage.stats.evictions),
+            expired_removals: AtomicUsize::new(loaded_storage.stats.expired_removals),
+        };
+
+        // Never hand out a version an entry already has
+        let version = loaded_storage
+            .store
+            .values()
+            .map(|entry| entry.version)
+            .fold(loaded_storage.version, u64::max);
+        self.version.store(version, Ordering::Relaxed);
+
+        // Replace current storage with loaded data
+        self.entry_count
+            .store(loaded_storage.store.len(), Ordering::Relaxed);
+        let index = loaded_storage
+            .store
+            .keys()
+            .map(|key| (scan_hash(key), key.clone()))
+            .collect();
+        let _ = std::mem::replace(&mut self.scan_index, RwLock::new(index));
+        let _ = std::mem::replace(&mut self.store, Arc::new(RwLock::new(loaded_storage.store)));
+        let _ = std::mem::replace(&mut self.stats, stats);
+        let _ = std::mem::replace(&mut self.options, loaded_storage.options);
+        self.is_dirty.store(false, Ordering::Relaxed);
+    }
+
+    /// Saves storage data to disk.
+    /// Used for persistence during shutdown and periodic snapshots.
+    ///
+    /// # Arguments
+    /// * `path` - Path where to save the storage file
+    ///
+    /// # Returns
+    /// `anyhow::Result<()>` - Success or error details
+    ///
+    /// # Example
+    /// ```rust
+    /// use volatix_core::{LockedStorage, StorageOptions};
+    /// use std::path::Path;
+    ///
+    /// let storage = LockedStorage::new(StorageOptions::default());
+    ///
+    /// let db_path = Path::new("/tmp/cache.bin").to_path_buf();
+    /// if storage.should_flush() {
+    ///     storage.save_to_disk(&db_path).expect("Failed to save cache");
+    /// }
+    /// ```
+    pub fn save_to_disk(&self, path: &PathBuf) -> anyhow::Result<()> {
+        // Written as the default database of a snapshot
+        encode_snapshot(path, &[(DEFAULT_DATABASE.to_string(), self.snapshot())])
+    }
+
+    /// Creates a serializable copy of the storage contents.
+    fn snapshot(&self) -> SerializableStorage {
+        // Convert atomic stats to non-atomic for serialization
+        let stats = NonAtomicStats {
+            total_entries: self.stats.total_entries.load(Ordering::Relaxed),
+            hits: self.stats.hits.load(Ordering::Relaxed),
+            misses: self.stats.misses.load(Ordering::Relaxed),
+            evictions: self.stats.evictions.load(Ordering::Relaxed),
+            expired_removals: self.stats.expired_removals.load(Ordering::Relaxed),
+        };
+
+        // Create serializable version
+        SerializableStorage {
+            store: self.store.read().clone(),
+            options: self.options,
+            stats,
+            version: self.version.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Name of the database every connection starts in.
+pub const DEFAULT_DATABASE: &str = "0";
+
+/// Most databases that can exist at once, like the default of Redis.
+pub const MAX_DATABASES: usize = 16;
+
+/// Starts every snapshot, followed by the layout version as two ASCII
+/// digits, e.g `VTXDBS02`.
+const SNAPSHOT_MAGIC: &[u8; 6] = b"VTXDBS";
+
+/// Layout version of the snapshots written by this build. Any change to
+/// the serialized form of `StorageEntry` or `SerializableStorage` bumps it
+/// and adds a migration from the previous version to `decode_snapshot`.
+///
+/// Known versions:
+/// * none - a single bare storage, entries with a relative TTL
+/// * `01` - named databases, with any of the entry layouts below, told
+///   apart by decoding
+/// * `02` - named databases, entries with an expiry deadline and a version
+const SNAPSHOT_VERSION: &[u8; 2] = b"02";
+
+/// Decodes a value that must span all of `data`, so that one layout is
+/// never mistaken for another. Lengths read from the wrong layout can't
+/// allocate past the size of `data`.
+fn decode_exact<T: serde::de::DeserializeOwned>(mut data: &[u8]) -> anyhow::Result<T> {
+    let value = bincode2::config()
+        .limit(data.len() as u64)
+        .deserialize_from(&mut data)?;
+    anyhow::ensure!(data.is_empty(), "{} trailing bytes", data.len());
+    Ok(value)
+}
+
+/// Decodes the databases of a snapshot written before layout versions,
+/// trying the entry layouts from the newest to the oldest.
+///
+/// # Arguments
+/// * `data` - The snapshot without its header
+/// * `named` - Whether it holds named databases or a single bare storage
+fn decode_unversioned(
+    data: &[u8],
+    named: bool,
+) -> anyhow::Result<Vec<(String, SerializableStorage)>> {
+    fn decode<S: serde::de::DeserializeOwned + Into<SerializableStorage>>(
+        data: &[u8],
+        named: bool,
+    ) -> anyhow::Result<Vec<(String, SerializableStorage)>> {
+        let loaded: Vec<(String, S)> = if named {
+            decode_exact(data)?
+        } else {
+            vec![(DEFAULT_DATABASE.to_string(), decode_exact(data)?)]
+        };
+        Ok(loaded.into_iter().map(|(n, s)| (n, s.into())).collect())
+    }
+
+    decode::<SerializableStorage>(data, named)
+        .or_else(|_| decode::<UnversionedStorage<EntryWithoutVersion>>(data, named))
+        .or_else(|_| decode::<UnversionedStorage<EntryWithTtl>>(data, named))
+        .context("snapshot doesn't match any known layout")
+}
+
+/// Decodes the databases of a snapshot, migrating older layouts.
+/// An empty snapshot holds no database.
+fn decode_snapshot(data: &[u8]) -> anyhow::Result<Vec<(String, SerializableStorage)>> {
+    if data.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let Some(rest) = data.strip_prefix(SNAPSHOT_MAGIC) else {
+        return decode_unversioned(data, false);
+    };
+    match rest.split_at_checked(SNAPSHOT_VERSION.len()) {
+        Some((version, data)) if version == SNAPSHOT_VERSION => {
+            decode_exact(data).context("deserialize databases")
+        }
+        Some((b"01", data)) => decode_unversioned(data, true),
+        Some((version, _)) => anyhow::bail!(
+            "Unsupported snapshot version {}",
+            String::from_utf8_lossy(version)
+        ),
+        None => anyhow::bail!("Truncated snapshot header"),
+    }
+}
+
+/// Writes a snapshot of named databases in the current layout.
+fn encode_snapshot(
+    path: &PathBuf,
+    databases: &[(String, SerializableStorage)],
+) -> anyhow::Result<()> {
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(Path::new(path))
+        .context("open db for writing")?;
+
+    let mut writer = BufWriter::new(file);
+    writer
+        .write_all(SNAPSHOT_MAGIC)
+        .and_then(|_| writer.write_all(SNAPSHOT_VERSION))
+        .context("write snapshot header")?;
+    bincode2::serialize_into(&mut writer, databases).context("serialize into db")?;
+    writer.flush().context("flush writer")?;
+    Ok(())
+}
+
+/// Reads a snapshot file, creating an empty one if it doesn't exist yet.
+fn read_snapshot(path: &PathBuf) -> anyhow::Result<Vec<(String, SerializableStorage)>> {
+    let path = Path::new(path);
+    if !path.exists() {
+        File::create(path)?;
+        return Ok(Vec::new()); // No existing data to load
+    }
+
+    let mut data = Vec::new();
+    File::open(path)
+        .context("open db")?
+        .read_to_end(&mut data)
+        .context("read db")?;
+    decode_snapshot(&data)
+}
+
+/// A set of named logical databases.
+/// Every database is an independent `LockedStorage` with its own options,
+/// statistics and keyspace. Databases are created by their first write,
+/// up to [`MAX_DATABASES`] of them.
+///
+/// # Example
+/// ```rust
+/// use volatix_core::{Databases, StorageOptions, StorageValue, DEFAULT_DATABASE};
+///
+/// let databases = Databases::new(StorageOptions::default());
+/// let sessions = databases.get_or_create("sessions").unwrap();
+/// sessions
+///     .write()
+///     .insert_entry("token".to_string(), StorageValue::Int(1))
+///     .unwrap();
+///
+/// assert!(databases.move_entry("token", "sessions", DEFAULT_DATABASE).unwrap());
+/// assert!(databases.get(DEFAULT_DATABASE).unwrap().read().key_exists("token"));
+/// ```
+pub struct Databases {
+    /// Databases by name
+    databases: RwLock<BTreeMap<String, Arc<RwLock<LockedStorage>>>>,
+    /// Options new databases are created with
+    default_options: StorageOptions,
+}
+
+impl Databases {
+    /// Creates a set holding only the default database.
+    ///
+    /// # Arguments
+    /// * `options` - Options of the default database and of databases
+    ///   created later on
+    pub fn new(options: StorageOptions) -> Self {
+        let databases = BTreeMap::from([(
+            DEFAULT_DATABASE.to_string(),
+            Arc::new(RwLock::new(LockedStorage::new(options))),
+        )]);
+
+        Databases {
+            databases: RwLock::new(databases),
+            default_options: options,
+        }
+    }
+
+    /// Returns a database if it exists.
+    pub fn get(&self, name: &str) -> Option<Arc<RwLock<LockedStorage>>> {
+        self.databases.read().get(name).cloned()
+    }
+
+    /// Returns a database, or an empty one that is never stored if it
+    /// doesn't exist. Reads go through it, so they don't create databases.
+    pub fn get_or_empty(&self, name: &str) -> Arc<RwLock<LockedStorage>> {
+        self.get(name)
+            .unwrap_or_else(|| Arc::new(RwLock::new(LockedStorage::new(self.default_options))))
+    }
+
+    /// Returns a database, creating an empty one if it doesn't exist yet.
+    ///
+    /// # Returns
+    /// The database, or an error if [`MAX_DATABASES`] already exist
+    pub fn get_or_create(&self, name: &str) -> Result<Arc<RwLock<LockedStorage>>, String> {
+        if let Some(db) = self.get(name) {
+            return Ok(db);
+        }
+
+        let mut databases = self.databases.write();
+        if let Some(db) = databases.get(name) {
+            return Ok(Arc::clone(db));
+        }
+        if databases.len() >= MAX_DATABASES {
+            return Err(format!("Too many databases, at most {MAX_DATABASES}"));
+        }
+
+        let db = Arc::new(RwLock::new(LockedStorage::new(self.default_options)));
+        // Mark the database dirty so that it gets persisted even while empty
+        db.read().is_dirty.store(true, Ordering::Relaxed);
+        databases.insert(name.to_string(), Arc::clone(&db));
+        Ok(db)
+    }
+
+    /// Returns the names of all databases in ascending order.
+    pub fn names(&self) -> Vec<String> {
+        self.databases.read().keys().cloned().collect()
+    }
+
+    /// Moves a key with its metadata from one database to another.
+    ///
+    /// # Arguments
+    /// * `key` - The key to move
+    /// * `from` - Name of the source database
+    /// * `to` - Name of the destination database, created if needed
+    ///
+    /// # Returns
+    /// `Ok(true)` if the key was moved, `Ok(false)` if it doesn't exist in
+    /// the source or already exists in the destination, or an error if both
+    /// databases are the same or the destination can't be created.
+    pub fn move_entry(&self, key: &str, from: &str, to: &str) -> Result<bool, String> {
+        if from == to {
+            return Err("Source and destination databases are the same".to_string());
+        }
+
+        let Some(source) = self.get(from) else {
+            return Ok(false);
+        };
+        let Some(entry) = source.write().take_entry(key) else {
+            return Ok(false);
+        };
+
+        // The check and the insert share one guard, so a concurrent write to
+        // the destination can't be overwritten
+        let entry = {
+            let destination = match self.get_or_create(to) {
+                Ok(destination) => destination,
+                Err(err) => {
+                    // Never hold both locks at once
+                    source.write().insert_raw_entry(key.to_string(), entry);
+                    return Err(err);
+                }
+            };
+            let mut destination = destination.write();
+            let exists = destination
+                .store
+                .read()
+                .get(key)
+                .is_some_and(|entry| !entry.is_expired());
+            if !exists {
+                destination.insert_raw_entry(key.to_string(), entry);
+                return Ok(true);
+            }
+            entry
+        };
+
+        // Never hold both locks at once, the entry goes back afterwards
+        source.write().insert_raw_entry(key.to_string(), entry);
+        Ok(false)
+    }
+
+    /// Checks whether any database has unsynched changes to disk.
+    pub fn should_flush(&self) -> bool {
+        self.databases
+            .read()
+            .values()
+            .any(|db| db.read().should_flush())
+    }
+
+    /// Loads all databases from a snapshot.
+    /// Snapshots of older layouts are migrated, and snapshots of a single
+    /// storage are loaded into the default database. A snapshot that can't
+    /// be decoded is an error rather than an empty store, so that it isn't
+    /// overwritten by the next snapshot.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the snapshot file
+    ///
+    /// # Returns
+    /// `anyhow::Result<()>` - Success or error details
+    pub fn load_from_disk(&self, path: &PathBuf) -> anyhow::Result<()> {
+        let loaded = read_snapshot(path)?;
+
+        let mut databases = self.databases.write();
+        for (name, storage) in loaded {
+            let mut db = LockedStorage::default();
+            db.restore(storage);
+            databases.insert(name, Arc::new(RwLock::new(db)));
+        }
+
+        Ok(())
+    }
+
+    /// Saves all databases to a snapshot and clears their dirty flags.
+    ///
+    /// # Arguments
+    /// * `path` - Path where to save the snapshot file
+    ///
+    /// # Returns
+    /// `anyhow::Result<()>` - Success or error details
+    pub fn save_to_disk(&self, path: &PathBuf) -> anyhow::Result<()> {
+        let databases: Vec<_> = self
+            .databases
+            .read()
+            .iter()
+            .map(|(name, db)| (name.clone(), Arc::clone(db)))
+            .collect();
+
+        // Writes need the write lock, so clearing the flag under the same
+        // read guard as the snapshot can't lose a write
+        let mut was_dirty = Vec::with_capacity(databases.len());
+        let snapshots: Vec<(String, SerializableStorage)> = databases
+            .iter()
+            .map(|(name, db)| {
+                let db = db.read();
+                was_dirty.push(db.is_dirty.swap(false, Ordering::Relaxed));
+                (name.clone(), db.snapshot())
+            })
+            .collect();
+
+        if let Err(err) = encode_snapshot(path, &snapshots) {
+            for ((_, db), dirty) in databases.iter().zip(was_dirty) {
+                if dirty {
+                    db.read().is_dirty.store(true, Ordering::Relaxed);
+                }
+            }
+            return Err(err);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod storage_tests {
+    use std::{collections::HashMap, sync::atomic::Ordering, thread, time::Duration};
+
+    use super::*;
+    use crate::parse_json;
+
+    // Test creating storage with custom options
+    #[test]
+    fn test_custom_options() {
+        let ttl = Duration::from_secs(30);
+        let max_capacity = 50;
+        let options = StorageOptions::new(
+            ttl,
+            max_capacity,
+            EvictionPolicy::Oldest,
+            Compression::Disabled,
+            0,
+        );
+        let storage = LockedStorage::new(options);
+
+        assert_eq!(storage.options.ttl, Duration::from_secs(30));
+        assert_eq!(storage.options.max_capacity, 50);
+        assert_eq!(storage.store.read().len(), 0);
+    }
+
+    // Test inserting and retrieving keys
+    #[test]
+    fn test_insert_and_get_key() {
+        let mut storage = LockedStorage::default();
+        let v = StorageValue::Text("test value".to_string());
+        storage
+            .insert_entry("test_key".to_string(), v.clone())
+            .unwrap();
+
+        let entry = storage.get_entry("test_key");
+        assert!(entry.is_some());
         assert_eq!(entry.unwrap().value, v);
     }
 
-    // Test removing keys
+    // Test removing keys
+    #[test]
+    fn test_remove_entry() {
+        let mut storage = LockedStorage::default();
+        let v = StorageValue::Text("test value".to_string());
+        storage.insert_entry("test_key".to_string(), v).unwrap();
+
+        assert!(storage.get_entry("test_key").is_some());
+
+        storage.remove_entry("test_key");
+        assert!(storage.get_entry("test_key").is_none());
+    }
+
+    // Test removing non-existent key
+    #[test]
+    fn test_remove_nonexistent_key() {
+        let mut storage = LockedStorage::default();
+        storage.remove_entry("nonexistent_key");
+        // Should not panic
+    }
+
+    // Test capacity enforcement
+    #[test]
+    fn test_capacity_enforcement() {
+        let options = StorageOptions::new(
+            Duration::from_secs(3600),
+            3,
+            EvictionPolicy::Oldest,
+            Compression::Disabled,
+            0,
+        );
+        let mut storage = LockedStorage::new(options);
+        let v1 = StorageValue::Text("value1".to_string());
+        let v2 = StorageValue::Text("value2".to_string());
+        let v3 = StorageValue::Text("value3".to_string());
+        let v4 = StorageValue::Text("value4".to_string());
+
+        // Insert up to capacity
+        storage.insert_entry("key1".to_string(), v1).unwrap();
+        storage.insert_entry("key2".to_string(), v2).unwrap();
+        storage.insert_entry("key3".to_string(), v3).unwrap();
+
+        assert_eq!(storage.store.read().len(), 3);
+        assert!(storage.is_full());
+
+        // Insert one more - oldest should be removed
+        storage.insert_entry("key4".to_string(), v4).unwrap();
+
+        assert_eq!(storage.store.read().len(), 3);
+        assert!(storage.get_entry("key1").is_none());
+        assert!(storage.get_entry("key2").is_some());
+        assert!(storage.get_entry("key3").is_some());
+        assert!(storage.get_entry("key4").is_some());
+    }
+
+    // Test TTL expiration
+    #[test]
+    fn test_ttl_expiration() {
+        let options = StorageOptions::new(
+            Duration::from_millis(100),
+            10,
+            EvictionPolicy::Oldest,
+            Compression::Disabled,
+            0,
+        );
+        let mut storage = LockedStorage::new(options);
+        let v1 = StorageValue::Text("value1".to_string());
+        let v2 = StorageValue::Text("value2".to_string());
+
+        storage.insert_entry("key1".to_string(), v1).unwrap();
+
+        // Verify key exists
+        assert!(storage.get_entry("key1").is_some());
+
+        // Wait for TTL to expire
+        thread::sleep(Duration::from_millis(150));
+
+        // Insert another key to trigger remove_expired()
+        storage.insert_entry("key2".to_string(), v2).unwrap();
+
+        // Verify expired key is gone
+        assert!(storage.get_entry("key1").is_none());
+        assert!(storage.get_entry("key2").is_some());
+    }
+
+    // Test the existing remove_expired function directly
+    #[test]
+    fn test_remove_expired_directly() {
+        let options = StorageOptions::new(
+            Duration::from_millis(100),
+            5,
+            EvictionPolicy::Oldest,
+            Compression::Disabled,
+            0,
+        );
+        let mut storage = LockedStorage::new(options);
+        let v1 = StorageValue::Text("value1".to_string());
+        let v2 = StorageValue::Text("value2".to_string());
+
+        storage.insert_entry("key1".to_string(), v1).unwrap();
+        storage.insert_entry("key2".to_string(), v2).unwrap();
+
+        assert_eq!(storage.store.read().len(), 2);
+
+        // Wait for TTL to expire
+        thread::sleep(Duration::from_millis(150));
+
+        // Call remove_expired directly
+        storage.remove_expired();
+
+        assert_eq!(storage.store.read().len(), 0);
+    }
+
+    // Test that oldest entries are removed when capacity is reached
+    #[test]
+    fn test_remove_oldest_key() {
+        let options = StorageOptions::new(
+            Duration::from_secs(3600),
+            3,
+            EvictionPolicy::Oldest,
+            Compression::Disabled,
+            0,
+        );
+        let mut storage = LockedStorage::new(options);
+        let v1 = StorageValue::Text("value1".to_string());
+        let v2 = StorageValue::Text("value2".to_string());
+        let v3 = StorageValue::Text("value3".to_string());
+        let v4 = StorageValue::Text("value4".to_string());
+
+        storage.insert_entry("key1".to_string(), v1).unwrap();
+
+        thread::sleep(Duration::from_millis(10)); // Ensure different timestamps
+        storage.insert_entry("key2".to_string(), v2).unwrap();
+        thread::sleep(Duration::from_millis(10));
+        storage.insert_entry("key3".to_string(), v3).unwrap();
+
+        assert_eq!(storage.store.read().len(), 3);
+
+        // Insert a new key - should remove the oldest (key1)
+        thread::sleep(Duration::from_millis(10));
+        storage.insert_entry("key4".to_string(), v4).unwrap();
+
+        assert_eq!(storage.store.read().len(), 3);
+        assert!(storage.get_entry("key1").is_none());
+        assert!(storage.get_entry("key2").is_some());
+        assert!(storage.get_entry("key3").is_some());
+        assert!(storage.get_entry("key4").is_some());
+    }
+
+    // Test updating an existing key
+    #[test]
+    fn test_update_existing_key() {
+        let mut storage = LockedStorage::default();
+
+        let v1 = StorageValue::Text("value1".to_string());
+        let v2 = StorageValue::Text("updated value".to_string());
+        storage.insert_entry("key1".to_string(), v1).unwrap();
+        let original_entry = storage.get_entry("key1").unwrap();
+
+        // Wait a moment to ensure timestamp is different
+        thread::sleep(Duration::from_millis(10));
+
+        // Update the key
+        storage.insert_entry("key1".to_string(), v2).unwrap();
+        let updated_entry = storage.get_entry("key1").unwrap();
+
+        assert_eq!(storage.store.read().len(), 1);
+        assert_eq!(
+            updated_entry.value,
+            StorageValue::Text("updated value".to_string())
+        );
+        assert!(updated_entry.created_at > original_entry.created_at);
+    }
+
+    #[test]
+    fn test_insert_with_custom_ttl() {
+        let mut storage = LockedStorage::default();
+        let ttl = Duration::from_secs(5);
+        let v1 = StorageValue::Text("custom val".to_string());
+        storage
+            .insert_with_ttl("custom_key".to_string(), v1, ttl)
+            .unwrap();
+
+        let entry = storage.get_entry("custom_key").unwrap();
+        assert_eq!(entry.value, StorageValue::Text("custom val".to_string()));
+        let remaining = entry.remaining_ttl().unwrap();
+        assert!(remaining <= ttl && remaining > ttl - Duration::from_secs(1));
+
+        let v2 = StorageValue::Text("forever".to_string());
+        let huge = Duration::from_secs(u64::MAX);
+        assert!(
+            storage
+                .insert_with_ttl("huge".to_string(), v2, huge)
+                .is_err()
+        );
+        assert!(!storage.key_exists("huge"));
+    }
+
+    #[test]
+    fn test_extend_ttl() {
+        let mut storage = LockedStorage::default();
+        let original_ttl = Duration::from_secs(5);
+        let extension = 10;
+        let v1 = StorageValue::Text("val".to_string());
+        storage
+            .insert_with_ttl("key".to_string(), v1, original_ttl)
+            .unwrap();
+
+        let result = storage.extend_ttl("key", extension);
+        assert!(result.is_ok());
+        let new_ttl = storage.time_to_live("key").unwrap().unwrap();
+        let expected = Duration::from_secs(extension as u64) + original_ttl;
+        assert!(new_ttl <= expected && new_ttl > expected - Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_extend_negative_ttl() {
+        let mut storage = LockedStorage::default();
+        let original_ttl = Duration::from_secs(5);
+        let extension = -10; // would overflow
+        let v1 = StorageValue::Text("val".to_string());
+        storage
+            .insert_with_ttl("key".to_string(), v1, original_ttl)
+            .unwrap();
+
+        let result = storage.extend_ttl("key", extension);
+        assert!(result.is_err());
+        let new_ttl = storage.time_to_live("key").unwrap().unwrap();
+        assert!(new_ttl <= original_ttl && new_ttl > original_ttl - Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_increment_by() {
+        let mut storage = LockedStorage::default();
+
+        // Missing keys start at 0
+        assert_eq!(storage.increment_by("counter", 5), Ok(5));
+        assert_eq!(storage.entry_count.load(Ordering::Relaxed), 1);
+        assert_eq!(storage.increment_by("counter", -7), Ok(-2));
+        assert_eq!(storage.increment_entry("counter"), Ok(-1));
+        assert_eq!(storage.decrement_entry("other"), Ok(-1));
+        assert_eq!(
+            storage.get_entry("counter").unwrap().value,
+            StorageValue::Int(-1)
+        );
+
+        // Overflow leaves the value untouched
+        storage
+            .insert_entry("max".to_string(), StorageValue::Int(i64::MAX))
+            .unwrap();
+        assert!(storage.increment_by("max", 1).is_err());
+        assert!(storage.increment_by("counter", i64::MIN).is_err());
+        assert_eq!(
+            storage.get_entry("max").unwrap().value,
+            StorageValue::Int(i64::MAX)
+        );
+
+        // Wrong types are rejected
+        storage
+            .insert_entry("name".to_string(), StorageValue::Text("bob".to_string()))
+            .unwrap();
+        storage
+            .insert_entry("ratio".to_string(), StorageValue::Float(0.5))
+            .unwrap();
+        assert!(storage.increment_by("name", 1).is_err());
+        assert!(storage.increment_by("ratio", 1).is_err());
+    }
+
+    #[test]
+    fn test_increment_by_float() {
+        let mut storage = LockedStorage::default();
+
+        assert_eq!(storage.increment_by_float("price", 1.5), Ok(1.5));
+        assert_eq!(storage.increment_by_float("price", -0.25), Ok(1.25));
+
+        // Integers are converted to floats
+        storage
+            .insert_entry("count".to_string(), StorageValue::Int(10))
+            .unwrap();
+        assert_eq!(storage.increment_by_float("count", 0.5), Ok(10.5));
+        assert_eq!(
+            storage.get_entry("count").unwrap().value,
+            StorageValue::Float(10.5)
+        );
+
+        assert!(storage.increment_by_float("price", f64::MAX).is_ok());
+        assert!(storage.increment_by_float("price", f64::MAX).is_err());
+        storage
+            .insert_entry("name".to_string(), StorageValue::Text("bob".to_string()))
+            .unwrap();
+        assert!(storage.increment_by_float("name", 1.0).is_err());
+    }
+
     #[test]
-    fn test_remove_entry() {
+    fn test_string_operations() {
         let mut storage = LockedStorage::default();
-        let v = StorageValue::Text("test value".to_string());
-        storage.insert_entry("test_key".to_string(), v).unwrap();
 
-        assert!(storage.get_entry("test_key").is_some());
+        assert_eq!(storage.append("log", "a;"), Ok(2));
+        assert_eq!(storage.append("log", "b;"), Ok(4));
+        assert_eq!(storage.string_length("log"), Ok(4));
+        assert_eq!(storage.string_length("missing"), Ok(0));
+
+        assert_eq!(storage.get_range("log", 0, 0).unwrap(), b"a");
+        assert_eq!(storage.get_range("log", -2, -1).unwrap(), b"b;");
+        assert_eq!(storage.get_range("log", 1, 100).unwrap(), b";b;");
+        assert_eq!(storage.get_range("log", -100, 1).unwrap(), b"a;");
+        assert!(storage.get_range("log", 3, 1).unwrap().is_empty());
+        assert!(storage.get_range("missing", 0, -1).unwrap().is_empty());
+
+        // SETRANGE pads missing bytes with zeros
+        assert_eq!(storage.set_range("log", 1, "!"), Ok(4));
+        assert_eq!(storage.set_range("padded", 2, "x"), Ok(3));
+        assert_eq!(storage.get_range("padded", 0, -1).unwrap(), b"\0\0x");
+        assert_eq!(storage.set_range("empty", 5, ""), Ok(0));
+        assert!(!storage.key_exists("empty"));
+        assert!(storage.set_range("log", MAX_STRING_SIZE, "x").is_err());
+
+        // Numbers and booleans stored by SET are strings too
+        storage
+            .insert_entry("n".to_string(), StorageValue::Int(10))
+            .unwrap();
+        assert_eq!(storage.string_length("n"), Ok(2));
+        assert_eq!(storage.get_range("n", 0, -1).unwrap(), b"10");
+        assert_eq!(storage.append("n", "5"), Ok(3));
+        assert_eq!(
+            storage.get_entry("n").unwrap().value,
+            StorageValue::Text("105".to_string())
+        );
+        storage
+            .insert_entry("f".to_string(), StorageValue::Float(1.5))
+            .unwrap();
+        assert_eq!(storage.set_range("f", 0, "2"), Ok(3));
+        assert_eq!(storage.get_range("f", 0, -1).unwrap(), b"2.5");
+        storage
+            .insert_entry("b".to_string(), StorageValue::Bool(true))
+            .unwrap();
+        assert_eq!(storage.string_length("b"), Ok(4));
 
-        storage.remove_entry("test_key");
-        assert!(storage.get_entry("test_key").is_none());
+        // Other types are not
+        storage
+            .insert_entry("l".to_string(), StorageValue::List(Vec::new()))
+            .unwrap();
+        assert!(storage.append("l", "1").is_err());
+        assert!(storage.string_length("l").is_err());
+        assert!(storage.get_range("l", 0, -1).is_err());
+
+        let entry = storage.get_and_remove("log").unwrap();
+        assert_eq!(entry.value, StorageValue::Text("a!b;".to_string()));
+        assert!(!storage.key_exists("log"));
+        assert!(storage.get_and_remove("log").is_none());
     }
 
-    // Test removing non-existent key
     #[test]
-    fn test_remove_nonexistent_key() {
+    fn test_bitmap_operations() {
         let mut storage = LockedStorage::default();
-        storage.remove_entry("nonexistent_key");
-        // Should not panic
+
+        assert_eq!(storage.set_bit("bits", 7, true), Ok(false));
+        assert_eq!(storage.set_bit("bits", 7, true), Ok(true));
+        assert_eq!(storage.set_bit("bits", 100, true), Ok(false));
+        assert_eq!(
+            storage.get_entry("bits").unwrap().value,
+            StorageValue::Bytes({
+                let mut bytes = vec![0; 13];
+                bytes[0] = 0x01;
+                bytes[12] = 0x08;
+                bytes
+            })
+        );
+        assert_eq!(storage.get_bit("bits", 7), Ok(true));
+        assert_eq!(storage.get_bit("bits", 6), Ok(false));
+        assert_eq!(storage.get_bit("bits", 10_000), Ok(false));
+        assert_eq!(storage.get_bit("missing", 0), Ok(false));
+        assert_eq!(storage.set_bit("bits", 7, false), Ok(true));
+        assert!(storage.set_bit("bits", MAX_STRING_SIZE * 8, true).is_err());
+
+        // Valid UTF-8 bitmaps stay bytes
+        storage.set_bit("char", 1, true).unwrap();
+        assert_eq!(
+            storage.get_entry("char").unwrap().value,
+            StorageValue::Bytes(vec![0x40])
+        );
+
+        // Numbers are bitmaps of their digits, "1" being 0x31
+        storage
+            .insert_entry("n".to_string(), StorageValue::Int(1))
+            .unwrap();
+        assert_eq!(storage.get_bit("n", 2), Ok(true));
+        assert_eq!(storage.set_bit("n", 0, true), Ok(false));
+
+        storage
+            .insert_entry("l".to_string(), StorageValue::List(Vec::new()))
+            .unwrap();
+        assert!(storage.set_bit("l", 0, true).is_err());
+        assert!(storage.get_bit("l", 0).is_err());
     }
 
-    // Test capacity enforcement
     #[test]
-    fn test_capacity_enforcement() {
-        let options = StorageOptions::new(
-            Duration::from_secs(3600),
-            3,
-            EvictionPolicy::Oldest,
-            Compression::Disabled,
-            0,
-        );
-        let mut storage = LockedStorage::new(options);
-        let v1 = StorageValue::Text("value1".to_string());
-        let v2 = StorageValue::Text("value2".to_string());
-        let v3 = StorageValue::Text("value3".to_string());
-        let v4 = StorageValue::Text("value4".to_string());
+    fn test_bit_count_and_pos() {
+        let mut storage = LockedStorage::default();
+        storage
+            .insert_entry(
+                "bits".to_string(),
+                StorageValue::Bytes(vec![0xff, 0xf0, 0x00, 0x01, 0, 0, 0, 0, 0, 0x03]),
+            )
+            .unwrap();
 
-        // Insert up to capacity
-        storage.insert_entry("key1".to_string(), v1).unwrap();
-        storage.insert_entry("key2".to_string(), v2).unwrap();
-        storage.insert_entry("key3".to_string(), v3).unwrap();
+        assert_eq!(storage.bit_count("bits", None), Ok(15));
+        assert_eq!(storage.bit_count("bits", Some((1, 1))), Ok(4));
+        assert_eq!(storage.bit_count("bits", Some((-1, -1))), Ok(2));
+        assert_eq!(storage.bit_count("bits", Some((5, 2))), Ok(0));
+        assert_eq!(storage.bit_count("missing", None), Ok(0));
+        assert_eq!(popcount(&[0xff; 17]), 17 * 8);
 
-        assert_eq!(storage.store.read().len(), 3);
-        assert!(storage.is_full());
+        assert_eq!(storage.bit_pos("bits", true, 0, None), Ok(0));
+        assert_eq!(storage.bit_pos("bits", false, 0, None), Ok(12));
+        assert_eq!(storage.bit_pos("bits", true, 2, None), Ok(31));
+        assert_eq!(storage.bit_pos("bits", true, 4, Some(8)), Ok(-1));
+        assert_eq!(storage.bit_pos("bits", true, -1, None), Ok(78));
 
-        // Insert one more - oldest should be removed
-        storage.insert_entry("key4".to_string(), v4).unwrap();
+        // Clear bits past the end only count without an explicit end
+        storage
+            .insert_entry("ones".to_string(), StorageValue::Bytes(vec![0xff, 0xff]))
+            .unwrap();
+        assert_eq!(storage.bit_pos("ones", false, 0, None), Ok(16));
+        assert_eq!(storage.bit_pos("ones", false, 0, Some(-1)), Ok(-1));
+        assert_eq!(storage.bit_pos("missing", false, 0, None), Ok(0));
+        assert_eq!(storage.bit_pos("missing", true, 0, None), Ok(-1));
+    }
 
-        assert_eq!(storage.store.read().len(), 3);
-        assert!(storage.get_entry("key1").is_none());
-        assert!(storage.get_entry("key2").is_some());
-        assert!(storage.get_entry("key3").is_some());
-        assert!(storage.get_entry("key4").is_some());
+    #[test]
+    fn test_bit_op() {
+        let mut storage = LockedStorage::default();
+        storage
+            .insert_entry("a".to_string(), StorageValue::Bytes(vec![0b1100, 0xff]))
+            .unwrap();
+        storage
+            .insert_entry("b".to_string(), StorageValue::Bytes(vec![0b1010]))
+            .unwrap();
+
+        let result = |storage: &LockedStorage| storage.get_entry("dest").unwrap().value;
+        assert_eq!(storage.bit_op(BitOp::And, "dest", &["a", "b"]), Ok(2));
+        assert_eq!(result(&storage), StorageValue::Bytes(vec![0b1000, 0]));
+        storage.bit_op(BitOp::Or, "dest", &["a", "b"]).unwrap();
+        assert_eq!(result(&storage), StorageValue::Bytes(vec![0b1110, 0xff]));
+        storage
+            .bit_op(BitOp::Xor, "dest", &["a", "b", "missing"])
+            .unwrap();
+        assert_eq!(result(&storage), StorageValue::Bytes(vec![0b0110, 0xff]));
+        storage.bit_op(BitOp::Not, "dest", &["b"]).unwrap();
+        assert_eq!(result(&storage), StorageValue::Bytes(vec![0b1111_0101]));
+
+        assert!(storage.bit_op(BitOp::Not, "dest", &["a", "b"]).is_err());
+        assert!(storage.bit_op::<&str>(BitOp::And, "dest", &[]).is_err());
+
+        // An empty result deletes the destination
+        assert_eq!(storage.bit_op(BitOp::Or, "dest", &["missing"]), Ok(0));
+        assert!(!storage.key_exists("dest"));
+
+        assert_eq!(BitOp::parse("xor"), Ok(BitOp::Xor));
+        assert!(BitOp::parse("NAND").is_err());
     }
 
-    // Test TTL expiration
     #[test]
-    fn test_ttl_expiration() {
-        let options = StorageOptions::new(
-            Duration::from_millis(100),
-            10,
-            EvictionPolicy::Oldest,
-            Compression::Disabled,
-            0,
+    fn test_hyperloglog() {
+        let mut storage = LockedStorage::default();
+        assert_eq!(storage.pf_add::<&str>("empty", &[]), Ok(true));
+        assert_eq!(storage.pf_count(&["empty", "missing"]), Ok(0));
+
+        let mon: Vec<String> = (0..5000).map(|i| format!("user:{i}")).collect();
+        let tue: Vec<String> = (2500..7500).map(|i| format!("user:{i}")).collect();
+        assert_eq!(storage.pf_add("mon", &mon), Ok(true));
+        assert_eq!(storage.pf_add("mon", &mon[..10]), Ok(false));
+        storage.pf_add("tue", &tue).unwrap();
+
+        let estimate = storage.pf_count(&["mon", "tue"]).unwrap();
+        assert!(estimate.abs_diff(7500) < 150, "estimated {estimate}");
+
+        // The destination's own sketch is part of the union
+        storage.pf_add("week", &["guest"]).unwrap();
+        let union = storage.pf_count(&["week", "mon", "tue"]).unwrap();
+        storage.pf_merge("week", &["mon", "tue"]).unwrap();
+        assert_eq!(storage.pf_count(&["week"]), Ok(union));
+
+        storage
+            .insert_entry("text".to_string(), StorageValue::Text("a".to_string()))
+            .unwrap();
+        assert!(storage.pf_add("text", &["a"]).is_err());
+        assert!(storage.pf_count(&["mon", "text"]).is_err());
+        assert!(storage.pf_merge("text", &["mon"]).is_err());
+
+        // Sketches survive snapshots
+        let path = std::env::temp_dir().join("volatix_hyperloglog_test.bin");
+        storage.save_to_disk(&path).unwrap();
+        let mut loaded = LockedStorage::default();
+        loaded.load_from_disk(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.pf_count(&["week"]), Ok(union));
+        assert_eq!(
+            loaded.get_entry("mon").unwrap().value,
+            storage.get_entry("mon").unwrap().value
         );
-        let mut storage = LockedStorage::new(options);
-        let v1 = StorageValue::Text("value1".to_string());
-        let v2 = StorageValue::Text("value2".to_string());
+    }
 
-        storage.insert_entry("key1".to_string(), v1).unwrap();
+    #[test]
+    fn test_membership_filters() {
+        let mut storage = LockedStorage::default();
+        storage.bf_reserve("seen", 0.01, 100).unwrap();
+        assert!(storage.bf_reserve("seen", 0.01, 100).is_err());
+        assert!(storage.bf_reserve("bad", 1.5, 100).is_err());
+
+        let items: Vec<String> = (0..1000).map(|i| format!("event:{i}")).collect();
+        let size = storage.get_entry("seen").unwrap().entry_size;
+        let added = storage.bf_add("seen", &items).unwrap();
+        assert!(added.iter().filter(|added| **added).count() > 990);
+        assert_eq!(storage.bf_exists("seen", &items), Ok(vec![true; 1000]));
+        assert_eq!(storage.bf_exists("missing", &["a"]), Ok(vec![false]));
+
+        // Growing past the capacity adds layers, which count towards the size
+        assert!(storage.get_entry("seen").unwrap().entry_size > size);
+
+        assert_eq!(storage.cf_add("jobs", "job:1", false), Ok(true));
+        assert_eq!(storage.cf_add("jobs", "job:1", true), Ok(false));
+        assert_eq!(storage.cf_exists("jobs", "job:1"), Ok(true));
+        assert_eq!(storage.cf_delete("jobs", "job:1"), Ok(true));
+        assert_eq!(storage.cf_delete("jobs", "job:1"), Ok(false));
+        assert!(storage.cf_delete("missing", "job:1").is_err());
+
+        assert!(storage.bf_add("jobs", &["a"]).is_err());
+        assert!(storage.cf_exists("seen", "a").is_err());
+        assert!(storage.cf_reserve("seen", 10).is_err());
+
+        storage.cf_add("jobs", "job:2", false).unwrap();
+        let path = std::env::temp_dir().join("volatix_filters_test.bin");
+        storage.save_to_disk(&path).unwrap();
+        let mut loaded = LockedStorage::default();
+        loaded.load_from_disk(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.bf_exists("seen", &items), Ok(vec![true; 1000]));
+        assert_eq!(loaded.cf_exists("jobs", "job:2"), Ok(true));
+    }
 
-        // Verify key exists
-        assert!(storage.get_entry("key1").is_some());
+    #[test]
+    fn test_list_push_and_pop() {
+        let mut storage = LockedStorage::default();
+        let text = |v: &str| StorageValue::Text(v.to_string());
 
-        // Wait for TTL to expire
-        thread::sleep(Duration::from_millis(150));
+        assert_eq!(
+            storage.list_push("q", vec![text("b"), text("c")], false),
+            Ok(2)
+        );
+        assert_eq!(
+            storage.list_push("q", vec![text("a"), text("z")], true),
+            Ok(4)
+        );
+        assert_eq!(
+            storage.get_entry("q").unwrap().value,
+            StorageValue::List(vec![text("z"), text("a"), text("b"), text("c")])
+        );
 
-        // Insert another key to trigger remove_expired()
-        storage.insert_entry("key2".to_string(), v2).unwrap();
+        assert_eq!(storage.list_pop("q", true), Ok(Some(text("z"))));
+        assert_eq!(storage.list_pop("q", false), Ok(Some(text("c"))));
+        assert_eq!(storage.list_len("q"), Ok(2));
 
-        // Verify expired key is gone
-        assert!(storage.get_entry("key1").is_none());
-        assert!(storage.get_entry("key2").is_some());
+        // The key is removed with its last value
+        storage.list_pop("q", true).unwrap();
+        storage.list_pop("q", true).unwrap();
+        assert!(!storage.key_exists("q"));
+        assert_eq!(storage.list_pop("q", true), Ok(None));
+        assert_eq!(storage.list_len("q"), Ok(0));
+
+        storage
+            .insert_entry("n".to_string(), StorageValue::Int(1))
+            .unwrap();
+        assert!(storage.list_push("n", vec![text("a")], true).is_err());
+        assert!(storage.list_pop("n", true).is_err());
+        assert!(storage.list_len("n").is_err());
     }
 
-    // Test the existing remove_expired function directly
     #[test]
-    fn test_remove_expired_directly() {
-        let options = StorageOptions::new(
-            Duration::from_millis(100),
-            5,
-            EvictionPolicy::Oldest,
-            Compression::Disabled,
-            0,
+    fn test_geo() {
+        let mut storage = LockedStorage::default();
+        let sicily = [
+            (13.361389, 38.115556, "Palermo"),
+            (15.087269, 37.502669, "Catania"),
+        ];
+        let around = |storage: &LockedStorage, radius: f64| {
+            let origin = GeoOrigin::Position(15.0, 37.0);
+            let found = storage.geo_search("sicily", &origin, GeoShape::Radius(radius));
+            found
+                .unwrap()
+                .into_iter()
+                .map(|m| m.member)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            storage.geo_add("sicily", &sicily, SetCondition::Always, false),
+            Ok(2)
         );
-        let mut storage = LockedStorage::new(options);
-        let v1 = StorageValue::Text("value1".to_string());
-        let v2 = StorageValue::Text("value2".to_string());
+        assert_eq!(
+            storage.geo_add("sicily", &sicily, SetCondition::Always, false),
+            Ok(0)
+        );
+        assert_eq!(around(&storage, 200_000.0), ["Catania", "Palermo"]);
 
-        storage.insert_entry("key1".to_string(), v1).unwrap();
-        storage.insert_entry("key2".to_string(), v2).unwrap();
+        // Only existing members are moved with XX, and counted with CH
+        let moved = [(15.0, 37.0, "Palermo"), (15.0, 37.0, "Agrigento")];
+        assert_eq!(
+            storage.geo_add("sicily", &moved, SetCondition::IfPresent, true),
+            Ok(1)
+        );
+        assert_eq!(around(&storage, 1.0), ["Palermo"]);
+        assert_eq!(
+            storage.geo_add("sicily", &moved, SetCondition::IfAbsent, false),
+            Ok(1)
+        );
+        assert_eq!(
+            storage.geo_add("none", &moved, SetCondition::IfPresent, false),
+            Ok(0)
+        );
+        assert!(!storage.key_exists("none"));
+
+        // Invalid positions write nothing
+        let invalid = [(1.0, 1.0, "a"), (200.0, 1.0, "b")];
+        assert!(
+            storage
+                .geo_add("sicily", &invalid, SetCondition::Always, false)
+                .is_err()
+        );
+        assert_eq!(storage.geo_dist("sicily", "a", "Palermo"), Ok(None));
 
-        assert_eq!(storage.store.read().len(), 2);
+        let distance = storage
+            .geo_dist("sicily", "Catania", "Palermo")
+            .unwrap()
+            .unwrap();
+        assert_eq!(format!("{:.3}", distance / 1000.0), "56.441");
+        assert_eq!(storage.geo_dist("missing", "a", "b"), Ok(None));
+
+        let origin = GeoOrigin::Member("Catania".to_string());
+        let found = storage.geo_search("sicily", &origin, GeoShape::Radius(10.0));
+        assert_eq!(found.unwrap()[0].member, "Catania");
+        let origin = GeoOrigin::Member("Rome".to_string());
+        assert!(
+            storage
+                .geo_search("sicily", &origin, GeoShape::Radius(10.0))
+                .is_err()
+        );
+        let found = storage.geo_search("missing", &origin, GeoShape::Radius(10.0));
+        assert_eq!(found, Ok(Vec::new()));
+    }
 
-        // Wait for TTL to expire
-        thread::sleep(Duration::from_millis(150));
+    #[test]
+    fn test_json() {
+        let mut storage = LockedStorage::default();
+        let path = |path: &str| JsonPath::parse(path).unwrap();
+        let doc = parse_json(r#"{"name":"Ada","visits":1,"tags":["a"]}"#).unwrap();
+        let get = |storage: &LockedStorage| storage.json_get("user", &[]).unwrap().unwrap();
+
+        // New documents are created at the root only
+        assert!(
+            storage
+                .json_set(
+                    "user",
+                    &path("$.name"),
+                    StorageValue::Null,
+                    SetCondition::Always
+                )
+                .is_err()
+        );
+        assert_eq!(
+            storage.json_set("user", &path("$"), doc.clone(), SetCondition::IfPresent),
+            Ok(false)
+        );
+        assert_eq!(
+            storage.json_set("user", &path("$"), doc, SetCondition::Always),
+            Ok(true)
+        );
+        assert_eq!(
+            storage.json_set(
+                "user",
+                &path("$.name"),
+                StorageValue::Null,
+                SetCondition::IfAbsent
+            ),
+            Ok(false)
+        );
+        assert_eq!(
+            storage.json_set(
+                "user",
+                &path("$.a.b"),
+                StorageValue::Null,
+                SetCondition::Always
+            ),
+            Ok(false)
+        );
 
-        // Call remove_expired directly
-        storage.remove_expired();
+        let version = storage.get_entry("user").unwrap().version;
+        assert_eq!(
+            storage.json_num_incr_by("user", &path("$.visits"), StorageValue::Float(0.5)),
+            Ok(StorageValue::List(vec![StorageValue::Float(1.5)]))
+        );
+        assert!(storage.get_entry("user").unwrap().version > version);
+        assert_eq!(
+            storage.json_num_incr_by("user", &path(".name"), StorageValue::Int(1)),
+            Err("Value is not a number".to_string())
+        );
+        assert!(
+            storage
+                .json_num_incr_by("missing", &path("$"), StorageValue::Int(1))
+                .is_err()
+        );
 
-        assert_eq!(storage.store.read().len(), 0);
+        let values = [StorageValue::Int(1), StorageValue::Bool(true)];
+        assert_eq!(
+            storage.json_arr_append("user", &path(".tags"), &values),
+            Ok(StorageValue::Int(3))
+        );
+        assert_eq!(storage.json_del("user", &path("$.tags[0]")), Ok(1));
+        assert_eq!(
+            get(&storage),
+            r#"{"name":"Ada","visits":1.5,"tags":[1,true]}"#
+        );
+
+        let paths = [path("$.name"), path(".visits")];
+        assert_eq!(
+            storage.json_get("user", &paths).unwrap().as_deref(),
+            Some(r#"{"$.name":["Ada"],".visits":1.5}"#)
+        );
+        assert!(storage.json_get("user", &[path(".missing")]).is_err());
+
+        assert_eq!(storage.json_del("user", &path("$")), Ok(1));
+        assert_eq!(storage.json_get("user", &[]), Ok(None));
+        assert_eq!(storage.json_del("user", &path("$")), Ok(0));
+
+        storage.pf_add("sketch", &["a"]).unwrap();
+        assert!(storage.json_get("sketch", &[]).is_err());
+        assert!(storage.json_del("sketch", &path("$.a")).is_err());
     }
 
-    // Test that oldest entries are removed when capacity is reached
     #[test]
-    fn test_remove_oldest_key() {
+    fn test_json_on_compressed_documents() {
         let options = StorageOptions::new(
-            Duration::from_secs(3600),
-            3,
+            Duration::from_secs(60),
+            100,
             EvictionPolicy::Oldest,
-            Compression::Disabled,
-            0,
+            Compression::Enabled,
+            64,
         );
         let mut storage = LockedStorage::new(options);
-        let v1 = StorageValue::Text("value1".to_string());
-        let v2 = StorageValue::Text("value2".to_string());
-        let v3 = StorageValue::Text("value3".to_string());
-        let v4 = StorageValue::Text("value4".to_string());
+        let root = JsonPath::parse("$").unwrap();
 
-        storage.insert_entry("key1".to_string(), v1).unwrap();
+        storage
+            .insert_entry("text".to_string(), StorageValue::Text("x".repeat(100)))
+            .unwrap();
+        assert!(storage.store.read()["text"].compressed);
 
-        thread::sleep(Duration::from_millis(10)); // Ensure different timestamps
-        storage.insert_entry("key2".to_string(), v2).unwrap();
-        thread::sleep(Duration::from_millis(10));
-        storage.insert_entry("key3".to_string(), v3).unwrap();
+        let expected = format!("\"{}\"", "x".repeat(100));
+        assert_eq!(storage.json_get("text", &[]), Ok(Some(expected)));
+        let reply = storage.json_num_incr_by("text", &root, StorageValue::Int(1));
+        assert_eq!(reply, Ok(StorageValue::List(vec![StorageValue::Null])));
+        assert!(storage.store.read()["text"].compressed);
 
-        assert_eq!(storage.store.read().len(), 3);
+        let value = StorageValue::Text("y".repeat(100));
+        assert_eq!(
+            storage.json_set("text", &root, value, SetCondition::Always),
+            Ok(true)
+        );
+        assert!(storage.store.read()["text"].compressed);
+        assert_eq!(
+            storage.get_entry("text").unwrap().value,
+            StorageValue::Text("y".repeat(100))
+        );
+    }
 
-        // Insert a new key - should remove the oldest (key1)
-        thread::sleep(Duration::from_millis(10));
-        storage.insert_entry("key4".to_string(), v4).unwrap();
+    #[test]
+    fn test_streams() {
+        let mut storage = LockedStorage::default();
+        let fields = |v: &str| vec![("v".to_string(), v.to_string())];
+        let all = (StreamId::MIN, StreamId::MAX);
 
-        assert_eq!(storage.store.read().len(), 3);
-        assert!(storage.get_entry("key1").is_none());
-        assert!(storage.get_entry("key2").is_some());
-        assert!(storage.get_entry("key3").is_some());
-        assert!(storage.get_entry("key4").is_some());
+        assert!(
+            storage
+                .stream_create_group("jobs", "workers", "$", false)
+                .is_err()
+        );
+        storage
+            .stream_create_group("jobs", "workers", "$", true)
+            .unwrap();
+        for i in 1..=5 {
+            storage
+                .stream_add("jobs", &format!("{i}-*"), fields(&i.to_string()), Some(4))
+                .unwrap();
+        }
+        assert_eq!(storage.stream_len("jobs"), Ok(4));
+        assert_eq!(storage.stream_len("missing"), Ok(0));
+        let newest = storage
+            .stream_range("jobs", all.0, all.1, Some(1), true)
+            .unwrap();
+        assert_eq!(newest, vec![(StreamId { ms: 5, seq: 0 }, fields("5"))]);
+
+        let read = storage
+            .stream_read_group("jobs", "workers", "w1", None, Some(3))
+            .unwrap();
+        assert_eq!(read.len(), 3);
+        assert!(
+            storage
+                .stream_read_group("jobs", "nope", "w1", None, None)
+                .is_err()
+        );
+        assert!(
+            storage
+                .stream_read_group("missing", "workers", "w1", None, None)
+                .is_err()
+        );
+
+        assert_eq!(storage.stream_ack("jobs", "workers", &[read[0].0]), Ok(1));
+        let summary = storage.stream_pending_summary("jobs", "workers").unwrap();
+        assert_eq!(summary.count, 2);
+        assert_eq!(summary.consumers, vec![("w1".to_string(), 2)]);
+        let pending = storage
+            .stream_pending("jobs", "workers", all.0, all.1, 10, None)
+            .unwrap();
+        assert_eq!(pending[0].0, read[1].0);
+
+        assert_eq!(storage.stream_trim("jobs", 1), Ok(3));
+        assert_eq!(storage.stream_trim("missing", 1), Ok(0));
+        storage
+            .insert_entry("text".to_string(), StorageValue::Text("a".to_string()))
+            .unwrap();
+        assert!(storage.stream_add("text", "*", fields("a"), None).is_err());
+        assert!(storage.stream_len("text").is_err());
+
+        // Streams and their groups survive snapshots
+        let path = std::env::temp_dir().join("volatix_stream_test.bin");
+        storage.save_to_disk(&path).unwrap();
+        let mut loaded = LockedStorage::default();
+        loaded.load_from_disk(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            loaded.stream_pending_summary("jobs", "workers"),
+            storage.stream_pending_summary("jobs", "workers")
+        );
+        assert!(loaded.stream_add("jobs", "5-0", fields("x"), None).is_err());
+        assert!(storage.stream_destroy_group("jobs", "workers").unwrap());
     }
 
-    // Test updating an existing key
     #[test]
-    fn test_update_existing_key() {
+    fn test_string_operations_on_compressed_entries() {
+        let options = StorageOptions::new(
+            Duration::from_secs(60),
+            100,
+            EvictionPolicy::Oldest,
+            Compression::Enabled,
+            64,
+        );
+        let mut storage = LockedStorage::new(options);
+
+        storage
+            .insert_entry("log".to_string(), StorageValue::Text("x".repeat(100)))
+            .unwrap();
+        assert!(storage.store.read()["log"].compressed);
+
+        assert_eq!(storage.append("log", "end"), Ok(103));
+        assert_eq!(storage.set_range("log", 0, "start"), Ok(103));
+        assert!(storage.store.read()["log"].compressed);
+        assert_eq!(storage.string_length("log"), Ok(103));
+        assert_eq!(storage.get_range("log", -3, -1).unwrap(), b"end");
+        assert_eq!(storage.get_range("log", 0, 5).unwrap(), b"startx");
+
+        // Small values are not compressed
+        assert_eq!(storage.append("short", "tiny"), Ok(4));
+        assert!(!storage.store.read()["short"].compressed);
+    }
+
+    #[test]
+    fn test_versions_increase_on_every_write() {
         let mut storage = LockedStorage::default();
+        let version = |storage: &LockedStorage, key: &str| storage.get_entry(key).unwrap().version;
 
-        let v1 = StorageValue::Text("value1".to_string());
-        let v2 = StorageValue::Text("updated value".to_string());
-        storage.insert_entry("key1".to_string(), v1).unwrap();
-        let original_entry = storage.get_entry("key1").unwrap();
+        storage
+            .insert_entry("k".to_string(), StorageValue::Int(1))
+            .unwrap();
+        let v1 = version(&storage, "k");
+        storage.increment_entry("k").unwrap();
+        let v2 = version(&storage, "k");
+        storage
+            .insert_entry("k".to_string(), StorageValue::Text("a".to_string()))
+            .unwrap();
+        let v3 = version(&storage, "k");
+        storage.append("k", "b").unwrap();
+        let v4 = version(&storage, "k");
+        assert!(v1 < v2 && v2 < v3 && v3 < v4);
+
+        // Reads and expiry changes keep the version
+        storage.extend_ttl("k", 10).unwrap();
+        storage.get_range("k", 0, -1).unwrap();
+        assert_eq!(version(&storage, "k"), v4);
+
+        // A recreated key never reuses an old version
+        storage.remove_entry("k");
+        storage
+            .insert_entry("k".to_string(), StorageValue::Int(1))
+            .unwrap();
+        assert!(version(&storage, "k") > v4);
+    }
 
-        // Wait a moment to ensure timestamp is different
-        thread::sleep(Duration::from_millis(10));
+    #[test]
+    fn test_compare_and_swap() {
+        let mut storage = LockedStorage::default();
 
-        // Update the key
-        storage.insert_entry("key1".to_string(), v2).unwrap();
-        let updated_entry = storage.get_entry("key1").unwrap();
+        // Version 0 only creates missing keys
+        let v1 = storage
+            .compare_and_swap("k", 0, StorageValue::Int(1))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            storage.compare_and_swap("k", 0, StorageValue::Int(9)),
+            Ok(None)
+        );
 
-        assert_eq!(storage.store.read().len(), 1);
+        let v2 = storage
+            .compare_and_swap("k", v1, StorageValue::Int(2))
+            .unwrap()
+            .unwrap();
+        assert!(v2 > v1);
         assert_eq!(
-            updated_entry.value,
-            StorageValue::Text("updated value".to_string())
+            storage.compare_and_swap("k", v1, StorageValue::Int(3)),
+            Ok(None)
         );
-        assert!(updated_entry.created_at > original_entry.created_at);
+        assert_eq!(storage.get_entry("k").unwrap().value, StorageValue::Int(2));
+        assert_eq!(
+            storage.compare_and_swap("missing", 5, StorageValue::Int(1)),
+            Ok(None)
+        );
+
+        // The expiry is kept
+        storage.persist("k");
+        storage
+            .compare_and_swap("k", v2, StorageValue::Int(4))
+            .unwrap()
+            .unwrap();
+        assert_eq!(storage.time_to_live("k"), Some(None));
     }
 
     #[test]
-    fn test_insert_with_custom_ttl() {
+    fn test_insert_entries_all_or_none() {
         let mut storage = LockedStorage::default();
-        let ttl = Duration::from_secs(5);
-        let v1 = StorageValue::Text("custom val".to_string());
         storage
-            .insert_with_ttl("custom_key".to_string(), v1, ttl)
+            .insert_entries(vec![
+                ("a".to_string(), StorageValue::Int(1)),
+                ("b".to_string(), StorageValue::Int(2)),
+            ])
             .unwrap();
+        assert_eq!(storage.get_entry("b").unwrap().value, StorageValue::Int(2));
+        assert_eq!(storage.entry_count.load(Ordering::Relaxed), 2);
 
-        let entry = storage.get_entry("custom_key").unwrap();
-        assert_eq!(entry.value, StorageValue::Text("custom val".to_string()));
-        assert_eq!(entry.ttl, ttl);
+        // A default TTL past the end of time fails before anything is written
+        let options = StorageOptions::new(
+            Duration::MAX,
+            100,
+            EvictionPolicy::LRU,
+            Compression::Disabled,
+            0,
+        );
+        let mut storage = LockedStorage::new(options);
+        assert!(
+            storage
+                .insert_entries(vec![("a".to_string(), StorageValue::Int(1))])
+                .is_err()
+        );
+        assert!(!storage.key_exists("a"));
     }
 
     #[test]
-    fn test_extend_ttl() {
+    fn test_compare_and_swap_after_flush() {
         let mut storage = LockedStorage::default();
-        let original_ttl = Duration::from_secs(5);
-        let extension = 10;
-        let v1 = StorageValue::Text("val".to_string());
         storage
-            .insert_with_ttl("key".to_string(), v1, original_ttl)
+            .insert_entry("k".to_string(), StorageValue::Int(1))
+            .unwrap();
+        storage
+            .insert_entry("k".to_string(), StorageValue::Int(2))
+            .unwrap();
+        let stale = storage.get_entry("k").unwrap().version;
+
+        storage.flush();
+        storage
+            .insert_entry("k".to_string(), StorageValue::Int(3))
+            .unwrap();
+        storage
+            .insert_entry("k".to_string(), StorageValue::Int(4))
             .unwrap();
 
-        let result = storage.extend_ttl("key", extension);
-        assert!(result.is_ok());
-        let new_ttl = storage.time_to_live("key").unwrap();
         assert_eq!(
-            new_ttl,
-            Duration::from_secs(extension as u64) + original_ttl
+            storage.compare_and_swap("k", stale, StorageValue::Int(5)),
+            Ok(None)
         );
+        assert_eq!(storage.get_entry("k").unwrap().value, StorageValue::Int(4));
     }
 
     #[test]
-    fn test_extend_negative_ttl() {
+    fn test_time_to_live_none_for_missing_key() {
+        let storage = LockedStorage::default();
+        assert!(storage.time_to_live("missing").is_none());
+    }
+
+    #[test]
+    fn test_time_to_live_is_remaining_time() {
         let mut storage = LockedStorage::default();
-        let original_ttl = Duration::from_secs(5);
-        let extension = -10; // would overflow
-        let v1 = StorageValue::Text("val".to_string());
         storage
-            .insert_with_ttl("key".to_string(), v1, original_ttl)
+            .insert_with_ttl(
+                "key".to_string(),
+                StorageValue::Int(1),
+                Duration::from_millis(500),
+            )
             .unwrap();
 
-        let result = storage.extend_ttl("key", extension);
-        assert!(result.is_err());
-        let new_ttl = storage.time_to_live("key").unwrap();
-        assert_eq!(new_ttl, original_ttl);
+        thread::sleep(Duration::from_millis(200));
+        let ttl = storage.time_to_live("key").unwrap().unwrap();
+        assert!(ttl <= Duration::from_millis(300));
     }
 
     #[test]
-    fn test_time_to_live_none_for_missing_key() {
-        let storage = LockedStorage::default();
-        assert!(storage.time_to_live("missing").is_none());
+    fn test_expire_at_and_persist() {
+        let mut storage = LockedStorage::default();
+        storage
+            .insert_entry("key".to_string(), StorageValue::Int(1))
+            .unwrap();
+
+        let deadline = SystemTime::now() + Duration::from_secs(60);
+        assert!(storage.expire_at("key", deadline));
+        assert_eq!(storage.get_entry("key").unwrap().expires_at, Some(deadline));
+        assert!(!storage.expire_at("missing", deadline));
+
+        // Persisted entries never expire and survive remove_expired
+        assert!(storage.persist("key"));
+        assert!(!storage.persist("key"));
+        assert!(!storage.persist("missing"));
+        assert_eq!(storage.time_to_live("key"), Some(None));
+        storage.remove_expired();
+        assert!(storage.key_exists("key"));
+
+        // EXPIRE on a persisted entry counts from now
+        storage.extend_ttl("key", 10).unwrap();
+        let ttl = storage.time_to_live("key").unwrap().unwrap();
+        assert!(ttl <= Duration::from_secs(10) && ttl > Duration::from_secs(9));
+        assert!(storage.extend_ttl("key", -20).is_err());
+
+        // A deadline in the past removes the entry
+        assert!(storage.expire_at("key", SystemTime::UNIX_EPOCH));
+        assert!(!storage.key_exists("key"));
+        assert_eq!(storage.entry_count.load(Ordering::Relaxed), 0);
     }
 
     #[test]
@@ -1454,6 +5425,628 @@ mod storage_tests {
         assert!(storage.get_entry("large").is_none());
     }
 
+    #[test]
+    fn test_frequency_sketch_estimates() {
+        let mut sketch = FrequencySketch::default();
+        sketch.ensure_capacity(100);
+        for _ in 0..5 {
+            sketch.increment("hot");
+        }
+        sketch.increment("cold");
+
+        assert!(sketch.frequency("hot") >= 5);
+        assert!(sketch.frequency("hot") > sketch.frequency("cold"));
+        assert_eq!(sketch.frequency("unseen"), 0);
+
+        sketch.age();
+        assert!(sketch.frequency("hot") >= 2);
+    }
+
+    #[test]
+    fn test_eviction_policy_tinylfu_resists_scans() {
+        let options = StorageOptions::new(
+            Duration::from_secs(10),
+            20,
+            EvictionPolicy::TinyLFU,
+            Compression::Disabled,
+            0,
+        );
+        let mut storage = LockedStorage::new(options);
+
+        for i in 0..10 {
+            let key = format!("hot{i}");
+            storage
+                .insert_entry(key.clone(), StorageValue::Int(i))
+                .unwrap();
+            for _ in 0..5 {
+                storage.get_entry(&key);
+            }
+        }
+
+        // A scan of keys that are only ever touched once
+        for i in 0..100 {
+            storage
+                .insert_entry(format!("cold{i}"), StorageValue::Int(i))
+                .unwrap();
+        }
+
+        assert!(storage.store.read().len() <= 20);
+        for i in 0..10 {
+            assert!(storage.get_entry(&format!("hot{i}")).is_some());
+        }
+    }
+
+    #[test]
+    fn test_eviction_policy_tinylfu_hit_ratio() {
+        // Read-through of a skewed workload over many more keys than fit
+        let hit_ratio = |policy: EvictionPolicy| {
+            let options = StorageOptions::new(
+                Duration::from_secs(60),
+                200,
+                policy,
+                Compression::Disabled,
+                0,
+            );
+            let mut storage = LockedStorage::new(options);
+            let mut seed: u64 = 0x9E37_79B9_7F4A_7C15;
+            let mut hits = 0;
+            for _ in 0..30_000 {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                let u = (seed >> 11) as f64 / (1u64 << 53) as f64;
+                let key = format!("key{}", (5_000.0 * u.powi(3)) as usize);
+                if storage.get_entry(&key).is_some() {
+                    hits += 1;
+                } else {
+                    storage.insert_entry(key, StorageValue::Int(0)).unwrap();
+                }
+            }
+            hits
+        };
+
+        assert!(hit_ratio(EvictionPolicy::TinyLFU) > hit_ratio(EvictionPolicy::LFU));
+    }
+
+    #[test]
+    fn test_entry_count_on_overwrite_and_remove() {
+        let mut storage = LockedStorage::default();
+        storage
+            .insert_entry("k".to_string(), StorageValue::Int(1))
+            .unwrap();
+        storage
+            .insert_entry("k".to_string(), StorageValue::Int(2))
+            .unwrap();
+        assert_eq!(storage.entry_count.load(Ordering::Relaxed), 1);
+
+        storage.remove_entry("k");
+        assert_eq!(storage.entry_count.load(Ordering::Relaxed), 0);
+    }
+
+    // Collects every key of a full SCAN iteration
+    fn scan_all(storage: &LockedStorage, count: usize, pattern: Option<&str>) -> Vec<String> {
+        let mut keys = Vec::new();
+        let mut cursor = 0;
+        loop {
+            let (next, page) = storage.scan(cursor, count, pattern, None);
+            keys.extend(page);
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn test_scan_visits_every_key_once() {
+        let mut storage = LockedStorage::default();
+        for i in 0..100 {
+            storage
+                .insert_entry(format!("key{i}"), StorageValue::Int(i))
+                .unwrap();
+        }
+
+        let mut expected = storage.get_keys();
+        expected.sort();
+        assert_eq!(scan_all(&storage, 7, None), expected);
+        assert_eq!(scan_all(&storage, 1000, None), expected);
+    }
+
+    #[test]
+    fn test_scan_after_removals() {
+        let mut storage = LockedStorage::default();
+        for i in 0..20 {
+            storage
+                .insert_entry(format!("key{i}"), StorageValue::Int(i))
+                .unwrap();
+        }
+        storage
+            .insert_with_ttl(
+                "short".to_string(),
+                StorageValue::Int(0),
+                Duration::from_millis(1),
+            )
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+
+        storage.remove_entry("key0");
+        storage.take_entry("key1");
+        storage.rename_entry("key2", "renamed");
+        storage.rename_entry("key3", "key4");
+        storage.remove_expired();
+
+        let mut expected = storage.get_keys();
+        expected.sort();
+        assert_eq!(expected.len(), 17);
+        assert_eq!(scan_all(&storage, 3, None), expected);
+    }
+
+    #[test]
+    fn test_scan_stable_across_inserts() {
+        let mut storage = LockedStorage::default();
+        for i in 0..50 {
+            storage
+                .insert_entry(format!("old{i}"), StorageValue::Int(i))
+                .unwrap();
+        }
+
+        let mut seen = Vec::new();
+        let mut cursor = 0;
+        let mut i = 0;
+        loop {
+            let (next, page) = storage.scan(cursor, 5, None, None);
+            seen.extend(page);
+
+            // Insert new keys in the middle of the iteration
+            storage
+                .insert_entry(format!("new{i}"), StorageValue::Int(i))
+                .unwrap();
+            i += 1;
+
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+
+        let old: Vec<_> = seen.iter().filter(|k| k.starts_with("old")).collect();
+        assert_eq!(old.len(), 50);
+        let mut deduped = seen.clone();
+        deduped.sort();
+        deduped.dedup();
+        assert_eq!(deduped.len(), seen.len());
+    }
+
+    #[test]
+    fn test_scan_match_and_type() {
+        let mut storage = LockedStorage::default();
+        storage
+            .insert_entry("user:1".to_string(), StorageValue::Int(1))
+            .unwrap();
+        storage
+            .insert_entry(
+                "user:2".to_string(),
+                StorageValue::List(vec![StorageValue::Int(2)]),
+            )
+            .unwrap();
+        storage
+            .insert_entry("order:1".to_string(), StorageValue::Int(1))
+            .unwrap();
+
+        assert_eq!(
+            scan_all(&storage, 2, Some("user:*")),
+            vec!["user:1".to_string(), "user:2".to_string()]
+        );
+
+        let (_, keys) = storage.scan(0, 10, None, Some("list"));
+        assert_eq!(keys, vec!["user:2".to_string()]);
+    }
+
+    #[test]
+    fn test_pattern_operations() {
+        let mut storage = LockedStorage::default();
+        for key in ["user:42:name", "user:42:age", "user:7:name", "order:42"] {
+            storage
+                .insert_entry(key.to_string(), StorageValue::Int(1))
+                .unwrap();
+        }
+
+        let mut keys = storage.get_keys_matching("user:42:*");
+        keys.sort();
+        assert_eq!(keys, vec!["user:42:age", "user:42:name"]);
+        assert_eq!(
+            storage.get_keys_matching("user:?:name"),
+            vec!["user:7:name"]
+        );
+
+        let entries = storage.get_entries_matching("*:name");
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|(_, e)| e.is_some()));
+
+        assert_eq!(storage.remove_matching("user:*"), 3);
+        assert_eq!(storage.get_keys(), vec!["order:42"]);
+        assert_eq!(storage.remove_matching("user:*"), 0);
+    }
+
+    #[test]
+    fn test_databases_are_isolated() {
+        let databases = Databases::new(StorageOptions::default());
+        let first = databases.get_or_create(DEFAULT_DATABASE).unwrap();
+        let second = databases.get_or_create("1").unwrap();
+
+        first
+            .write()
+            .insert_entry("k".to_string(), StorageValue::Int(1))
+            .unwrap();
+        second
+            .write()
+            .set_config_entry(&ConfigEntry::MaxCapacity(10));
+
+        assert!(!second.read().key_exists("k"));
+        assert_eq!(first.read().get_options().max_capacity, 1_000_000);
+        assert_eq!(second.read().get_options().max_capacity, 10);
+        assert_eq!(databases.names(), vec!["0", "1"]);
+    }
+
+    #[test]
+    fn test_keyspace_info() {
+        let mut storage = LockedStorage::default();
+        assert_eq!(storage.keyspace_info(), KeyspaceInfo::default());
+
+        storage
+            .insert_with_expiry("a".to_string(), StorageValue::Int(1), None)
+            .unwrap();
+        storage
+            .insert_with_ttl(
+                "b".to_string(),
+                StorageValue::Text("value".to_string()),
+                Duration::from_secs(60),
+            )
+            .unwrap();
+        storage
+            .insert_with_ttl(
+                "c".to_string(),
+                StorageValue::Int(3),
+                Duration::from_millis(10),
+            )
+            .unwrap();
+        thread::sleep(Duration::from_millis(20));
+
+        let info = storage.keyspace_info();
+        assert_eq!(info.keys, 2);
+        assert_eq!(info.expires, 1);
+        let usage = |key: &str| storage.inspect_entry(key, |e| e.entry_size + key.len());
+        assert_eq!(
+            Some(info.memory),
+            usage("a").zip(usage("b")).map(|(a, b)| a + b)
+        );
+    }
+
+    #[test]
+    fn test_databases_move_entry() {
+        let databases = Databases::new(StorageOptions::default());
+        let first = databases.get_or_create(DEFAULT_DATABASE).unwrap();
+        first
+            .write()
+            .insert_with_ttl(
+                "k".to_string(),
+                StorageValue::Int(1),
+                Duration::from_secs(60),
+            )
+            .unwrap();
+
+        assert!(databases.move_entry("k", DEFAULT_DATABASE, "1").unwrap());
+        assert!(!first.read().key_exists("k"));
+        let second = databases.get("1").unwrap();
+        assert!(
+            second
+                .read()
+                .get_entry("k")
+                .unwrap()
+                .remaining_ttl()
+                .unwrap()
+                > Duration::from_secs(59)
+        );
+        assert_eq!(first.read().entry_count.load(Ordering::Relaxed), 0);
+        assert_eq!(second.read().entry_count.load(Ordering::Relaxed), 1);
+
+        // Missing keys and existing destination keys are not moved
+        assert!(
+            !databases
+                .move_entry("missing", DEFAULT_DATABASE, "1")
+                .unwrap()
+        );
+        first
+            .write()
+            .insert_entry("k".to_string(), StorageValue::Int(2))
+            .unwrap();
+        let version = first.read().get_entry("k").unwrap().version;
+        let accesses = |db: &Arc<RwLock<LockedStorage>>| {
+            let db = db.read();
+            (
+                db.stats.hits.load(Ordering::Relaxed),
+                db.stats.misses.load(Ordering::Relaxed),
+            )
+        };
+        let before = accesses(&second);
+        assert!(!databases.move_entry("k", DEFAULT_DATABASE, "1").unwrap());
+        assert!(databases.move_entry("k", "1", "1").is_err());
+
+        // A refused move leaves the source untouched and counts no access
+        let entry = first.read().get_entry("k").unwrap();
+        assert_eq!(entry.value, StorageValue::Int(2));
+        assert_eq!(entry.version, version);
+        assert_eq!(accesses(&second), before);
+    }
+
+    #[test]
+    fn test_databases_created_on_demand() {
+        let databases = Databases::new(StorageOptions::default());
+
+        // Reading a missing database doesn't create it
+        let missing = databases.get_or_empty("missing");
+        assert!(missing.read().get_entry("k").is_none());
+        assert!(databases.get("missing").is_none());
+
+        for i in 1..MAX_DATABASES {
+            databases.get_or_create(&i.to_string()).unwrap();
+        }
+        assert_eq!(databases.names().len(), MAX_DATABASES);
+        assert!(databases.get_or_create("one too many").is_err());
+        assert!(databases.get_or_create("1").is_ok());
+
+        // A move that can't create its destination keeps the key
+        databases
+            .get_or_create(DEFAULT_DATABASE)
+            .unwrap()
+            .write()
+            .insert_entry("k".to_string(), StorageValue::Int(1))
+            .unwrap();
+        assert!(databases.move_entry("k", DEFAULT_DATABASE, "new").is_err());
+        assert!(
+            databases
+                .get(DEFAULT_DATABASE)
+                .unwrap()
+                .read()
+                .key_exists("k")
+        );
+    }
+
+    #[test]
+    fn test_databases_failed_save_stays_dirty() {
+        let databases = Databases::new(StorageOptions::default());
+        databases
+            .get_or_create(DEFAULT_DATABASE)
+            .unwrap()
+            .write()
+            .insert_entry("k".to_string(), StorageValue::Int(1))
+            .unwrap();
+
+        let path = std::env::temp_dir()
+            .join("volatix_missing_dir")
+            .join("snapshot.bin");
+        assert!(databases.save_to_disk(&path).is_err());
+        assert!(databases.should_flush());
+    }
+
+    #[test]
+    fn test_databases_persistence() {
+        let path = std::env::temp_dir().join("volatix_databases_test.bin");
+        let databases = Databases::new(StorageOptions::default());
+        databases
+            .get_or_create("cache")
+            .unwrap()
+            .write()
+            .insert_entry("k".to_string(), StorageValue::Text("v".to_string()))
+            .unwrap();
+        databases
+            .get_or_create("cache")
+            .unwrap()
+            .write()
+            .set_config_entry(&ConfigEntry::EvictPolicy(EvictionPolicy::LFU));
+
+        // A deleted entry's version must not be handed out again after loading
+        let cache = databases.get("cache").unwrap();
+        let version = cache.read().get_entry("k").unwrap().version;
+        cache
+            .write()
+            .insert_entry("gone".to_string(), StorageValue::Int(1))
+            .unwrap();
+        let deleted_version = cache.read().get_entry("gone").unwrap().version;
+        cache.write().remove_entry("gone");
+
+        assert!(databases.should_flush());
+        databases.save_to_disk(&path).unwrap();
+        assert!(!databases.should_flush());
+
+        let loaded = Databases::new(StorageOptions::default());
+        loaded.load_from_disk(&path).unwrap();
+        let cache = loaded.get("cache").unwrap();
+        assert_eq!(
+            cache.read().get_entry("k").unwrap().value,
+            StorageValue::Text("v".to_string())
+        );
+        assert!(matches!(
+            cache.read().get_options().eviction_policy,
+            EvictionPolicy::LFU
+        ));
+        assert_eq!(scan_all(&cache.read(), 10, None), vec!["k".to_string()]);
+
+        // Versions survive the snapshot and keep increasing after it
+        assert_eq!(cache.read().get_entry("k").unwrap().version, version);
+        cache
+            .write()
+            .insert_entry("k2".to_string(), StorageValue::Int(1))
+            .unwrap();
+        assert!(cache.read().get_entry("k2").unwrap().version > deleted_version);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_snapshot_migrations() {
+        // Snapshots written by earlier releases, holding `old` = "value" with
+        // a TTL of 100 years in the default database, and `other` in the
+        // `cache` database for the ones with named databases
+        let fixtures: [(&[u8], bool); 4] = [
+            (
+                include_bytes!("../tests/fixtures/snapshot_unversioned.bin"),
+                false,
+            ),
+            (
+                include_bytes!("../tests/fixtures/snapshot_v01_ttl.bin"),
+                true,
+            ),
+            (
+                include_bytes!("../tests/fixtures/snapshot_v01_deadline.bin"),
+                true,
+            ),
+            (
+                include_bytes!("../tests/fixtures/snapshot_v01_versioned.bin"),
+                true,
+            ),
+        ];
+
+        let path = std::env::temp_dir().join("volatix_migration_test.bin");
+        for (fixture, named) in fixtures {
+            std::fs::write(&path, fixture).unwrap();
+            let databases = Databases::new(StorageOptions::default());
+            databases.load_from_disk(&path).unwrap();
+
+            let db = databases.get(DEFAULT_DATABASE).unwrap();
+            let entry = db.read().get_entry("old").unwrap();
+            assert_eq!(entry.value, StorageValue::Text("value".to_string()));
+            assert!(entry.remaining_ttl().unwrap() > Duration::from_secs(90 * 365 * 86400));
+            assert_eq!(databases.get("cache").is_some(), named);
+            // Version 0 stands for a missing key in CAS
+            assert!(entry.version > 0);
+
+            // Saved again in the current layout
+            databases.save_to_disk(&path).unwrap();
+            let reloaded = Databases::new(StorageOptions::default());
+            reloaded.load_from_disk(&path).unwrap();
+            let db = reloaded.get(DEFAULT_DATABASE).unwrap();
+            let reloaded_entry = db.read().get_entry("old").unwrap();
+            assert_eq!(reloaded_entry.value, entry.value);
+            assert_eq!(reloaded_entry.version, entry.version);
+        }
+
+        // Snapshots that can't be decoded are errors, never an empty store
+        let truncated = &include_bytes!("../tests/fixtures/snapshot_unversioned.bin")[..100];
+        for data in [truncated, b"VTXDBS01garbage", b"VTXDBS99", b"VTXDBS0"] {
+            std::fs::write(&path, data).unwrap();
+            let databases = Databases::new(StorageOptions::default());
+            assert!(databases.load_from_disk(&path).is_err());
+        }
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_set_options_parse() {
+        assert_eq!(
+            SetOptions::parse::<&str>(&[]).unwrap(),
+            SetOptions::default()
+        );
+
+        let options = SetOptions::parse(&["xx", "px", "1500", "get"]).unwrap();
+        assert_eq!(options.condition, SetCondition::IfPresent);
+        assert_eq!(options.expiry, SetExpiry::In(Duration::from_millis(1500)));
+        assert!(options.get);
+        assert_eq!(options.to_args(), vec!["XX", "PX", "1500", "GET"]);
+
+        let options = SetOptions::parse(&["EXAT", "1700000000"]).unwrap();
+        assert_eq!(
+            options.expiry,
+            SetExpiry::At(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+        );
+        assert_eq!(options.to_args(), vec!["EXAT", "1700000000"]);
+
+        assert!(SetOptions::parse(&["NX", "XX"]).is_err());
+        assert!(SetOptions::parse(&["EX", "10", "KEEPTTL"]).is_err());
+        assert!(SetOptions::parse(&["EX"]).is_err());
+        assert!(SetOptions::parse(&["EX", "0"]).is_err());
+        assert!(SetOptions::parse(&["EX", "-5"]).is_err());
+        assert!(SetOptions::parse(&["EXAT", &u64::MAX.to_string()]).is_err());
+        assert!(SetOptions::parse(&["FOO"]).is_err());
+    }
+
+    #[test]
+    fn test_insert_with_options_conditions() {
+        let mut storage = LockedStorage::default();
+        let nx = SetOptions::parse(&["NX"]).unwrap();
+        let xx = SetOptions::parse(&["XX"]).unwrap();
+
+        let (written, old) = storage
+            .insert_with_options("k".to_string(), StorageValue::Int(1), &xx)
+            .unwrap();
+        assert!(!written && old.is_none());
+        assert!(!storage.key_exists("k"));
+
+        let (written, _) = storage
+            .insert_with_options("k".to_string(), StorageValue::Int(1), &nx)
+            .unwrap();
+        assert!(written);
+
+        let (written, old) = storage
+            .insert_with_options("k".to_string(), StorageValue::Int(2), &nx)
+            .unwrap();
+        assert!(!written);
+        assert_eq!(old.unwrap().value, StorageValue::Int(1));
+
+        let (written, old) = storage
+            .insert_with_options("k".to_string(), StorageValue::Int(3), &xx)
+            .unwrap();
+        assert!(written);
+        assert_eq!(old.unwrap().value, StorageValue::Int(1));
+        assert_eq!(storage.get_entry("k").unwrap().value, StorageValue::Int(3));
+    }
+
+    #[test]
+    fn test_insert_with_options_expiry() {
+        let mut storage = LockedStorage::default();
+        let ex = SetOptions::parse(&["EX", "100"]).unwrap();
+        storage
+            .insert_with_options("k".to_string(), StorageValue::Int(1), &ex)
+            .unwrap();
+        let deadline = storage.get_entry("k").unwrap().expires_at;
+        let ttl = storage.time_to_live("k").unwrap().unwrap();
+        assert!(ttl <= Duration::from_secs(100) && ttl > Duration::from_secs(99));
+
+        // KEEPTTL retains the deadline instead of the global default
+        let keep = SetOptions::parse(&["KEEPTTL"]).unwrap();
+        storage
+            .insert_with_options("k".to_string(), StorageValue::Int(2), &keep)
+            .unwrap();
+        assert_eq!(storage.get_entry("k").unwrap().expires_at, deadline);
+
+        // ...including the lack of one
+        storage.persist("k");
+        storage
+            .insert_with_options("k".to_string(), StorageValue::Int(2), &keep)
+            .unwrap();
+        assert_eq!(storage.time_to_live("k"), Some(None));
+
+        // A deadline in the past removes the key
+        let past = SetOptions::parse(&["EXAT", "1"]).unwrap();
+        let (written, _) = storage
+            .insert_with_options("k".to_string(), StorageValue::Int(3), &past)
+            .unwrap();
+        assert!(written);
+        assert!(!storage.key_exists("k"));
+
+        // A TTL past the end of time is an error, not a panic
+        let forever = SetOptions::parse(&["EX", &u64::MAX.to_string()]).unwrap();
+        assert!(
+            storage
+                .insert_with_options("k".to_string(), StorageValue::Int(4), &forever)
+                .is_err()
+        );
+        assert!(!storage.key_exists("k"));
+    }
+
     #[test]
     fn test_reset_stats() {
         let mut storage = LockedStorage::default();
@@ -0,0 +1,565 @@
+//! Append-only streams backing the `X*` commands.
+//!
+//! A stream is a log of field-value entries ordered by `StreamId`s, which
+//! are generated from the current time unless given explicitly. Consumer
+//! groups share the entries of a stream between consumers: each entry is
+//! delivered to a single consumer of the group and stays pending until that
+//! consumer acknowledges it.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// The field-value pairs of a stream entry.
+pub type StreamFields = Vec<(String, String)>;
+
+/// Identifier of a stream entry, `<milliseconds>-<sequence>`.
+/// Entries are ordered by their identifiers.
+///
+/// # Example
+/// ```rust
+/// use volatix_core::StreamId;
+///
+/// let id = StreamId::parse("1700000000000-3", 0).unwrap();
+/// assert_eq!(id, StreamId { ms: 1700000000000, seq: 3 });
+/// assert_eq!(id.to_string(), "1700000000000-3");
+///
+/// // A missing sequence takes the given default
+/// assert_eq!(StreamId::parse("5", u64::MAX).unwrap(), StreamId { ms: 5, seq: u64::MAX });
+/// assert_eq!(StreamId::parse("-", 0).unwrap(), StreamId::MIN);
+/// ```
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct StreamId {
+    pub ms: u64,
+    pub seq: u64,
+}
+
+impl StreamId {
+    pub const MIN: StreamId = StreamId { ms: 0, seq: 0 };
+    pub const MAX: StreamId = StreamId {
+        ms: u64::MAX,
+        seq: u64::MAX,
+    };
+
+    /// Parses an identifier. `-` and `+` are the smallest and largest
+    /// identifiers, and a missing sequence number is replaced by
+    /// `default_seq`.
+    pub fn parse(id: &str, default_seq: u64) -> Result<Self, String> {
+        let invalid = || format!("Invalid stream ID {id}");
+        match id {
+            "-" => Ok(Self::MIN),
+            "+" => Ok(Self::MAX),
+            _ => match id.split_once('-') {
+                Some((ms, seq)) => Ok(Self {
+                    ms: ms.parse().map_err(|_| invalid())?,
+                    seq: seq.parse().map_err(|_| invalid())?,
+                }),
+                None => Ok(Self {
+                    ms: id.parse().map_err(|_| invalid())?,
+                    seq: default_seq,
+                }),
+            },
+        }
+    }
+
+    /// The identifier following this one, if any.
+    fn next(self) -> Option<Self> {
+        match self.seq.checked_add(1) {
+            Some(seq) => Some(Self { ms: self.ms, seq }),
+            None => self.ms.checked_add(1).map(|ms| Self { ms, seq: 0 }),
+        }
+    }
+}
+
+impl Display for StreamId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.ms, self.seq)
+    }
+}
+
+/// An entry delivered to a consumer and not acknowledged yet.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct PendingEntry {
+    /// The consumer the entry was delivered to
+    pub consumer: String,
+    /// When the entry was delivered
+    pub delivered_at: SystemTime,
+    /// How many times the entry was delivered
+    pub deliveries: u64,
+}
+
+impl PendingEntry {
+    /// Time elapsed since the entry was delivered.
+    pub fn idle(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(self.delivered_at)
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+struct ConsumerGroup {
+    /// The last entry delivered to any consumer of the group
+    last_delivered: StreamId,
+    pending: BTreeMap<StreamId, PendingEntry>,
+}
+
+/// Summary of the pending entries of a consumer group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingSummary {
+    /// Number of pending entries
+    pub count: usize,
+    /// Smallest and largest pending identifiers, if any
+    pub range: Option<(StreamId, StreamId)>,
+    /// Number of pending entries per consumer, sorted by consumer
+    pub consumers: Vec<(String, usize)>,
+}
+
+/// An append-only log of entries with consumer groups.
+///
+/// # Example
+/// ```rust
+/// use volatix_core::{Stream, StreamId};
+///
+/// let mut stream = Stream::default();
+/// let fields = vec![("temp".to_string(), "21".to_string())];
+/// let id = stream.add("*", fields.clone()).unwrap();
+///
+/// let entries = stream.range(StreamId::MIN, StreamId::MAX, None, false);
+/// assert_eq!(entries, vec![(id, fields)]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct Stream {
+    entries: BTreeMap<StreamId, StreamFields>,
+    /// The largest identifier ever added, identifiers are never reused
+    /// even after the entry is trimmed
+    last_id: StreamId,
+    groups: BTreeMap<String, ConsumerGroup>,
+}
+
+impl Stream {
+    /// Appends an entry.
+    ///
+    /// # Arguments
+    /// * `id` - `*` to generate the identifier from the current time,
+    ///   `<ms>-*` to only generate the sequence number, or an explicit
+    ///   identifier greater than every identifier in the stream
+    /// * `fields` - The field-value pairs of the entry, at least one
+    ///
+    /// # Returns
+    /// The identifier of the new entry, or an error if `id` is invalid
+    pub fn add(&mut self, id: &str, fields: StreamFields) -> Result<StreamId, String> {
+        if fields.is_empty() {
+            return Err("A stream entry needs at least one field".to_string());
+        }
+
+        let last = self.last_id;
+        let id = match id {
+            "*" => {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis() as u64;
+                if now > last.ms {
+                    StreamId { ms: now, seq: 0 }
+                } else {
+                    last.next().ok_or("Stream IDs are exhausted")?
+                }
+            }
+            _ => match id.strip_suffix("-*") {
+                Some(ms) => {
+                    let ms = ms.parse().map_err(|_| format!("Invalid stream ID {id}"))?;
+                    match ms == last.ms {
+                        true => last.next().ok_or("Stream IDs are exhausted")?,
+                        false => StreamId { ms, seq: 0 },
+                    }
+                }
+                None => StreamId::parse(id, 0)?,
+            },
+        };
+
+        if id == StreamId::MIN {
+            return Err("The stream ID must be greater than 0-0".to_string());
+        }
+        if id <= last {
+            return Err(format!(
+                "The stream ID must be greater than the last one ({last})"
+            ));
+        }
+
+        self.entries.insert(id, fields);
+        self.last_id = id;
+        Ok(id)
+    }
+
+    /// Number of entries in the stream.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the stream has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The largest identifier ever added to the stream.
+    pub fn last_id(&self) -> StreamId {
+        self.last_id
+    }
+
+    /// Returns the entries with identifiers between `start` and `end`
+    /// inclusive, in ascending order or in descending order if `rev` is set.
+    pub fn range(
+        &self,
+        start: StreamId,
+        end: StreamId,
+        count: Option<usize>,
+        rev: bool,
+    ) -> Vec<(StreamId, StreamFields)> {
+        if start > end {
+            return Vec::new();
+        }
+
+        let range = self.entries.range(start..=end);
+        let entries: Box<dyn Iterator<Item = _>> = match rev {
+            true => Box::new(range.rev()),
+            false => Box::new(range),
+        };
+        entries
+            .take(count.unwrap_or(usize::MAX))
+            .map(|(id, fields)| (*id, fields.clone()))
+            .collect()
+    }
+
+    /// Removes the oldest entries until at most `max_len` remain.
+    ///
+    /// # Returns
+    /// The number of removed entries
+    pub fn trim(&mut self, max_len: usize) -> usize {
+        let excess = self.entries.len().saturating_sub(max_len);
+        for _ in 0..excess {
+            self.entries.pop_first();
+        }
+        excess
+    }
+
+    /// Creates a consumer group that will deliver the entries following
+    /// `last_delivered`.
+    ///
+    /// # Returns
+    /// An error if the group already exists
+    pub fn create_group(&mut self, group: &str, last_delivered: StreamId) -> Result<(), String> {
+        if self.groups.contains_key(group) {
+            return Err(format!("Consumer group {group} already exists"));
+        }
+
+        let group_state = ConsumerGroup {
+            last_delivered,
+            pending: BTreeMap::new(),
+        };
+        self.groups.insert(group.to_string(), group_state);
+        Ok(())
+    }
+
+    /// Deletes a consumer group and its pending entries.
+    ///
+    /// # Returns
+    /// Whether the group existed
+    pub fn destroy_group(&mut self, group: &str) -> bool {
+        self.groups.remove(group).is_some()
+    }
+
+    /// Delivers entries to a consumer of a group.
+    ///
+    /// # Arguments
+    /// * `group` - The consumer group
+    /// * `consumer` - The consumer reading
+    /// * `after` - `None` to deliver entries never delivered to the group,
+    ///   which then become pending, or an identifier to read the consumer's
+    ///   own pending entries following it again
+    /// * `count` - Maximum number of entries to return
+    ///
+    /// # Returns
+    /// The entries, or an error if the group doesn't exist. Pending entries
+    /// that were trimmed from the stream are returned without fields.
+    pub fn read_group(
+        &mut self,
+        group: &str,
+        consumer: &str,
+        after: Option<StreamId>,
+        count: Option<usize>,
+    ) -> Result<Vec<(StreamId, StreamFields)>, String> {
+        let Some(state) = self.groups.get_mut(group) else {
+            return Err(format!("No consumer group {group}"));
+        };
+        let count = count.unwrap_or(usize::MAX);
+
+        let Some(after) = after else {
+            let Some(start) = state.last_delivered.next() else {
+                return Ok(Vec::new());
+            };
+            let entries: Vec<_> = self
+                .entries
+                .range(start..)
+                .take(count)
+                .map(|(id, fields)| (*id, fields.clone()))
+                .collect();
+
+            let now = SystemTime::now();
+            for (id, _) in &entries {
+                let pending = PendingEntry {
+                    consumer: consumer.to_string(),
+                    delivered_at: now,
+                    deliveries: 1,
+                };
+                state.pending.insert(*id, pending);
+                state.last_delivered = *id;
+            }
+            return Ok(entries);
+        };
+
+        let Some(start) = after.next() else {
+            return Ok(Vec::new());
+        };
+        Ok(state
+            .pending
+            .range(start..)
+            .filter(|(_, pending)| pending.consumer == consumer)
+            .take(count)
+            .map(|(id, _)| (*id, self.entries.get(id).cloned().unwrap_or_default()))
+            .collect())
+    }
+
+    /// Acknowledges entries, removing them from the pending entries of a
+    /// group.
+    ///
+    /// # Returns
+    /// The number of entries that were pending, or an error if the group
+    /// doesn't exist
+    pub fn ack(&mut self, group: &str, ids: &[StreamId]) -> Result<usize, String> {
+        let Some(state) = self.groups.get_mut(group) else {
+            return Err(format!("No consumer group {group}"));
+        };
+        Ok(ids
+            .iter()
+            .filter(|id| state.pending.remove(id).is_some())
+            .count())
+    }
+
+    /// Summarizes the pending entries of a group.
+    pub fn pending_summary(&self, group: &str) -> Result<PendingSummary, String> {
+        let Some(state) = self.groups.get(group) else {
+            return Err(format!("No consumer group {group}"));
+        };
+
+        let mut consumers: BTreeMap<&str, usize> = BTreeMap::new();
+        for pending in state.pending.values() {
+            *consumers.entry(&pending.consumer).or_default() += 1;
+        }
+        let range = state
+            .pending
+            .first_key_value()
+            .zip(state.pending.last_key_value())
+            .map(|((first, _), (last, _))| (*first, *last));
+
+        Ok(PendingSummary {
+            count: state.pending.len(),
+            range,
+            consumers: consumers
+                .into_iter()
+                .map(|(consumer, count)| (consumer.to_string(), count))
+                .collect(),
+        })
+    }
+
+    /// Lists the pending entries of a group between `start` and `end`
+    /// inclusive, optionally only those of one consumer.
+    pub fn pending(
+        &self,
+        group: &str,
+        start: StreamId,
+        end: StreamId,
+        count: usize,
+        consumer: Option<&str>,
+    ) -> Result<Vec<(StreamId, PendingEntry)>, String> {
+        let Some(state) = self.groups.get(group) else {
+            return Err(format!("No consumer group {group}"));
+        };
+        if start > end {
+            return Ok(Vec::new());
+        }
+
+        Ok(state
+            .pending
+            .range(start..=end)
+            .filter(|(_, pending)| consumer.is_none_or(|c| pending.consumer == c))
+            .take(count)
+            .map(|(id, pending)| (*id, pending.clone()))
+            .collect())
+    }
+
+    /// Approximate memory used by the stream, in bytes.
+    pub(crate) fn size_in_bytes(&self) -> usize {
+        let entries: usize = self
+            .entries
+            .values()
+            .map(|fields| {
+                size_of::<StreamId>()
+                    + fields
+                        .iter()
+                        .map(|(field, value)| field.capacity() + value.capacity())
+                        .sum::<usize>()
+            })
+            .sum();
+        let groups: usize = self
+            .groups
+            .iter()
+            .map(|(name, group)| {
+                name.capacity()
+                    + group
+                        .pending
+                        .values()
+                        .map(|p| size_of::<(StreamId, PendingEntry)>() + p.consumer.capacity())
+                        .sum::<usize>()
+            })
+            .sum();
+        entries + groups
+    }
+}
+
+#[cfg(test)]
+mod stream_tests {
+    use super::*;
+
+    fn fields(value: &str) -> StreamFields {
+        vec![("value".to_string(), value.to_string())]
+    }
+
+    fn id(ms: u64, seq: u64) -> StreamId {
+        StreamId { ms, seq }
+    }
+
+    #[test]
+    fn test_add_ids() {
+        let mut stream = Stream::default();
+        assert_eq!(stream.add("5-1", fields("a")), Ok(id(5, 1)));
+        assert_eq!(stream.add("5-*", fields("b")), Ok(id(5, 2)));
+        assert_eq!(stream.add("7-*", fields("c")), Ok(id(7, 0)));
+        assert!(stream.add("7-0", fields("d")).is_err());
+        assert!(stream.add("6-*", fields("d")).is_err());
+        assert!(stream.add("8-0", vec![]).is_err());
+        assert!(Stream::default().add("0-0", fields("a")).is_err());
+        assert!(stream.add("abc", fields("d")).is_err());
+
+        let generated = stream.add("*", fields("d")).unwrap();
+        assert!(generated > id(7, 0));
+        // Generated IDs keep increasing even within the same millisecond
+        let next = stream.add("*", fields("e")).unwrap();
+        assert!(next > generated);
+
+        // IDs ahead of the clock are followed by sequence increments
+        let mut stream = Stream::default();
+        stream
+            .add(&format!("{}-0", u64::MAX - 1), fields("a"))
+            .unwrap();
+        assert_eq!(stream.add("*", fields("b")), Ok(id(u64::MAX - 1, 1)));
+    }
+
+    #[test]
+    fn test_range_and_trim() {
+        let mut stream = Stream::default();
+        for i in 1..=5 {
+            stream
+                .add(&format!("{i}-0"), fields(&i.to_string()))
+                .unwrap();
+        }
+        assert_eq!(stream.len(), 5);
+
+        let ids = |entries: Vec<(StreamId, StreamFields)>| {
+            entries.into_iter().map(|(id, _)| id.ms).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ids(stream.range(id(2, 0), id(4, 0), None, false)),
+            [2, 3, 4]
+        );
+        assert_eq!(
+            ids(stream.range(StreamId::MIN, StreamId::MAX, Some(2), true)),
+            [5, 4]
+        );
+        assert!(stream.range(id(4, 0), id(2, 0), None, false).is_empty());
+
+        assert_eq!(stream.trim(3), 2);
+        assert_eq!(stream.trim(3), 0);
+        assert_eq!(
+            ids(stream.range(StreamId::MIN, StreamId::MAX, None, false)),
+            [3, 4, 5]
+        );
+
+        // Trimmed IDs are not reused
+        assert!(stream.add("2-0", fields("x")).is_err());
+    }
+
+    #[test]
+    fn test_consumer_groups() {
+        let mut stream = Stream::default();
+        for i in 1..=4 {
+            stream
+                .add(&format!("{i}-0"), fields(&i.to_string()))
+                .unwrap();
+        }
+        stream.create_group("workers", id(1, 0)).unwrap();
+        assert!(stream.create_group("workers", StreamId::MIN).is_err());
+        assert!(stream.read_group("missing", "a", None, None).is_err());
+
+        let alice = stream
+            .read_group("workers", "alice", None, Some(2))
+            .unwrap();
+        assert_eq!(
+            alice,
+            vec![(id(2, 0), fields("2")), (id(3, 0), fields("3"))]
+        );
+        let bob = stream.read_group("workers", "bob", None, None).unwrap();
+        assert_eq!(bob, vec![(id(4, 0), fields("4"))]);
+        assert!(
+            stream
+                .read_group("workers", "bob", None, None)
+                .unwrap()
+                .is_empty()
+        );
+
+        // History only contains the consumer's own pending entries
+        let history = stream.read_group("workers", "alice", Some(StreamId::MIN), None);
+        assert_eq!(history.unwrap(), alice);
+
+        let summary = stream.pending_summary("workers").unwrap();
+        assert_eq!(summary.count, 3);
+        assert_eq!(summary.range, Some((id(2, 0), id(4, 0))));
+        assert_eq!(
+            summary.consumers,
+            vec![("alice".to_string(), 2), ("bob".to_string(), 1)]
+        );
+
+        assert_eq!(stream.ack("workers", &[id(2, 0), id(9, 0)]), Ok(1));
+        assert_eq!(stream.ack("workers", &[id(2, 0)]), Ok(0));
+        let pending = stream
+            .pending("workers", StreamId::MIN, StreamId::MAX, 10, Some("alice"))
+            .unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].0, id(3, 0));
+        assert_eq!(pending[0].1.deliveries, 1);
+
+        // Trimmed pending entries are returned without fields
+        stream.trim(0);
+        let history = stream.read_group("workers", "alice", Some(StreamId::MIN), None);
+        assert_eq!(history.unwrap(), vec![(id(3, 0), vec![])]);
+
+        assert!(stream.destroy_group("workers"));
+        assert!(!stream.destroy_group("workers"));
+    }
+}
@@ -0,0 +1,147 @@
+use std::{net::TcpStream, time::Instant};
+
+use volatix_core::{Databases, LockedStorage, RespValue, StorageValue, bulkerror, null};
+
+/// A BLPOP/BRPOP that found every list empty and has to wait for a push.
+#[derive(Debug)]
+pub struct BlockedPop {
+    /// Lists to pop from, in order of preference
+    pub keys: Vec<String>,
+    /// Pop from the head (BLPOP) instead of the tail (BRPOP)
+    pub front: bool,
+    /// When to give up and reply with null, `None` to wait forever
+    pub deadline: Option<Instant>,
+}
+
+/// A connection parked on a blocking pop.
+struct Parked {
+    client_id: usize,
+    /// Database selected when the client blocked
+    db: String,
+    pop: BlockedPop,
+    stream: TcpStream,
+    /// Requests sent by the client while it was blocked
+    queued: Vec<(Vec<u8>, TcpStream)>,
+}
+
+/// A parked connection that got its reply.
+pub struct Unblocked {
+    pub client_id: usize,
+    pub response: RespValue,
+    pub stream: TcpStream,
+    /// Requests to process now that the client is served, in order
+    pub queued: Vec<(Vec<u8>, TcpStream)>,
+}
+
+/// Connections waiting on BLPOP/BRPOP. They are kept aside by the task
+/// handler, which keeps serving other clients and wakes them when one of
+/// their lists is pushed to or their timeout expires.
+/// Clients blocked on the same key are served in the order they blocked.
+#[derive(Default)]
+pub struct BlockedClients {
+    parked: Vec<Parked>,
+}
+
+impl BlockedClients {
+    /// Parks a connection until one of the lists of `pop` gets a value.
+    pub fn park(&mut self, client_id: usize, db: String, pop: BlockedPop, stream: TcpStream) {
+        self.parked.push(Parked {
+            client_id,
+            db,
+            pop,
+            stream,
+            queued: Vec::new(),
+        });
+    }
+
+    /// Whether the client is waiting on a blocking pop.
+    pub fn is_blocked(&self, client_id: usize) -> bool {
+        self.parked.iter().any(|p| p.client_id == client_id)
+    }
+
+    /// Holds a request sent by a blocked client until it is served.
+    pub fn queue(&mut self, client_id: usize, data: Vec<u8>, stream: TcpStream) {
+        if let Some(parked) = self.parked.iter_mut().find(|p| p.client_id == client_id) {
+            parked.queued.push((data, stream));
+        }
+    }
+
+    /// Forgets a client whose connection was closed.
+    pub fn remove(&mut self, client_id: usize) {
+        self.parked.retain(|p| p.client_id != client_id);
+    }
+
+    /// Serves the clients blocked on `keys` of database `db`, which were
+    /// just pushed to, for as long as the lists have values.
+    pub fn wake(&mut self, db: &str, keys: &[String], databases: &Databases) -> Vec<Unblocked> {
+        if keys.is_empty() || self.parked.is_empty() {
+            return Vec::new();
+        }
+
+        let Some(storage) = databases.get(db) else {
+            return Vec::new();
+        };
+        let mut served = Vec::new();
+        let mut i = 0;
+        while i < self.parked.len() {
+            let parked = &self.parked[i];
+            let waiting = parked.db == db && parked.pop.keys.iter().any(|k| keys.contains(k));
+            match waiting.then(|| pop_first(&parked.pop.keys, parked.pop.front, &storage)) {
+                Some(Some(response)) => served.push(unblocked(self.parked.remove(i), response)),
+                _ => i += 1,
+            }
+        }
+        served
+    }
+
+    /// Replies null to the clients whose timeout expired by `now`.
+    pub fn expire(&mut self, now: Instant) -> Vec<Unblocked> {
+        let (expired, parked) = std::mem::take(&mut self.parked)
+            .into_iter()
+            .partition(|p| p.pop.deadline.is_some_and(|deadline| deadline <= now));
+        self.parked = parked;
+
+        expired
+            .into_iter()
+            .map(|parked| unblocked(parked, null!()))
+            .collect()
+    }
+
+    /// The earliest timeout among the blocked clients.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.parked.iter().filter_map(|p| p.pop.deadline).min()
+    }
+}
+
+fn unblocked(parked: Parked, response: RespValue) -> Unblocked {
+    Unblocked {
+        client_id: parked.client_id,
+        response,
+        stream: parked.stream,
+        queued: parked.queued,
+    }
+}
+
+/// Pops a value from the first non-empty list among `keys`.
+///
+/// # Returns
+/// The RESP3 reply `[key, value]`, an error if a key holds another type, or
+/// `None` if every list is empty
+pub fn pop_first(
+    keys: &[String],
+    front: bool,
+    storage: &parking_lot::RwLock<LockedStorage>,
+) -> Option<RespValue> {
+    let mut storage = storage.write();
+    for key in keys {
+        match storage.list_pop(key, front) {
+            Ok(Some(value)) => {
+                let reply = StorageValue::List(vec![StorageValue::Text(key.clone()), value]);
+                return Some(RespValue::from(reply));
+            }
+            Ok(None) => continue,
+            Err(e) => return Some(bulkerror!(&e)),
+        }
+    }
+    None
+}
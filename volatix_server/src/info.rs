@@ -0,0 +1,342 @@
+use std::{
+    fmt::Write,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use parking_lot::Mutex;
+use volatix_core::{Databases, KeyspaceInfo};
+
+use crate::metrics::Metrics;
+
+/// Sections of the INFO reply, in the order they are rendered.
+const SECTIONS: [&str; 6] = [
+    "server",
+    "clients",
+    "memory",
+    "persistence",
+    "stats",
+    "keyspace",
+];
+
+/// Outcome of the last snapshot written to disk.
+#[derive(Debug, Clone, Copy)]
+struct Snapshot {
+    at: SystemTime,
+    ok: bool,
+}
+
+/// Server-wide state reported by the INFO command and the metrics endpoint.
+/// Shared by the connection, snapshot and task handler threads.
+#[derive(Debug)]
+pub struct ServerInfo {
+    started_at: Instant,
+    port: u16,
+    /// Seconds between two snapshots to disk
+    snapshot_interval: u64,
+    /// Open client connections
+    connected_clients: AtomicUsize,
+    /// Connections accepted since startup
+    total_connections: AtomicUsize,
+    /// `None` until the first snapshot is written
+    last_snapshot: Mutex<Option<Snapshot>>,
+    /// Command and snapshot timings
+    pub metrics: Metrics,
+}
+
+impl ServerInfo {
+    pub fn new(port: u16, snapshot_interval: u64) -> Self {
+        ServerInfo {
+            started_at: Instant::now(),
+            port,
+            snapshot_interval,
+            connected_clients: AtomicUsize::new(0),
+            total_connections: AtomicUsize::new(0),
+            last_snapshot: Mutex::new(None),
+            metrics: Metrics::default(),
+        }
+    }
+
+    /// Time since the server started.
+    pub fn uptime(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    /// Number of open client connections.
+    pub fn connected_clients(&self) -> usize {
+        self.connected_clients.load(Ordering::Relaxed)
+    }
+
+    /// Number of connections accepted since startup.
+    pub fn total_connections(&self) -> usize {
+        self.total_connections.load(Ordering::Relaxed)
+    }
+
+    /// Counts a newly accepted connection.
+    pub fn client_connected(&self) {
+        self.connected_clients.fetch_add(1, Ordering::Relaxed);
+        self.total_connections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts a closed connection.
+    pub fn client_disconnected(&self) {
+        self.connected_clients.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Records the time, duration and outcome of a snapshot to disk.
+    pub fn snapshot_done(&self, ok: bool, elapsed: Duration) {
+        self.metrics.snapshot_done(elapsed);
+        *self.last_snapshot.lock() = Some(Snapshot {
+            at: SystemTime::now(),
+            ok,
+        });
+    }
+
+    /// Renders the INFO reply: a `# Section` header followed by `field:value`
+    /// lines for every requested section.
+    ///
+    /// # Arguments
+    /// * `sections` - Names of the sections to render, case-insensitive.
+    ///   All sections are rendered if empty or if it contains `all`
+    /// * `databases` - All logical databases
+    ///
+    /// # Returns
+    /// The rendered sections or an error naming an unknown section
+    pub fn render<S: AsRef<str>>(
+        &self,
+        sections: &[S],
+        databases: &Databases,
+    ) -> Result<String, String> {
+        let mut requested = Vec::new();
+        for section in sections {
+            let name = section.as_ref().to_lowercase();
+            match name.as_str() {
+                "all" | "default" | "everything" => requested.extend(SECTIONS),
+                s => match SECTIONS.iter().find(|&&known| known == s) {
+                    Some(&known) => requested.push(known),
+                    None => return Err(format!("Unknown INFO section: {name}")),
+                },
+            }
+        }
+        if requested.is_empty() {
+            requested.extend(SECTIONS);
+        }
+
+        // Every database is read once, whatever the number of sections
+        let keyspaces: Vec<(String, KeyspaceInfo)> = databases
+            .names()
+            .into_iter()
+            .filter_map(|name| {
+                let info = databases.get(&name)?.read().keyspace_info();
+                Some((name, info))
+            })
+            .collect();
+
+        let mut out = String::new();
+        for section in SECTIONS.iter().filter(|s| requested.contains(s)) {
+            if !out.is_empty() {
+                out.push_str("\r\n");
+            }
+            let mut title = section.to_string();
+            title[..1].make_ascii_uppercase();
+            let _ = write!(out, "# {title}\r\n");
+
+            for (field, value) in self.section(section, databases, &keyspaces) {
+                let _ = write!(out, "{field}:{value}\r\n");
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// Lists the fields of a single section.
+    fn section(
+        &self,
+        section: &str,
+        databases: &Databases,
+        keyspaces: &[(String, KeyspaceInfo)],
+    ) -> Vec<(String, String)> {
+        let field = |name: &str, value: &dyn ToString| (name.to_string(), value.to_string());
+
+        match section {
+            "server" => {
+                let uptime = self.uptime().as_secs();
+                vec![
+                    field("volatix_version", &env!("CARGO_PKG_VERSION")),
+                    field("process_id", &std::process::id()),
+                    field("tcp_port", &self.port),
+                    field("uptime_in_seconds", &uptime),
+                    field("uptime_in_days", &(uptime / 86400)),
+                ]
+            }
+            "clients" => vec![
+                field("connected_clients", &self.connected_clients()),
+                field("total_connections_received", &self.total_connections()),
+            ],
+            "memory" => {
+                let used: usize = keyspaces.iter().map(|(_, info)| info.memory).sum();
+                vec![
+                    field("used_memory", &used),
+                    field("used_memory_human", &human_bytes(used)),
+                ]
+            }
+            "persistence" => {
+                let last = *self.last_snapshot.lock();
+                let (time, status) = match last {
+                    Some(s) => {
+                        let time = s.at.duration_since(UNIX_EPOCH).unwrap_or_default();
+                        (time.as_secs(), if s.ok { "ok" } else { "err" })
+                    }
+                    None => (0, "none"),
+                };
+                vec![
+                    field("dirty", &(databases.should_flush() as u8)),
+                    field("last_snapshot_time", &time),
+                    field("last_snapshot_status", &status),
+                    field("snapshot_interval", &self.snapshot_interval),
+                ]
+            }
+            "stats" => {
+                let (mut hits, mut misses, mut evictions, mut expired) = (0, 0, 0, 0);
+                for name in databases.names() {
+                    let Some(db) = databases.get(&name) else {
+                        continue;
+                    };
+                    let stats = db.read().get_stats();
+                    hits += stats.hits.load(Ordering::Relaxed);
+                    misses += stats.misses.load(Ordering::Relaxed);
+                    evictions += stats.evictions.load(Ordering::Relaxed);
+                    expired += stats.expired_removals.load(Ordering::Relaxed);
+                }
+                vec![
+                    field("keyspace_hits", &hits),
+                    field("keyspace_misses", &misses),
+                    field("evicted_keys", &evictions),
+                    field("expired_keys", &expired),
+                ]
+            }
+            "keyspace" => keyspaces
+                .iter()
+                .filter(|(_, info)| info.keys > 0)
+                .map(|(name, info)| {
+                    let persistent = info.keys - info.expires;
+                    let value = format!(
+                        "keys={},expires={},persistent={}",
+                        info.keys, info.expires, persistent
+                    );
+                    (name.clone(), value)
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Formats a byte count the way `used_memory_human` shows it, e.g `1.50K`.
+fn human_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["K", "M", "G", "T"];
+
+    if bytes < 1024 {
+        return format!("{bytes}B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.2}{}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod info_tests {
+    use std::time::Duration;
+
+    use volatix_core::{StorageOptions, StorageValue};
+
+    use super::*;
+
+    fn databases() -> Databases {
+        let databases = Databases::new(StorageOptions::default());
+        let db = databases.get_or_create("0").unwrap();
+        let mut store = db.write();
+        store
+            .insert_with_expiry("a".to_string(), StorageValue::Int(1), None)
+            .unwrap();
+        let expires_at = SystemTime::now() + Duration::from_secs(60);
+        store
+            .insert_with_expiry("b".to_string(), StorageValue::Int(2), Some(expires_at))
+            .unwrap();
+        drop(store);
+        databases.get_or_create("1").unwrap();
+        databases
+    }
+
+    #[test]
+    fn test_render_named_section() {
+        let server = ServerInfo::new(7878, 60);
+        server.client_connected();
+        server.client_connected();
+        server.client_disconnected();
+        let databases = databases();
+
+        assert_eq!(
+            server.render(&["CLIENTS"], &databases),
+            Ok("# Clients\r\nconnected_clients:1\r\ntotal_connections_received:2\r\n".to_string())
+        );
+        // Empty databases are left out of the keyspace
+        assert_eq!(
+            server.render(&["keyspace"], &databases),
+            Ok("# Keyspace\r\n0:keys=2,expires=1,persistent=1\r\n".to_string())
+        );
+        assert_eq!(
+            server.render(&["keyspace", "clients", "keyspace"], &databases),
+            Ok(concat!(
+                "# Clients\r\nconnected_clients:1\r\ntotal_connections_received:2\r\n",
+                "\r\n# Keyspace\r\n0:keys=2,expires=1,persistent=1\r\n"
+            )
+            .to_string())
+        );
+        assert_eq!(
+            server.render(&["clients", "nope"], &databases),
+            Err("Unknown INFO section: nope".to_string())
+        );
+    }
+
+    #[test]
+    fn test_render_all_sections() {
+        let server = ServerInfo::new(7878, 60);
+        let databases = databases();
+
+        let out = server.render::<&str>(&[], &databases).unwrap();
+        let titles: Vec<&str> = out.lines().filter(|l| l.starts_with("# ")).collect();
+        assert_eq!(
+            titles,
+            [
+                "# Server",
+                "# Clients",
+                "# Memory",
+                "# Persistence",
+                "# Stats",
+                "# Keyspace"
+            ]
+        );
+        // Sections are separated by an empty line
+        assert_eq!(out.matches("\r\n\r\n# ").count(), SECTIONS.len() - 1);
+        assert!(out.contains("\r\ntcp_port:7878\r\n"));
+        assert!(out.contains("\r\nlast_snapshot_status:none\r\n"));
+        assert!(out.contains("\r\nsnapshot_interval:60\r\n"));
+        assert!(out.ends_with("\r\n0:keys=2,expires=1,persistent=1\r\n"));
+
+        let all = server.render(&["all"], &databases).unwrap();
+        let all_titles: Vec<&str> = all.lines().filter(|l| l.starts_with("# ")).collect();
+        assert_eq!(all_titles, titles);
+    }
+
+    #[test]
+    fn test_human_bytes() {
+        assert_eq!(human_bytes(512), "512B");
+        assert_eq!(human_bytes(1536), "1.50K");
+        assert_eq!(human_bytes(3 * 1024 * 1024), "3.00M");
+    }
+}
@@ -1,31 +1,44 @@
 use std::{
+    collections::{HashMap, VecDeque},
     env,
     io::{Read, Write},
     net::{Ipv4Addr, SocketAddr, SocketAddrV4, TcpListener, TcpStream},
     path::{Path, PathBuf},
     sync::{
         Arc,
-        atomic::{AtomicBool, Ordering},
-        mpsc::{Receiver, Sender, channel},
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{Receiver, RecvTimeoutError, Sender, channel},
     },
     thread::JoinHandle,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
+mod blocking;
+mod info;
+mod metrics;
 mod process;
+mod session;
 
 use clap::Parser;
-use parking_lot::RwLock;
 use volatix_core::{
-    LockedStorage, Message, StorageOptions, handle_messages, parse_request, volatix_ascii_art,
+    Databases, Message, RespValue, StorageOptions, handle_messages, is_inline, parse_inline,
+    parse_request, split_inline, volatix_ascii_art,
 };
 
-use crate::process::process_request;
+use crate::{
+    blocking::{BlockedClients, Unblocked},
+    info::ServerInfo,
+    metrics::metrics_handler,
+    process::{command_name, process_request},
+    session::Session,
+};
 
 // start the server backend on this port
 const DEFAULT_PORT: u16 = 7878;
 // FLush snapshots to disk in this interval
 const SNAPSHOTS_INTERVAL_TIME: u64 = 60 * 5; // In seconds
+// Connections typing a longer inline command without ending the line are closed
+const MAX_INLINE_LEN: usize = 64 * 1024;
 
 #[derive(Debug, Parser)]
 struct Cli {
@@ -37,6 +50,12 @@ struct Cli {
         help = "Flush data to disk in every interval seconds"
     )]
     snapshots_interval: Option<u64>,
+    #[arg(
+        short = 'm',
+        long = "metrics_port",
+        help = "Serve Prometheus metrics over HTTP on this port"
+    )]
+    metrics_port: Option<u16>,
 }
 
 // FIX: This may misbehave outside of unix environments
@@ -90,34 +109,67 @@ fn write_to_stream(response_rx: Receiver<Response>, message_tx: Arc<Sender<Messa
 }
 
 enum Task {
-    Process { data: Vec<u8>, stream: TcpStream },
+    Process {
+        client_id: usize,
+        data: Vec<u8>,
+        stream: TcpStream,
+    },
+    // The client's connection was closed
+    Disconnect {
+        client_id: usize,
+    },
     Break,
 }
 
 fn read_from_stream(
+    client_id: usize,
     mut stream: TcpStream,
+    server: Arc<ServerInfo>,
     message_tx: Arc<Sender<Message>>,
     task_tx: Arc<Sender<Task>>,
 ) {
     let mut buffer = vec![0u8; 4096];
-    loop {
+    // Start of an inline command whose line hasn't been read in full yet
+    let mut partial = Vec::new();
+    'read: loop {
         match stream.read(&mut buffer) {
             Ok(0) => break, // Client disconnected
             Ok(n) => {
-                let data = buffer[..n].to_vec();
-
-                // Clone stream for response
-                match stream.try_clone() {
-                    Ok(stream_clone) => {
-                        let _ = task_tx.send(Task::Process {
-                            data,
-                            stream: stream_clone,
-                        });
-                    }
-                    Err(e) => {
-                        let _ = message_tx.send(Message::Error(e.to_string()));
+                partial.extend_from_slice(&buffer[..n]);
+
+                // Pipelined inline commands are processed one line at a time,
+                // RESP frames as they are read
+                let (lines, rest) = split_inline(&partial);
+                let mut requests: Vec<Vec<u8>> = lines.iter().map(|line| line.to_vec()).collect();
+                let rest = rest.to_vec();
+                partial.clear();
+                if is_inline(&rest) {
+                    if rest.len() > MAX_INLINE_LEN {
+                        let _ = message_tx.send(Message::Error(
+                            "Inline command too long, closing the connection".to_string(),
+                        ));
                         break;
                     }
+                    partial = rest;
+                } else if !rest.is_empty() {
+                    requests.push(rest);
+                }
+
+                for data in requests {
+                    // Clone stream for response
+                    match stream.try_clone() {
+                        Ok(stream_clone) => {
+                            let _ = task_tx.send(Task::Process {
+                                client_id,
+                                data,
+                                stream: stream_clone,
+                            });
+                        }
+                        Err(e) => {
+                            let _ = message_tx.send(Message::Error(e.to_string()));
+                            break 'read;
+                        }
+                    }
                 }
             }
             Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
@@ -130,37 +182,148 @@ fn read_from_stream(
             }
         }
     }
+
+    server.client_disconnected();
+    let _ = task_tx.send(Task::Disconnect { client_id });
 }
 
 fn task_handler(
     task_rx: Receiver<Task>,
-    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+    databases: Arc<Databases>,
+    server: Arc<ServerInfo>,
     message_tx: Arc<Sender<Message>>,
     response_tx: Arc<Sender<Response>>,
 ) {
+    // Connection state such as the selected database
+    let mut sessions: HashMap<usize, Session> = HashMap::new();
+    // Connections waiting on BLPOP/BRPOP, set aside so that they don't hold
+    // up the other clients
+    let mut blocked = BlockedClients::default();
+    // Requests of unblocked clients that arrived while they were waiting
+    let mut backlog: VecDeque<Task> = VecDeque::new();
+
+    // Replies to an unblocked client and replays the requests it sent since
+    let serve =
+        |client: Unblocked, sessions: &HashMap<usize, Session>, backlog: &mut VecDeque<Task>| {
+            let inner = match sessions.get(&client.client_id) {
+                Some(session) => session.encode(client.response),
+                None => client.response.encode(),
+            };
+            let _ = response_tx.send(Response::Data {
+                inner,
+                stream: client.stream,
+            });
+            backlog.extend(
+                client
+                    .queued
+                    .into_iter()
+                    .map(|(data, stream)| Task::Process {
+                        client_id: client.client_id,
+                        data,
+                        stream,
+                    }),
+            );
+        };
+
+    // Replies to a request that couldn't be parsed
+    let reject = |err: volatix_core::Error, stream: TcpStream| {
+        let err = format!("Invalid request: {err}");
+        let _ = message_tx.send(Message::Error(err.clone()));
+
+        let _ = response_tx.send(Response::Data {
+            inner: err.as_bytes().to_vec(),
+            stream,
+        });
+    };
+
     loop {
-        if let Ok(t) = task_rx.recv() {
+        // Wake up in time for the earliest timeout of a blocked client
+        let task = match (backlog.pop_front(), blocked.next_deadline()) {
+            (Some(t), _) => Ok(t),
+            (None, Some(deadline)) => {
+                task_rx.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            }
+            (None, None) => task_rx.recv().map_err(RecvTimeoutError::from),
+        };
+
+        for client in blocked.expire(Instant::now()) {
+            serve(client, &sessions, &mut backlog);
+        }
+
+        if let Ok(t) = task {
             match t {
-                Task::Process { data, stream } => match parse_request(&data) {
-                    Ok(req) => {
-                        let storage = Arc::clone(&storage);
-                        let response = process_request(&req, storage, Arc::clone(&message_tx));
-
-                        let _ = response_tx.send(Response::Data {
-                            inner: response,
-                            stream,
-                        });
-                    }
-                    Err(err) => {
-                        let err = format!("Invalid request: {err}");
-                        let _ = message_tx.send(Message::Error(format!("Invalid request: {err}")));
-
-                        let _ = response_tx.send(Response::Data {
-                            inner: err.as_bytes().to_vec(),
-                            stream,
-                        });
+                // Requests of a blocked client wait until it is served
+                Task::Process {
+                    client_id,
+                    data,
+                    stream,
+                } if blocked.is_blocked(client_id) => blocked.queue(client_id, data, stream),
+                Task::Process {
+                    client_id,
+                    data,
+                    stream,
+                } => {
+                    // Lines typed over telnet or netcat, one per task, become
+                    // arrays of bulk strings
+                    let data = if is_inline(&data) {
+                        match parse_inline(&data) {
+                            // Blank lines are ignored
+                            Ok(args) if args.is_empty() => continue,
+                            Ok(args) => RespValue::Array(
+                                args.into_iter().map(RespValue::BulkString).collect(),
+                            )
+                            .encode(),
+                            Err(err) => {
+                                reject(err, stream);
+                                continue;
+                            }
+                        }
+                    } else {
+                        data
+                    };
+
+                    match parse_request(&data) {
+                        Ok(req) => {
+                            let session = sessions
+                                .entry(client_id)
+                                .or_insert_with(|| Session::new(client_id));
+                            let started = Instant::now();
+                            let response = process_request(
+                                &req,
+                                session,
+                                &databases,
+                                &server,
+                                Arc::clone(&message_tx),
+                            );
+                            server
+                                .metrics
+                                .command_done(&command_name(&req), started.elapsed());
+
+                            match session.blocked.take() {
+                                Some(pop) => {
+                                    blocked.park(client_id, session.db.clone(), pop, stream)
+                                }
+                                None => {
+                                    let _ = response_tx.send(Response::Data {
+                                        inner: session.encode(response),
+                                        stream,
+                                    });
+                                }
+                            }
+
+                            let pushed = std::mem::take(&mut session.pushed);
+                            let db = session.db.clone();
+                            for client in blocked.wake(&db, &pushed, &databases) {
+                                serve(client, &sessions, &mut backlog);
+                            }
+                        }
+                        Err(err) => reject(err, stream),
                     }
-                },
+                }
+                Task::Disconnect { client_id } => {
+                    sessions.remove(&client_id);
+                    blocked.remove(client_id);
+                }
                 Task::Break => break,
             }
         }
@@ -169,7 +332,8 @@ fn task_handler(
 
 fn snapshots_handler(
     interval: u64,
-    storage: Arc<RwLock<LockedStorage>>,
+    databases: Arc<Databases>,
+    server: Arc<ServerInfo>,
     shutdown: Arc<AtomicBool>,
     path: &PathBuf,
 ) {
@@ -181,27 +345,36 @@ fn snapshots_handler(
             std::thread::sleep(Duration::from_secs(5));
         }
 
-        if storage.read().should_flush() && storage.read().save_to_disk(path).is_ok() {
-            storage.write().toggle_dirty_flag();
+        if databases.should_flush() {
+            let started = Instant::now();
+            let saved = databases.save_to_disk(path);
+            server.snapshot_done(saved.is_ok(), started.elapsed());
         }
     }
 }
 
 fn client_handler(
     listener: TcpListener,
+    server: Arc<ServerInfo>,
     shutdown: Arc<AtomicBool>,
     message_tx: Arc<Sender<Message>>,
     task_tx: Arc<Sender<Task>>,
 ) {
+    let next_client_id = AtomicUsize::new(0);
     loop {
         if shutdown.load(Ordering::Relaxed) {
             break;
         }
         match listener.accept() {
             Ok((client, _)) => {
+                let client_id = next_client_id.fetch_add(1, Ordering::Relaxed);
+                server.client_connected();
+                let server = Arc::clone(&server);
                 let message_tx = Arc::clone(&message_tx);
                 let task_tx = Arc::clone(&task_tx);
-                std::thread::spawn(move || read_from_stream(client, message_tx, task_tx));
+                std::thread::spawn(move || {
+                    read_from_stream(client_id, client, server, message_tx, task_tx)
+                });
             }
             Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {
                 std::thread::sleep(Duration::from_millis(1));
@@ -244,13 +417,13 @@ fn main() -> anyhow::Result<()> {
 
     // Intialise storage data
     let options = StorageOptions::default();
-    let storage: Arc<RwLock<LockedStorage>> =
-        Arc::new(parking_lot::RwLock::new(LockedStorage::new(options)));
+    let databases = Arc::new(Databases::new(options));
     let persistent_path = get_persistent_path(".volatix.bin")?;
-    {
-        // avoid deadlock
-        storage.write().load_from_disk(&persistent_path)?;
-    }
+    databases.load_from_disk(&persistent_path)?;
+
+    // Server state reported by INFO
+    let snapshots_interval = args.snapshots_interval.unwrap_or(SNAPSHOTS_INTERVAL_TIME);
+    let server = Arc::new(ServerInfo::new(port, snapshots_interval));
 
     let shutdown = Arc::new(AtomicBool::new(false));
     let shutdown_signal: Arc<AtomicBool> = Arc::clone(&shutdown);
@@ -260,16 +433,17 @@ fn main() -> anyhow::Result<()> {
         println!("Received SIGTERM signal! Shutting down ...");
     })?;
 
-    let snapshots_storage: Arc<RwLock<LockedStorage>> = Arc::clone(&storage);
+    let snapshots_databases = Arc::clone(&databases);
     let snapshots_persistent_path = persistent_path.clone();
+    let snapshots_server = Arc::clone(&server);
     let snapshots_shutdown = Arc::clone(&shutdown);
     workers.push(Worker {
         id: "snapshots_handler".into(),
         handle: std::thread::spawn(move || {
-            let interval = args.snapshots_interval.unwrap_or(SNAPSHOTS_INTERVAL_TIME);
             snapshots_handler(
-                interval,
-                snapshots_storage,
+                snapshots_interval,
+                snapshots_databases,
+                snapshots_server,
                 snapshots_shutdown,
                 &snapshots_persistent_path,
             );
@@ -279,6 +453,7 @@ fn main() -> anyhow::Result<()> {
     let (task_tx, task_rx) = channel::<Task>();
     let task_tx = Arc::new(task_tx);
 
+    let listener_server = Arc::clone(&server);
     let listener_shutdown = Arc::clone(&shutdown);
     let listener_message_tx = Arc::clone(&message_tx);
     let listener_task_tx = Arc::clone(&task_tx);
@@ -288,6 +463,7 @@ fn main() -> anyhow::Result<()> {
         handle: std::thread::spawn(move || {
             client_handler(
                 listener,
+                listener_server,
                 listener_shutdown,
                 listener_message_tx,
                 listener_task_tx,
@@ -298,16 +474,46 @@ fn main() -> anyhow::Result<()> {
     let (response_tx, response_rx) = channel::<Response>();
     let response_tx = Arc::new(response_tx);
 
-    let handler_storage = Arc::clone(&storage);
+    let handler_databases = Arc::clone(&databases);
+    let handler_server = Arc::clone(&server);
     let handler_message_tx = Arc::clone(&message_tx);
     let handler_res_tx = Arc::clone(&response_tx);
     workers.push(Worker {
         id: "task_handler".into(),
         handle: std::thread::spawn(move || {
-            task_handler(task_rx, handler_storage, handler_message_tx, handler_res_tx)
+            task_handler(
+                task_rx,
+                handler_databases,
+                handler_server,
+                handler_message_tx,
+                handler_res_tx,
+            )
         }),
     });
 
+    // Prometheus metrics endpoint, only if asked for
+    if let Some(metrics_port) = args.metrics_port {
+        let addr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), metrics_port));
+        let metrics_listener = TcpListener::bind(addr)?;
+        metrics_listener.set_nonblocking(true)?;
+        println!("Metrics served on http://{addr}/metrics");
+
+        let metrics_server = Arc::clone(&server);
+        let metrics_databases = Arc::clone(&databases);
+        let metrics_shutdown = Arc::clone(&shutdown);
+        workers.push(Worker {
+            id: "metrics_handler".into(),
+            handle: std::thread::spawn(move || {
+                metrics_handler(
+                    metrics_listener,
+                    metrics_server,
+                    metrics_databases,
+                    metrics_shutdown,
+                )
+            }),
+        });
+    }
+
     let responder_message_tx = Arc::clone(&message_tx);
     workers.push(Worker {
         id: "response_writer".into(),
@@ -328,9 +534,9 @@ fn main() -> anyhow::Result<()> {
         let _ = worker.handle.join();
     }
 
-    if storage.read().should_flush() {
+    if databases.should_flush() {
         println!("Saving data to disk...");
-        storage.read().save_to_disk(&persistent_path)?;
+        databases.save_to_disk(&persistent_path)?;
         println!("Complete saving data!");
     }
 
@@ -0,0 +1,425 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use parking_lot::Mutex;
+use volatix_core::Databases;
+
+use crate::info::ServerInfo;
+
+/// Upper bounds in seconds of the command latency buckets.
+const COMMAND_BUCKETS: [f64; 12] = [
+    0.0001, 0.00025, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.5, 1.0,
+];
+
+/// Upper bounds in seconds of the snapshot duration buckets.
+const SNAPSHOT_BUCKETS: [f64; 10] = [0.005, 0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// A Prometheus histogram with fixed buckets.
+#[derive(Debug, Clone)]
+struct Histogram {
+    bounds: &'static [f64],
+    /// Observations per bucket, not cumulative
+    buckets: Vec<u64>,
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Histogram {
+            bounds,
+            buckets: vec![0; bounds.len()],
+            count: 0,
+            sum: 0.0,
+        }
+    }
+
+    fn observe(&mut self, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64();
+        if let Some(i) = self.bounds.iter().position(|&b| secs <= b) {
+            self.buckets[i] += 1;
+        }
+        self.count += 1;
+        self.sum += secs;
+    }
+
+    /// Writes the `_bucket`, `_sum` and `_count` series of the histogram.
+    ///
+    /// # Arguments
+    /// * `out` - Buffer to write to
+    /// * `name` - Name of the metric
+    /// * `labels` - Labels of the series, e.g `command="GET"`, may be empty
+    fn write(&self, out: &mut String, name: &str, labels: &str) {
+        let sep = if labels.is_empty() { "" } else { "," };
+        let mut cumulative = 0;
+        for (bound, n) in self.bounds.iter().zip(&self.buckets) {
+            cumulative += n;
+            let _ = writeln!(
+                out,
+                "{name}_bucket{{{labels}{sep}le=\"{bound}\"}} {cumulative}"
+            );
+        }
+        let _ = writeln!(
+            out,
+            "{name}_bucket{{{labels}{sep}le=\"+Inf\"}} {}",
+            self.count
+        );
+
+        let labels = if labels.is_empty() {
+            String::new()
+        } else {
+            format!("{{{labels}}}")
+        };
+        let _ = writeln!(out, "{name}_sum{labels} {}", self.sum);
+        let _ = writeln!(out, "{name}_count{labels} {}", self.count);
+    }
+}
+
+/// Command and snapshot timings exported on the metrics endpoint.
+#[derive(Debug)]
+pub struct Metrics {
+    /// Latencies by command name
+    commands: Mutex<BTreeMap<String, Histogram>>,
+    snapshots: Mutex<Histogram>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Metrics {
+            commands: Mutex::new(BTreeMap::new()),
+            snapshots: Mutex::new(Histogram::new(&SNAPSHOT_BUCKETS)),
+        }
+    }
+}
+
+impl Metrics {
+    /// Records the time taken to process a command.
+    pub fn command_done(&self, command: &str, elapsed: Duration) {
+        let mut commands = self.commands.lock();
+        match commands.get_mut(command) {
+            Some(h) => h.observe(elapsed),
+            None => {
+                let mut h = Histogram::new(&COMMAND_BUCKETS);
+                h.observe(elapsed);
+                commands.insert(command.to_string(), h);
+            }
+        }
+    }
+
+    /// Records the time taken to write a snapshot to disk.
+    pub fn snapshot_done(&self, elapsed: Duration) {
+        self.snapshots.lock().observe(elapsed);
+    }
+}
+
+/// Writes the `# HELP` and `# TYPE` lines of a metric.
+fn describe(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+/// Renders all metrics in the Prometheus text exposition format.
+///
+/// # Arguments
+/// * `server` - Server-wide state, holding the connection counts and timings
+/// * `databases` - All logical databases, labelled by name
+pub fn render_metrics(server: &ServerInfo, databases: &Databases) -> String {
+    let mut out = String::new();
+
+    describe(
+        &mut out,
+        "volatix_uptime_seconds",
+        "gauge",
+        "Seconds since the server started.",
+    );
+    let _ = writeln!(out, "volatix_uptime_seconds {}", server.uptime().as_secs());
+
+    describe(
+        &mut out,
+        "volatix_connected_clients",
+        "gauge",
+        "Open client connections.",
+    );
+    let _ = writeln!(
+        out,
+        "volatix_connected_clients {}",
+        server.connected_clients()
+    );
+    describe(
+        &mut out,
+        "volatix_connections_received_total",
+        "counter",
+        "Client connections accepted since startup.",
+    );
+    let _ = writeln!(
+        out,
+        "volatix_connections_received_total {}",
+        server.total_connections()
+    );
+
+    // Per database series, one sample per database and metric
+    let per_db: Vec<(String, [usize; 6])> = databases
+        .names()
+        .into_iter()
+        .filter_map(|name| {
+            let db = databases.get(&name)?;
+            let db = db.read();
+            let info = db.keyspace_info();
+            let stats = db.get_stats();
+            let samples = [
+                info.keys,
+                info.memory,
+                stats.hits.load(Ordering::Relaxed),
+                stats.misses.load(Ordering::Relaxed),
+                stats.evictions.load(Ordering::Relaxed),
+                stats.expired_removals.load(Ordering::Relaxed),
+            ];
+            Some((name, samples))
+        })
+        .collect();
+
+    let series = [
+        ("volatix_keys", "gauge", "Live keys in the database."),
+        (
+            "volatix_memory_used_bytes",
+            "gauge",
+            "Bytes used by the keys and values of the database.",
+        ),
+        (
+            "volatix_keyspace_hits_total",
+            "counter",
+            "Lookups that found their key.",
+        ),
+        (
+            "volatix_keyspace_misses_total",
+            "counter",
+            "Lookups that didn't find their key.",
+        ),
+        (
+            "volatix_evicted_keys_total",
+            "counter",
+            "Keys removed by the eviction policy.",
+        ),
+        (
+            "volatix_expired_keys_total",
+            "counter",
+            "Keys removed after their TTL expired.",
+        ),
+    ];
+    for (i, (name, kind, help)) in series.iter().enumerate() {
+        describe(&mut out, name, kind, help);
+        for (db, samples) in &per_db {
+            let _ = writeln!(out, "{name}{{db=\"{}\"}} {}", escape(db), samples[i]);
+        }
+    }
+
+    let commands = server.metrics.commands.lock().clone();
+    describe(
+        &mut out,
+        "volatix_commands_total",
+        "counter",
+        "Commands processed, by command.",
+    );
+    for (command, h) in &commands {
+        let _ = writeln!(
+            out,
+            "volatix_commands_total{{command=\"{}\"}} {}",
+            escape(command),
+            h.count
+        );
+    }
+    describe(
+        &mut out,
+        "volatix_command_duration_seconds",
+        "histogram",
+        "Time taken to process a command, by command.",
+    );
+    for (command, h) in &commands {
+        let labels = format!("command=\"{}\"", escape(command));
+        h.write(&mut out, "volatix_command_duration_seconds", &labels);
+    }
+
+    describe(
+        &mut out,
+        "volatix_snapshot_duration_seconds",
+        "histogram",
+        "Time taken to write a snapshot to disk.",
+    );
+    let snapshots = server.metrics.snapshots.lock().clone();
+    snapshots.write(&mut out, "volatix_snapshot_duration_seconds", "");
+
+    out
+}
+
+/// Escapes a label value for the text exposition format.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Answers a single HTTP request: the metrics on `GET /metrics`, 404 for
+/// anything else.
+fn serve_metrics(mut stream: TcpStream, server: &ServerInfo, databases: &Databases) {
+    let _ = stream.set_nonblocking(false);
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+
+    // Only the request line matters, the headers are left unread
+    let mut request_line = String::new();
+    if BufReader::new(&stream)
+        .read_line(&mut request_line)
+        .is_err()
+    {
+        return;
+    }
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next(), parts.next());
+
+    let (status, body) = match (method, path) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", render_metrics(server, databases)),
+        _ => ("404 Not Found", "Not Found\n".to_string()),
+    };
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    let _ = stream.write_all(response.as_bytes());
+}
+
+/// Serves the Prometheus metrics endpoint until shutdown.
+/// Scrapes are rare, so requests are answered one at a time.
+pub fn metrics_handler(
+    listener: TcpListener,
+    server: Arc<ServerInfo>,
+    databases: Arc<Databases>,
+    shutdown: Arc<AtomicBool>,
+) {
+    while !shutdown.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => serve_metrics(stream, &server, &databases),
+            Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            Err(_) => continue,
+        }
+    }
+}
+
+#[cfg(test)]
+mod metrics_tests {
+    use volatix_core::{StorageOptions, StorageValue};
+
+    use super::*;
+
+    #[test]
+    fn test_histogram_buckets_are_cumulative() {
+        let mut h = Histogram::new(&COMMAND_BUCKETS);
+        h.observe(Duration::from_micros(50));
+        h.observe(Duration::from_micros(200));
+        h.observe(Duration::from_secs(2));
+
+        let mut out = String::new();
+        h.write(&mut out, "latency", "command=\"GET\"");
+        let lines: Vec<&str> = out.lines().collect();
+
+        assert_eq!(lines.len(), COMMAND_BUCKETS.len() + 3);
+        assert_eq!(lines[0], "latency_bucket{command=\"GET\",le=\"0.0001\"} 1");
+        assert_eq!(lines[1], "latency_bucket{command=\"GET\",le=\"0.00025\"} 2");
+        assert_eq!(
+            lines[COMMAND_BUCKETS.len() - 1],
+            "latency_bucket{command=\"GET\",le=\"1\"} 2"
+        );
+        // Observations above the last bound only count in +Inf
+        assert_eq!(
+            lines[COMMAND_BUCKETS.len()],
+            "latency_bucket{command=\"GET\",le=\"+Inf\"} 3"
+        );
+
+        let counts: Vec<u64> = lines[..=COMMAND_BUCKETS.len()]
+            .iter()
+            .map(|line| line.rsplit(' ').next().unwrap().parse().unwrap())
+            .collect();
+        assert!(counts.windows(2).all(|w| w[0] <= w[1]));
+
+        let sum: f64 = lines[COMMAND_BUCKETS.len() + 1]
+            .strip_prefix("latency_sum{command=\"GET\"} ")
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((sum - 2.00025).abs() < 1e-9);
+        assert_eq!(
+            lines[COMMAND_BUCKETS.len() + 2],
+            "latency_count{command=\"GET\"} 3"
+        );
+    }
+
+    #[test]
+    fn test_histogram_without_labels() {
+        let h = Histogram::new(&SNAPSHOT_BUCKETS);
+        let mut out = String::new();
+        h.write(&mut out, "snapshot", "");
+
+        assert!(out.starts_with("snapshot_bucket{le=\"0.005\"} 0\n"));
+        assert!(
+            out.ends_with("snapshot_bucket{le=\"+Inf\"} 0\nsnapshot_sum 0\nsnapshot_count 0\n")
+        );
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("plain"), "plain");
+        assert_eq!(escape("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+
+    #[test]
+    fn test_render_metrics() {
+        let server = ServerInfo::new(7878, 60);
+        server.client_connected();
+        server.metrics.command_done("GET", Duration::from_millis(1));
+        server.metrics.command_done("GET", Duration::from_millis(3));
+
+        let databases = Databases::new(StorageOptions::default());
+        databases
+            .get_or_create("0")
+            .unwrap()
+            .write()
+            .insert_entry("k".to_string(), StorageValue::Int(1))
+            .unwrap();
+        databases.get_or_create("we\"ird").unwrap();
+
+        let out = render_metrics(&server, &databases);
+        for line in [
+            "# TYPE volatix_connected_clients gauge",
+            "volatix_connected_clients 1",
+            "volatix_connections_received_total 1",
+            "# TYPE volatix_keys gauge",
+            "volatix_keys{db=\"0\"} 1",
+            "volatix_keys{db=\"we\\\"ird\"} 0",
+            "# TYPE volatix_keyspace_hits_total counter",
+            "volatix_commands_total{command=\"GET\"} 2",
+            "# TYPE volatix_command_duration_seconds histogram",
+            "volatix_command_duration_seconds_bucket{command=\"GET\",le=\"0.001\"} 1",
+            "volatix_command_duration_seconds_bucket{command=\"GET\",le=\"0.005\"} 2",
+            "volatix_command_duration_seconds_count{command=\"GET\"} 2",
+            "volatix_snapshot_duration_seconds_count 0",
+        ] {
+            assert!(out.lines().any(|l| l == line), "missing `{line}` in\n{out}");
+        }
+
+        // Every metric is described once
+        let types = out.lines().filter(|l| l.starts_with("# TYPE")).count();
+        let helps = out.lines().filter(|l| l.starts_with("# HELP")).count();
+        assert_eq!(types, 12);
+        assert_eq!(types, helps);
+    }
+}
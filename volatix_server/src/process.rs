@@ -1,13 +1,20 @@
 use std::{
     collections::HashMap,
     sync::{Arc, mpsc::Sender},
-    time::Duration,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use volatix_core::{
-    Compression, ConfigEntry, EvictionPolicy, LockedStorage, Message, RequestType, StorageValue,
-    array, batch_getlist_entries, boolean, bulkerror, bulkstring, integer, null,
-    storagevalue_to_string,
+    BitOp, Compression, ConfigEntry, Databases, EvictionPolicy, GeoOrigin, GeoShape, GeoUnit,
+    JsonPath, LockedStorage, Message, RequestType, RespValue, SetCondition, SetExpiry, SetOptions,
+    StorageValue, StreamFields, StreamId, array, batch_getlist_entries, boolean, bulkerror,
+    bulkstring, integer, map, null, parse_json, simplestring, to_json,
+};
+
+use crate::{
+    blocking::{BlockedPop, pop_first},
+    info::ServerInfo,
+    session::Session,
 };
 
 fn request_type_to_storage_value(req: &RequestType) -> Result<StorageValue, String> {
@@ -64,6 +71,14 @@ fn request_type_to_storage_value(req: &RequestType) -> Result<StorageValue, Stri
             Ok(StorageValue::List(elems))
         }
 
+        // Strings sent in chunks are joined back together
+        RequestType::StreamedString { chunks } => {
+            Ok(StorageValue::Text(String::from_utf8_lossy(&chunks.concat()).to_string()))
+        }
+
+        // Attributes only describe the value, which is kept as is
+        RequestType::Attribute { value, .. } => request_type_to_storage_value(value),
+
         // Convert maps to our internal map format
         RequestType::Map { children } => {
             let elems = children
@@ -95,26 +110,179 @@ pub enum Command {
     GetList,    // Retrieve multiple keys
     DeleteList, // Remove multiple keys
 
+    // Pattern operations
+    Keys,        // List keys matching a pattern
+    GetMatch,    // Retrieve all keys matching a pattern
+    DeleteMatch, // Remove all keys matching a pattern
+
     // Configuration management
     ConfSet, // Set a configuration option
     ConfGet, // Get a configuration option
 
     // TTL management
-    Expire,  // Modify TTL of an existing key
-    SetwTtl, // Set a value with specific TTL
-    GetTtl,  // Get remaining TTL for a key
+    Expire,    // Modify TTL of an existing key
+    ExpireAt,  // Expire a key at a unix time in seconds
+    PExpire,   // Expire a key in a number of milliseconds
+    PExpireAt, // Expire a key at a unix time in milliseconds
+    Persist,   // Remove the expiry of a key
+    SetwTtl,   // Set a value with specific TTL
+    GetTtl,    // Get remaining TTL for a key
+    PTtl,      // Get remaining TTL for a key in milliseconds
 
     // Administrative
-    Dump,     // Get detailed entry information
-    SetMap,   // Store multiple key-value pairs (like Redis MSET)
-    Incr,     // Increment an integer value
-    Decr,     // Decrement an integer value
-    Rename,   // Rename a key
-    EvictNow, // Evict entries
+    Dump,        // Get detailed entry information
+    Type,        // Get the type of a value
+    Object,      // Inspect the access frequency, idle time or encoding of an entry
+    Memory,      // Estimate the memory used by an entry
+    Info,        // Report server, memory, persistence and keyspace information
+    SetMap,      // Store multiple key-value pairs (like Redis MSET)
+    Incr,        // Increment an integer value
+    Decr,        // Decrement an integer value
+    IncrBy,      // Add an amount to an integer value
+    DecrBy,      // Subtract an amount from an integer value
+    IncrByFloat, // Add a floating point amount to a number
+    Rename,      // Rename a key
+    EvictNow,    // Evict entries
+    Scan,        // Iterate over keys with a cursor
+
+    // String operations
+    Append,   // Append to a string value
+    StrLen,   // Get the length of a string value
+    GetRange, // Get a substring of a string value
+    SetRange, // Overwrite part of a string value
+    GetDel,   // Get a value and delete its key
+    GetEx,    // Get a value and change its expiry
+
+    // Bitmaps
+    SetBit,   // Set or clear a bit
+    GetBit,   // Get a bit
+    BitCount, // Count the set bits
+    BitOp,    // Combine bitmaps into a destination key
+    BitPos,   // Find the first set or clear bit
+
+    // HyperLogLog
+    PfAdd,   // Add elements to a cardinality sketch
+    PfCount, // Estimate the number of distinct elements
+    PfMerge, // Merge sketches into a destination key
+
+    // Bloom and cuckoo filters
+    BfReserve, // Create a Bloom filter with an error rate and capacity
+    BfAdd,     // Add an item to a Bloom filter
+    BfMAdd,    // Add several items to a Bloom filter
+    BfExists,  // Check whether an item was possibly added
+    BfMExists, // Check several items
+    CfReserve, // Create a cuckoo filter with a capacity
+    CfAdd,     // Add an item to a cuckoo filter
+    CfAddNx,   // Add an item unless it is possibly present
+    CfExists,  // Check whether an item was possibly added
+    CfDel,     // Delete an item from a cuckoo filter
+
+    // Lists
+    LPush, // Push values onto the head of a list
+    RPush, // Push values onto the tail of a list
+    LPop,  // Pop the first value of a list
+    RPop,  // Pop the last value of a list
+    LLen,  // Get the length of a list
+    BLPop, // Pop the first value, waiting for a push if the lists are empty
+    BRPop, // Pop the last value, waiting for a push if the lists are empty
+
+    // Streams
+    XAdd,       // Append an entry to a stream
+    XRange,     // Get stream entries in an ID range
+    XRevRange,  // Get stream entries in an ID range, newest first
+    XLen,       // Get the number of entries in a stream
+    XTrim,      // Trim a stream to a maximum length
+    XGroup,     // Create or destroy a consumer group
+    XReadGroup, // Read entries as a consumer of a group
+    XAck,       // Acknowledge entries delivered to a group
+    XPending,   // Inspect the pending entries of a group
+
+    // Geospatial
+    GeoAdd,    // Add members with positions
+    GeoDist,   // Get the distance between two members
+    GeoSearch, // Find members within a radius or a box
+
+    // JSON documents
+    JsonSet,       // Write a value at a path
+    JsonGet,       // Read the values at paths
+    JsonDel,       // Remove the values at a path
+    JsonNumIncrBy, // Increment the numbers at a path
+    JsonArrAppend, // Append values to the arrays at a path
+
+    // Optimistic concurrency
+    GetV, // Get a value with its version
+    Cas,  // Set a value if its version matches
+
+    // Logical databases
+    Select, // Switch the connection to another database
+    Move,   // Move a key to another database
+
+    // Connection
+    Hello, // Negotiate the protocol version of the connection
+
+    // Redis names, for off-the-shelf clients
+    Del,      // Delete keys, counting the removed ones
+    MGet,     // Get the values of several keys
+    MSet,     // Set several key-value pairs
+    SetEx,    // Set a value with a TTL in seconds
+    Ttl,      // Get the remaining TTL in seconds, -2 if missing
+    Config,   // CONFIG GET/SET
+    DbSize,   // Count the keys of the selected database
+    FlushAll, // Flush every database
+    Ping,     // Check the connection
+    Echo,     // Reply with the argument
 
     Unknown, // Invalid or unsupported command
 }
 
+impl Command {
+    /// Whether the command can add keys to the selected database or change
+    /// its options. Only these create a database that doesn't exist yet.
+    ///
+    /// # Arguments
+    /// * `args` - Command arguments, for commands with subcommands
+    fn is_write(&self, args: &[RequestType]) -> bool {
+        match self {
+            Command::Config => matches!(
+                args.first(),
+                Some(RequestType::BulkString { data }) if data.eq_ignore_ascii_case(b"SET")
+            ),
+            Command::Set
+            | Command::SetList
+            | Command::SetMap
+            | Command::ConfSet
+            | Command::SetwTtl
+            | Command::Incr
+            | Command::Decr
+            | Command::IncrBy
+            | Command::DecrBy
+            | Command::IncrByFloat
+            | Command::Append
+            | Command::SetRange
+            | Command::SetBit
+            | Command::BitOp
+            | Command::PfAdd
+            | Command::PfMerge
+            | Command::BfReserve
+            | Command::BfAdd
+            | Command::BfMAdd
+            | Command::CfReserve
+            | Command::CfAdd
+            | Command::CfAddNx
+            | Command::LPush
+            | Command::RPush
+            | Command::XAdd
+            | Command::XGroup
+            | Command::GeoAdd
+            | Command::JsonSet
+            | Command::Cas
+            | Command::MSet
+            | Command::SetEx => true,
+            _ => false,
+        }
+    }
+}
+
 /// Attempts to automatically detect the type of a string value.
 /// Uses heuristics to determine if a string should be parsed as:
 /// - Integer (i64)
@@ -161,7 +329,7 @@ fn get_value_type(value: &str) -> StorageValue {
 /// # Supported Configuration Keys
 /// - `MAXCAP`: Maximum cache capacity (positive integer)
 /// - `GLOBALTTL`: Default TTL in seconds (positive integer)
-/// - `EVICTPOLICY`: Eviction strategy (OLDEST, LFU, LRU, SIZEAWARE)
+/// - `EVICTPOLICY`: Eviction strategy (OLDEST, LFU, LRU, SIZEAWARE, TINYLFU)
 /// - `COMPRESSION`: Enable/disable compression (ENABLE/DISABLE)
 /// - `COMPTHRESHOLD`: Compression size threshold (positive integer)
 fn config_entry(key: &str, value: &StorageValue) -> Result<ConfigEntry, String> {
@@ -192,6 +360,7 @@ fn config_entry(key: &str, value: &StorageValue) -> Result<ConfigEntry, String>
                 "LFU" => Ok(ConfigEntry::EvictPolicy(EvictionPolicy::LFU)),
                 "LRU" => Ok(ConfigEntry::EvictPolicy(EvictionPolicy::LRU)),
                 "SIZEAWARE" => Ok(ConfigEntry::EvictPolicy(EvictionPolicy::SizeAware)),
+                "TINYLFU" => Ok(ConfigEntry::EvictPolicy(EvictionPolicy::TinyLFU)),
                 _ => Err("Invalid EVICTPOLICY value".to_string()),
             },
             _ => Err("Invalid EVICTPOLICY value".to_string()),
@@ -220,29 +389,35 @@ fn config_entry(key: &str, value: &StorageValue) -> Result<ConfigEntry, String>
 ///
 /// # Arguments
 ///   * `data` - A reference to an array of u8
-///   * `storage` - Storage engine reference
+///   * `storage` - The selected database
+///   * `session` - State of the connection that sent the request
+///   * `databases` - All logical databases
+///   * `server` - Server-wide state
 ///   * `message_tx` - A message sender
 ///
 /// # Returns
-/// RESP3-encoded response bytes
+/// The RESP3 reply
 ///
 /// # Command Format
 /// All commands follow the pattern: COMMAND
 fn process_single_command(
     data: &[u8],
     storage: Arc<parking_lot::RwLock<LockedStorage>>,
+    session: &mut Session,
+    databases: &Databases,
+    server: &ServerInfo,
     message_tx: Arc<Sender<Message>>,
-) -> Vec<u8> {
+) -> RespValue {
     let cmd = String::from_utf8_lossy(data).to_string();
     match cmd.to_uppercase().as_str() {
         // Client handshake - Redis compatibility
         // New RESP connections should begin with HELLO
-        "HELLO" => bulkstring!(Some("HELLO")),
+        "HELLO" => handle_hello_command(&[], session),
 
         // Performance monitoring commands
         "GETSTATS" => {
             let stats = storage.read().get_stats();
-            bulkstring!(Some(&stats.to_string()))
+            map!(&stats.to_map())
         }
         "RESETSTATS" => {
             storage.write().reset_stats();
@@ -252,7 +427,7 @@ fn process_single_command(
         // Configuration management
         "CONFOPTIONS" => {
             let options = storage.read().get_options();
-            bulkstring!(Some(&options.to_string()))
+            map!(&options.to_map())
         }
 
         "CONFRESET" => {
@@ -275,6 +450,20 @@ fn process_single_command(
             array!(&keys)
         }
 
+        // List all logical databases
+        "DBLIST" => array!(&databases.names()),
+
+        // Server information, all sections
+        "INFO" => match server.render::<&str>(&[], databases) {
+            Ok(info) => bulkstring!(Some(&info)),
+            Err(e) => bulkerror!(&e),
+        },
+
+        // Redis names, for off-the-shelf clients
+        "DBSIZE" => handle_dbsize_command(storage),
+        "FLUSHALL" => handle_flushall_command(databases),
+        "PING" => handle_ping_command(&[]),
+
         other => {
             let err = format!("Unknown single command: {other}!");
             let _ = message_tx.send(Message::Debug(err.clone()));
@@ -284,6 +473,50 @@ fn process_single_command(
     }
 }
 
+/// Commands that are sent on their own, without arguments.
+const SINGLE_COMMANDS: [&str; 12] = [
+    "HELLO",
+    "GETSTATS",
+    "RESETSTATS",
+    "CONFOPTIONS",
+    "CONFRESET",
+    "FLUSH",
+    "KEYS",
+    "DBLIST",
+    "INFO",
+    "DBSIZE",
+    "FLUSHALL",
+    "PING",
+];
+
+/// Names the command of a request, used to label the metrics of commands.
+/// Unknown commands all share the `UNKNOWN` name.
+///
+/// # Arguments
+/// * `req` - The parsed RESP3 request
+///
+/// # Returns
+/// The uppercased command name
+pub fn command_name(req: &RequestType) -> String {
+    let name = match req {
+        RequestType::BulkString { data } => {
+            let name = String::from_utf8_lossy(data).to_uppercase();
+            SINGLE_COMMANDS.contains(&name.as_str()).then_some(name)
+        }
+        RequestType::Array { children } => match children.first() {
+            Some(first @ RequestType::BulkString { data })
+                if !matches!(get_command(first), Command::Unknown) =>
+            {
+                Some(String::from_utf8_lossy(data).to_uppercase())
+            }
+            _ => None,
+        },
+        _ => None,
+    };
+
+    name.unwrap_or_else(|| "UNKNOWN".to_string())
+}
+
 /// Main request processing function that routes RESP3 requests to appropriate handlers.
 /// This is the entry point for all client requests after RESP3 parsing.
 ///
@@ -306,23 +539,50 @@ fn process_single_command(
 ///
 /// # Arguments
 /// * `req` - The parsed RESP3 request
-/// * `storage` - Thread-safe reference to the storage engine
+/// * `session` - State of the connection that sent the request
+/// * `databases` - All logical databases
+/// * `server` - Server-wide state
 ///
 /// # Returns
-/// `Vec<u8>` - RESP3-encoded response bytes
+/// `RespValue` - The RESP3 reply, encoded by the caller
 pub fn process_request(
     req: &RequestType,
-    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+    session: &mut Session,
+    databases: &Databases,
+    server: &ServerInfo,
     message_tx: Arc<Sender<Message>>,
-) -> Vec<u8> {
+) -> RespValue {
+    // Requests operate on the database selected by the connection, which
+    // only writes create
+    let writes = match req {
+        RequestType::Array { children } => children
+            .first()
+            .is_some_and(|first| get_command(first).is_write(&children[1..])),
+        _ => false,
+    };
+    let storage = match writes {
+        true => match databases.get_or_create(&session.db) {
+            Ok(storage) => storage,
+            Err(e) => return bulkerror!(&e),
+        },
+        false => databases.get_or_empty(&session.db),
+    };
+
     match req {
         // Handle single command strings (no arguments)
-        RequestType::BulkString { data } => {
-            process_single_command(data, Arc::clone(&storage), Arc::clone(&message_tx))
-        }
+        RequestType::BulkString { data } => process_single_command(
+            data,
+            storage,
+            session,
+            databases,
+            server,
+            Arc::clone(&message_tx),
+        ),
 
         // Handle command arrays (commands with arguments)
-        RequestType::Array { children } => process_array(children, storage),
+        RequestType::Array { children } => {
+            process_array(children, storage, session, databases, server)
+        }
 
         // All other request types are invalid
         _ => {
@@ -359,10 +619,20 @@ fn get_command(req_type: &RequestType) -> Command {
 
                 // Administrative and debugging
                 "DUMP" => Command::Dump,
+                "TYPE" => Command::Type,
+                "OBJECT" => Command::Object,
+                "MEMORY" => Command::Memory,
+                "INFO" => Command::Info,
+                "EVICTNOW" => Command::EvictNow,
 
                 // TTL management
                 "GETTTL" => Command::GetTtl,
+                "PTTL" => Command::PTtl,
                 "EXPIRE" => Command::Expire,
+                "EXPIREAT" => Command::ExpireAt,
+                "PEXPIRE" => Command::PExpire,
+                "PEXPIREAT" => Command::PExpireAt,
+                "PERSIST" => Command::Persist,
                 "SETWTTL" => Command::SetwTtl,
 
                 // Batch operations
@@ -371,13 +641,108 @@ fn get_command(req_type: &RequestType) -> Command {
                 "SETLIST" => Command::SetList,
                 "SETMAP" => Command::SetMap,
 
+                // Pattern operations
+                "KEYS" => Command::Keys,
+                "SCAN" => Command::Scan,
+                "GETMATCH" => Command::GetMatch,
+                "DELETEMATCH" => Command::DeleteMatch,
+
                 // Arithmetic operations
                 "INCR" => Command::Incr,
                 "DECR" => Command::Decr,
+                "INCRBY" => Command::IncrBy,
+                "DECRBY" => Command::DecrBy,
+                "INCRBYFLOAT" => Command::IncrByFloat,
 
                 // Key management
                 "RENAME" => Command::Rename,
-                "EVICTNOW" => Command::EvictNow,
+
+                // String operations
+                "APPEND" => Command::Append,
+                "STRLEN" => Command::StrLen,
+                "GETRANGE" => Command::GetRange,
+                "SETRANGE" => Command::SetRange,
+                "GETDEL" => Command::GetDel,
+                "GETEX" => Command::GetEx,
+
+                // Bitmaps
+                "SETBIT" => Command::SetBit,
+                "GETBIT" => Command::GetBit,
+                "BITCOUNT" => Command::BitCount,
+                "BITOP" => Command::BitOp,
+                "BITPOS" => Command::BitPos,
+
+                // HyperLogLog
+                "PFADD" => Command::PfAdd,
+                "PFCOUNT" => Command::PfCount,
+                "PFMERGE" => Command::PfMerge,
+
+                // Bloom and cuckoo filters
+                "BF.RESERVE" => Command::BfReserve,
+                "BF.ADD" => Command::BfAdd,
+                "BF.MADD" => Command::BfMAdd,
+                "BF.EXISTS" => Command::BfExists,
+                "BF.MEXISTS" => Command::BfMExists,
+                "CF.RESERVE" => Command::CfReserve,
+                "CF.ADD" => Command::CfAdd,
+                "CF.ADDNX" => Command::CfAddNx,
+                "CF.EXISTS" => Command::CfExists,
+                "CF.DEL" => Command::CfDel,
+
+                // Lists
+                "LPUSH" => Command::LPush,
+                "RPUSH" => Command::RPush,
+                "LPOP" => Command::LPop,
+                "RPOP" => Command::RPop,
+                "LLEN" => Command::LLen,
+                "BLPOP" => Command::BLPop,
+                "BRPOP" => Command::BRPop,
+
+                // Streams
+                "XADD" => Command::XAdd,
+                "XRANGE" => Command::XRange,
+                "XREVRANGE" => Command::XRevRange,
+                "XLEN" => Command::XLen,
+                "XTRIM" => Command::XTrim,
+                "XGROUP" => Command::XGroup,
+                "XREADGROUP" => Command::XReadGroup,
+                "XACK" => Command::XAck,
+                "XPENDING" => Command::XPending,
+
+                // Geospatial
+                "GEOADD" => Command::GeoAdd,
+                "GEODIST" => Command::GeoDist,
+                "GEOSEARCH" => Command::GeoSearch,
+
+                // JSON documents
+                "JSON.SET" => Command::JsonSet,
+                "JSON.GET" => Command::JsonGet,
+                "JSON.DEL" => Command::JsonDel,
+                "JSON.NUMINCRBY" => Command::JsonNumIncrBy,
+                "JSON.ARRAPPEND" => Command::JsonArrAppend,
+
+                // Optimistic concurrency
+                "GETV" => Command::GetV,
+                "CAS" => Command::Cas,
+
+                // Logical databases
+                "SELECT" => Command::Select,
+                "MOVE" => Command::Move,
+
+                // Connection
+                "HELLO" => Command::Hello,
+
+                // Redis names
+                "DEL" => Command::Del,
+                "MGET" => Command::MGet,
+                "MSET" => Command::MSet,
+                "SETEX" => Command::SetEx,
+                "TTL" => Command::Ttl,
+                "CONFIG" => Command::Config,
+                "DBSIZE" => Command::DbSize,
+                "FLUSHALL" => Command::FlushAll,
+                "PING" => Command::Ping,
+                "ECHO" => Command::Echo,
 
                 _ => Command::Unknown,
             }
@@ -402,7 +767,7 @@ fn get_command(req_type: &RequestType) -> Command {
 fn handle_get_command(
     children: &[RequestType],
     storage: Arc<parking_lot::RwLock<LockedStorage>>,
-) -> Vec<u8> {
+) -> RespValue {
     if children.is_empty() {
         return bulkerror!("Command missing some arguments");
     }
@@ -414,10 +779,7 @@ fn handle_get_command(
 
             // respond with the entry's `value` field
             match entry {
-                Some(v) => {
-                    let res = storagevalue_to_string(&v.value);
-                    res.as_bytes().to_vec()
-                }
+                Some(v) => RespValue::from(&v.value),
                 None => null!(),
             }
         }
@@ -437,7 +799,7 @@ fn handle_get_command(
 fn handle_exists_command(
     children: &[RequestType],
     storage: Arc<parking_lot::RwLock<LockedStorage>>,
-) -> Vec<u8> {
+) -> RespValue {
     if children.is_empty() {
         return bulkerror!("Command missing some arguments");
     }
@@ -453,44 +815,69 @@ fn handle_exists_command(
 }
 
 /// Handles SET command: stores a key-value pair.
-/// Format: `SET key value`
+/// Format: `SET key value [NX | XX] [GET] [EX seconds | PX milliseconds |
+/// EXAT unix-seconds | PXAT unix-milliseconds | KEEPTTL]`
 /// Uses automatic type detection for the value.
 ///
 /// # Arguments
-/// * `children` - Command arguments (key and value)
+/// * `children` - Command arguments (key, value and options)
 /// * `storage` - Storage engine reference
 ///
 /// # Returns
-/// RESP3 response: "SUCCESS" or error message
+/// RESP3 response: "SUCCESS", null if an NX/XX condition prevented the
+/// write, or the previous value (null if none) when GET is given
 ///
 /// # Example
 /// Input: `*3\r\n$3\r\nSET\r\n$4\r\nname\r\n$4\r\nJohn\r\n`
 /// Output: `$7\r\nSUCCESS\r\n`
+///
+/// `SET lock owner1 NX PX 3000` only takes the lock if nobody holds it
 fn handle_set_command(
     children: &[RequestType],
     storage: Arc<parking_lot::RwLock<LockedStorage>>,
-) -> Vec<u8> {
+) -> RespValue {
     if children.len() < 2 {
         return bulkerror!("Command missing some arguments");
     }
 
-    let mut i = 0;
+    let key = match &children[0] {
+        RequestType::BulkString { data } => String::from_utf8_lossy(data).to_string(),
+        _ => return bulkerror!("Invalid request type for SET key"),
+    };
 
-    if let RequestType::BulkString { data } = &children[i] {
-        let key = String::from_utf8_lossy(data).to_string();
-        i += 1;
-        if let RequestType::BulkString { data } = &children[i] {
+    let entry_value = match &children[1] {
+        RequestType::BulkString { data } => {
             let entry_value = String::from_utf8_lossy(data).to_string();
-            let entry_value = get_value_type(&entry_value); // Auto-detect type
-            match storage.write().insert_entry(key, entry_value) {
-                Ok(()) => bulkstring!(Some("SUCCESS")),
-                Err(e) => bulkerror!(&e),
+            get_value_type(&entry_value) // Auto-detect type
+        }
+        _ => return bulkerror!("Invalid request type for SET value"),
+    };
+
+    let mut args = Vec::new();
+    for child in &children[2..] {
+        match child {
+            RequestType::BulkString { data } | RequestType::Integer { data } => {
+                args.push(String::from_utf8_lossy(data).to_string())
             }
-        } else {
-            bulkerror!("Invalid request type for SET value")
+            _ => return bulkerror!("Invalid request type for SET option"),
         }
-    } else {
-        bulkerror!("Invalid request type for SET key")
+    }
+    let options = match SetOptions::parse(&args) {
+        Ok(o) => o,
+        Err(e) => return bulkerror!(&e),
+    };
+
+    match storage
+        .write()
+        .insert_with_options(key, entry_value, &options)
+    {
+        Ok((_, old)) if options.get => match old {
+            Some(entry) => RespValue::from(&entry.value),
+            None => null!(),
+        },
+        Ok((true, _)) => bulkstring!(Some("SUCCESS")),
+        Ok((false, _)) => null!(),
+        Err(e) => bulkerror!(&e),
     }
 }
 
@@ -506,7 +893,7 @@ fn handle_set_command(
 fn handle_delete_command(
     children: &[RequestType],
     storage: Arc<parking_lot::RwLock<LockedStorage>>,
-) -> Vec<u8> {
+) -> RespValue {
     if children.is_empty() {
         return bulkerror!("Command missing some arguments");
     }
@@ -530,14 +917,15 @@ fn handle_delete_command(
 /// * `storage` - Storage engine reference
 ///
 /// # Returns
-/// RESP3 response: detailed entry information or null if not found
+/// RESP3 map of the entry's typed fields or null if not found
 ///
 /// # Example Output
-/// "Value: John, Created_at: SystemTime, Last accessed: SystemTime, Access Count: 5, Entry size: 128, Ttl: 3600, Compressed: false"
+/// `{value: John, type: text, created_at: 1700000000000, last_accessed: 1700000042000,
+/// access_count: 5, entry_size: 128, ttl: 3600, compressed: false, version: 7}`
 fn handle_dump_command(
     children: &[RequestType],
     storage: Arc<parking_lot::RwLock<LockedStorage>>,
-) -> Vec<u8> {
+) -> RespValue {
     if children.is_empty() {
         return bulkerror!("Command missing some arguments");
     }
@@ -548,7 +936,7 @@ fn handle_dump_command(
             let entry = storage.read().get_entry(&key);
 
             match entry {
-                Some(e) => bulkstring!(Some(&e.to_string())),
+                Some(e) => map!(&e.to_map()),
                 None => null!(),
             }
         }
@@ -556,6 +944,139 @@ fn handle_dump_command(
     }
 }
 
+/// Handles TYPE command: gets the type of the value stored at a key.
+/// Format: `TYPE key`
+///
+/// # Arguments
+/// * `children` - Command arguments (should contain the key)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 simple string with the type name (e.g `text`, `list`, `stream`),
+/// or `none` if the key doesn't exist
+///
+/// # Example
+/// Input: `*2\r\n$4\r\nTYPE\r\n$4\r\njobs\r\n`
+/// Output: `+list\r\n`
+fn handle_type_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "TYPE") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [key] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+
+    let type_name = storage.read().inspect_entry(key, |e| e.type_name());
+    simplestring!(type_name.unwrap_or("none"))
+}
+
+/// Handles OBJECT command: inspects how an entry is used and stored.
+/// Format: `OBJECT FREQ|IDLETIME|ENCODING key`
+/// Inspecting an entry doesn't count as an access.
+///
+/// # Arguments
+/// * `children` - Command arguments (subcommand and key)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer with the number of accesses (FREQ) or the seconds since the
+/// last access (IDLETIME), bulk string `raw` or `compressed` (ENCODING), or
+/// null if the key doesn't exist
+fn handle_object_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "OBJECT") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [subcommand, key] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+
+    let storage = storage.read();
+    let reply = match subcommand.to_uppercase().as_str() {
+        "FREQ" => storage.inspect_entry(key, |e| integer!(e.access_count)),
+        "IDLETIME" => storage.inspect_entry(key, |e| {
+            let idle = e.last_accessed.elapsed().unwrap_or_default();
+            integer!(idle.as_secs())
+        }),
+        "ENCODING" => storage.inspect_entry(key, |e| bulkstring!(Some(e.encoding()))),
+        _ => return bulkerror!(&format!("Unsupported OBJECT subcommand {subcommand}")),
+    };
+    reply.unwrap_or(null!())
+}
+
+/// Handles MEMORY command: estimates the memory used by an entry.
+/// Format: `MEMORY USAGE key [SAMPLES count]`
+/// SAMPLES is accepted for compatibility, sizes are always exact estimates.
+///
+/// # Arguments
+/// * `children` - Command arguments (USAGE, key and options)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer with the size of the key and value in bytes, or null if
+/// the key doesn't exist
+fn handle_memory_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "MEMORY") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let key = match args.as_slice() {
+        [subcommand, ..] if !subcommand.eq_ignore_ascii_case("USAGE") => {
+            return bulkerror!(&format!("Unsupported MEMORY subcommand {subcommand}"));
+        }
+        [_, key] => key,
+        [_, key, option, _] if option.eq_ignore_ascii_case("SAMPLES") => key,
+        _ => return bulkerror!("Command missing some arguments"),
+    };
+
+    match storage
+        .read()
+        .inspect_entry(key, |e| e.entry_size + key.len())
+    {
+        Some(size) => integer!(size),
+        None => null!(),
+    }
+}
+
+/// Handles INFO command: reports server-wide information.
+/// Format: `INFO [section ...]`
+/// Sections are server, clients, memory, persistence, stats and keyspace,
+/// all of them are returned if none is named.
+///
+/// # Arguments
+/// * `children` - Command arguments (section names)
+/// * `databases` - All logical databases
+/// * `server` - Server-wide state
+///
+/// # Returns
+/// RESP3 bulk string with a `# Section` header and `field:value` lines per
+/// section, or an error for unknown sections
+fn handle_info_command(
+    children: &[RequestType],
+    databases: &Databases,
+    server: &ServerInfo,
+) -> RespValue {
+    let sections = match string_arguments(children, "INFO") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+
+    match server.render(&sections, databases) {
+        Ok(info) => bulkstring!(Some(&info)),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
 /// Handles CONFGET command: retrieves a configuration setting.
 /// Format: `CONFGET key`
 ///
@@ -564,14 +1085,15 @@ fn handle_dump_command(
 /// * `storage` - Storage engine reference
 ///
 /// # Returns
-/// RESP3 response: configuration value or null if key doesn't exist
+/// RESP3 map from the option name to its typed value, or null if key
+/// doesn't exist
 ///
 /// # Supported Keys
 /// - EVICTPOLICY, MAXCAP, GLOBALTTL, COMPRESSION, COMPRESSIONTHRESHOLD
 fn handle_confget_command(
     children: &[RequestType],
     storage: Arc<parking_lot::RwLock<LockedStorage>>,
-) -> Vec<u8> {
+) -> RespValue {
     if children.is_empty() {
         return bulkerror!("Command missing some arguments");
     }
@@ -582,7 +1104,7 @@ fn handle_confget_command(
             let entry = storage.read().get_config_entry(&key);
 
             match entry {
-                Some(e) => bulkstring!(Some(&e.to_string())),
+                Some(e) => map!(&[(e.name(), e.value())]),
                 None => null!(),
             }
         }
@@ -607,7 +1129,7 @@ fn handle_confget_command(
 fn handle_confset_command(
     children: &[RequestType],
     storage: Arc<parking_lot::RwLock<LockedStorage>>,
-) -> Vec<u8> {
+) -> RespValue {
     if children.len() < 2 {
         return bulkerror!("Command missing some arguments");
     }
@@ -644,11 +1166,12 @@ fn handle_confset_command(
 /// * `storage` - Storage engine reference
 ///
 /// # Returns
-/// RESP3 integer response: TTL in seconds, or null if key doesn't exist
+/// RESP3 integer response: remaining TTL in seconds (rounded up), -1 if the key
+/// never expires, or null if key doesn't exist
 fn handle_getttl_command(
     children: &[RequestType],
     storage: Arc<parking_lot::RwLock<LockedStorage>>,
-) -> Vec<u8> {
+) -> RespValue {
     if children.is_empty() {
         return bulkerror!("Command missing some arguments");
     }
@@ -658,13 +1181,47 @@ fn handle_getttl_command(
             let key = String::from_utf8_lossy(data).to_string();
             let ttl = storage.read().time_to_live(&key);
             match ttl {
-                Some(ttl) => integer!(ttl.as_secs() as i64),
+                Some(Some(ttl)) => integer!(ttl.as_millis().div_ceil(1000) as i64),
+                Some(None) => integer!(-1),
                 None => null!(),
             }
         }
         _ => bulkerror!("Invalid request type for GETTTL key"),
     }
 }
+
+/// Handles PTTL command: retrieves the remaining TTL for a key in milliseconds.
+/// Format: `PTTL key`
+///
+/// # Arguments
+/// * `children` - Command arguments (should contain the key)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer response: remaining TTL in milliseconds, -1 if the key
+/// never expires, or null if key doesn't exist
+fn handle_pttl_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    if children.is_empty() {
+        return bulkerror!("Command missing some arguments");
+    }
+
+    match &children[0] {
+        RequestType::BulkString { data } => {
+            let key = String::from_utf8_lossy(data).to_string();
+            let ttl = storage.read().time_to_live(&key);
+            match ttl {
+                Some(Some(ttl)) => integer!(ttl.as_millis() as i64),
+                Some(None) => integer!(-1),
+                None => null!(),
+            }
+        }
+        _ => bulkerror!("Invalid request type for PTTL key"),
+    }
+}
+
 /// Handles SETWTTL command: stores a key-value pair with specific TTL.
 /// Format: `SETWTTL key value ttl_seconds`
 ///
@@ -681,7 +1238,7 @@ fn handle_getttl_command(
 fn handle_setwttl_command(
     children: &[RequestType],
     storage: Arc<parking_lot::RwLock<LockedStorage>>,
-) -> Vec<u8> {
+) -> RespValue {
     if children.len() < 3 {
         return bulkerror!("Command missing some arguments");
     }
@@ -738,7 +1295,7 @@ fn handle_setwttl_command(
 fn handle_expire_command(
     children: &[RequestType],
     storage: Arc<parking_lot::RwLock<LockedStorage>>,
-) -> Vec<u8> {
+) -> RespValue {
     if children.len() < 2 {
         return bulkerror!("Command missing some arguments");
     }
@@ -761,9 +1318,7 @@ fn handle_expire_command(
                     match storage.write().extend_ttl(&key, addition_ttl) {
                         Ok(()) => bulkstring!(Some("SUCCESS")),
                         Err(e) => bulkerror!(&e),
-                    };
-
-                    bulkstring!(Some("SUCCESS"))
+                    }
                 }
                 _ => bulkerror!("Invalid request type for EXPIRE value"),
             }
@@ -772,6 +1327,141 @@ fn handle_expire_command(
     }
 }
 
+/// Sets the expiry deadline of a key from a `key time` argument pair.
+/// Shared by EXPIREAT, PEXPIRE and PEXPIREAT which only differ in how the
+/// time argument maps to a deadline.
+///
+/// # Arguments
+/// * `children` - Command arguments (key and time)
+/// * `storage` - Storage engine reference
+/// * `command` - Command name used in error messages
+/// * `deadline` - Maps the time argument to a deadline, `None` on overflow
+///
+/// # Returns
+/// RESP3 boolean response: #t if the key exists, #f otherwise
+fn handle_expire_deadline(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+    command: &str,
+    deadline: impl Fn(i64) -> Option<SystemTime>,
+) -> RespValue {
+    if children.len() < 2 {
+        return bulkerror!("Command missing some arguments");
+    }
+
+    let key = match &children[0] {
+        RequestType::BulkString { data } => String::from_utf8_lossy(data).to_string(),
+        _ => return bulkerror!(&format!("Invalid request type for {command} key")),
+    };
+
+    let deadline = match &children[1] {
+        RequestType::BulkString { data } | RequestType::Integer { data } => {
+            match String::from_utf8_lossy(data)
+                .parse::<i64>()
+                .ok()
+                .and_then(deadline)
+            {
+                Some(deadline) => deadline,
+                None => return bulkerror!(&format!("Invalid {command} time")),
+            }
+        }
+        _ => return bulkerror!(&format!("Invalid request type for {command} time")),
+    };
+
+    boolean!(storage.write().expire_at(&key, deadline))
+}
+
+/// Handles EXPIREAT command: expires a key at an absolute unix time.
+/// Format: `EXPIREAT key unix_seconds`
+/// A time in the past deletes the key.
+///
+/// # Arguments
+/// * `children` - Command arguments (key and unix time in seconds)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 boolean response: #t if the key exists, #f otherwise
+fn handle_expireat_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    handle_expire_deadline(children, storage, "EXPIREAT", |secs| {
+        UNIX_EPOCH.checked_add(Duration::from_secs(secs.max(0) as u64))
+    })
+}
+
+/// Handles PEXPIRE command: sets the TTL of a key in milliseconds.
+/// Format: `PEXPIRE key milliseconds`
+/// Unlike EXPIRE the TTL is replaced rather than extended, and a TTL that
+/// is not positive deletes the key.
+///
+/// # Arguments
+/// * `children` - Command arguments (key and TTL in milliseconds)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 boolean response: #t if the key exists, #f otherwise
+///
+/// # Example
+/// `PEXPIRE session 1500` expires the session in 1.5 seconds
+fn handle_pexpire_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    handle_expire_deadline(children, storage, "PEXPIRE", |millis| {
+        if millis <= 0 {
+            return Some(UNIX_EPOCH);
+        }
+        SystemTime::now().checked_add(Duration::from_millis(millis as u64))
+    })
+}
+
+/// Handles PEXPIREAT command: expires a key at an absolute unix time.
+/// Format: `PEXPIREAT key unix_milliseconds`
+/// A time in the past deletes the key.
+///
+/// # Arguments
+/// * `children` - Command arguments (key and unix time in milliseconds)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 boolean response: #t if the key exists, #f otherwise
+fn handle_pexpireat_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    handle_expire_deadline(children, storage, "PEXPIREAT", |millis| {
+        UNIX_EPOCH.checked_add(Duration::from_millis(millis.max(0) as u64))
+    })
+}
+
+/// Handles PERSIST command: removes the expiry of a key so it never expires.
+/// Format: `PERSIST key`
+///
+/// # Arguments
+/// * `children` - Command arguments (should contain the key)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 boolean response: #t if an expiry was removed, #f if the key does
+/// not exist or never expires
+fn handle_persist_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    if children.is_empty() {
+        return bulkerror!("Command missing some arguments");
+    }
+
+    match &children[0] {
+        RequestType::BulkString { data } => {
+            let key = String::from_utf8_lossy(data).to_string();
+            boolean!(storage.write().persist(&key))
+        }
+        _ => bulkerror!("Invalid request type for PERSIST key"),
+    }
+}
+
 /// Handles DELETELIST command: removes multiple keys in one operation.
 /// Format: `DELETELIST key1 key2 key3 ...`
 /// More efficient than individual DELETE commands.
@@ -785,7 +1475,7 @@ fn handle_expire_command(
 fn handle_deletelist_command(
     children: &[RequestType],
     storage: Arc<parking_lot::RwLock<LockedStorage>>,
-) -> Vec<u8> {
+) -> RespValue {
     if children.is_empty() {
         return null!();
     }
@@ -818,7 +1508,7 @@ fn handle_deletelist_command(
 fn handle_getlist_command(
     children: &[RequestType],
     storage: Arc<parking_lot::RwLock<LockedStorage>>,
-) -> Vec<u8> {
+) -> RespValue {
     if children.is_empty() {
         return null!();
     }
@@ -838,36 +1528,129 @@ fn handle_getlist_command(
     batch_getlist_entries!(&entries)
 }
 
-/// Converts a RESP3 request type to our internal StorageValue format.
-/// This handles the translation between the protocol layer and storage layer.
+/// Handles KEYS command with a pattern: lists the keys matching a glob pattern.
+/// Format: `KEYS pattern`
+/// Supports `*`, `?`, `[abc]` and `\` escaping. The single command `KEYS`
+/// lists every key.
 ///
 /// # Arguments
-/// * `req` - The RESP3 request type to convert
+/// * `children` - Command arguments (should contain the pattern)
+/// * `storage` - Storage engine reference
 ///
 /// # Returns
-/// `Result<StorageValue, String>` - The converted value or an error message
+/// RESP3 array response of the matching keys, or null if none match
 ///
 /// # Example
-/// Input: `*3\r\n$7\r\nSETLIST\r\n$5\r\nitems\r\n*3\r\n$5\r\napple\r\n$6\r\nbanana\r\n$6\r\norange\r\n`
-/// Stores: "items" -> ["apple", "banana", "orange"]
-fn handle_setlist_command(
+/// `KEYS user:42:*` returns `[user:42:name, user:42:age]`
+fn handle_keys_command(
     children: &[RequestType],
     storage: Arc<parking_lot::RwLock<LockedStorage>>,
-) -> Vec<u8> {
+) -> RespValue {
     if children.is_empty() {
-        return null!();
+        return bulkerror!("Command missing some arguments");
     }
 
-    let key = match &children[0] {
-        RequestType::BulkString { data } => match String::from_utf8(data.to_vec()) {
-            Ok(v) => v,
-            Err(e) => return bulkerror!(&format!("Setlist key has invalid characters: {e}")),
-        },
-
-        _ => return bulkerror!("Unsupported request_type for setlist key"),
-    };
-
-    let vals = match request_type_to_storage_value(&children[1]) {
+    match &children[0] {
+        RequestType::BulkString { data } => {
+            let pattern = String::from_utf8_lossy(data).to_string();
+            let keys = storage.read().get_keys_matching(&pattern);
+            if keys.is_empty() {
+                return null!();
+            }
+            array!(&keys)
+        }
+        _ => bulkerror!("Invalid request type for KEYS pattern"),
+    }
+}
+
+/// Handles GETMATCH command: retrieves all keys matching a glob pattern.
+/// Format: `GETMATCH pattern`
+/// Returns the same format as GETLIST.
+///
+/// # Arguments
+/// * `children` - Command arguments (should contain the pattern)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 array response: [[key1, value1], [key2, value2]] or null if none match
+fn handle_getmatch_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    if children.is_empty() {
+        return bulkerror!("Command missing some arguments");
+    }
+
+    match &children[0] {
+        RequestType::BulkString { data } => {
+            let pattern = String::from_utf8_lossy(data).to_string();
+            let entries = storage.read().get_entries_matching(&pattern);
+            batch_getlist_entries!(&entries)
+        }
+        _ => bulkerror!("Invalid request type for GETMATCH pattern"),
+    }
+}
+
+/// Handles DELETEMATCH command: removes all keys matching a glob pattern.
+/// Format: `DELETEMATCH pattern`
+///
+/// # Arguments
+/// * `children` - Command arguments (should contain the pattern)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer response: the number of removed keys
+///
+/// # Example
+/// `DELETEMATCH session:*` removes every session and returns how many there were
+fn handle_deletematch_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    if children.is_empty() {
+        return bulkerror!("Command missing some arguments");
+    }
+
+    match &children[0] {
+        RequestType::BulkString { data } => {
+            let pattern = String::from_utf8_lossy(data).to_string();
+            let removed = storage.write().remove_matching(&pattern);
+            integer!(removed)
+        }
+        _ => bulkerror!("Invalid request type for DELETEMATCH pattern"),
+    }
+}
+
+/// Converts a RESP3 request type to our internal StorageValue format.
+/// This handles the translation between the protocol layer and storage layer.
+///
+/// # Arguments
+/// * `req` - The RESP3 request type to convert
+///
+/// # Returns
+/// `Result<StorageValue, String>` - The converted value or an error message
+///
+/// # Example
+/// Input: `*3\r\n$7\r\nSETLIST\r\n$5\r\nitems\r\n*3\r\n$5\r\napple\r\n$6\r\nbanana\r\n$6\r\norange\r\n`
+/// Stores: "items" -> ["apple", "banana", "orange"]
+fn handle_setlist_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    if children.is_empty() {
+        return null!();
+    }
+
+    let key = match &children[0] {
+        RequestType::BulkString { data } => match String::from_utf8(data.to_vec()) {
+            Ok(v) => v,
+            Err(e) => return bulkerror!(&format!("Setlist key has invalid characters: {e}")),
+        },
+
+        _ => return bulkerror!("Unsupported request_type for setlist key"),
+    };
+
+    let vals = match request_type_to_storage_value(&children[1]) {
         Ok(v) => v,
         Err(e) => return bulkerror!(&e),
     };
@@ -897,7 +1680,7 @@ fn handle_setlist_command(
 fn handle_setmap_command(
     children: &[RequestType],
     storage: Arc<parking_lot::RwLock<LockedStorage>>,
-) -> Vec<u8> {
+) -> RespValue {
     if children.is_empty() {
         return null!();
     }
@@ -957,21 +1740,22 @@ fn handle_setmap_command(
 
 /// Handles INCR command: increments an integer value by 1.
 /// Format: `INCR key`
-/// Only works if the existing value is an integer.
+/// A missing key is created at 0 before being incremented.
 ///
 /// # Arguments
 /// * `children` - Command arguments (should contain the key)
 /// * `storage` - Storage engine reference
 ///
 /// # Returns
-/// RESP3 response: "SUCCESS" or error if key doesn't exist/isn't an integer
+/// RESP3 integer response with the new value, or error if the value isn't
+/// an integer or would overflow
 ///
 /// # Example
 /// If "counter" = 5, then `INCR counter` makes it 6
 fn handle_incr_command(
     children: &[RequestType],
     storage: Arc<parking_lot::RwLock<LockedStorage>>,
-) -> Vec<u8> {
+) -> RespValue {
     if children.is_empty() {
         return bulkerror!("Command missing some arguments");
     }
@@ -979,8 +1763,10 @@ fn handle_incr_command(
     match &children[0] {
         RequestType::BulkString { data } => {
             let key = String::from_utf8_lossy(data);
-            storage.write().increment_entry(&key);
-            bulkstring!(Some("SUCCESS"))
+            match storage.write().increment_entry(&key) {
+                Ok(n) => integer!(n),
+                Err(e) => bulkerror!(&e),
+            }
         }
         _ => bulkerror!("Invalid INCR key type"),
     }
@@ -988,21 +1774,22 @@ fn handle_incr_command(
 
 /// Handles DECR command: decrements an integer value by 1.
 /// Format: `DECR key`
-/// Only works if the existing value is an integer.
+/// A missing key is created at 0 before being decremented.
 ///
 /// # Arguments
 /// * `children` - Command arguments (should contain the key)
 /// * `storage` - Storage engine reference
 ///
 /// # Returns
-/// RESP3 response: "SUCCESS" or error if key doesn't exist/isn't an integer
+/// RESP3 integer response with the new value, or error if the value isn't
+/// an integer or would overflow
 ///
 /// # Example
 /// If "counter" = 5, then `DECR counter` makes it 4
 fn handle_decr_command(
     children: &[RequestType],
     storage: Arc<parking_lot::RwLock<LockedStorage>>,
-) -> Vec<u8> {
+) -> RespValue {
     if children.is_empty() {
         return bulkerror!("Command missing some arguments");
     }
@@ -1010,101 +1797,2530 @@ fn handle_decr_command(
     match &children[0] {
         RequestType::BulkString { data } => {
             let key = String::from_utf8_lossy(data);
-            storage.write().decrement_entry(&key);
-            bulkstring!(Some("SUCCESS"))
+            match storage.write().decrement_entry(&key) {
+                Ok(n) => integer!(n),
+                Err(e) => bulkerror!(&e),
+            }
         }
         _ => bulkerror!("Invalid DECR key type"),
     }
 }
 
-/// Handles RENAME command: renames an existing key.
-/// Format: `RENAME old_key new_key`
-/// The old key is removed and the value is stored under the new key.
+/// Extracts the key and the textual amount of `INCRBY`-like commands.
+///
+/// # Returns
+/// `(key, amount)` or the RESP3 error to reply with
+fn key_and_amount(children: &[RequestType], command: &str) -> Result<(String, String), RespValue> {
+    if children.len() < 2 {
+        return Err(bulkerror!("Command missing some arguments"));
+    }
+
+    let key = match &children[0] {
+        RequestType::BulkString { data } => String::from_utf8_lossy(data).to_string(),
+        _ => return Err(bulkerror!(&format!("Invalid {command} key type"))),
+    };
+
+    match &children[1] {
+        RequestType::BulkString { data }
+        | RequestType::Integer { data }
+        | RequestType::BigNumber { data } => {
+            Ok((key, String::from_utf8_lossy(data).trim().to_string()))
+        }
+        _ => Err(bulkerror!(&format!("Invalid {command} amount type"))),
+    }
+}
+
+/// Handles INCRBY and DECRBY commands: adds or subtracts an amount to an
+/// integer value.
+/// Format: `INCRBY key amount` / `DECRBY key amount`
+/// A missing key is created at 0 first.
 ///
 /// # Arguments
-/// * `children` - Command arguments (old key and new key)
+/// * `children` - Command arguments (key and amount)
 /// * `storage` - Storage engine reference
+/// * `negate` - Whether to subtract the amount (DECRBY)
 ///
 /// # Returns
-/// RESP3 response: "SUCCESS" (always succeeds, even if old key doesn't exist)
+/// RESP3 integer response with the new value, or error if the value isn't
+/// an integer or would overflow
 ///
 /// # Example
-/// `RENAME user:123 customer:123` moves the value from user:123 to customer:123
-fn handle_rename_command(
+/// If "counter" = 5, then `INCRBY counter 10` makes it 15
+fn handle_incrby_command(
     children: &[RequestType],
     storage: Arc<parking_lot::RwLock<LockedStorage>>,
-) -> Vec<u8> {
-    if children.len() < 2 {
+    negate: bool,
+) -> RespValue {
+    let command = if negate { "DECRBY" } else { "INCRBY" };
+    let (key, amount) = match key_and_amount(children, command) {
+        Ok(v) => v,
+        Err(e) => return e,
+    };
+
+    let delta = match amount.parse::<i64>() {
+        Ok(n) if negate => n.checked_neg(),
+        Ok(n) => Some(n),
+        Err(_) => return bulkerror!(&format!("Invalid {command} amount")),
+    };
+    let Some(delta) = delta else {
+        return bulkerror!("Increment or decrement would overflow");
+    };
+
+    match storage.write().increment_by(&key, delta) {
+        Ok(n) => integer!(n),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles INCRBYFLOAT command: adds a floating point amount to a number.
+/// Format: `INCRBYFLOAT key amount`
+/// A missing key is created at 0 first and integers become floats.
+///
+/// # Arguments
+/// * `children` - Command arguments (key and amount)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 double response with the new value, or error if the value isn't
+/// a number or the result isn't finite
+///
+/// # Example
+/// If "price" = 10, then `INCRBYFLOAT price -0.25` makes it 9.75
+fn handle_incrbyfloat_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let (key, amount) = match key_and_amount(children, "INCRBYFLOAT") {
+        Ok(v) => v,
+        Err(e) => return e,
+    };
+
+    let delta = match amount.parse::<f64>() {
+        Ok(f) if f.is_finite() => f,
+        _ => return bulkerror!("Invalid INCRBYFLOAT amount"),
+    };
+
+    match storage.write().increment_by_float(&key, delta) {
+        Ok(f) => RespValue::Double(f),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Converts the request arguments to strings.
+/// Integers are accepted as well, as clients often send offsets that way.
+///
+/// # Returns
+/// The arguments or the RESP3 error to reply with
+fn string_arguments(children: &[RequestType], command: &str) -> Result<Vec<String>, RespValue> {
+    children
+        .iter()
+        .map(|child| match child {
+            RequestType::BulkString { data } | RequestType::Integer { data } => {
+                Ok(String::from_utf8_lossy(data).to_string())
+            }
+            _ => Err(bulkerror!(&format!(
+                "Invalid request type for {command} argument"
+            ))),
+        })
+        .collect()
+}
+
+/// Handles APPEND command: appends text to a string value.
+/// Format: `APPEND key value`
+/// A missing key is created with the value.
+///
+/// # Arguments
+/// * `children` - Command arguments (key and value)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer response with the new length in bytes, or error if the
+/// value isn't a string
+///
+/// # Example
+/// If "log" = "a;", then `APPEND log b;` makes it "a;b;" and returns 4
+fn handle_append_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "APPEND") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [key, value] = args.as_slice() else {
         return bulkerror!("Command missing some arguments");
+    };
+
+    match storage.write().append(key, value) {
+        Ok(len) => integer!(len),
+        Err(e) => bulkerror!(&e),
     }
+}
 
-    let mut i = 0;
-    match &children[i] {
-        RequestType::BulkString { data } => {
-            let old_key = String::from_utf8_lossy(data);
-            i += 1;
-            match &children[i] {
-                RequestType::BulkString { data } => {
-                    let new_key = String::from_utf8_lossy(data);
+/// Handles STRLEN command: gets the length of a string value.
+/// Format: `STRLEN key`
+///
+/// # Arguments
+/// * `children` - Command arguments (should contain the key)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer response with the length in bytes (0 if the key doesn't
+/// exist), or error if the value isn't a string
+fn handle_strlen_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    if children.is_empty() {
+        return bulkerror!("Command missing some arguments");
+    }
 
-                    storage.write().rename_entry(&old_key, &new_key);
-                    bulkstring!(Some("SUCCESS"))
-                }
-                _ => bulkerror!("Invalid RENAME new_key type"),
+    match &children[0] {
+        RequestType::BulkString { data } => {
+            let key = String::from_utf8_lossy(data);
+            match storage.read().string_length(&key) {
+                Ok(len) => integer!(len),
+                Err(e) => bulkerror!(&e),
             }
         }
-        _ => bulkerror!("Invalid RENAME old_key type"),
+        _ => bulkerror!("Invalid request type for STRLEN key"),
     }
 }
 
-/// Handles EVICTNOW command: evicts entries based on the current eviction policy
-/// This command has an optional integer value that represents the number of entries
-/// to evict. The fallback value is 0, if the value is not specified.
-/// Format: `EVICTNOW`
-///         `EVICTNOW 10`
+/// Handles GETRANGE command: gets a substring of a string value.
+/// Format: `GETRANGE key start end`
+/// Offsets are inclusive byte offsets, negative ones count from the end.
 ///
 /// # Arguments
-/// * `children` - Command arguments (count). Children can be empty
+/// * `children` - Command arguments (key, start and end)
 /// * `storage` - Storage engine reference
 ///
 /// # Returns
-/// RESP3 response: "SUCCESS" (always succeeds)
+/// RESP3 bulk string response with the substring (empty if the key doesn't
+/// exist), or error if the value isn't a string
 ///
-fn handle_evictnow_command(
+/// # Example
+/// If "greeting" = "Hello World", then `GETRANGE greeting -5 -1` returns "World"
+fn handle_getrange_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "GETRANGE") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [key, start, end] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+    let (Ok(start), Ok(end)) = (start.parse::<i64>(), end.parse::<i64>()) else {
+        return bulkerror!("Invalid GETRANGE offset");
+    };
+
+    match storage.read().get_range(key, start, end) {
+        // The range may not be valid UTF-8
+        Ok(bytes) => RespValue::BulkString(bytes),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles SETRANGE command: overwrites part of a string value.
+/// Format: `SETRANGE key offset value`
+/// The string is padded with zero bytes up to `offset` if needed and a
+/// missing key is created.
+///
+/// # Arguments
+/// * `children` - Command arguments (key, offset and value)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer response with the new length in bytes, or error if the
+/// value isn't a string or the offset is invalid
+///
+/// # Example
+/// If "greeting" = "Hello World", then `SETRANGE greeting 6 Redis` makes it "Hello Redis"
+fn handle_setrange_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "SETRANGE") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [key, offset, value] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+    let Ok(offset) = offset.parse::<usize>() else {
+        return bulkerror!("Invalid SETRANGE offset");
+    };
+
+    match storage.write().set_range(key, offset, value) {
+        Ok(len) => integer!(len),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles GETDEL command: gets a value and deletes its key.
+/// Format: `GETDEL key`
+///
+/// # Arguments
+/// * `children` - Command arguments (should contain the key)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 response with the removed value, or null if the key doesn't exist
+fn handle_getdel_command(
     children: &[RequestType],
     storage: Arc<parking_lot::RwLock<LockedStorage>>,
-) -> Vec<u8> {
+) -> RespValue {
     if children.is_empty() {
-        storage.write().evict_entries(0);
-        return bulkstring!(Some("SUCCESS"));
+        return bulkerror!("Command missing some arguments");
     }
 
     match &children[0] {
-        RequestType::Integer { data } => {
-            let int_str = match String::from_utf8(data.to_vec()) {
-                Ok(s) => s,
-                Err(e) => return bulkerror!(&e.to_string()),
-            };
-            let int = match int_str.parse::<usize>() {
-                Ok(i) => i,
-                Err(e) => return bulkerror!(&e.to_string()),
-            };
-            storage.write().evict_entries(int);
-            bulkstring!(Some("SUCCESS"))
+        RequestType::BulkString { data } => {
+            let key = String::from_utf8_lossy(data);
+            match storage.write().get_and_remove(&key) {
+                Some(entry) => RespValue::from(&entry.value),
+                None => null!(),
+            }
         }
-        _ => bulkerror!("Invalid count type for evictnow command"),
+        _ => bulkerror!("Invalid request type for GETDEL key"),
     }
 }
 
-/// Processes array-based commands by routing to appropriate handlers.
-/// This is called for all commands that come as RESP3 arrays.
+/// Handles GETEX command: gets a value and optionally changes its expiry.
+/// Format: `GETEX key [EX seconds | PX milliseconds | EXAT unix-seconds |
+/// PXAT unix-milliseconds | PERSIST]`
 ///
 /// # Arguments
-/// * `children` - Array of request elements (first is command, rest are args)
+/// * `children` - Command arguments (key and expiry option)
 /// * `storage` - Storage engine reference
 ///
 /// # Returns
-/// RESP3-encoded response bytes
+/// RESP3 response with the value, or null if the key doesn't exist
+///
+/// # Example
+/// `GETEX session EX 600` reads the session and keeps it for 10 more minutes
+fn handle_getex_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "GETEX") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let Some((key, options)) = args.split_first() else {
+        return bulkerror!("Command missing some arguments");
+    };
+
+    // `None` removes the expiry, SetExpiry::Default leaves it untouched
+    let expiry = match options {
+        [option] if option.eq_ignore_ascii_case("PERSIST") => None,
+        _ => match SetOptions::parse(options) {
+            Ok(SetOptions {
+                condition: SetCondition::Always,
+                expiry,
+                get: false,
+            }) if expiry != SetExpiry::Keep => Some(expiry),
+            Ok(_) => return bulkerror!("Invalid GETEX option"),
+            Err(e) => return bulkerror!(&e),
+        },
+    };
+
+    // Read and update under a single lock
+    let mut storage = storage.write();
+    let Some(entry) = storage.get_entry(key) else {
+        return null!();
+    };
+    match expiry {
+        None => {
+            storage.persist(key);
+        }
+        Some(SetExpiry::In(ttl)) => match SystemTime::now().checked_add(ttl) {
+            Some(deadline) => {
+                storage.expire_at(key, deadline);
+            }
+            None => return bulkerror!("Invalid expire time"),
+        },
+        Some(SetExpiry::At(deadline)) => {
+            storage.expire_at(key, deadline);
+        }
+        Some(SetExpiry::Default | SetExpiry::Keep) => {}
+    }
+
+    RespValue::from(&entry.value)
+}
+
+/// Parses a bit value argument, which must be 0 or 1.
+fn parse_bit(arg: &str) -> Option<bool> {
+    match arg {
+        "0" => Some(false),
+        "1" => Some(true),
+        _ => None,
+    }
+}
+
+/// Handles SETBIT command: sets or clears a single bit of a bitmap.
+/// Format: `SETBIT key offset 0|1`
+/// The bitmap grows with zero bytes as needed and a missing key is created.
+///
+/// # Arguments
+/// * `children` - Command arguments (key, bit offset and bit)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer response with the previous bit, or error if the value isn't
+/// a string
+///
+/// # Example
+/// `SETBIT active:2024-01-01 42 1` marks user 42 as active on that day
+fn handle_setbit_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "SETBIT") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [key, offset, bit] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+    let Ok(offset) = offset.parse::<usize>() else {
+        return bulkerror!("Invalid SETBIT offset");
+    };
+    let Some(bit) = parse_bit(bit) else {
+        return bulkerror!("Invalid SETBIT bit, expected 0 or 1");
+    };
+
+    match storage.write().set_bit(key, offset, bit) {
+        Ok(old) => integer!(old as u8),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles GETBIT command: gets a single bit of a bitmap.
+/// Format: `GETBIT key offset`
+///
+/// # Arguments
+/// * `children` - Command arguments (key and bit offset)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer response with the bit (0 past the end or for missing keys),
+/// or error if the value isn't a string
+fn handle_getbit_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "GETBIT") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [key, offset] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+    let Ok(offset) = offset.parse::<usize>() else {
+        return bulkerror!("Invalid GETBIT offset");
+    };
+
+    match storage.read().get_bit(key, offset) {
+        Ok(bit) => integer!(bit as u8),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles BITCOUNT command: counts the set bits of a bitmap.
+/// Format: `BITCOUNT key [start end]`
+/// The optional range is in bytes, negative offsets count from the end.
+///
+/// # Arguments
+/// * `children` - Command arguments (key and optional byte range)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer response with the number of set bits, or error if the
+/// value isn't a string
+///
+/// # Example
+/// `BITCOUNT active:2024-01-01` returns the number of active users that day
+fn handle_bitcount_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "BITCOUNT") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let (key, range) = match args.as_slice() {
+        [key] => (key, None),
+        [key, start, end] => match (start.parse::<i64>(), end.parse::<i64>()) {
+            (Ok(start), Ok(end)) => (key, Some((start, end))),
+            _ => return bulkerror!("Invalid BITCOUNT range"),
+        },
+        [] => return bulkerror!("Command missing some arguments"),
+        _ => return bulkerror!("BITCOUNT takes a key and an optional start and end"),
+    };
+
+    match storage.read().bit_count(key, range) {
+        Ok(count) => integer!(count),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles BITOP command: combines bitmaps into a destination key.
+/// Format: `BITOP AND|OR|XOR|NOT destkey key [key ...]`
+/// Shorter values are zero padded, NOT takes a single source key.
+///
+/// # Arguments
+/// * `children` - Command arguments (operation, destination and source keys)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer response with the length of the result in bytes, or error
+/// if a value isn't a string
+///
+/// # Example
+/// `BITOP AND active:both active:mon active:tue` stores the users active on both days
+fn handle_bitop_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "BITOP") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [op, destination, keys @ ..] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+    let op = match BitOp::parse(op) {
+        Ok(op) => op,
+        Err(e) => return bulkerror!(&e),
+    };
+
+    match storage.write().bit_op(op, destination, keys) {
+        Ok(len) => integer!(len),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles BITPOS command: finds the first bit set to 0 or 1 in a bitmap.
+/// Format: `BITPOS key 0|1 [start [end]]`
+/// The optional range is in bytes, negative offsets count from the end.
+///
+/// # Arguments
+/// * `children` - Command arguments (key, bit and optional byte range)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer response with the bit offset or -1 if not found, or error
+/// if the value isn't a string
+fn handle_bitpos_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "BITPOS") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [key, bit, range @ ..] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+    let Some(bit) = parse_bit(bit) else {
+        return bulkerror!("Invalid BITPOS bit, expected 0 or 1");
+    };
+
+    let range: Result<Vec<i64>, _> = range.iter().map(|offset| offset.parse::<i64>()).collect();
+    let (start, end) = match range.as_deref() {
+        Ok([]) => (0, None),
+        Ok([start]) => (*start, None),
+        Ok([start, end]) => (*start, Some(*end)),
+        _ => return bulkerror!("Invalid BITPOS range"),
+    };
+
+    match storage.read().bit_pos(key, bit, start, end) {
+        Ok(pos) => integer!(pos),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles PFADD command: adds elements to a HyperLogLog sketch.
+/// Format: `PFADD key [element ...]`
+///
+/// # Arguments
+/// * `children` - Command arguments (key and elements)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer response, 1 if the key was created or its estimate may
+/// have changed and 0 otherwise, or error if the value isn't a sketch
+fn handle_pfadd_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "PFADD") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [key, elements @ ..] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+
+    match storage.write().pf_add(key, elements) {
+        Ok(changed) => integer!(changed as u8),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles PFCOUNT command: estimates the number of distinct elements.
+/// Format: `PFCOUNT key [key ...]`
+/// With several keys the cardinality of their union is estimated.
+///
+/// # Arguments
+/// * `children` - Command arguments (the keys)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer response with the estimate, or error if a value isn't a
+/// sketch
+fn handle_pfcount_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let keys = match string_arguments(children, "PFCOUNT") {
+        Ok(keys) => keys,
+        Err(e) => return e,
+    };
+    if keys.is_empty() {
+        return bulkerror!("Command missing some arguments");
+    }
+
+    match storage.read().pf_count(&keys) {
+        Ok(count) => integer!(count),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles PFMERGE command: merges sketches into a destination key.
+/// Format: `PFMERGE destkey [sourcekey ...]`
+/// An existing destination sketch is part of the union.
+///
+/// # Arguments
+/// * `children` - Command arguments (destination and source keys)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 "SUCCESS" response, or error if a value isn't a sketch
+fn handle_pfmerge_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "PFMERGE") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [destination, sources @ ..] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+
+    match storage.write().pf_merge(destination, sources) {
+        Ok(()) => bulkstring!(Some("SUCCESS")),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Encodes per-item filter results: a single integer for the single-item
+/// commands, an array of integers for the multi-item ones.
+fn filter_results(results: Vec<bool>, multi: bool) -> RespValue {
+    if !multi {
+        return integer!(results[0] as u8);
+    }
+
+    let results = results
+        .into_iter()
+        .map(|result| StorageValue::Int(result as i64))
+        .collect();
+    RespValue::from(&StorageValue::List(results))
+}
+
+/// Handles BF.RESERVE command: creates an empty Bloom filter.
+/// Format: `BF.RESERVE key error_rate capacity`
+///
+/// # Arguments
+/// * `children` - Command arguments (key, false positive rate and capacity)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 "SUCCESS" response, or error if the key exists or a parameter is
+/// invalid
+fn handle_bfreserve_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "BF.RESERVE") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [key, error_rate, capacity] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+    let Ok(error_rate) = error_rate.parse::<f64>() else {
+        return bulkerror!("Invalid error rate, expected a number");
+    };
+    let Ok(capacity) = capacity.parse::<usize>() else {
+        return bulkerror!("Invalid capacity, expected a positive integer");
+    };
+
+    match storage.write().bf_reserve(key, error_rate, capacity) {
+        Ok(()) => bulkstring!(Some("SUCCESS")),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles BF.ADD and BF.MADD commands: adds items to a Bloom filter,
+/// creating it with default parameters if needed.
+/// Format: `BF.ADD key item` or `BF.MADD key item [item ...]`
+///
+/// # Arguments
+/// * `children` - Command arguments (key and items)
+/// * `storage` - Storage engine reference
+/// * `multi` - Whether several items are accepted (BF.MADD)
+///
+/// # Returns
+/// RESP3 integer response (an array of them for BF.MADD), 1 if the item
+/// was added and 0 if it was possibly added before
+fn handle_bfadd_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+    multi: bool,
+) -> RespValue {
+    let args = match string_arguments(children, "BF.ADD") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let items = match args.as_slice() {
+        [_, item] => std::slice::from_ref(item),
+        [_, items @ ..] if multi && !items.is_empty() => items,
+        _ => return bulkerror!("Command missing some arguments"),
+    };
+
+    match storage.write().bf_add(&args[0], items) {
+        Ok(added) => filter_results(added, multi),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles BF.EXISTS and BF.MEXISTS commands: checks whether items were
+/// possibly added to a Bloom filter.
+/// Format: `BF.EXISTS key item` or `BF.MEXISTS key item [item ...]`
+///
+/// # Arguments
+/// * `children` - Command arguments (key and items)
+/// * `storage` - Storage engine reference
+/// * `multi` - Whether several items are accepted (BF.MEXISTS)
+///
+/// # Returns
+/// RESP3 integer response (an array of them for BF.MEXISTS), 1 if the item
+/// was possibly added and 0 if it definitely was not
+fn handle_bfexists_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+    multi: bool,
+) -> RespValue {
+    let args = match string_arguments(children, "BF.EXISTS") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let items = match args.as_slice() {
+        [_, item] => std::slice::from_ref(item),
+        [_, items @ ..] if multi && !items.is_empty() => items,
+        _ => return bulkerror!("Command missing some arguments"),
+    };
+
+    match storage.read().bf_exists(&args[0], items) {
+        Ok(found) => filter_results(found, multi),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles CF.RESERVE command: creates an empty cuckoo filter.
+/// Format: `CF.RESERVE key capacity`
+///
+/// # Arguments
+/// * `children` - Command arguments (key and capacity)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 "SUCCESS" response, or error if the key exists or the capacity is
+/// invalid
+fn handle_cfreserve_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "CF.RESERVE") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [key, capacity] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+    let Ok(capacity) = capacity.parse::<usize>() else {
+        return bulkerror!("Invalid capacity, expected a positive integer");
+    };
+
+    match storage.write().cf_reserve(key, capacity) {
+        Ok(()) => bulkstring!(Some("SUCCESS")),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles CF.ADD and CF.ADDNX commands: adds an item to a cuckoo filter,
+/// creating it with the default capacity if needed.
+/// Format: `CF.ADD key item` or `CF.ADDNX key item`
+/// CF.ADD stores duplicates, CF.ADDNX skips items possibly present.
+///
+/// # Arguments
+/// * `children` - Command arguments (key and item)
+/// * `storage` - Storage engine reference
+/// * `if_absent` - Whether possibly present items are skipped (CF.ADDNX)
+///
+/// # Returns
+/// RESP3 integer response, 1 if the item was added and 0 if it was skipped,
+/// or error if the filter is full
+fn handle_cfadd_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+    if_absent: bool,
+) -> RespValue {
+    let args = match string_arguments(children, "CF.ADD") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [key, item] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+
+    match storage.write().cf_add(key, item, if_absent) {
+        Ok(added) => integer!(added as u8),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles CF.EXISTS command: checks whether an item was possibly added to
+/// a cuckoo filter.
+/// Format: `CF.EXISTS key item`
+///
+/// # Arguments
+/// * `children` - Command arguments (key and item)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer response, 1 if the item was possibly added and 0 if it
+/// definitely was not
+fn handle_cfexists_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "CF.EXISTS") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [key, item] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+
+    match storage.read().cf_exists(key, item) {
+        Ok(found) => integer!(found as u8),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles CF.DEL command: deletes one copy of an item from a cuckoo filter.
+/// Format: `CF.DEL key item`
+///
+/// # Arguments
+/// * `children` - Command arguments (key and item)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer response, 1 if the item was deleted and 0 if it was not
+/// found, or error if the key doesn't exist
+fn handle_cfdel_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "CF.DEL") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [key, item] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+
+    match storage.write().cf_delete(key, item) {
+        Ok(deleted) => integer!(deleted as u8),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Converts stream entries to a list of `[id, [field, value, ...]]` pairs.
+fn stream_entries(entries: Vec<(StreamId, StreamFields)>) -> StorageValue {
+    let entries = entries
+        .into_iter()
+        .map(|(id, fields)| {
+            let fields = fields
+                .into_iter()
+                .flat_map(|(field, value)| [StorageValue::Text(field), StorageValue::Text(value)])
+                .collect();
+            StorageValue::List(vec![
+                StorageValue::Text(id.to_string()),
+                StorageValue::List(fields),
+            ])
+        })
+        .collect();
+    StorageValue::List(entries)
+}
+
+/// Parses a leading `MAXLEN [=|~] n` option. `~` (approximate trimming)
+/// trims exactly.
+///
+/// # Returns
+/// The maximum length if the option is present and the remaining arguments
+fn parse_max_len(args: &[String]) -> Result<(Option<usize>, &[String]), RespValue> {
+    let rest = match args {
+        [option, rest @ ..] if option.eq_ignore_ascii_case("MAXLEN") => match rest {
+            [exact, rest @ ..] if exact == "=" || exact == "~" => rest,
+            _ => rest,
+        },
+        _ => return Ok((None, args)),
+    };
+
+    match rest {
+        [max_len, rest @ ..] => match max_len.parse::<usize>() {
+            Ok(max_len) => Ok((Some(max_len), rest)),
+            Err(_) => Err(bulkerror!("Invalid MAXLEN, expected a positive integer")),
+        },
+        [] => Err(bulkerror!("Command missing some arguments")),
+    }
+}
+
+/// Handles LPUSH and RPUSH commands: pushes values onto the head or the
+/// tail of a list, creating it if needed.
+/// Format: `LPUSH key value [value ...]`
+/// Clients blocked on the list are served once the request is processed.
+///
+/// # Arguments
+/// * `children` - Command arguments (key and values)
+/// * `storage` - Storage engine reference
+/// * `session` - State of the connection, recording the pushed key
+/// * `front` - Push onto the head (LPUSH) instead of the tail (RPUSH)
+///
+/// # Returns
+/// RESP3 integer response with the length of the list, or error if the
+/// value isn't a list
+fn handle_push_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+    session: &mut Session,
+    front: bool,
+) -> RespValue {
+    let [key, values @ ..] = children else {
+        return bulkerror!("Command missing some arguments");
+    };
+    if values.is_empty() {
+        return bulkerror!("Command missing some arguments");
+    }
+
+    let key = match key {
+        RequestType::BulkString { data } => String::from_utf8_lossy(data).to_string(),
+        _ => return bulkerror!("Invalid request type for list key"),
+    };
+    let values = match values.iter().map(request_type_to_storage_value).collect() {
+        Ok(values) => values,
+        Err(e) => return bulkerror!(&e),
+    };
+
+    match storage.write().list_push(&key, values, front) {
+        Ok(len) => {
+            session.pushed.push(key);
+            integer!(len)
+        }
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles LPOP and RPOP commands: removes and returns the first or the last
+/// value of a list. The key is deleted with its last value.
+/// Format: `LPOP key`
+///
+/// # Arguments
+/// * `children` - Command arguments (should contain the key)
+/// * `storage` - Storage engine reference
+/// * `front` - Pop from the head (LPOP) instead of the tail (RPOP)
+///
+/// # Returns
+/// RESP3 response with the value, null for a missing key, or error if the
+/// value isn't a list
+fn handle_pop_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+    front: bool,
+) -> RespValue {
+    let name = if front { "LPOP" } else { "RPOP" };
+    let args = match string_arguments(children, name) {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [key] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+
+    match storage.write().list_pop(key, front) {
+        Ok(Some(value)) => RespValue::from(&value),
+        Ok(None) => null!(),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles LLEN command: gets the length of a list.
+/// Format: `LLEN key`
+///
+/// # Arguments
+/// * `children` - Command arguments (should contain the key)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer response, 0 for a missing key
+fn handle_llen_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "LLEN") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [key] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+
+    match storage.read().list_len(key) {
+        Ok(len) => integer!(len),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles BLPOP and BRPOP commands: pops a value from the first non-empty
+/// list, or blocks the connection until a value is pushed to one of them.
+/// Format: `BLPOP key [key ...] timeout`
+/// The timeout is in seconds and may be fractional, 0 waits forever.
+/// A blocked connection is parked by the task handler, which keeps serving
+/// other clients in the meantime.
+///
+/// # Arguments
+/// * `children` - Command arguments (keys and timeout)
+/// * `storage` - Storage engine reference
+/// * `session` - State of the connection, marked as blocked if needed
+/// * `front` - Pop from the head (BLPOP) instead of the tail (BRPOP)
+///
+/// # Returns
+/// RESP3 array response [key, value], null once the timeout expires, or a
+/// placeholder that is not sent while the connection is blocked
+fn handle_blocking_pop_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+    session: &mut Session,
+    front: bool,
+) -> RespValue {
+    let name = if front { "BLPOP" } else { "BRPOP" };
+    let args = match string_arguments(children, name) {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [keys @ .., timeout] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+    if keys.is_empty() {
+        return bulkerror!("Command missing some arguments");
+    }
+
+    let timeout = match timeout.parse().map(Duration::try_from_secs_f64) {
+        Ok(Ok(timeout)) => timeout,
+        _ => return bulkerror!("Timeout is not a valid number of seconds"),
+    };
+
+    if let Some(response) = pop_first(keys, front, &storage) {
+        return response;
+    }
+
+    // A timeout too far away to be represented waits forever as well
+    let deadline = match timeout.is_zero() {
+        true => None,
+        false => Instant::now().checked_add(timeout),
+    };
+    session.blocked = Some(BlockedPop {
+        keys: keys.to_vec(),
+        front,
+        deadline,
+    });
+    // Never sent, the task handler parks the connection instead
+    null!()
+}
+
+/// Handles XADD command: appends an entry to a stream.
+/// Format: `XADD key [MAXLEN [=|~] n] <*|id> field value [field value ...]`
+/// `*` generates a time-ordered ID, `<ms>-*` only the sequence number.
+///
+/// # Arguments
+/// * `children` - Command arguments (key, options, ID and field-value pairs)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 bulk string response with the ID of the entry, or error if the ID
+/// is not greater than the last one
+///
+/// # Example
+/// Input: `*5\r\n$4\r\nXADD\r\n$4\r\njobs\r\n$1\r\n*\r\n$3\r\njob\r\n$6\r\nresize\r\n`
+/// Output: `$15\r\n1700000000000-0\r\n`
+fn handle_xadd_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "XADD") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [key, rest @ ..] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+    let (max_len, rest) = match parse_max_len(rest) {
+        Ok(parsed) => parsed,
+        Err(e) => return e,
+    };
+    let [id, pairs @ ..] = rest else {
+        return bulkerror!("Command missing some arguments");
+    };
+    if pairs.is_empty() || pairs.len() % 2 != 0 {
+        return bulkerror!("XADD expects field value pairs");
+    }
+
+    let fields = pairs
+        .chunks(2)
+        .map(|pair| (pair[0].clone(), pair[1].clone()))
+        .collect();
+    match storage.write().stream_add(key, id, fields, max_len) {
+        Ok(id) => bulkstring!(Some(id.to_string())),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles XRANGE and XREVRANGE commands: gets the entries of a stream
+/// within an ID range.
+/// Format: `XRANGE key start end [COUNT n]` or
+/// `XREVRANGE key end start [COUNT n]`
+/// `-` and `+` are the smallest and largest IDs, and an ID without sequence
+/// number covers the whole millisecond.
+///
+/// # Arguments
+/// * `children` - Command arguments (key, range and optional count)
+/// * `storage` - Storage engine reference
+/// * `rev` - Whether entries are returned newest first (XREVRANGE)
+///
+/// # Returns
+/// RESP3 array of `[id, [field, value, ...]]` entries
+fn handle_xrange_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+    rev: bool,
+) -> RespValue {
+    let args = match string_arguments(children, "XRANGE") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let (key, first, second, count) = match args.as_slice() {
+        [key, first, second] => (key, first, second, None),
+        [key, first, second, option, count] if option.eq_ignore_ascii_case("COUNT") => {
+            match count.parse::<usize>() {
+                Ok(count) => (key, first, second, Some(count)),
+                Err(_) => return bulkerror!("Invalid COUNT, expected a positive integer"),
+            }
+        }
+        _ => return bulkerror!("Command missing some arguments"),
+    };
+    let (start, end) = if rev {
+        (second, first)
+    } else {
+        (first, second)
+    };
+    let range =
+        StreamId::parse(start, 0).and_then(|start| Ok((start, StreamId::parse(end, u64::MAX)?)));
+    let Ok((start, end)) = range else {
+        return bulkerror!("Invalid stream ID");
+    };
+
+    match storage.read().stream_range(key, start, end, count, rev) {
+        Ok(entries) => RespValue::from(&stream_entries(entries)),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles XLEN command: gets the number of entries in a stream.
+/// Format: `XLEN key`
+///
+/// # Arguments
+/// * `children` - Command arguments (should contain the key)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer response, 0 for a missing key
+fn handle_xlen_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "XLEN") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [key] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+
+    match storage.read().stream_len(key) {
+        Ok(len) => integer!(len),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles XTRIM command: removes the oldest entries of a stream.
+/// Format: `XTRIM key MAXLEN [=|~] n`
+///
+/// # Arguments
+/// * `children` - Command arguments (key and maximum length)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer response with the number of removed entries
+fn handle_xtrim_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "XTRIM") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [key, rest @ ..] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+    let max_len = match parse_max_len(rest) {
+        Ok((Some(max_len), [])) => max_len,
+        Ok(_) => return bulkerror!("XTRIM expects MAXLEN [=|~] n"),
+        Err(e) => return e,
+    };
+
+    match storage.write().stream_trim(key, max_len) {
+        Ok(removed) => integer!(removed),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles XGROUP command: manages the consumer groups of a stream.
+/// Format: `XGROUP CREATE key group <id|$> [MKSTREAM]` or
+/// `XGROUP DESTROY key group`
+/// A new group delivers the entries following the given ID, `$` meaning
+/// only entries added from now on.
+///
+/// # Arguments
+/// * `children` - Command arguments (subcommand, key, group and options)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 "SUCCESS" response for CREATE, integer response for DESTROY
+/// (1 if the group existed), or error
+fn handle_xgroup_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "XGROUP") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let Some(subcommand) = args.first() else {
+        return bulkerror!("Command missing some arguments");
+    };
+
+    match (subcommand.to_uppercase().as_str(), &args[1..]) {
+        ("CREATE", [key, group, id, options @ ..]) => {
+            let create = match options {
+                [] => false,
+                [option] if option.eq_ignore_ascii_case("MKSTREAM") => true,
+                _ => return bulkerror!("Invalid XGROUP CREATE option"),
+            };
+            match storage.write().stream_create_group(key, group, id, create) {
+                Ok(()) => bulkstring!(Some("SUCCESS")),
+                Err(e) => bulkerror!(&e),
+            }
+        }
+        ("DESTROY", [key, group]) => match storage.write().stream_destroy_group(key, group) {
+            Ok(destroyed) => integer!(destroyed as u8),
+            Err(e) => bulkerror!(&e),
+        },
+        ("CREATE" | "DESTROY", _) => bulkerror!("Command missing some arguments"),
+        _ => bulkerror!(&format!("Unknown XGROUP subcommand {subcommand}")),
+    }
+}
+
+/// Handles XREADGROUP command: reads entries as a consumer of a group.
+/// Format: `XREADGROUP GROUP group consumer [COUNT n] STREAMS key [key ...] id [id ...]`
+/// The ID `>` delivers entries never delivered to the group, which stay
+/// pending until acknowledged with XACK. Any other ID re-reads the
+/// consumer's own pending entries following it.
+///
+/// # Arguments
+/// * `children` - Command arguments (group, consumer, options, keys and IDs)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 array of `[key, entries]` pairs for the streams that returned
+/// entries, null if none did, or error if a key or group doesn't exist
+fn handle_xreadgroup_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "XREADGROUP") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [keyword, group, consumer, rest @ ..] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+    if !keyword.eq_ignore_ascii_case("GROUP") {
+        return bulkerror!("XREADGROUP expects GROUP group consumer");
+    }
+
+    let mut count = None;
+    let mut rest = rest;
+    let streams = loop {
+        match rest {
+            [option, n, tail @ ..] if option.eq_ignore_ascii_case("COUNT") => {
+                let Ok(n) = n.parse::<usize>() else {
+                    return bulkerror!("Invalid COUNT, expected a positive integer");
+                };
+                count = Some(n);
+                rest = tail;
+            }
+            [option, tail @ ..] if option.eq_ignore_ascii_case("STREAMS") => break tail,
+            [option, ..] => return bulkerror!(&format!("Unsupported XREADGROUP option {option}")),
+            [] => return bulkerror!("Command missing some arguments"),
+        }
+    };
+    if streams.is_empty() || streams.len() % 2 != 0 {
+        return bulkerror!("XREADGROUP expects as many IDs as keys");
+    }
+
+    let (keys, ids) = streams.split_at(streams.len() / 2);
+    let mut replies = Vec::new();
+    let mut storage = storage.write();
+    for (key, id) in keys.iter().zip(ids) {
+        let after = match id.as_str() {
+            ">" => None,
+            _ => match StreamId::parse(id, 0) {
+                Ok(id) => Some(id),
+                Err(e) => return bulkerror!(&e),
+            },
+        };
+        match storage.stream_read_group(key, group, consumer, after, count) {
+            Ok(entries) if entries.is_empty() => {}
+            Ok(entries) => replies.push(StorageValue::List(vec![
+                StorageValue::Text(key.clone()),
+                stream_entries(entries),
+            ])),
+            Err(e) => return bulkerror!(&e),
+        }
+    }
+
+    if replies.is_empty() {
+        return null!();
+    }
+    RespValue::from(&StorageValue::List(replies))
+}
+
+/// Handles XACK command: acknowledges entries delivered to a group.
+/// Format: `XACK key group id [id ...]`
+///
+/// # Arguments
+/// * `children` - Command arguments (key, group and IDs)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer response with the number of entries that were pending
+fn handle_xack_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "XACK") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [key, group, ids @ ..] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+    if ids.is_empty() {
+        return bulkerror!("Command missing some arguments");
+    }
+    let ids: Result<Vec<StreamId>, String> = ids.iter().map(|id| StreamId::parse(id, 0)).collect();
+    let ids = match ids {
+        Ok(ids) => ids,
+        Err(e) => return bulkerror!(&e),
+    };
+
+    match storage.write().stream_ack(key, group, &ids) {
+        Ok(acked) => integer!(acked),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles XPENDING command: inspects the pending entries of a group.
+/// Format: `XPENDING key group` for a summary, or
+/// `XPENDING key group start end count [consumer]` for the entries.
+///
+/// # Arguments
+/// * `children` - Command arguments (key, group and optional range)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// The summary as a RESP3 array `[count, first id, last id, [[consumer,
+/// count], ...]]`, or an array of `[id, consumer, idle ms, deliveries]`
+/// entries
+fn handle_xpending_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "XPENDING") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+
+    let reply = match args.as_slice() {
+        [key, group] => storage
+            .read()
+            .stream_pending_summary(key, group)
+            .map(|summary| {
+                let (first, last) = match summary.range {
+                    Some((first, last)) => (
+                        StorageValue::Text(first.to_string()),
+                        StorageValue::Text(last.to_string()),
+                    ),
+                    None => (StorageValue::Null, StorageValue::Null),
+                };
+                let consumers = summary
+                    .consumers
+                    .into_iter()
+                    .map(|(consumer, count)| {
+                        StorageValue::List(vec![
+                            StorageValue::Text(consumer),
+                            StorageValue::Int(count as i64),
+                        ])
+                    })
+                    .collect();
+                StorageValue::List(vec![
+                    StorageValue::Int(summary.count as i64),
+                    first,
+                    last,
+                    StorageValue::List(consumers),
+                ])
+            }),
+        [key, group, start, end, count, consumer @ ..] if consumer.len() <= 1 => {
+            let range = StreamId::parse(start, 0)
+                .and_then(|start| Ok((start, StreamId::parse(end, u64::MAX)?)));
+            let Ok((start, end)) = range else {
+                return bulkerror!("Invalid stream ID");
+            };
+            let Ok(count) = count.parse::<usize>() else {
+                return bulkerror!("Invalid count, expected a positive integer");
+            };
+
+            let consumer = consumer.first().map(String::as_str);
+            storage
+                .read()
+                .stream_pending(key, group, start, end, count, consumer)
+                .map(|pending| {
+                    let entries = pending
+                        .into_iter()
+                        .map(|(id, entry)| {
+                            StorageValue::List(vec![
+                                StorageValue::Text(id.to_string()),
+                                StorageValue::Text(entry.consumer.clone()),
+                                StorageValue::Int(entry.idle().as_millis() as i64),
+                                StorageValue::Int(entry.deliveries as i64),
+                            ])
+                        })
+                        .collect();
+                    StorageValue::List(entries)
+                })
+        }
+        _ => return bulkerror!("Command missing some arguments"),
+    };
+
+    match reply {
+        Ok(reply) => RespValue::from(&reply),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles GEOADD command: adds members with positions to a geospatial index.
+/// Format: `GEOADD key [NX|XX] [CH] longitude latitude member [...]`
+/// NX only adds new members, XX only moves existing ones, and CH counts moved
+/// members in the reply. Latitudes must be within +/-85.05112878 degrees.
+///
+/// # Arguments
+/// * `children` - Command arguments (key, options and positions)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer response with the number of members added, or error if a
+/// position is invalid
+fn handle_geoadd_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "GEOADD") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [key, rest @ ..] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+
+    let mut condition = SetCondition::Always;
+    let mut count_changed = false;
+    let mut rest = rest;
+    while let [option, tail @ ..] = rest {
+        match option.to_uppercase().as_str() {
+            "NX" | "XX" if condition != SetCondition::Always => {
+                return bulkerror!("NX and XX options are mutually exclusive");
+            }
+            "NX" => condition = SetCondition::IfAbsent,
+            "XX" => condition = SetCondition::IfPresent,
+            "CH" => count_changed = true,
+            _ => break,
+        }
+        rest = tail;
+    }
+    if rest.is_empty() || rest.len() % 3 != 0 {
+        return bulkerror!("GEOADD expects longitude latitude member triples");
+    }
+
+    let mut members = Vec::new();
+    for triple in rest.chunks(3) {
+        let (Ok(longitude), Ok(latitude)) = (triple[0].parse(), triple[1].parse()) else {
+            return bulkerror!("Invalid float type for longitude or latitude");
+        };
+        members.push((longitude, latitude, &triple[2]));
+    }
+
+    match storage
+        .write()
+        .geo_add(key, &members, condition, count_changed)
+    {
+        Ok(count) => integer!(count),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles GEODIST command: gets the distance between two members.
+/// Format: `GEODIST key member1 member2 [m|km|mi|ft]`
+///
+/// # Arguments
+/// * `children` - Command arguments (key, members and optional unit)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 bulk string with the distance (4 decimals, meters by default),
+/// or null if a member is missing
+fn handle_geodist_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "GEODIST") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let (key, a, b, unit) = match args.as_slice() {
+        [key, a, b] => (key, a, b, GeoUnit::Meters),
+        [key, a, b, unit] => match GeoUnit::parse(unit) {
+            Ok(unit) => (key, a, b, unit),
+            Err(e) => return bulkerror!(&e),
+        },
+        _ => return bulkerror!("Command missing some arguments"),
+    };
+
+    match storage.read().geo_dist(key, a, b) {
+        Ok(Some(distance)) => {
+            bulkstring!(Some(format!("{:.4}", distance / unit.meters()).as_str()))
+        }
+        Ok(None) => null!(),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles GEOSEARCH command: finds the members within a radius or a box.
+/// Format: `GEOSEARCH key FROMMEMBER member|FROMLONLAT longitude latitude
+/// BYRADIUS radius unit|BYBOX width height unit [ASC|DESC] [COUNT n]
+/// [WITHDIST] [WITHCOORD]`
+/// Members are sorted from the nearest (ASC, the default) or the furthest.
+///
+/// # Arguments
+/// * `children` - Command arguments (key, center, shape and options)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 array of members, or of [member, distance, [longitude, latitude]]
+/// arrays with WITHDIST and WITHCOORD. Distances use the unit of the shape
+fn handle_geosearch_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "GEOSEARCH") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [key, rest @ ..] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+
+    let number = |arg: &String| arg.parse::<f64>().ok().filter(|n| *n >= 0.0);
+    let mut origin = None;
+    let mut shape = None;
+    let mut descending = false;
+    let mut count = None;
+    let (mut with_dist, mut with_coord) = (false, false);
+    let mut rest = rest;
+    loop {
+        rest = match rest {
+            [option, member, tail @ ..] if option.eq_ignore_ascii_case("FROMMEMBER") => {
+                origin = Some(GeoOrigin::Member(member.clone()));
+                tail
+            }
+            [option, longitude, latitude, tail @ ..]
+                if option.eq_ignore_ascii_case("FROMLONLAT") =>
+            {
+                let (Ok(longitude), Ok(latitude)) = (longitude.parse(), latitude.parse()) else {
+                    return bulkerror!("Invalid float type for longitude or latitude");
+                };
+                origin = Some(GeoOrigin::Position(longitude, latitude));
+                tail
+            }
+            [option, radius, unit, tail @ ..] if option.eq_ignore_ascii_case("BYRADIUS") => {
+                let (Some(radius), Ok(unit)) = (number(radius), GeoUnit::parse(unit)) else {
+                    return bulkerror!("BYRADIUS expects a positive radius and a unit");
+                };
+                shape = Some((GeoShape::Radius(radius * unit.meters()), unit));
+                tail
+            }
+            [option, width, height, unit, tail @ ..] if option.eq_ignore_ascii_case("BYBOX") => {
+                let (Some(width), Some(height), Ok(unit)) =
+                    (number(width), number(height), GeoUnit::parse(unit))
+                else {
+                    return bulkerror!("BYBOX expects a positive width, height and a unit");
+                };
+                let (width, height) = (width * unit.meters(), height * unit.meters());
+                shape = Some((GeoShape::Box { width, height }, unit));
+                tail
+            }
+            [option, n, tail @ ..] if option.eq_ignore_ascii_case("COUNT") => {
+                match n.parse::<usize>() {
+                    Ok(n) if n > 0 => count = Some(n),
+                    _ => return bulkerror!("Invalid COUNT, expected a positive integer"),
+                }
+                tail
+            }
+            [option, tail @ ..] => {
+                match option.to_uppercase().as_str() {
+                    "ASC" => descending = false,
+                    "DESC" => descending = true,
+                    "WITHDIST" => with_dist = true,
+                    "WITHCOORD" => with_coord = true,
+                    _ => return bulkerror!(&format!("Unsupported GEOSEARCH option {option}")),
+                }
+                tail
+            }
+            [] => break,
+        };
+    }
+    let (Some(origin), Some((shape, unit))) = (origin, shape) else {
+        return bulkerror!("GEOSEARCH expects FROMMEMBER or FROMLONLAT, and BYRADIUS or BYBOX");
+    };
+
+    let mut found = match storage.read().geo_search(key, &origin, shape) {
+        Ok(found) => found,
+        Err(e) => return bulkerror!(&e),
+    };
+    if descending {
+        found.reverse();
+    }
+    found.truncate(count.unwrap_or(found.len()));
+
+    let replies = found
+        .into_iter()
+        .map(|m| {
+            if !with_dist && !with_coord {
+                return StorageValue::Text(m.member);
+            }
+            let mut reply = vec![StorageValue::Text(m.member)];
+            if with_dist {
+                let distance = format!("{:.4}", m.distance / unit.meters());
+                reply.push(StorageValue::Text(distance));
+            }
+            if with_coord {
+                reply.push(StorageValue::List(vec![
+                    StorageValue::Text(m.longitude.to_string()),
+                    StorageValue::Text(m.latitude.to_string()),
+                ]));
+            }
+            StorageValue::List(reply)
+        })
+        .collect();
+    RespValue::from(&StorageValue::List(replies))
+}
+
+/// Handles JSON.SET command: writes a value inside a JSON document.
+/// Format: `JSON.SET key path value [NX|XX]`
+/// New keys must be set at the root path `$`.
+///
+/// # Arguments
+/// * `children` - Command arguments (key, path, JSON value and condition)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 SUCCESS, or null if the condition failed or the parent of the path
+/// doesn't exist
+///
+/// # Example
+/// Input: `*4\r\n$8\r\nJSON.SET\r\n$4\r\nuser\r\n$1\r\n$\r\n$14\r\n{"name":"Ada"}\r\n`
+/// Output: `$7\r\nSUCCESS\r\n`
+fn handle_json_set_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "JSON.SET") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let (key, path, value, condition) = match args.as_slice() {
+        [key, path, value] => (key, path, value, SetCondition::Always),
+        [key, path, value, condition] => match condition.to_uppercase().as_str() {
+            "NX" => (key, path, value, SetCondition::IfAbsent),
+            "XX" => (key, path, value, SetCondition::IfPresent),
+            _ => return bulkerror!(&format!("Unsupported JSON.SET option {condition}")),
+        },
+        _ => return bulkerror!("Command missing some arguments"),
+    };
+    let (path, value) = match (JsonPath::parse(path), parse_json(value)) {
+        (Ok(path), Ok(value)) => (path, value),
+        (Err(e), _) | (_, Err(e)) => return bulkerror!(&e),
+    };
+
+    match storage.write().json_set(key, &path, value, condition) {
+        Ok(true) => bulkstring!(Some("SUCCESS")),
+        Ok(false) => null!(),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles JSON.GET command: reads values from a JSON document.
+/// Format: `JSON.GET key [path ...]`
+///
+/// # Arguments
+/// * `children` - Command arguments (key and paths)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 bulk string with the JSON text: the whole document without paths,
+/// the matches of a single path, or an object keyed by path for several.
+/// Null if the key doesn't exist
+fn handle_json_get_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "JSON.GET") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [key, paths @ ..] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+    let paths: Vec<JsonPath> = match paths.iter().map(|p| JsonPath::parse(p)).collect() {
+        Ok(paths) => paths,
+        Err(e) => return bulkerror!(&e),
+    };
+
+    match storage.read().json_get(key, &paths) {
+        Ok(Some(json)) => bulkstring!(Some(json.as_str())),
+        Ok(None) => null!(),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles JSON.DEL command: removes values from a JSON document.
+/// Format: `JSON.DEL key [path]`
+/// The path defaults to the root, which deletes the key.
+///
+/// # Arguments
+/// * `children` - Command arguments (key and optional path)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer with the number of values removed
+fn handle_json_del_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "JSON.DEL") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let (key, path) = match args.as_slice() {
+        [key] => (key, "$"),
+        [key, path] => (key, path.as_str()),
+        _ => return bulkerror!("Command missing some arguments"),
+    };
+    let path = match JsonPath::parse(path) {
+        Ok(path) => path,
+        Err(e) => return bulkerror!(&e),
+    };
+
+    match storage.write().json_del(key, &path) {
+        Ok(removed) => integer!(removed),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles JSON.NUMINCRBY command: increments numbers in a JSON document.
+/// Format: `JSON.NUMINCRBY key path increment`
+///
+/// # Arguments
+/// * `children` - Command arguments (key, path and increment)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 bulk string with the new value as JSON, or an array of the new
+/// values (null for non-numbers) for `$` paths
+fn handle_json_numincrby_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "JSON.NUMINCRBY") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [key, path, increment] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+    let path = match JsonPath::parse(path) {
+        Ok(path) => path,
+        Err(e) => return bulkerror!(&e),
+    };
+    let increment = match parse_json(increment) {
+        Ok(value @ (StorageValue::Int(_) | StorageValue::Float(_))) => value,
+        _ => return bulkerror!("Increment is not a number"),
+    };
+
+    match storage.write().json_num_incr_by(key, &path, increment) {
+        Ok(reply) => bulkstring!(Some(to_json(&reply).as_str())),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles JSON.ARRAPPEND command: appends values to arrays in a JSON
+/// document.
+/// Format: `JSON.ARRAPPEND key path value [value ...]`
+///
+/// # Arguments
+/// * `children` - Command arguments (key, path and JSON values)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer with the new length of the array, or an array of lengths
+/// (null for non-arrays) for `$` paths
+fn handle_json_arrappend_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "JSON.ARRAPPEND") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [key, path, values @ ..] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+    if values.is_empty() {
+        return bulkerror!("Command missing some arguments");
+    }
+    let path = match JsonPath::parse(path) {
+        Ok(path) => path,
+        Err(e) => return bulkerror!(&e),
+    };
+    let values: Vec<StorageValue> = match values.iter().map(|v| parse_json(v)).collect() {
+        Ok(values) => values,
+        Err(e) => return bulkerror!(&e),
+    };
+
+    match storage.write().json_arr_append(key, &path, &values) {
+        Ok(reply) => RespValue::from(&reply),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles GETV command: retrieves a value together with its version.
+/// Format: `GETV key`
+/// The version changes on every write and is passed to CAS.
+///
+/// # Arguments
+/// * `children` - Command arguments (should contain the key)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 array response `[value, version]`, or null if the key doesn't exist
+///
+/// # Example
+/// Input: `*2\r\n$4\r\nGETV\r\n$4\r\nname\r\n`
+/// Output: `*2\r\n$4\r\nJohn\r\n:7\r\n`
+fn handle_getv_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    if children.is_empty() {
+        return bulkerror!("Command missing some arguments");
+    }
+
+    match &children[0] {
+        RequestType::BulkString { data } => {
+            let key = String::from_utf8_lossy(data);
+            match storage.read().get_entry(&key) {
+                Some(entry) => {
+                    RespValue::Array(vec![RespValue::from(&entry.value), integer!(entry.version)])
+                }
+                None => null!(),
+            }
+        }
+        _ => bulkerror!("Invalid request type for GETV key"),
+    }
+}
+
+/// Handles CAS command: stores a value only if the key is at a given version.
+/// Format: `CAS key version value`
+/// Version 0 only writes if the key doesn't exist. The check and the write
+/// happen under a single storage lock. The entry keeps its expiry.
+///
+/// # Arguments
+/// * `children` - Command arguments (key, expected version and value)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer response with the new version, or null if the version
+/// didn't match
+///
+/// # Example
+/// `CAS name 7 Jane` sets "name" to "Jane" if nobody wrote it since GETV
+/// returned version 7
+fn handle_cas_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "CAS") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    let [key, version, value] = args.as_slice() else {
+        return bulkerror!("Command missing some arguments");
+    };
+    let Ok(version) = version.parse::<u64>() else {
+        return bulkerror!("Invalid CAS version");
+    };
+
+    match storage
+        .write()
+        .compare_and_swap(key, version, get_value_type(value))
+    {
+        Ok(Some(version)) => integer!(version),
+        Ok(None) => null!(),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles RENAME command: renames an existing key.
+/// Format: `RENAME old_key new_key`
+/// The old key is removed and the value is stored under the new key.
+///
+/// # Arguments
+/// * `children` - Command arguments (old key and new key)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 response: "SUCCESS" (always succeeds, even if old key doesn't exist)
+///
+/// # Example
+/// `RENAME user:123 customer:123` moves the value from user:123 to customer:123
+fn handle_rename_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    if children.len() < 2 {
+        return bulkerror!("Command missing some arguments");
+    }
+
+    let mut i = 0;
+    match &children[i] {
+        RequestType::BulkString { data } => {
+            let old_key = String::from_utf8_lossy(data);
+            i += 1;
+            match &children[i] {
+                RequestType::BulkString { data } => {
+                    let new_key = String::from_utf8_lossy(data);
+
+                    storage.write().rename_entry(&old_key, &new_key);
+                    bulkstring!(Some("SUCCESS"))
+                }
+                _ => bulkerror!("Invalid RENAME new_key type"),
+            }
+        }
+        _ => bulkerror!("Invalid RENAME old_key type"),
+    }
+}
+
+/// Handles EVICTNOW command: evicts entries based on the current eviction policy
+/// This command has an optional integer value that represents the number of entries
+/// to evict. The fallback value is 0, if the value is not specified.
+/// Format: `EVICTNOW`
+///         `EVICTNOW 10`
+///
+/// # Arguments
+/// * `children` - Command arguments (count). Children can be empty
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 response: "SUCCESS" (always succeeds)
+///
+fn handle_evictnow_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    if children.is_empty() {
+        storage.write().evict_entries(0);
+        return bulkstring!(Some("SUCCESS"));
+    }
+
+    match &children[0] {
+        RequestType::Integer { data } => {
+            let int_str = match String::from_utf8(data.to_vec()) {
+                Ok(s) => s,
+                Err(e) => return bulkerror!(&e.to_string()),
+            };
+            let int = match int_str.parse::<usize>() {
+                Ok(i) => i,
+                Err(e) => return bulkerror!(&e.to_string()),
+            };
+            storage.write().evict_entries(int);
+            bulkstring!(Some("SUCCESS"))
+        }
+        _ => bulkerror!("Invalid count type for evictnow command"),
+    }
+}
+
+/// Default number of keys visited by a SCAN call
+const DEFAULT_SCAN_COUNT: usize = 10;
+
+/// Handles SCAN command: returns a page of keys and the cursor to continue from.
+/// Format: `SCAN cursor [MATCH pattern] [COUNT count] [TYPE type]`
+/// A cursor of 0 starts a new iteration, and a returned cursor of 0 ends it.
+///
+/// # Arguments
+/// * `children` - Command arguments (cursor followed by optional filters)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 array response: [next_cursor, [key, key, ...]]
+///
+/// # Example
+/// `SCAN 0 MATCH user:* COUNT 100` returns up to 100 keys starting with `user:`
+fn handle_scan_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    if children.is_empty() {
+        return bulkerror!("Command missing some arguments");
+    }
+
+    let cursor = match &children[0] {
+        RequestType::BulkString { data } | RequestType::Integer { data } => {
+            match String::from_utf8_lossy(data).parse::<u64>() {
+                Ok(c) => c,
+                Err(_) => return bulkerror!("Invalid SCAN cursor"),
+            }
+        }
+        _ => return bulkerror!("Invalid request type for SCAN cursor"),
+    };
+
+    let mut pattern = None;
+    let mut count = DEFAULT_SCAN_COUNT;
+    let mut type_name = None;
+
+    // Options come in `NAME value` pairs
+    for option in children[1..].chunks(2) {
+        let (name, value) = match option {
+            [
+                RequestType::BulkString { data: name },
+                RequestType::BulkString { data: value } | RequestType::Integer { data: value },
+            ] => (
+                String::from_utf8_lossy(name).to_uppercase(),
+                String::from_utf8_lossy(value).to_string(),
+            ),
+            _ => return bulkerror!("Invalid SCAN options"),
+        };
+
+        match name.as_str() {
+            "MATCH" => pattern = Some(value),
+            "COUNT" => match value.parse::<usize>() {
+                Ok(c) if c > 0 => count = c,
+                _ => return bulkerror!("Invalid SCAN count"),
+            },
+            "TYPE" => type_name = Some(value),
+            _ => return bulkerror!(&format!("Unknown SCAN option: {name}")),
+        }
+    }
+
+    let (cursor, keys) =
+        storage
+            .read()
+            .scan(cursor, count, pattern.as_deref(), type_name.as_deref());
+
+    // [cursor, [keys]]
+    RespValue::Array(vec![RespValue::bulk(cursor.to_string()), array!(&keys)])
+}
+
+/// Handles SELECT command: switches the connection to another logical database.
+/// Format: `SELECT db`
+/// Databases are created by their first write, each with its own options and
+/// stats. Until then the selected database reads as empty.
+///
+/// # Arguments
+/// * `children` - Command arguments (should contain the database name)
+/// * `session` - State of the connection that sent the request
+///
+/// # Returns
+/// RESP3 response: "SUCCESS" or error message
+///
+/// # Example
+/// `SELECT 1` makes every following command of the connection use database `1`
+fn handle_select_command(children: &[RequestType], session: &mut Session) -> RespValue {
+    if children.is_empty() {
+        return bulkerror!("Command missing some arguments");
+    }
+
+    match &children[0] {
+        RequestType::BulkString { data } | RequestType::Integer { data } => {
+            let db = String::from_utf8_lossy(data).to_string();
+            if db.is_empty() {
+                return bulkerror!("Invalid SELECT database name");
+            }
+            session.db = db;
+            bulkstring!(Some("SUCCESS"))
+        }
+        _ => bulkerror!("Invalid request type for SELECT database"),
+    }
+}
+
+/// Handles MOVE command: moves a key from the selected database to another one.
+/// Format: `MOVE key db`
+/// The entry keeps its TTL and metadata.
+///
+/// # Arguments
+/// * `children` - Command arguments (key and destination database)
+/// * `session` - State of the connection that sent the request
+/// * `databases` - All logical databases
+///
+/// # Returns
+/// RESP3 boolean response: #t if the key was moved, #f if it doesn't exist
+/// or already exists in the destination database
+fn handle_move_command(
+    children: &[RequestType],
+    session: &Session,
+    databases: &Databases,
+) -> RespValue {
+    if children.len() < 2 {
+        return bulkerror!("Command missing some arguments");
+    }
+
+    let key = match &children[0] {
+        RequestType::BulkString { data } => String::from_utf8_lossy(data).to_string(),
+        _ => return bulkerror!("Invalid request type for MOVE key"),
+    };
+
+    match &children[1] {
+        RequestType::BulkString { data } | RequestType::Integer { data } => {
+            let db = String::from_utf8_lossy(data).to_string();
+            match databases.move_entry(&key, &session.db, &db) {
+                Ok(moved) => boolean!(moved),
+                Err(e) => bulkerror!(&e),
+            }
+        }
+        _ => bulkerror!("Invalid request type for MOVE database"),
+    }
+}
+
+/// Handles HELLO command: negotiates the protocol version of the connection.
+/// Format: `HELLO [protover [AUTH username password] [SETNAME clientname]]`
+/// Without a version the connection keeps its protocol. RESP2 connections
+/// get every reply downgraded, see `RespValue::into_resp2`.
+/// No password is configured, so AUTH accepts any password for the
+/// `default` user like a Redis server without `requirepass`.
+///
+/// # Arguments
+/// * `children` - Command arguments (protocol version and options)
+/// * `session` - State of the connection that sent the request
+///
+/// # Returns
+/// RESP3 map describing the server and the connection, sent in the
+/// negotiated protocol, or an error leaving the connection unchanged
+///
+/// # Example
+/// `HELLO 2 SETNAME worker` switches the connection to RESP2 and names it
+fn handle_hello_command(children: &[RequestType], session: &mut Session) -> RespValue {
+    let args = match string_arguments(children, "HELLO") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+
+    let mut protocol = session.protocol;
+    let mut name = session.name.clone();
+    let mut i = 0;
+
+    if let Some(version) = args.first() {
+        protocol = match version.parse::<i64>() {
+            Ok(v @ 2..=3) => v as u8,
+            Ok(_) => {
+                return RespValue::SimpleError("NOPROTO unsupported protocol version".to_string());
+            }
+            Err(_) => return bulkerror!("Protocol version is not an integer or out of range"),
+        };
+        i += 1;
+    }
+
+    while i < args.len() {
+        match args[i].to_uppercase().as_str() {
+            "AUTH" if i + 2 < args.len() => {
+                if args[i + 1] != "default" {
+                    return bulkerror!("WRONGPASS invalid username-password pair");
+                }
+                i += 3;
+            }
+            "SETNAME" if i + 1 < args.len() => {
+                let n = &args[i + 1];
+                if n.chars().any(|c| !c.is_ascii_graphic()) {
+                    return bulkerror!(
+                        "Client names cannot contain spaces, newlines or special characters"
+                    );
+                }
+                name = (!n.is_empty()).then(|| n.clone());
+                i += 2;
+            }
+            other => return bulkerror!(&format!("Syntax error in HELLO option '{other}'")),
+        }
+    }
+
+    session.protocol = protocol;
+    session.name = name;
+
+    let field = |name: &str, value: RespValue| (RespValue::bulk(name), value);
+    RespValue::Map(vec![
+        field("server", RespValue::bulk("volatix")),
+        field("version", RespValue::bulk(env!("CARGO_PKG_VERSION"))),
+        field("proto", integer!(protocol)),
+        field("id", integer!(session.id)),
+        field("mode", RespValue::bulk("standalone")),
+        field("role", RespValue::bulk("master")),
+        field("modules", RespValue::Array(Vec::new())),
+    ])
+}
+
+/// Handles DEL command: removes keys from the cache.
+/// Format: `DEL key [key ...]`
+/// Redis name of DELETE, which takes a single key and always succeeds.
+///
+/// # Arguments
+/// * `children` - Command arguments (the keys)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer response with the number of keys removed
+fn handle_del_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let keys = match string_arguments(children, "DEL") {
+        Ok(keys) if !keys.is_empty() => keys,
+        Ok(_) => return bulkerror!("Command missing some arguments"),
+        Err(e) => return e,
+    };
+
+    let mut storage = storage.write();
+    let removed = keys
+        .iter()
+        .filter(|key| storage.take_entry(key).is_some())
+        .count();
+    integer!(removed)
+}
+
+/// Handles MGET command: retrieves the values of several keys.
+/// Format: `MGET key [key ...]`
+///
+/// # Arguments
+/// * `children` - Command arguments (the keys)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 array with the value of every key as a bulk string, like Redis,
+/// null for missing keys and values that aren't strings or numbers
+fn handle_mget_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let keys = match string_arguments(children, "MGET") {
+        Ok(keys) if !keys.is_empty() => keys,
+        Ok(_) => return bulkerror!("Command missing some arguments"),
+        Err(e) => return e,
+    };
+
+    let storage = storage.read();
+    let values = keys
+        .iter()
+        .map(|key| {
+            let value = storage.get_entry(key).map(|entry| entry.value);
+            match value {
+                Some(StorageValue::Bytes(bytes)) => RespValue::bulk(bytes),
+                Some(
+                    value @ (StorageValue::Int(_)
+                    | StorageValue::Float(_)
+                    | StorageValue::Bool(_)
+                    | StorageValue::Text(_)),
+                ) => RespValue::bulk(value.to_string()),
+                _ => null!(),
+            }
+        })
+        .collect();
+    RespValue::Array(values)
+}
+
+/// Handles MSET command: stores several key-value pairs at once.
+/// Format: `MSET key value [key value ...]`
+/// Values are typed like SET values and get the default TTL.
+///
+/// # Arguments
+/// * `children` - Command arguments (keys and values)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 response: "OK" or error message
+fn handle_mset_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "MSET") {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+    if args.is_empty() || args.len() % 2 != 0 {
+        return bulkerror!("MSET expects key value pairs");
+    }
+
+    // All pairs are prepared before any is written and written under one
+    // lock, so readers see all of them or none, even when MSET fails
+    let pairs = args
+        .chunks(2)
+        .map(|pair| (pair[0].clone(), get_value_type(&pair[1])));
+    match storage.write().insert_entries(pairs) {
+        Ok(()) => simplestring!("OK"),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles SETEX command: stores a key-value pair with a TTL.
+/// Format: `SETEX key seconds value`
+/// Redis name of SETWTTL, note the different argument order.
+///
+/// # Arguments
+/// * `children` - Command arguments (key, TTL and value)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 response: "OK" or error message
+fn handle_setex_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let args = match string_arguments(children, "SETEX") {
+        Ok(args) if args.len() == 3 => args,
+        Ok(_) => return bulkerror!("SETEX expects a key, seconds and a value"),
+        Err(e) => return e,
+    };
+
+    let ttl = match args[1].parse::<u64>() {
+        Ok(secs) if secs > 0 => Duration::from_secs(secs),
+        _ => return bulkerror!("Invalid SETEX ttl"),
+    };
+    let Some(deadline) = SystemTime::now().checked_add(ttl) else {
+        return bulkerror!("Invalid expire time");
+    };
+    let value = get_value_type(&args[2]);
+
+    match storage
+        .write()
+        .insert_with_expiry(args[0].clone(), value, Some(deadline))
+    {
+        Ok(()) => simplestring!("OK"),
+        Err(e) => bulkerror!(&e),
+    }
+}
+
+/// Handles TTL command: retrieves the remaining TTL for a key.
+/// Format: `TTL key`
+/// Redis name of GETTTL, with the Redis replies: the TTL is rounded to the
+/// nearest second and missing keys give -2 rather than null.
+///
+/// # Arguments
+/// * `children` - Command arguments (should contain the key)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer response: remaining TTL in seconds, -1 if the key never
+/// expires, -2 if it doesn't exist
+fn handle_ttl_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    if children.is_empty() {
+        return bulkerror!("Command missing some arguments");
+    }
+
+    match &children[0] {
+        RequestType::BulkString { data } => {
+            let key = String::from_utf8_lossy(data).to_string();
+            let ttl = storage.read().time_to_live(&key);
+            match ttl {
+                Some(Some(ttl)) => integer!((ttl.as_millis() + 500) / 1000),
+                Some(None) => integer!(-1),
+                None => integer!(-2),
+            }
+        }
+        _ => bulkerror!("Invalid request type for TTL key"),
+    }
+}
+
+/// Handles CONFIG command: reads or updates a configuration setting.
+/// Format: `CONFIG GET key` or `CONFIG SET key value`
+/// Redis names of CONFGET and CONFSET. Unknown keys give an empty map, as
+/// Redis does for parameters that match nothing.
+///
+/// # Arguments
+/// * `children` - Command arguments (subcommand and its arguments)
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// The reply of CONFGET or CONFSET
+fn handle_config_command(
+    children: &[RequestType],
+    storage: Arc<parking_lot::RwLock<LockedStorage>>,
+) -> RespValue {
+    let subcommand = match children.first() {
+        Some(RequestType::BulkString { data }) => String::from_utf8_lossy(data).to_uppercase(),
+        Some(_) => return bulkerror!("Invalid request type for CONFIG subcommand"),
+        None => return bulkerror!("Command missing some arguments"),
+    };
+
+    match subcommand.as_str() {
+        "GET" => match handle_confget_command(&children[1..], storage) {
+            RespValue::NullBulkString => RespValue::Map(Vec::new()),
+            reply => reply,
+        },
+        "SET" => handle_confset_command(&children[1..], storage),
+        other => bulkerror!(&format!("Unknown CONFIG subcommand: {other}")),
+    }
+}
+
+/// Handles DBSIZE command: counts the keys of the selected database.
+/// Format: `DBSIZE`
+///
+/// # Arguments
+/// * `storage` - Storage engine reference
+///
+/// # Returns
+/// RESP3 integer response with the number of live keys
+fn handle_dbsize_command(storage: Arc<parking_lot::RwLock<LockedStorage>>) -> RespValue {
+    integer!(storage.read().keyspace_info().keys)
+}
+
+/// Handles FLUSHALL command: removes every key of every database.
+/// Format: `FLUSHALL`
+/// The databases keep their options, like FLUSH does.
+///
+/// # Arguments
+/// * `databases` - All logical databases
+///
+/// # Returns
+/// RESP3 response: "OK"
+fn handle_flushall_command(databases: &Databases) -> RespValue {
+    for name in databases.names() {
+        if let Some(db) = databases.get(&name) {
+            db.write().flush();
+        }
+    }
+    simplestring!("OK")
+}
+
+/// Handles PING command: checks that the server answers.
+/// Format: `PING [message]`
+///
+/// # Arguments
+/// * `children` - Command arguments (optional message)
+///
+/// # Returns
+/// RESP3 simple string "PONG", or the message as a bulk string
+fn handle_ping_command(children: &[RequestType]) -> RespValue {
+    match children {
+        [] => simplestring!("PONG"),
+        [RequestType::BulkString { data }] => RespValue::bulk(data),
+        [_] => bulkerror!("Invalid request type for PING message"),
+        _ => bulkerror!("PING takes at most one message"),
+    }
+}
+
+/// Handles ECHO command: replies with its argument.
+/// Format: `ECHO message`
+///
+/// # Arguments
+/// * `children` - Command arguments (the message)
+///
+/// # Returns
+/// RESP3 bulk string with the message, unchanged
+fn handle_echo_command(children: &[RequestType]) -> RespValue {
+    match children {
+        [RequestType::BulkString { data }] => RespValue::bulk(data),
+        [] => bulkerror!("Command missing some arguments"),
+        [_] => bulkerror!("Invalid request type for ECHO message"),
+        _ => bulkerror!("ECHO takes a single message"),
+    }
+}
+
+/// Processes array-based commands by routing to appropriate handlers.
+/// This is called for all commands that come as RESP3 arrays.
+///
+/// # Arguments
+/// * `children` - Array of request elements (first is command, rest are args)
+/// * `storage` - The selected database
+/// * `session` - State of the connection that sent the request
+/// * `databases` - All logical databases
+/// * `server` - Server-wide state
+///
+/// # Returns
+/// The RESP3 reply
 ///
 /// # Command Format
 /// All commands follow the pattern: [COMMAND, arg1, arg2, ...]
@@ -1113,7 +4329,10 @@ fn handle_evictnow_command(
 fn process_array(
     children: &[RequestType],
     storage: Arc<parking_lot::RwLock<LockedStorage>>,
-) -> Vec<u8> {
+    session: &mut Session,
+    databases: &Databases,
+    server: &ServerInfo,
+) -> RespValue {
     let mut i = 0;
     let command = get_command(&children[i]); // Extract command from first element
     i += 1; // Skip to arguments
@@ -1126,18 +4345,97 @@ fn process_array(
         Command::Set => handle_set_command(&children[i..], storage),
         Command::Delete => handle_delete_command(&children[i..], storage),
         Command::Dump => handle_dump_command(&children[i..], storage),
+        Command::Type => handle_type_command(&children[i..], storage),
+        Command::Object => handle_object_command(&children[i..], storage),
+        Command::Memory => handle_memory_command(&children[i..], storage),
+        Command::Info => handle_info_command(&children[i..], databases, server),
         Command::ConfGet => handle_confget_command(&children[i..], storage),
         Command::ConfSet => handle_confset_command(&children[i..], storage),
         Command::GetTtl => handle_getttl_command(&children[i..], storage),
         Command::SetwTtl => handle_setwttl_command(&children[i..], storage),
         Command::Expire => handle_expire_command(&children[i..], storage),
+        Command::ExpireAt => handle_expireat_command(&children[i..], storage),
+        Command::PExpire => handle_pexpire_command(&children[i..], storage),
+        Command::PExpireAt => handle_pexpireat_command(&children[i..], storage),
+        Command::Persist => handle_persist_command(&children[i..], storage),
+        Command::PTtl => handle_pttl_command(&children[i..], storage),
         Command::DeleteList => handle_deletelist_command(&children[i..], storage),
         Command::GetList => handle_getlist_command(&children[i..], storage),
         Command::SetList => handle_setlist_command(&children[i..], storage),
         Command::SetMap => handle_setmap_command(&children[i..], storage),
+        Command::Keys => handle_keys_command(&children[i..], storage),
+        Command::GetMatch => handle_getmatch_command(&children[i..], storage),
+        Command::DeleteMatch => handle_deletematch_command(&children[i..], storage),
         Command::Incr => handle_incr_command(&children[i..], storage),
         Command::Decr => handle_decr_command(&children[i..], storage),
+        Command::IncrBy => handle_incrby_command(&children[i..], storage, false),
+        Command::DecrBy => handle_incrby_command(&children[i..], storage, true),
+        Command::IncrByFloat => handle_incrbyfloat_command(&children[i..], storage),
         Command::Rename => handle_rename_command(&children[i..], storage),
+        Command::Append => handle_append_command(&children[i..], storage),
+        Command::StrLen => handle_strlen_command(&children[i..], storage),
+        Command::GetRange => handle_getrange_command(&children[i..], storage),
+        Command::SetRange => handle_setrange_command(&children[i..], storage),
+        Command::GetDel => handle_getdel_command(&children[i..], storage),
+        Command::GetEx => handle_getex_command(&children[i..], storage),
+        Command::SetBit => handle_setbit_command(&children[i..], storage),
+        Command::GetBit => handle_getbit_command(&children[i..], storage),
+        Command::BitCount => handle_bitcount_command(&children[i..], storage),
+        Command::BitOp => handle_bitop_command(&children[i..], storage),
+        Command::BitPos => handle_bitpos_command(&children[i..], storage),
+        Command::PfAdd => handle_pfadd_command(&children[i..], storage),
+        Command::PfCount => handle_pfcount_command(&children[i..], storage),
+        Command::PfMerge => handle_pfmerge_command(&children[i..], storage),
+        Command::BfReserve => handle_bfreserve_command(&children[i..], storage),
+        Command::BfAdd => handle_bfadd_command(&children[i..], storage, false),
+        Command::BfMAdd => handle_bfadd_command(&children[i..], storage, true),
+        Command::BfExists => handle_bfexists_command(&children[i..], storage, false),
+        Command::BfMExists => handle_bfexists_command(&children[i..], storage, true),
+        Command::CfReserve => handle_cfreserve_command(&children[i..], storage),
+        Command::CfAdd => handle_cfadd_command(&children[i..], storage, false),
+        Command::CfAddNx => handle_cfadd_command(&children[i..], storage, true),
+        Command::CfExists => handle_cfexists_command(&children[i..], storage),
+        Command::CfDel => handle_cfdel_command(&children[i..], storage),
+        Command::LPush => handle_push_command(&children[i..], storage, session, true),
+        Command::RPush => handle_push_command(&children[i..], storage, session, false),
+        Command::LPop => handle_pop_command(&children[i..], storage, true),
+        Command::RPop => handle_pop_command(&children[i..], storage, false),
+        Command::LLen => handle_llen_command(&children[i..], storage),
+        Command::BLPop => handle_blocking_pop_command(&children[i..], storage, session, true),
+        Command::BRPop => handle_blocking_pop_command(&children[i..], storage, session, false),
+        Command::XAdd => handle_xadd_command(&children[i..], storage),
+        Command::XRange => handle_xrange_command(&children[i..], storage, false),
+        Command::XRevRange => handle_xrange_command(&children[i..], storage, true),
+        Command::XLen => handle_xlen_command(&children[i..], storage),
+        Command::XTrim => handle_xtrim_command(&children[i..], storage),
+        Command::XGroup => handle_xgroup_command(&children[i..], storage),
+        Command::XReadGroup => handle_xreadgroup_command(&children[i..], storage),
+        Command::XAck => handle_xack_command(&children[i..], storage),
+        Command::XPending => handle_xpending_command(&children[i..], storage),
+        Command::GeoAdd => handle_geoadd_command(&children[i..], storage),
+        Command::GeoDist => handle_geodist_command(&children[i..], storage),
+        Command::GeoSearch => handle_geosearch_command(&children[i..], storage),
+        Command::JsonSet => handle_json_set_command(&children[i..], storage),
+        Command::JsonGet => handle_json_get_command(&children[i..], storage),
+        Command::JsonDel => handle_json_del_command(&children[i..], storage),
+        Command::JsonNumIncrBy => handle_json_numincrby_command(&children[i..], storage),
+        Command::JsonArrAppend => handle_json_arrappend_command(&children[i..], storage),
+        Command::GetV => handle_getv_command(&children[i..], storage),
+        Command::Cas => handle_cas_command(&children[i..], storage),
         Command::EvictNow => handle_evictnow_command(&children[i..], storage),
+        Command::Scan => handle_scan_command(&children[i..], storage),
+        Command::Select => handle_select_command(&children[i..], session),
+        Command::Move => handle_move_command(&children[i..], session, databases),
+        Command::Hello => handle_hello_command(&children[i..], session),
+        Command::Del => handle_del_command(&children[i..], storage),
+        Command::MGet => handle_mget_command(&children[i..], storage),
+        Command::MSet => handle_mset_command(&children[i..], storage),
+        Command::SetEx => handle_setex_command(&children[i..], storage),
+        Command::Ttl => handle_ttl_command(&children[i..], storage),
+        Command::Config => handle_config_command(&children[i..], storage),
+        Command::DbSize => handle_dbsize_command(storage),
+        Command::FlushAll => handle_flushall_command(databases),
+        Command::Ping => handle_ping_command(&children[i..]),
+        Command::Echo => handle_echo_command(&children[i..]),
     }
 }
@@ -0,0 +1,43 @@
+use volatix_core::{DEFAULT_DATABASE, RespValue};
+
+use crate::blocking::BlockedPop;
+
+/// Per-connection state kept by the task handler between requests.
+#[derive(Debug)]
+pub struct Session {
+    /// Id of the connection, reported by HELLO
+    pub id: usize,
+    /// Name of the database selected with SELECT
+    pub db: String,
+    /// RESP version negotiated with HELLO, 2 or 3
+    pub protocol: u8,
+    /// Name given with HELLO SETNAME
+    pub name: Option<String>,
+    /// Set by BLPOP/BRPOP when the connection has to wait for a push
+    pub blocked: Option<BlockedPop>,
+    /// Lists pushed to by the last request, which may wake blocked clients
+    pub pushed: Vec<String>,
+}
+
+impl Session {
+    /// Connections start on the default database, speaking RESP2 until
+    /// they ask for RESP3 with HELLO, like Redis.
+    pub fn new(id: usize) -> Self {
+        Session {
+            id,
+            db: DEFAULT_DATABASE.to_string(),
+            protocol: 2,
+            name: None,
+            blocked: None,
+            pushed: Vec::new(),
+        }
+    }
+
+    /// Encodes a reply in the protocol version of the connection.
+    pub fn encode(&self, reply: RespValue) -> Vec<u8> {
+        match self.protocol {
+            2 => reply.into_resp2().encode(),
+            _ => reply.encode(),
+        }
+    }
+}